@@ -0,0 +1,384 @@
+//! A command line tool for generating cynic code ahead of time.
+//!
+//! Running `query_dsl!` on a very large schema can add a lot to build times.
+//! `cynic-cli generate-dsl` writes the same code out to a file, which can be
+//! checked in & used as a module instead of invoking the macro.
+//!
+//! `cynic-cli generate-fragments` converts existing `.graphql` query
+//! documents into the equivalent `QueryFragment` structs.
+
+use std::path::{Path, PathBuf};
+
+use cynic_codegen::query_dsl::QueryDslParams;
+use cynic_querygen::QueryGenOptions;
+
+const USAGE: &str = "Usage: cynic-cli <COMMAND> [OPTIONS]
+
+Commands:
+    generate-dsl          Generates the query DSL for a schema
+    generate-fragments    Generates QueryFragments from GraphQL query documents";
+
+const DSL_USAGE: &str = "Usage: cynic-cli generate-dsl <SCHEMA> -o <OUTPUT> [OPTIONS]
+
+Generates the query DSL for SCHEMA, writing it to the file OUTPUT.
+
+Options:
+    -o, --output <OUTPUT>       The file (or directory with --per-type) to write to
+    --per-type                  Writes a file for each type into the OUTPUT directory
+    --ordered-floats            Maps GraphQL Floats to cynic::OrderedFloat
+    --preview <NAME>            Enables fields behind the NAME schema preview
+    --type <NAME>               Only generates the DSL for the NAME type, and the types its fields use.
+                                Can be given more than once
+    --visibility <VISIBILITY>   Sets the visibility of the generated DSL, e.g. pub(crate)
+    --grouped-modules           Groups the generated items into a module for each kind of type";
+
+const FRAGMENTS_USAGE: &str =
+    "Usage: cynic-cli generate-fragments <SCHEMA> <QUERIES>... -o <OUTPUT> [OPTIONS]
+
+Generates QueryFragments for each of the GraphQL query documents in QUERIES.
+QUERIES can be files, or directories containing .graphql or .gql files.
+
+If QUERIES is a single file the code is written to the file OUTPUT, otherwise a
+file is written into the OUTPUT directory for each query document.
+
+Options:
+    -o, --output <OUTPUT>       The file (or directory) to write to
+    --schema-path <PATH>        The schema path to put in the generated code.
+                                Defaults to SCHEMA
+    --query-module <MODULE>     The query_dsl module the generated code uses.
+                                Defaults to query_dsl";
+
+fn main() {
+    if let Err(message) = run(std::env::args().skip(1).collect()) {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+
+    match args.next().as_deref() {
+        Some("generate-dsl") => generate_dsl(GenerateDsl::parse(args)?),
+        Some("generate-fragments") => generate_fragments(GenerateFragments::parse(args)?),
+        Some("help") | Some("--help") | Some("-h") | None => {
+            println!("{}\n\n{}\n\n{}", USAGE, DSL_USAGE, FRAGMENTS_USAGE);
+            Ok(())
+        }
+        Some(other) => Err(format!("Unknown command: {}\n\n{}", other, USAGE)),
+    }
+}
+
+#[derive(Debug)]
+struct GenerateDsl {
+    params: QueryDslParams,
+    output: PathBuf,
+    per_type: bool,
+}
+
+impl GenerateDsl {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<GenerateDsl, String> {
+        let mut schema = None;
+        let mut output = None;
+        let mut per_type = false;
+        let mut ordered_floats = false;
+        let mut previews = vec![];
+        let mut types = vec![];
+        let mut visibility = None;
+        let mut grouped_modules = false;
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("{} requires a value\n\n{}", name, DSL_USAGE))
+            };
+
+            match arg.as_str() {
+                "-o" | "--output" => output = Some(PathBuf::from(value(&arg)?)),
+                "--per-type" => per_type = true,
+                "--ordered-floats" => ordered_floats = true,
+                "--preview" => previews.push(value(&arg)?),
+                "--type" => types.push(value(&arg)?),
+                "--visibility" => visibility = Some(value(&arg)?),
+                "--grouped-modules" => grouped_modules = true,
+                other if other.starts_with('-') => {
+                    return Err(format!("Unknown option: {}\n\n{}", other, DSL_USAGE))
+                }
+                other if schema.is_none() => schema = Some(other.to_string()),
+                other => return Err(format!("Unexpected argument: {}\n\n{}", other, DSL_USAGE)),
+            }
+        }
+
+        let schema = schema.ok_or_else(|| format!("A schema is required\n\n{}", DSL_USAGE))?;
+        let output = output.ok_or_else(|| format!("An output is required\n\n{}", DSL_USAGE))?;
+
+        if per_type && (visibility.is_some() || grouped_modules) {
+            return Err(
+                "--visibility & --grouped-modules can't be used with --per-type".to_string(),
+            );
+        }
+
+        let mut params = QueryDslParams::new(schema);
+        params.ordered_floats = ordered_floats;
+        params.previews = previews;
+        if !types.is_empty() {
+            params.types = Some(types);
+        }
+        params.visibility = visibility;
+        params.grouped_modules = grouped_modules;
+
+        Ok(GenerateDsl {
+            params,
+            output,
+            per_type,
+        })
+    }
+}
+
+fn generate_dsl(command: GenerateDsl) -> Result<(), String> {
+    let result = if command.per_type {
+        cynic_codegen::output_query_dsl_per_type_with_params(command.params, &command.output)
+    } else {
+        cynic_codegen::output_query_dsl_with_params(command.params, &command.output)
+    };
+
+    result.map_err(|e| e.to_string())
+}
+
+#[derive(Debug)]
+struct GenerateFragments {
+    schema: PathBuf,
+    queries: Vec<PathBuf>,
+    output: PathBuf,
+    options: QueryGenOptions,
+}
+
+impl GenerateFragments {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<GenerateFragments, String> {
+        let mut schema = None;
+        let mut queries = vec![];
+        let mut output = None;
+        let mut schema_path = None;
+        let mut query_module = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("{} requires a value\n\n{}", name, FRAGMENTS_USAGE))
+            };
+
+            match arg.as_str() {
+                "-o" | "--output" => output = Some(PathBuf::from(value(&arg)?)),
+                "--schema-path" => schema_path = Some(value(&arg)?),
+                "--query-module" => query_module = Some(value(&arg)?),
+                other if other.starts_with('-') => {
+                    return Err(format!("Unknown option: {}\n\n{}", other, FRAGMENTS_USAGE))
+                }
+                other if schema.is_none() => schema = Some(other.to_string()),
+                other => queries.push(PathBuf::from(other)),
+            }
+        }
+
+        let schema =
+            schema.ok_or_else(|| format!("A schema is required\n\n{}", FRAGMENTS_USAGE))?;
+        let output =
+            output.ok_or_else(|| format!("An output is required\n\n{}", FRAGMENTS_USAGE))?;
+        if queries.is_empty() {
+            return Err(format!(
+                "At least one query document is required\n\n{}",
+                FRAGMENTS_USAGE
+            ));
+        }
+
+        let options = QueryGenOptions {
+            schema_path: schema_path.unwrap_or_else(|| schema.clone()),
+            query_module: query_module.unwrap_or_else(|| QueryGenOptions::default().query_module),
+        };
+
+        Ok(GenerateFragments {
+            schema: PathBuf::from(schema),
+            queries,
+            output,
+            options,
+        })
+    }
+
+    /// The query documents to generate code for, along with the file to
+    /// write each one to.
+    fn documents(&self) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+        if let [query] = self.queries.as_slice() {
+            if !query.is_dir() {
+                return Ok(vec![(query.clone(), self.output.clone())]);
+            }
+        }
+
+        let mut documents = vec![];
+        for query in &self.queries {
+            if query.is_dir() {
+                let mut entries = std::fs::read_dir(query)
+                    .map_err(|e| format!("Could not read {}: {}", query.display(), e))?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Could not read {}: {}", query.display(), e))?;
+                entries.retain(|path| is_query_document(path));
+                entries.sort();
+                documents.extend(entries);
+            } else {
+                documents.push(query.clone());
+            }
+        }
+
+        Ok(documents
+            .into_iter()
+            .map(|document| {
+                let output = self.output.join(output_filename(&document));
+                (document, output)
+            })
+            .collect())
+    }
+}
+
+fn is_query_document(path: &Path) -> bool {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => extension == "graphql" || extension == "gql",
+        None => false,
+    }
+}
+
+/// The name of the file the code for `document` is written to when
+/// generating code for several documents.
+fn output_filename(document: &Path) -> String {
+    let stem = document
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+
+    format!("{}.rs", stem.replace(|c: char| !c.is_alphanumeric(), "_"))
+}
+
+fn generate_fragments(command: GenerateFragments) -> Result<(), String> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))
+    };
+
+    let schema = read(&command.schema)?;
+    let documents = command.documents()?;
+    if documents.is_empty() {
+        return Err("No query documents were found".to_string());
+    }
+
+    if documents.len() != 1 || documents[0].1 != command.output {
+        std::fs::create_dir_all(&command.output)
+            .map_err(|e| format!("Could not create {}: {}", command.output.display(), e))?;
+    }
+
+    for (document, output) in documents {
+        let code = cynic_querygen::document_to_fragment_structs(
+            read(&document)?,
+            &schema,
+            &command.options,
+        )
+        .map_err(|e| format!("Could not generate code for {}: {}", document.display(), e))?;
+
+        std::fs::write(&output, code)
+            .map_err(|e| format!("Could not write {}: {}", output.display(), e))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cynic_codegen::SchemaSource;
+
+    fn parse(args: &[&str]) -> Result<GenerateDsl, String> {
+        GenerateDsl::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parsing_generate_dsl() {
+        let command = parse(&[
+            "schema.graphql",
+            "-o",
+            "src/schema_dsl.rs",
+            "--preview",
+            "antiope",
+            "--visibility",
+            "pub(crate)",
+            "--type",
+            "Query",
+            "--type",
+            "Film",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            command.params.schema,
+            SchemaSource::Path("schema.graphql".into())
+        );
+        assert_eq!(command.output, PathBuf::from("src/schema_dsl.rs"));
+        assert_eq!(command.params.previews, vec!["antiope"]);
+        assert_eq!(command.params.visibility.as_deref(), Some("pub(crate)"));
+        assert_eq!(
+            command.params.types,
+            Some(vec!["Query".to_string(), "Film".to_string()])
+        );
+        assert!(!command.per_type);
+
+        assert!(parse(&["schema.graphql"]).is_err());
+        assert!(parse(&["schema.graphql", "-o"]).is_err());
+        assert!(parse(&["schema.graphql", "-o", "out", "--unknown"]).is_err());
+        assert!(parse(&[
+            "schema.graphql",
+            "-o",
+            "out",
+            "--per-type",
+            "--grouped-modules"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_parsing_generate_fragments() {
+        let parse =
+            |args: &[&str]| GenerateFragments::parse(args.iter().map(|arg| arg.to_string()));
+
+        let command = parse(&[
+            "schema.graphql",
+            "queries/films.graphql",
+            "queries/people",
+            "-o",
+            "src/queries",
+            "--query-module",
+            "schema_dsl",
+        ])
+        .unwrap();
+
+        assert_eq!(command.schema, PathBuf::from("schema.graphql"));
+        assert_eq!(
+            command.queries,
+            vec![
+                PathBuf::from("queries/films.graphql"),
+                PathBuf::from("queries/people")
+            ]
+        );
+        assert_eq!(command.output, PathBuf::from("src/queries"));
+        assert_eq!(command.options.schema_path, "schema.graphql");
+        assert_eq!(command.options.query_module, "schema_dsl");
+
+        assert!(parse(&["schema.graphql", "-o", "out"]).is_err());
+        assert!(parse(&["schema.graphql", "query.graphql"]).is_err());
+    }
+
+    #[test]
+    fn test_output_filenames() {
+        assert_eq!(
+            output_filename(Path::new("queries/all-films.graphql")),
+            "all_films.rs"
+        );
+        assert!(is_query_document(Path::new("films.gql")));
+        assert!(!is_query_document(Path::new("README.md")));
+    }
+}
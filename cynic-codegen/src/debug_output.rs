@@ -0,0 +1,93 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+};
+
+/// The environment variable that enables writing macro output to disk.
+const DEBUG_VAR: &str = "CYNIC_DEBUG_CODEGEN";
+
+/// Writes the output of a macro to disk, if `CYNIC_DEBUG_CODEGEN` is set.
+///
+/// This is intended to help debug the code cynic generates.  Output is
+/// written to `cynic-codegen/{kind}-{name}-{hash}.rs` in the `OUT_DIR` of the
+/// crate being built, or the system temporary directory if that crate has no
+/// build script.  The hash of the output keeps items with the same name in
+/// different modules apart.  Any problems writing the output are ignored.
+///
+/// `code` is only called if the environment variable is set, so that macros
+/// don't pay for converting their output to a string on every expansion.
+pub fn write_debug_output(kind: &str, name: &str, code: impl FnOnce() -> String) {
+    if !debug_enabled(std::env::var(DEBUG_VAR).ok().as_deref()) {
+        return;
+    }
+    let code = code();
+
+    let directory = std::env::var_os("OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("cynic-codegen");
+
+    if std::fs::create_dir_all(&directory).is_err() {
+        return;
+    }
+
+    let path = directory.join(debug_filename(kind, name, &code));
+    if std::fs::write(&path, code).is_ok() {
+        crate::format_code(&path);
+    }
+}
+
+fn debug_enabled(value: Option<&str>) -> bool {
+    match value {
+        None | Some("") | Some("0") | Some("false") => false,
+        Some(_) => true,
+    }
+}
+
+fn debug_filename(kind: &str, name: &str, code: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+
+    let mut hasher = DefaultHasher::new();
+    code.hash(&mut hasher);
+
+    format!("{}-{}-{:016x}.rs", kind, name, hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_enabled() {
+        assert!(!debug_enabled(None));
+        assert!(!debug_enabled(Some("0")));
+        assert!(!debug_enabled(Some("")));
+        assert!(debug_enabled(Some("1")));
+    }
+
+    #[test]
+    fn test_debug_filename() {
+        let filename = debug_filename("QueryFragment", "AllFilms", "struct AllFilms;");
+        assert!(filename.starts_with("QueryFragment-AllFilms-"));
+        assert!(filename.ends_with(".rs"));
+
+        assert!(debug_filename("query_dsl", "src/schema.graphql", "")
+            .starts_with("query_dsl-src_schema_graphql-"));
+    }
+
+    #[test]
+    fn test_debug_filenames_are_unique() {
+        assert_eq!(
+            debug_filename("QueryFragment", "Film", "mod a {}"),
+            debug_filename("QueryFragment", "Film", "mod a {}")
+        );
+        assert_ne!(
+            debug_filename("QueryFragment", "Film", "mod a {}"),
+            debug_filename("QueryFragment", "Film", "mod b {}")
+        );
+    }
+}
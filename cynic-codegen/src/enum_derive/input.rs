@@ -8,12 +8,26 @@ pub struct EnumDeriveInput {
     pub(super) ident: proc_macro2::Ident,
     pub(super) data: darling::ast::Data<EnumDeriveVariant, ()>,
 
-    pub schema_path: SpannedValue<String>,
+    #[darling(default)]
+    pub schema_path: Option<SpannedValue<String>>,
+    /// The SDL of the schema, for schemas that are written inline rather
+    /// than loaded from `schema_path`.
+    #[darling(default)]
+    pub sdl: Option<SpannedValue<String>>,
     pub query_module: SpannedValue<String>,
     pub graphql_type: SpannedValue<String>,
 
     #[darling(default)]
     pub(super) rename_all: Option<RenameAll>,
+
+    #[darling(default)]
+    pub(super) impl_serialize: bool,
+
+    #[darling(default)]
+    pub(super) impl_display: bool,
+
+    #[darling(default)]
+    pub(super) impl_from_str: bool,
 }
 
 #[derive(Debug, darling::FromVariant)]
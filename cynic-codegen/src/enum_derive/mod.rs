@@ -3,9 +3,12 @@ use std::collections::HashMap;
 
 use crate::{
     ident::{RenameAll, RenameRule},
-    load_schema,
-    schema::{Definition, Document, EnumType, EnumValue, TypeDefinition},
-    Ident,
+    schema::{
+        load_derive_schema, Definition, Document, EnumType, EnumValue, SchemaSource, TypeDefinition,
+    },
+    serde_serialize::serde_serialize_impl,
+    suggestions::did_you_mean,
+    Ident, TypePath,
 };
 
 pub(crate) mod input;
@@ -20,8 +23,7 @@ pub fn enum_derive(ast: &syn::DeriveInput) -> Result<TokenStream, syn::Error> {
     let enum_span = ast.span();
 
     match EnumDeriveInput::from_derive_input(ast) {
-        Ok(input) => load_schema(&*input.schema_path)
-            .map_err(|e| e.to_syn_error(input.schema_path.span()))
+        Ok(input) => load_derive_schema(&input.schema_path, &input.sdl, enum_span)
             .and_then(|schema| enum_derive_impl(input, &schema, enum_span))
             .or_else(|e| Ok(e.to_compile_error())),
         Err(e) => Ok(e.write_errors()),
@@ -44,11 +46,18 @@ pub fn enum_derive_impl(
         None
     });
     if enum_def.is_none() {
+        let schema_source = SchemaSource::from_attributes(
+            &input.schema_path,
+            &input.sdl,
+            input.graphql_type.span(),
+        )?;
         return Err(syn::Error::new(
             input.graphql_type.span(),
             format!(
-                "Could not find an enum named {} in {}",
-                *input.graphql_type, *input.schema_path
+                "Could not find an enum named {} in {}{}",
+                *input.graphql_type,
+                schema_source.name(),
+                did_you_mean(enum_names(schema), &*input.graphql_type)
             ),
         ));
     }
@@ -75,9 +84,54 @@ pub fn enum_derive_impl(
 
         let variants: Vec<_> = pairs.iter().map(|(variant, _)| &variant.ident).collect();
 
-        let query_module = Ident::for_module(&input.query_module);
+        let query_module =
+            TypePath::for_query_module(&input.query_module, input.query_module.span());
         let enum_marker_ident = Ident::for_type(&*input.graphql_type);
 
+        let serde_impl = if input.impl_serialize {
+            Some(serde_serialize_impl(&ident, &syn::Generics::default()))
+        } else {
+            None
+        };
+
+        let display_impl = if input.impl_display {
+            Some(quote! {
+                #[automatically_derived]
+                impl ::std::fmt::Display for #ident {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        f.write_str(match self {
+                            #(
+                                #ident::#variants => #string_literals,
+                            )*
+                        })
+                    }
+                }
+            })
+        } else {
+            None
+        };
+
+        let from_str_impl = if input.impl_from_str {
+            let graphql_type = proc_macro2::Literal::string(&*input.graphql_type);
+            Some(quote! {
+                #[automatically_derived]
+                impl ::std::str::FromStr for #ident {
+                    type Err = ::cynic::UnknownVariantError;
+
+                    fn from_str(s: &str) -> Result<Self, Self::Err> {
+                        match s {
+                            #(
+                                #string_literals => Ok(#ident::#variants),
+                            )*
+                            _ => Err(::cynic::UnknownVariantError::new(#graphql_type, s))
+                        }
+                    }
+                }
+            })
+        } else {
+            None
+        };
+
         Ok(quote! {
             #[automatically_derived]
             impl ::cynic::Enum<#query_module::#enum_marker_ident> for #ident {
@@ -95,8 +149,8 @@ pub fn enum_derive_impl(
 
             #[automatically_derived]
             impl ::cynic::SerializableArgument for #ident {
-                fn serialize(&self) -> Result<::cynic::serde_json::Value, ::cynic::SerializeError> {
-                    Ok(::cynic::serde_json::to_value(match self {
+                fn serialize(&self) -> Result<::cynic::__private::serde_json::Value, ::cynic::SerializeError> {
+                    Ok(::cynic::__private::serde_json::to_value(match self {
                         #(
                             #ident::#variants => #string_literals.to_string(),
                         )*
@@ -105,6 +159,10 @@ pub fn enum_derive_impl(
             }
 
             ::cynic::impl_into_argument_for_options!(#ident);
+
+            #serde_impl
+            #display_impl
+            #from_str_impl
         })
     } else {
         Err(syn::Error::new(
@@ -114,6 +172,13 @@ pub fn enum_derive_impl(
     }
 }
 
+fn enum_names(schema: &Document) -> impl Iterator<Item = &str> {
+    schema.definitions.iter().filter_map(|def| match def {
+        Definition::TypeDefinition(TypeDefinition::Enum(e)) => Some(e.name.as_str()),
+        _ => None,
+    })
+}
+
 fn join_variants<'a>(
     variants: &'a [EnumDeriveVariant],
     enum_def: &'a EnumType,
@@ -144,8 +209,13 @@ fn join_variants<'a>(
                 syn::Error::new(
                     variant.ident.span(),
                     format!(
-                        "Could not find a variant {} in the GraphQL enum {}",
-                        transformed_ident, enum_name
+                        "Could not find a variant {} in the GraphQL enum {}{}",
+                        transformed_ident,
+                        enum_name,
+                        did_you_mean(
+                            enum_def.values.iter().map(|value| value.name.as_str()),
+                            &transformed_ident.to_string()
+                        )
                     ),
                 )
                 .to_compile_error(),
@@ -323,4 +393,20 @@ mod tests {
 
         assert_matches!(result, Err(_));
     }
+
+    #[test]
+    fn join_variants_suggests_close_variants() {
+        let variants = vec![EnumDeriveVariant {
+            ident: proc_macro2::Ident::new("CHEESCAKE", Span::call_site()),
+            rename: None,
+        }];
+        let mut gql_enum = EnumType::new("Desserts".into());
+        gql_enum.values.push(EnumValue::new("CHEESECAKE".into()));
+
+        let result = join_variants(&variants, &gql_enum, "Desserts", None, &Span::call_site());
+
+        assert_matches!(result, Err(errors) => {
+            assert!(errors.to_string().contains("Did you mean CHEESECAKE?"))
+        });
+    }
 }
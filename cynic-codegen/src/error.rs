@@ -5,20 +5,48 @@ pub enum Error {
     IoError(String),
     ParseError(String),
     FileNotFound(String),
+    IntrospectionError(String),
+    FetchError(String),
+    UnknownType(String, String),
+    UnknownExtension(String),
+    InvalidOption(String),
+    OutDirNotSet,
 }
 
 impl Error {
     pub fn to_syn_error(self, schema_span: Span) -> syn::Error {
-        let message = match self {
-            Error::IoError(e) => format!("Could not load schema file: {}", e),
-            Error::ParseError(e) => format!("Could not parse schema file: {}", e),
-            Error::FileNotFound(e) => format!("Could not find file: {}", e),
-        };
+        syn::Error::new(schema_span, self.to_string())
+    }
+}
 
-        syn::Error::new(schema_span, message)
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::IoError(e) => write!(f, "Could not load schema file: {}", e),
+            Error::ParseError(e) => write!(f, "Could not parse schema file: {}", e),
+            Error::FileNotFound(e) => write!(f, "Could not find file: {}", e),
+            Error::FetchError(e) => write!(f, "Could not fetch schema: {}", e),
+            Error::UnknownType(name, location) => write!(
+                f,
+                "The schema references a type that is not defined: {} (in {})",
+                name, location
+            ),
+            Error::UnknownExtension(name) => write!(
+                f,
+                "The schema extends a type that is not defined: {}",
+                name
+            ),
+            Error::InvalidOption(e) => write!(f, "Invalid query_dsl option: {}", e),
+            Error::IntrospectionError(e) => {
+                write!(f, "Could not parse introspection result: {}", e)
+            }
+            Error::OutDirNotSet => write!(f, "Could not resolve $OUT_DIR: OUT_DIR is not set.  Does this crate have a build script?"),
+        }
     }
 }
 
+impl std::error::Error for Error {}
+
 impl From<graphql_parser::schema::ParseError> for Error {
     fn from(e: graphql_parser::schema::ParseError) -> Error {
         Error::ParseError(e.to_string())
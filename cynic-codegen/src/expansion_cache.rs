@@ -0,0 +1,125 @@
+use proc_macro2::TokenStream;
+use std::path::PathBuf;
+
+/// The environment variable that enables the expansion cache.
+const CACHE_DIR_VAR: &str = "CYNIC_EXPANSION_CACHE";
+
+/// An on-disk cache of macro output.
+///
+/// Generating the query DSL for a large schema can be slow, and has to be
+/// redone for every clean build.  If `CYNIC_EXPANSION_CACHE` is set to a
+/// directory, the output is written there keyed by a hash of the schema, the
+/// macro input & the version of cynic-codegen, so later builds can reuse it.
+pub struct ExpansionCache {
+    directory: PathBuf,
+}
+
+impl ExpansionCache {
+    /// Returns the cache if it's been enabled.
+    pub fn from_env() -> Option<ExpansionCache> {
+        let directory = std::env::var_os(CACHE_DIR_VAR)?;
+        if directory.is_empty() {
+            return None;
+        }
+
+        Some(ExpansionCache {
+            directory: directory.into(),
+        })
+    }
+
+    /// Builds a cache key from all the inputs to an expansion.
+    pub fn key(inputs: &[&str]) -> String {
+        // We hash the inputs twice with different seeds so collisions would
+        // need to happen in both hashes at once.
+        let mut first = Fnv1a::new(0xcbf2_9ce4_8422_2325);
+        let mut second = Fnv1a::new(0x8422_2325_cbf2_9ce4);
+
+        for input in std::iter::once(&env!("CARGO_PKG_VERSION")).chain(inputs) {
+            // Including the length keeps inputs from running into each other
+            for bytes in &[&input.len().to_le_bytes()[..], input.as_bytes()] {
+                first.write(bytes);
+                second.write(bytes);
+            }
+        }
+
+        format!("{:016x}{:016x}", first.0, second.0)
+    }
+
+    /// Looks up the output for `key`.  Any problems reading the cache are
+    /// treated as misses.
+    pub fn get(&self, key: &str) -> Option<TokenStream> {
+        let contents = std::fs::read_to_string(self.path(key)).ok()?;
+        contents.parse().ok()
+    }
+
+    /// Stores the output for `key`.
+    ///
+    /// Failing to write to the cache shouldn't fail the build, so any errors
+    /// are ignored.
+    pub fn put(&self, key: &str, tokens: &TokenStream) {
+        if std::fs::create_dir_all(&self.directory).is_err() {
+            return;
+        }
+
+        // Write to a temporary file & rename it so concurrent builds never
+        // see a partially written file.
+        let temp_path = self
+            .directory
+            .join(format!("{}.{}.tmp", key, std::process::id()));
+        if std::fs::write(&temp_path, tokens.to_string()).is_ok() {
+            let _ = std::fs::rename(&temp_path, self.path(key));
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.rs", key))
+    }
+}
+
+/// The 64 bit FNV-1a hash, which unlike the hashers in std is stable across
+/// rust versions.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new(seed: u64) -> Self {
+        Fnv1a(seed)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_depend_on_all_inputs() {
+        let key = ExpansionCache::key(&["schema", "input"]);
+
+        assert_eq!(key, ExpansionCache::key(&["schema", "input"]));
+        assert_ne!(key, ExpansionCache::key(&["schema", "other input"]));
+        assert_ne!(key, ExpansionCache::key(&["schemai", "nput"]));
+        assert_eq!(key.len(), 32);
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let cache = ExpansionCache {
+            directory: std::env::temp_dir().join("cynic-expansion-cache-test"),
+        };
+        let key = ExpansionCache::key(&["round trip"]);
+        let tokens: TokenStream = quote::quote! { pub struct Test; };
+
+        cache.put(&key, &tokens);
+
+        assert_eq!(
+            cache.get(&key).map(|t| t.to_string()),
+            Some(tokens.to_string())
+        );
+        assert!(cache.get(&ExpansionCache::key(&["missing"])).is_none());
+    }
+}
@@ -7,6 +7,8 @@ pub struct FieldArgument {
     pub(crate) argument_type: FieldType,
     pub(crate) gql_name: String,
     pub(crate) gql_type: String,
+    /// The default value the schema gives this argument, if any.
+    pub(crate) default_value: Option<String>,
 }
 
 impl FieldArgument {
@@ -18,11 +20,16 @@ impl FieldArgument {
             argument_type: FieldType::from_schema_type(&value.value_type, type_index),
             gql_type: value.value_type.to_graphql_string(),
             gql_name: value.name.clone(),
+            default_value: value.default_value.as_ref().map(|value| value.to_string()),
         }
     }
 
+    /// Whether this argument has to be provided.
+    ///
+    /// Arguments that are nullable or have a default value in the schema can
+    /// be left out.
     pub fn is_required(&self) -> bool {
-        !self.argument_type.is_nullable()
+        !self.argument_type.is_nullable() && self.default_value.is_none()
     }
 
     pub fn generic_parameter(&self) -> Option<GenericParameter> {
@@ -37,7 +37,19 @@ impl FieldType {
                 nullable,
             ),
             Type::NamedType(name) => {
-                if type_index.is_scalar(name) {
+                if let Some(scalar_path) = type_index.scalar_override(name) {
+                    FieldType::Scalar(scalar_path.clone(), nullable)
+                } else if name == "_Any" {
+                    // Apollo Federation's entity representations.
+                    FieldType::Scalar(
+                        TypePath::new_absolute(vec![
+                            Ident::new("cynic"),
+                            Ident::new("federation"),
+                            Ident::new("Any"),
+                        ]),
+                        nullable,
+                    )
+                } else if type_index.is_scalar(name) {
                     FieldType::Scalar(Ident::for_type(name).into(), nullable)
                 } else if type_index.is_enum(name) {
                     FieldType::Enum(Ident::for_type(name), nullable)
@@ -48,6 +60,14 @@ impl FieldType {
                         TypePath::new_builtin(Ident::for_inbuilt_scalar("i32")),
                         nullable,
                     )
+                } else if name == "Float" && type_index.ordered_floats() {
+                    FieldType::Scalar(
+                        TypePath::new_absolute(vec![
+                            Ident::new("cynic"),
+                            Ident::new("OrderedFloat"),
+                        ]),
+                        nullable,
+                    )
                 } else if name == "Float" {
                     FieldType::Scalar(
                         TypePath::new_builtin(Ident::for_inbuilt_scalar("f64")),
@@ -0,0 +1,208 @@
+use darling::ast::Style;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+
+use super::input::{FragmentArgumentsDeriveField, FragmentArgumentsDeriveInput};
+use crate::type_validation::optional_inner_type;
+
+/// Generates a builder for a FragmentArguments struct.
+///
+/// Each required (i.e. non `Option`) field gets a type parameter on the
+/// builder which starts out as `()` and becomes the type of the field once
+/// the associated setter is called.  `build` is only implemented once all of
+/// those parameters have been filled in, so forgetting a required argument
+/// is a compile error rather than a runtime one.
+pub fn builder_impl(input: &FragmentArgumentsDeriveInput) -> Result<TokenStream, syn::Error> {
+    let ident = &input.ident;
+    let vis = &input.vis;
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "FragmentArguments builders can't currently be generated for generic structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        darling::ast::Data::Struct(fields) if fields.style == Style::Struct => &fields.fields,
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "FragmentArguments builders can only be generated for named structs",
+            ))
+        }
+    };
+
+    let builder_ident = format_ident!("{}Builder", ident);
+
+    let builder_fields = fields.iter().map(BuilderField::new).collect::<Vec<_>>();
+
+    let type_params = builder_fields
+        .iter()
+        .filter_map(|f| f.type_param.as_ref())
+        .collect::<Vec<_>>();
+
+    let struct_fields = builder_fields.iter().map(|f| {
+        let name = f.ident;
+        match &f.type_param {
+            Some(param) => quote! { #name: #param },
+            None => {
+                let ty = f.ty;
+                quote! { #name: #ty }
+            }
+        }
+    });
+
+    let initial_params = builder_fields
+        .iter()
+        .filter(|f| f.type_param.is_some())
+        .map(|_| quote! { () });
+    let initial_values = builder_fields.iter().map(|f| {
+        let name = f.ident;
+        match &f.type_param {
+            Some(_) => quote! { #name: () },
+            None => quote! { #name: ::core::option::Option::None },
+        }
+    });
+
+    let setters = builder_fields.iter().map(|field| {
+        let name = field.ident;
+        match &field.type_param {
+            Some(param) => {
+                let ty = field.ty;
+                let output_params = builder_fields
+                    .iter()
+                    .filter_map(|f| f.type_param.as_ref())
+                    .map(|p| {
+                        if p == param {
+                            quote! { #ty }
+                        } else {
+                            quote! { #p }
+                        }
+                    });
+                let other_fields = builder_fields
+                    .iter()
+                    .map(|f| f.ident)
+                    .filter(|other| *other != name)
+                    .collect::<Vec<_>>();
+
+                quote! {
+                    #vis fn #name(self, #name: #ty) -> #builder_ident<#(#output_params),*> {
+                        #builder_ident {
+                            #name,
+                            #(#other_fields: self.#other_fields,)*
+                        }
+                    }
+                }
+            }
+            None => {
+                let inner_ty = optional_inner_type(field.ty).unwrap();
+                quote! {
+                    #vis fn #name(mut self, #name: #inner_ty) -> Self {
+                        self.#name = ::core::option::Option::Some(#name);
+                        self
+                    }
+                }
+            }
+        }
+    });
+
+    let complete_params = builder_fields
+        .iter()
+        .filter(|f| f.type_param.is_some())
+        .map(|f| f.ty);
+    let field_names = builder_fields.iter().map(|f| f.ident).collect::<Vec<_>>();
+
+    Ok(quote! {
+        #[allow(non_camel_case_types)]
+        #vis struct #builder_ident<#(#type_params),*> {
+            #(#struct_fields,)*
+        }
+
+        #[automatically_derived]
+        impl #ident {
+            /// Returns a builder for this struct.
+            #vis fn builder() -> #builder_ident<#(#initial_params),*> {
+                #builder_ident {
+                    #(#initial_values,)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        #[allow(non_camel_case_types)]
+        impl<#(#type_params),*> #builder_ident<#(#type_params),*> {
+            #(#setters)*
+        }
+
+        #[automatically_derived]
+        impl #builder_ident<#(#complete_params),*> {
+            #vis fn build(self) -> #ident {
+                #ident {
+                    #(#field_names: self.#field_names,)*
+                }
+            }
+        }
+    })
+}
+
+struct BuilderField<'a> {
+    ident: &'a proc_macro2::Ident,
+    ty: &'a syn::Type,
+    type_param: Option<proc_macro2::Ident>,
+}
+
+impl<'a> BuilderField<'a> {
+    fn new(field: &'a FragmentArgumentsDeriveField) -> Self {
+        let ident = field.ident.as_ref().unwrap();
+        let type_param = if optional_inner_type(&field.ty).is_some() {
+            None
+        } else {
+            Some(proc_macro2::Ident::new(
+                &format!("__{}", ident),
+                Span::call_site(),
+            ))
+        };
+
+        BuilderField {
+            ident,
+            ty: &field.ty,
+            type_param,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use darling::FromDeriveInput;
+
+    #[test]
+    fn test_builder_rejects_tuple_structs() {
+        let input = FragmentArgumentsDeriveInput::from_derive_input(&syn::parse_quote! {
+            #[cynic(builder)]
+            struct Arguments(i32);
+        })
+        .unwrap();
+
+        assert!(builder_impl(&input).is_err());
+    }
+
+    #[test]
+    fn test_builder_has_a_param_per_required_field() {
+        let input = FragmentArgumentsDeriveInput::from_derive_input(&syn::parse_quote! {
+            #[cynic(builder)]
+            struct Arguments {
+                id: i32,
+                first: Option<i32>,
+                after: String,
+            }
+        })
+        .unwrap();
+
+        let output = builder_impl(&input).unwrap().to_string();
+
+        assert!(output.contains("struct ArgumentsBuilder < __id , __after >"));
+        assert!(output.contains("impl ArgumentsBuilder < i32 , String >"));
+    }
+}
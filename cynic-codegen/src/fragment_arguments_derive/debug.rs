@@ -0,0 +1,96 @@
+use darling::ast::Style;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::input::FragmentArgumentsDeriveInput;
+
+/// Generates a `Debug` impl that redacts any fields marked
+/// `#[cynic(secret)]`.
+///
+/// Returns `None` if there are no secret fields, in which case users are free
+/// to derive `Debug` themselves.
+pub fn redacted_debug_impl(input: &FragmentArgumentsDeriveInput) -> Option<TokenStream> {
+    let fields = match &input.data {
+        darling::ast::Data::Struct(fields) => fields,
+        _ => return None,
+    };
+
+    if !fields.fields.iter().any(|f| f.secret) {
+        return None;
+    }
+
+    let ident = &input.ident;
+    let name = proc_macro2::Literal::string(&ident.to_string());
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match fields.style {
+        Style::Struct => {
+            let entries = fields.fields.iter().map(|field| {
+                let field_ident = field.ident.as_ref().unwrap();
+                let field_name = proc_macro2::Literal::string(&field_ident.to_string());
+                if field.secret {
+                    quote! { .field(#field_name, &"<redacted>") }
+                } else {
+                    quote! { .field(#field_name, &self.#field_ident) }
+                }
+            });
+            quote! { f.debug_struct(#name)#(#entries)*.finish() }
+        }
+        Style::Tuple | Style::Unit => {
+            let entries = fields.fields.iter().enumerate().map(|(i, field)| {
+                let index = syn::Index::from(i);
+                if field.secret {
+                    quote! { .field(&"<redacted>") }
+                } else {
+                    quote! { .field(&self.#index) }
+                }
+            });
+            quote! { f.debug_tuple(#name)#(#entries)*.finish() }
+        }
+    };
+
+    Some(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::std::fmt::Debug for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use darling::FromDeriveInput;
+
+    #[test]
+    fn test_no_debug_impl_without_secrets() {
+        let input = FragmentArgumentsDeriveInput::from_derive_input(&syn::parse_quote! {
+            struct Arguments {
+                id: i32,
+            }
+        })
+        .unwrap();
+
+        assert!(redacted_debug_impl(&input).is_none());
+    }
+
+    #[test]
+    fn test_secret_fields_are_redacted() {
+        let input = FragmentArgumentsDeriveInput::from_derive_input(&syn::parse_quote! {
+            struct Arguments {
+                id: i32,
+                #[cynic(secret)]
+                token: String,
+            }
+        })
+        .unwrap();
+
+        let output = redacted_debug_impl(&input).unwrap().to_string();
+
+        assert!(output.contains(r#". field ("id" , & self . id)"#));
+        assert!(output.contains(r#". field ("token" , & "<redacted>")"#));
+        assert!(!output.contains("self . token"));
+    }
+}
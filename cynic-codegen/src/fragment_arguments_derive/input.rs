@@ -0,0 +1,21 @@
+#[derive(darling::FromDeriveInput)]
+#[darling(attributes(cynic), supports(struct_any))]
+pub struct FragmentArgumentsDeriveInput {
+    pub(super) ident: proc_macro2::Ident,
+    pub(super) vis: syn::Visibility,
+    pub(super) generics: syn::Generics,
+    pub(super) data: darling::ast::Data<(), FragmentArgumentsDeriveField>,
+
+    #[darling(default)]
+    pub(super) builder: bool,
+}
+
+#[derive(Debug, darling::FromField)]
+#[darling(attributes(cynic))]
+pub struct FragmentArgumentsDeriveField {
+    pub(super) ident: Option<proc_macro2::Ident>,
+    pub(super) ty: syn::Type,
+
+    #[darling(default)]
+    pub(super) secret: bool,
+}
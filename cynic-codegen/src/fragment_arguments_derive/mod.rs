@@ -1,9 +1,35 @@
 use proc_macro2::TokenStream;
 
+mod builder;
+mod debug;
+pub(crate) mod input;
+
+pub use input::FragmentArgumentsDeriveInput;
+
 pub fn fragment_arguments_derive(ast: &syn::DeriveInput) -> Result<TokenStream, syn::Error> {
+    use darling::FromDeriveInput;
+
+    match FragmentArgumentsDeriveInput::from_derive_input(ast) {
+        Ok(input) => fragment_arguments_derive_impl(input),
+        Err(e) => Ok(e.write_errors()),
+    }
+}
+
+pub fn fragment_arguments_derive_impl(
+    input: FragmentArgumentsDeriveInput,
+) -> Result<TokenStream, syn::Error> {
     use quote::quote;
 
-    let ident = &ast.ident;
+    let ident = &input.ident;
+
+    let builder = if input.builder {
+        Some(builder::builder_impl(&input)?)
+    } else {
+        None
+    };
+
+    let debug = debug::redacted_debug_impl(&input);
+
     Ok(quote! {
         impl ::cynic::FragmentArguments for #ident {}
 
@@ -12,5 +38,8 @@ pub fn fragment_arguments_derive(ast: &syn::DeriveInput) -> Result<TokenStream,
                 &()
             }
         }
+
+        #builder
+        #debug
     })
 }
@@ -1,16 +1,52 @@
 use darling::util::SpannedValue;
 
+use crate::ident::RenameAll;
+
 #[derive(darling::FromDeriveInput)]
 #[darling(attributes(cynic), supports(struct_named))]
 pub struct FragmentDeriveInput {
     pub(super) ident: proc_macro2::Ident,
     pub(super) data: darling::ast::Data<(), FragmentDeriveField>,
 
-    pub schema_path: SpannedValue<String>,
+    #[darling(default)]
+    pub schema_path: Option<SpannedValue<String>>,
+    /// The SDL of the schema, for schemas that are written inline rather
+    /// than loaded from `schema_path`.
+    #[darling(default)]
+    pub sdl: Option<SpannedValue<String>>,
     pub query_module: SpannedValue<String>,
     pub graphql_type: SpannedValue<String>,
     #[darling(default)]
     pub argument_struct: Option<syn::Ident>,
+    #[darling(default)]
+    pub deprecated: DeprecationPolicy,
+    #[darling(default)]
+    pub(super) rename_all: Option<RenameAll>,
+    /// Whether to output this fragment as a named fragment, rather than
+    /// inlining its fields wherever it's used.
+    #[darling(default)]
+    pub(super) named_fragment: bool,
+}
+
+/// What to do when a fragment selects a field that is deprecated in the
+/// schema.
+#[derive(darling::FromMeta, Clone, Copy, Debug, PartialEq)]
+pub enum DeprecationPolicy {
+    /// Leave it to the usual rust deprecation warnings.
+    #[darling(rename = "warn")]
+    Warn,
+    /// Silence any deprecation warnings.
+    #[darling(rename = "allow")]
+    Allow,
+    /// Fail to compile.
+    #[darling(rename = "deny")]
+    Deny,
+}
+
+impl Default for DeprecationPolicy {
+    fn default() -> Self {
+        DeprecationPolicy::Warn
+    }
 }
 
 #[derive(darling::FromField)]
@@ -23,4 +59,59 @@ pub struct FragmentDeriveField {
 
     #[darling(default)]
     pub(super) flatten: bool,
+
+    /// Whether this field is another QueryFragment on the same GraphQL
+    /// type, whose fields should be selected directly into this one.
+    #[darling(default)]
+    pub(super) spread: bool,
+
+    /// Whether to decode this scalar field with serde, rather than the
+    /// type of the scalar in the schema.  This lets free-form scalars like
+    /// `JSON` be decoded into a `serde_json::Value` or any other type that
+    /// implements `Deserialize`.
+    #[darling(default)]
+    pub(super) scalar_as_json: bool,
+
+    /// Whether this field should contain the `__typename` of the object.
+    /// Fields named `__typename` are treated like this automatically.
+    #[darling(default)]
+    pub(super) typename: bool,
+
+    /// The name of the GraphQL field this selects, if it's not the same as
+    /// the name of the rust field.
+    #[darling(default)]
+    pub(super) rename: Option<SpannedValue<String>>,
+
+    /// Whether to select this field under an alias of the rust field name.
+    /// This lets a fragment select the same GraphQL field more than once,
+    /// e.g. with different arguments.
+    #[darling(default)]
+    pub(super) alias: bool,
+
+    /// Only select this field if this expression is true, using an
+    /// `@include` directive.
+    #[darling(default)]
+    pub(super) include_if: Option<SpannedValue<String>>,
+
+    /// Skip this field if this expression is true, using a `@skip`
+    /// directive.
+    #[darling(default)]
+    pub(super) skip_if: Option<SpannedValue<String>>,
+
+    /// For fields that contain the fragment itself, the maximum depth to
+    /// select them to.  Past this depth the field is left out of the query,
+    /// and decodes to `None`.
+    #[darling(default)]
+    pub(super) recurse: Option<SpannedValue<u8>>,
+}
+
+impl FragmentDeriveField {
+    pub(super) fn is_typename(&self) -> bool {
+        self.typename
+            || self
+                .ident
+                .as_ref()
+                .map(|ident| ident == "__typename")
+                .unwrap_or(false)
+    }
 }
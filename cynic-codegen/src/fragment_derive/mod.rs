@@ -2,17 +2,23 @@ use std::collections::{HashMap, HashSet};
 
 use proc_macro2::{Span, TokenStream};
 
-use crate::{load_schema, type_validation::check_types_are_compatible, FieldType, Ident, TypePath};
+use crate::{
+    ident::{RenameAll, RenameRule},
+    schema::{load_derive_schema, SchemaSource},
+    suggestions::did_you_mean,
+    type_validation::{check_types_are_compatible, optional_inner_type, strip_pointer_types},
+    FieldType, Ident, TypePath,
+};
 
 mod arguments;
 mod schema_parsing;
 
 pub(crate) mod input;
 
-use arguments::{arguments_from_field_attrs, FieldArgument};
+use arguments::{arguments_from_field_attrs, ArgumentExpression, FieldArgument};
 use schema_parsing::{Field, Object};
 
-pub use input::{FragmentDeriveField, FragmentDeriveInput};
+pub use input::{DeprecationPolicy, FragmentDeriveField, FragmentDeriveInput};
 
 pub(crate) use schema_parsing::Schema;
 
@@ -20,9 +26,8 @@ pub fn fragment_derive(ast: &syn::DeriveInput) -> Result<TokenStream, syn::Error
     use darling::FromDeriveInput;
 
     match FragmentDeriveInput::from_derive_input(ast) {
-        Ok(input) => load_schema(&*input.schema_path)
-            .map_err(|e| e.to_syn_error(input.schema_path.span()))
-            .map(Schema::from)
+        Ok(input) => load_derive_schema(&input.schema_path, &input.sdl, input.ident.span())
+            .map(|document| Schema::from(&*document))
             .and_then(|schema| fragment_derive_impl(input, &schema))
             .or_else(|e| Ok(e.to_compile_error())),
         Err(e) => Ok(e.write_errors()),
@@ -35,7 +40,8 @@ pub fn fragment_derive_impl(
 ) -> Result<TokenStream, syn::Error> {
     use quote::{quote, quote_spanned};
 
-    let schema_path = input.schema_path;
+    let schema_source =
+        SchemaSource::from_attributes(&input.schema_path, &input.sdl, input.ident.span())?;
 
     let graphql_type = input.graphql_type;
     let object = schema
@@ -43,7 +49,18 @@ pub fn fragment_derive_impl(
         .get(&Ident::for_type(&*graphql_type))
         .ok_or(syn::Error::new(
             graphql_type.span(),
-            format!("Can't find {} in {}", *graphql_type, *schema_path),
+            format!(
+                "Can't find {} in {}{}",
+                *graphql_type,
+                schema_source.name(),
+                did_you_mean(
+                    schema
+                        .objects
+                        .values()
+                        .map(|object| object.graphql_name.as_str()),
+                    &*graphql_type
+                )
+            ),
         ))?;
 
     let argument_struct = if let Some(arg_struct) = input.argument_struct {
@@ -58,14 +75,24 @@ pub fn fragment_derive_impl(
 
     if let darling::ast::Data::Struct(fields) = &input.data {
         let query_module = input.query_module;
-        let fragment_impl = FragmentImpl::new_for(
+        let mut fragment_impl = FragmentImpl::new_for(
             &fields,
             &input.ident,
             &object,
-            Ident::new_spanned(&*query_module, query_module.span()).into(),
+            TypePath::for_query_module(&*query_module, query_module.span()),
             &graphql_type,
             argument_struct,
+            input.deprecated,
+            input.rename_all,
         )?;
+        if input.named_fragment && fragment_impl.is_recursive() {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "Recursive fragments can't be output as named fragments",
+            ));
+        }
+        fragment_impl.named_fragment = input.named_fragment;
+
         Ok(quote::quote! {
             #fragment_impl
         })
@@ -79,9 +106,13 @@ pub fn fragment_derive_impl(
 
 enum SelectorFunction {
     Field(TypePath),
+    Typename,
     Opt(Box<SelectorFunction>),
     Vector(Box<SelectorFunction>),
     Flatten(Box<SelectorFunction>),
+    /// Selects the fields of another fragment directly, rather than a
+    /// field.
+    Spread,
 }
 
 impl SelectorFunction {
@@ -117,8 +148,10 @@ impl SelectorFunction {
 impl SelectorFunction {
     fn to_call(
         &self,
+        select_function: &proc_macro2::Ident,
         required_arguments: &[FieldArgument],
         optional_arguments: &[FieldArgument],
+        alias: Option<&str>,
         inner_selection_tokens: TokenStream,
     ) -> TokenStream {
         use quote::quote;
@@ -130,6 +163,7 @@ impl SelectorFunction {
                 let required_arguments = required_arguments.iter().map(|arg| &arg.expr);
                 let optional_arg_names = optional_arguments.iter().map(|arg| &arg.argument_name);
                 let optional_arg_exprs = optional_arguments.iter().map(|arg| &arg.expr);
+                let alias = alias.map(|alias| quote! { .alias(#alias) });
 
                 quote! {
                     #type_path(
@@ -138,29 +172,40 @@ impl SelectorFunction {
                     #(
                         .#optional_arg_names(#optional_arg_exprs)
                     )*
-                    .select(#inner_selection_tokens)
+                    #alias
+                    .#select_function(#inner_selection_tokens)
                 }
             }
+            SelectorFunction::Typename => quote! {
+                ::cynic::selection_set::typename()
+            },
+            SelectorFunction::Spread => inner_selection_tokens,
             SelectorFunction::Opt(inner) => inner.to_call(
+                select_function,
                 required_arguments,
                 optional_arguments,
+                alias,
                 inner_selection_tokens,
             ),
             SelectorFunction::Vector(inner) => inner.to_call(
+                select_function,
                 required_arguments,
                 optional_arguments,
+                alias,
                 inner_selection_tokens,
             ),
             SelectorFunction::Flatten(inner) => {
                 let inner_call = inner.to_call(
+                    select_function,
                     required_arguments,
                     optional_arguments,
+                    alias,
                     inner_selection_tokens,
                 );
 
                 quote! {
                     #inner_call.map(|item| {
-                        use ::cynic::utils::FlattenInto;
+                        use ::cynic::__private::FlattenInto;
                         item.flatten_into()
                     })
                 }
@@ -171,8 +216,12 @@ impl SelectorFunction {
 
 enum SelectorCallStyle {
     QueryFragment(syn::Type),
+    /// A field containing the fragment that's being derived.
+    Recursive,
     Enum(syn::Type),
     Scalar,
+    /// A scalar that should be decoded using serde.
+    ScalarAsJson,
 }
 
 struct FieldSelectorCall {
@@ -180,6 +229,92 @@ struct FieldSelectorCall {
     style: SelectorCallStyle,
     required_arguments: Vec<FieldArgument>,
     optional_arguments: Vec<FieldArgument>,
+    /// The alias to select the field under, if it has one.
+    alias: Option<String>,
+    /// Whether the field is wrapped in Box, Arc or Rc pointers that need to be
+    /// added after decoding.
+    wrap_pointers: bool,
+    condition: Option<FieldCondition>,
+    recursion: Option<FieldRecursion>,
+}
+
+/// An `@include` or `@skip` directive on a field.
+struct FieldCondition {
+    function: proc_macro2::Ident,
+    expr: ArgumentExpression,
+}
+
+impl FieldCondition {
+    fn for_field(field: &FragmentDeriveField) -> Result<Option<Self>, syn::Error> {
+        use std::convert::TryFrom;
+
+        let (function, expr) = match (&field.include_if, &field.skip_if) {
+            (None, None) => return Ok(None),
+            (Some(expr), None) => ("include_if", expr),
+            (None, Some(expr)) => ("skip_if", expr),
+            (Some(_), Some(expr)) => {
+                return Err(syn::Error::new(
+                    expr.span(),
+                    "A field can't have both include_if and skip_if",
+                ))
+            }
+        };
+
+        let parsed_expr = syn::parse_str::<syn::Expr>(expr)
+            .map_err(|e| syn::Error::new(expr.span(), e.to_string()))?;
+
+        Ok(Some(FieldCondition {
+            function: quote::format_ident!("{}", function),
+            expr: ArgumentExpression::try_from(parsed_expr)
+                .map_err(|e| syn::Error::new(expr.span(), e.to_string()))?,
+        }))
+    }
+}
+
+/// The `recurse` attribute on a field that contains the fragment itself.
+struct FieldRecursion {
+    max_depth: u8,
+    /// Whether the GraphQL field is required, in which case the field is
+    /// wrapped in an extra Option that's None past `max_depth`.
+    wrap_in_option: bool,
+}
+
+impl FieldRecursion {
+    fn for_field(
+        field: &FragmentDeriveField,
+        field_type: &FieldType,
+    ) -> Result<Option<Self>, syn::Error> {
+        let max_depth = match &field.recurse {
+            Some(max_depth) => max_depth,
+            None => return Ok(None),
+        };
+
+        if **max_depth == 0 {
+            return Err(syn::Error::new(
+                max_depth.span(),
+                "recurse must be at least 1",
+            ));
+        }
+
+        if field.flatten || field.include_if.is_some() || field.skip_if.is_some() {
+            return Err(syn::Error::new(
+                max_depth.span(),
+                "Recursive fields can't use flatten, include_if or skip_if",
+            ));
+        }
+
+        if optional_inner_type(&field.ty).is_none() {
+            return Err(syn::Error::new(
+                max_depth.span(),
+                "Recursive fields must be wrapped in an Option",
+            ));
+        }
+
+        Ok(Some(FieldRecursion {
+            max_depth: **max_depth,
+            wrap_in_option: !field_type.is_nullable(),
+        }))
+    }
 }
 
 impl quote::ToTokens for FieldSelectorCall {
@@ -191,20 +326,63 @@ impl quote::ToTokens for FieldSelectorCall {
             SelectorCallStyle::QueryFragment(field_type) => quote! {
                 #field_type::fragment(FromArguments::from_arguments(args))
             },
+            SelectorCallStyle::Recursive => quote! {
+                Self::__cynic_recursive_fragment(args, depth + 1)
+            },
             SelectorCallStyle::Enum(enum_type) => quote! {
                 #enum_type::select()
             },
+            SelectorCallStyle::ScalarAsJson => quote! {
+                ::cynic::selection_set::serde()
+            },
+        };
+
+        let select_function = match &self.style {
+            SelectorCallStyle::ScalarAsJson => quote::format_ident!("select_with"),
+            _ => quote::format_ident!("select"),
         };
 
         let selector_function_call = &self.selector_function.to_call(
+            &select_function,
             &self.required_arguments,
             &self.optional_arguments,
+            self.alias.as_deref(),
             inner_selection_tokens,
         );
 
-        tokens.append_all(quote! {
-            #selector_function_call
-        });
+        let mut call = quote! { #selector_function_call };
+
+        if self.wrap_pointers {
+            call = quote! {
+                #call.map(|item| {
+                    use ::cynic::__private::WrapInto;
+                    item.wrap_into()
+                })
+            };
+        }
+
+        if let Some(FieldCondition { function, expr }) = &self.condition {
+            call = quote! {
+                ::cynic::selection_set::#function(#expr, #call)
+            };
+        }
+
+        if let Some(recursion) = &self.recursion {
+            if recursion.wrap_in_option {
+                call = quote! { #call.map(Some) };
+            }
+
+            let max_depth = proc_macro2::Literal::u8_unsuffixed(recursion.max_depth);
+            call = quote! {
+                if depth < #max_depth {
+                    #call
+                } else {
+                    ::cynic::selection_set::none()
+                }
+            };
+        }
+
+        tokens.append_all(call);
     }
 }
 
@@ -233,6 +411,8 @@ struct FragmentImpl {
     constructor_params: Vec<ConstructorParameter>,
     argument_struct: syn::Type,
     graphql_type_name: String,
+    deprecated: DeprecationPolicy,
+    named_fragment: bool,
 }
 
 impl FragmentImpl {
@@ -243,6 +423,8 @@ impl FragmentImpl {
         query_dsl_path: TypePath,
         graphql_type_name: &str,
         argument_struct: syn::Type,
+        deprecated: DeprecationPolicy,
+        rename_all: Option<RenameAll>,
     ) -> Result<Self, syn::Error> {
         let target_struct = Ident::new_spanned(&name.to_string(), name.span());
         let selector_struct_path = TypePath::concat(&[
@@ -259,6 +441,18 @@ impl FragmentImpl {
             ));
         }
 
+        if fields.fields.is_empty() {
+            // GraphQL doesn't allow empty selection sets, so servers would reject
+            // any query containing this fragment.
+            return Err(syn::Error::new(
+                name.span(),
+                format!(
+                    "{} doesn't select any fields.  QueryFragments must select at least one field from {}",
+                    name, graphql_type_name
+                ),
+            ));
+        }
+
         for field in &fields.fields {
             if let Some(ident) = &field.ident {
                 let field_name = ident.to_string();
@@ -267,12 +461,121 @@ impl FragmentImpl {
                     type_path: field.ty.clone(),
                 });
 
-                let arguments = arguments_from_field_attrs(&field.attrs)?;
+                if field.is_typename() {
+                    if field.alias {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "Fields that select __typename can't use alias",
+                        ));
+                    }
+                    if !is_string_type(&field.ty) {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "Fields that select __typename must have the type String",
+                        ));
+                    }
+
+                    field_selectors.push(FieldSelectorCall {
+                        selector_function: SelectorFunction::Typename,
+                        style: SelectorCallStyle::Scalar,
+                        required_arguments: vec![],
+                        optional_arguments: vec![],
+                        alias: None,
+                        wrap_pointers: false,
+                        condition: None,
+                        recursion: None,
+                    });
+                    continue;
+                }
 
-                let field_name = Ident::for_field(&field_name);
+                if field.spread {
+                    field_selectors.push(spread_selector(field, ident)?);
+                    continue;
+                }
+
+                let arguments = arguments_from_field_attrs(&field.attrs)?;
 
-                if let Some(gql_field) = object.fields.get(&field_name) {
-                    check_types_are_compatible(&gql_field.field_type, &field.ty, field.flatten)?;
+                // The DSL names its functions after the GraphQL field, so we
+                // find the GraphQL name & convert that in the same way.
+                let graphql_name = Ident::from_proc_macro2(
+                    ident,
+                    RenameRule::new(rename_all, field.rename.as_ref()),
+                );
+                let gql_field = object
+                    .field_for(&graphql_name.to_string())
+                    .map_err(|e| syn::Error::new(ident.span(), e))?;
+
+                if let Some(gql_field) = gql_field {
+                    let field_name = gql_field.name.clone();
+
+                    if let (DeprecationPolicy::Deny, Some(reason)) =
+                        (deprecated, &gql_field.deprecation)
+                    {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            format!(
+                                "Field {} on {} is deprecated: {}",
+                                gql_field.graphql_name, graphql_type_name, reason
+                            ),
+                        ));
+                    }
+
+                    let condition = FieldCondition::for_field(field)?;
+                    let recursion = FieldRecursion::for_field(field, &gql_field.field_type)?;
+
+                    // Conditional fields are wrapped in an extra Option, which
+                    // is None if the field was left out.  Required recursive
+                    // fields are too, as they're left out past their max depth.
+                    let wrapped_in_option = condition.is_some()
+                        || recursion.as_ref().map_or(false, |r| r.wrap_in_option);
+                    let field_type =
+                        match (wrapped_in_option, optional_inner_type(&field.ty)) {
+                            (false, _) => &field.ty,
+                            (true, Some(inner_type)) => inner_type,
+                            (true, None) => return Err(syn::Error::new(
+                                ident.span(),
+                                "Fields with include_if or skip_if must be wrapped in an Option",
+                            )),
+                        };
+
+                    if field.flatten && !matches!(gql_field.field_type, FieldType::List(_, _)) {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "flatten can only be used on fields that are lists",
+                        ));
+                    }
+
+                    if field.scalar_as_json {
+                        if !gql_field.field_type.contains_scalar() {
+                            return Err(syn::Error::new(
+                                ident.span(),
+                                "scalar_as_json can only be used on scalar fields",
+                            ));
+                        }
+                        if field.flatten || recursion.is_some() {
+                            return Err(syn::Error::new(
+                                ident.span(),
+                                "scalar_as_json can't be used with flatten or recurse",
+                            ));
+                        }
+                    } else {
+                        check_types_are_compatible(
+                            &gql_field.field_type,
+                            field_type,
+                            field.flatten,
+                        )?;
+                    }
+
+                    // Pointers are added after decoding, so we work out the
+                    // selection on the type without them.
+                    let unwrapped_type = strip_pointer_types(field_type);
+                    if unwrapped_type.is_some() && field.flatten {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "Flattened fields can't contain Box, Arc or Rc",
+                        ));
+                    }
+                    let inner_type = unwrapped_type.as_ref().unwrap_or(field_type);
 
                     let (required_arguments, optional_arguments) =
                         validate_and_group_args(arguments, gql_field, ident.span())?;
@@ -286,26 +589,57 @@ impl FragmentImpl {
                             ]),
                             field.flatten,
                         ),
-                        style: if gql_field.field_type.contains_scalar() {
+                        style: if recursion.is_some() {
+                            let recursive_type =
+                                gql_field.field_type.get_inner_type_from_syn(inner_type);
+                            if !is_self_type(&recursive_type, name) {
+                                return Err(syn::Error::new(
+                                    ident.span(),
+                                    format!(
+                                        "recurse can only be used on fields that contain {}",
+                                        name
+                                    ),
+                                ));
+                            }
+                            SelectorCallStyle::Recursive
+                        } else if field.scalar_as_json {
+                            SelectorCallStyle::ScalarAsJson
+                        } else if gql_field.field_type.contains_scalar() {
                             SelectorCallStyle::Scalar
                         } else if gql_field.field_type.contains_enum() {
                             SelectorCallStyle::Enum(
-                                gql_field.field_type.get_inner_type_from_syn(&field.ty),
+                                gql_field.field_type.get_inner_type_from_syn(inner_type),
                             )
                         } else {
                             SelectorCallStyle::QueryFragment(
-                                gql_field.field_type.get_inner_type_from_syn(&field.ty),
+                                gql_field.field_type.get_inner_type_from_syn(inner_type),
                             )
                         },
                         required_arguments,
                         optional_arguments,
+                        alias: if field.alias {
+                            Some(ident.to_string().trim_start_matches("r#").to_string())
+                        } else {
+                            None
+                        },
+                        wrap_pointers: unwrapped_type.is_some(),
+                        condition,
+                        recursion,
                     })
                 } else {
                     return Err(syn::Error::new(
                         ident.span(),
                         format!(
-                            "Field {} does not exist on the GraphQL type {}",
-                            field_name, graphql_type_name
+                            "Field {} does not exist on the GraphQL type {}{}",
+                            graphql_name,
+                            graphql_type_name,
+                            did_you_mean(
+                                object
+                                    .fields
+                                    .values()
+                                    .map(|field| field.graphql_name.as_str()),
+                                &graphql_name.to_string()
+                            )
                         ),
                     ));
                 }
@@ -319,10 +653,75 @@ impl FragmentImpl {
             constructor_params,
             argument_struct,
             graphql_type_name: graphql_type_name.to_string(),
+            deprecated,
+            named_fragment: false,
         })
     }
 }
 
+/// Builds the selector for a field with the `spread` attribute.
+///
+/// These contain another QueryFragment on the same GraphQL type, so rather
+/// than selecting a field we select that fragment's fields alongside our
+/// own.  The selector types of the two fragments have to match, so the
+/// compiler checks the fragment is for the right type.
+fn spread_selector(
+    field: &FragmentDeriveField,
+    ident: &proc_macro2::Ident,
+) -> Result<FieldSelectorCall, syn::Error> {
+    if field.rename.is_some() || field.flatten || field.recurse.is_some() || field.alias {
+        return Err(syn::Error::new(
+            ident.span(),
+            "Spread fields can't use rename, alias, flatten or recurse",
+        ));
+    }
+
+    if !arguments_from_field_attrs(&field.attrs)?.is_empty() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "Spread fields can't have arguments",
+        ));
+    }
+
+    let condition = FieldCondition::for_field(field)?;
+
+    let field_type =
+        match (&condition, optional_inner_type(&field.ty)) {
+            (None, None) => &field.ty,
+            (Some(_), Some(inner_type)) => inner_type,
+            (None, Some(_)) => return Err(syn::Error::new(
+                ident.span(),
+                "Spread fields can't be wrapped in an Option unless they use include_if or skip_if",
+            )),
+            (Some(_), None) => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Fields with include_if or skip_if must be wrapped in an Option",
+                ))
+            }
+        };
+
+    let unwrapped_type = strip_pointer_types(field_type);
+    let inner_type = unwrapped_type.clone().unwrap_or_else(|| field_type.clone());
+
+    Ok(FieldSelectorCall {
+        selector_function: SelectorFunction::Spread,
+        style: SelectorCallStyle::QueryFragment(inner_type),
+        required_arguments: vec![],
+        optional_arguments: vec![],
+        alias: None,
+        wrap_pointers: unwrapped_type.is_some(),
+        condition,
+        recursion: None,
+    })
+}
+
+impl FragmentImpl {
+    fn is_recursive(&self) -> bool {
+        self.fields.iter().any(|field| field.recursion.is_some())
+    }
+}
+
 impl quote::ToTokens for FragmentImpl {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         use quote::{quote, TokenStreamExt};
@@ -339,7 +738,52 @@ impl quote::ToTokens for FragmentImpl {
             .map(|p| &p.name)
             .collect::<Vec<_>>();
 
-        let map_function = quote::format_ident!("map{}", fields.len());
+        let allow_deprecated = if self.deprecated == DeprecationPolicy::Allow {
+            quote! { #[allow(deprecated)] }
+        } else {
+            quote! {}
+        };
+
+        let mut selection_set = map_fields(quote! { new }, fields);
+
+        if self.named_fragment {
+            let fragment_name = proc_macro2::Literal::string(&self.target_struct.to_string());
+            selection_set = quote! {
+                ::cynic::selection_set::named_fragment(#fragment_name, #graphql_type, #selection_set)
+            };
+        }
+
+        let fragment_body = quote! {
+            use ::cynic::{QueryFragment, FromArguments, Enum};
+
+            let new = |#(#constructor_params),*| #target_struct {
+                #(#constructor_param_names),*
+            };
+
+            #selection_set
+        };
+
+        let fragment_body = if self.is_recursive() {
+            // Recursive fields need to know how deep they are, so the fragment
+            // is built by a function that keeps track of that.
+            tokens.append_all(quote! {
+                #[automatically_derived]
+                impl #target_struct {
+                    #[doc(hidden)]
+                    #allow_deprecated
+                    fn __cynic_recursive_fragment(
+                        args: &#argument_struct,
+                        depth: u8,
+                    ) -> ::cynic::SelectionSet<'static, Self, #selector_struct> {
+                        #fragment_body
+                    }
+                }
+            });
+
+            quote! { Self::__cynic_recursive_fragment(args, 0) }
+        } else {
+            fragment_body
+        };
 
         tokens.append_all(quote! {
             #[automatically_derived]
@@ -347,19 +791,9 @@ impl quote::ToTokens for FragmentImpl {
                 type SelectionSet = ::cynic::SelectionSet<'static, Self, #selector_struct>;
                 type Arguments = #argument_struct;
 
+                #allow_deprecated
                 fn fragment(args: &Self::Arguments) -> Self::SelectionSet {
-                    use ::cynic::{QueryFragment, FromArguments, Enum};
-
-                    let new = |#(#constructor_params),*| #target_struct {
-                        #(#constructor_param_names),*
-                    };
-
-                    ::cynic::selection_set::#map_function(
-                        new,
-                        #(
-                            #fields
-                        ),*
-                    )
+                    #fragment_body
                 }
 
                 fn graphql_type() -> String {
@@ -370,6 +804,78 @@ impl quote::ToTokens for FragmentImpl {
     }
 }
 
+/// The highest arity `map` function in `cynic::selection_set`.
+const MAX_MAP_ARITY: usize = 50;
+
+/// Combines the selection sets of `fields` with `constructor`.
+///
+/// Fragments with more fields than the largest `map` function are split
+/// into chunks that each decode to a tuple, and the tuples are then
+/// combined with another `map`.
+fn map_fields(constructor: TokenStream, fields: &[FieldSelectorCall]) -> TokenStream {
+    use quote::{format_ident, quote};
+
+    if fields.len() <= MAX_MAP_ARITY {
+        let map_function = format_ident!("map{}", fields.len());
+        return quote! {
+            ::cynic::selection_set::#map_function(
+                #constructor,
+                #(
+                    #fields
+                ),*
+            )
+        };
+    }
+
+    let chunks = fields.chunks(MAX_MAP_ARITY).collect::<Vec<_>>();
+    let chunk_params = chunks
+        .iter()
+        .enumerate()
+        .map(|(chunk_index, chunk)| {
+            (0..chunk.len())
+                .map(|i| format_ident!("field_{}_{}", chunk_index, i))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let chunk_selections = chunks
+        .iter()
+        .zip(&chunk_params)
+        .map(|(chunk, params)| map_fields(quote! { |#(#params),*| (#(#params,)*) }, chunk));
+
+    let all_params = chunk_params.iter().flatten();
+    let map_function = format_ident!("map{}", chunks.len());
+
+    quote! {
+        ::cynic::selection_set::#map_function(
+            |#((#(#chunk_params,)*)),*| #constructor(#(#all_params),*),
+            #(
+                #chunk_selections
+            ),*
+        )
+    }
+}
+
+/// Whether `ty` is `Self` or the type named `name`.
+fn is_self_type(ty: &syn::Type, name: &syn::Ident) -> bool {
+    if let syn::Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return (segment.ident == *name || segment.ident == "Self")
+                && segment.arguments.is_empty();
+        }
+    }
+    false
+}
+
+fn is_string_type(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == "String" && segment.arguments.is_empty();
+        }
+    }
+    false
+}
+
 /// Validates the FieldArguments against the arguments defined on field
 /// in the schema.  If everythings good, groups into required & optional
 /// arguments in the correct order.
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use crate::{schema, FieldType, Ident, TypeIndex};
+use crate::{
+    schema::{self, FieldExt},
+    FieldType, Ident, TypeIndex,
+};
 
 pub struct Schema {
     pub objects: HashMap<Ident, Object>,
@@ -8,9 +11,15 @@ pub struct Schema {
 
 impl From<schema::Document> for Schema {
     fn from(document: schema::Document) -> Self {
+        Schema::from(&document)
+    }
+}
+
+impl From<&schema::Document> for Schema {
+    fn from(document: &schema::Document) -> Self {
         use schema::{Definition, TypeDefinition};
 
-        let type_index = TypeIndex::for_schema(&document);
+        let type_index = TypeIndex::for_schema(document);
 
         let mut objects = HashMap::new();
 
@@ -20,6 +29,13 @@ impl From<schema::Document> for Schema {
                     let object = Object::from_object(object, &type_index);
                     objects.insert(object.name.clone(), object);
                 }
+                Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+                    // Fragments on interfaces can select any of the interface's
+                    // fields, so we treat them like objects here.
+                    let object =
+                        Object::from_object(&schema::interface_as_object(interface), &type_index);
+                    objects.insert(object.name.clone(), object);
+                }
                 _ => {}
             }
         }
@@ -32,39 +48,92 @@ pub struct Object {
     pub selector_struct: Ident,
     pub fields: HashMap<Ident, Field>,
     pub name: Ident,
+    pub graphql_name: String,
 }
 
 impl Object {
     fn from_object(obj: &schema::ObjectType, scalar_names: &TypeIndex) -> Object {
+        // These have to match the names of the functions in the query DSL.
+        let field_names = Ident::for_fields(obj.fields.iter().map(|f| f.name.as_str()));
+
         Object {
             selector_struct: Ident::for_type(&obj.name),
             fields: obj
                 .fields
                 .iter()
-                .map(|f| Field::from_field(f, scalar_names))
+                .zip(field_names)
+                .map(|(f, name)| Field::from_field(f, name, scalar_names))
                 .map(|f| (f.name.clone(), f))
                 .collect(),
             name: Ident::for_type(&obj.name),
+            graphql_name: obj.name.clone(),
+        }
+    }
+
+    /// Finds the field that a QueryFragment field with the GraphQL name
+    /// `graphql_name` selects.
+    ///
+    /// Fields with exactly that name are preferred, otherwise we look for a
+    /// field whose name is the same once converted to snake case.  It's an
+    /// error if there's more than one of those, as we can't tell which was
+    /// meant.
+    pub fn field_for(&self, graphql_name: &str) -> Result<Option<&Field>, String> {
+        if let Some(field) = self
+            .fields
+            .values()
+            .find(|field| field.graphql_name == graphql_name)
+        {
+            return Ok(Some(field));
+        }
+
+        let ident = Ident::for_field(graphql_name);
+        let mut candidates = self
+            .fields
+            .values()
+            .filter(|field| Ident::for_field(&field.graphql_name) == ident)
+            .collect::<Vec<_>>();
+
+        match candidates.len() {
+            0 | 1 => Ok(candidates.pop()),
+            _ => {
+                let mut names = candidates
+                    .iter()
+                    .map(|field| field.graphql_name.as_str())
+                    .collect::<Vec<_>>();
+                names.sort_unstable();
+
+                Err(format!(
+                    "{} could refer to any of the fields {} on {}.  Use rename to pick one.",
+                    graphql_name,
+                    names.join(", "),
+                    self.name
+                ))
+            }
         }
     }
 }
 
 pub struct Field {
     pub arguments: Vec<Argument>,
+    /// The name of the function for this field in the query DSL.
     pub name: Ident,
+    pub graphql_name: String,
     pub field_type: FieldType,
+    pub deprecation: Option<String>,
 }
 
 impl Field {
-    fn from_field(field: &schema::Field, type_index: &TypeIndex) -> Field {
+    fn from_field(field: &schema::Field, name: Ident, type_index: &TypeIndex) -> Field {
         Field {
-            name: Ident::for_field(&field.name),
+            name,
+            graphql_name: field.name.clone(),
             field_type: FieldType::from_schema_type(&field.field_type, type_index),
             arguments: field
                 .arguments
                 .iter()
                 .map(|a| Argument::from_input_value(a, type_index))
                 .collect(),
+            deprecation: field.deprecation(),
         }
     }
 }
@@ -79,7 +148,7 @@ impl Argument {
         let argument_type = FieldType::from_schema_type(&value.value_type, type_index);
         Argument {
             name: Ident::for_field(&value.name),
-            required: !argument_type.is_nullable(),
+            required: !argument_type.is_nullable() && value.default_value.is_none(),
         }
     }
 }
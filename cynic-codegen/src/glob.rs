@@ -0,0 +1,149 @@
+//! Just enough globbing to find the files that a schema is split across.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Whether `pattern` contains any wildcards.
+pub fn is_pattern(pattern: &str) -> bool {
+    pattern.contains(&['*', '?'][..])
+}
+
+/// Finds the files that match `pattern`, sorted by path.
+///
+/// `*` matches any part of a file or directory name, `?` matches any single
+/// character and a `**` directory matches any number of directories.
+pub fn expand(pattern: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+
+    for component in pattern.components() {
+        let segment = match component {
+            Component::Normal(segment) => segment.to_str(),
+            _ => None,
+        };
+
+        paths = match segment {
+            Some("**") => paths.iter().flat_map(|path| directories(path)).collect(),
+            Some(segment) if is_pattern(segment) => {
+                let segment = segment.chars().collect::<Vec<_>>();
+                paths
+                    .iter()
+                    .flat_map(|path| entries(path))
+                    .filter(|path| {
+                        path.file_name()
+                            .and_then(|name| name.to_str())
+                            .map_or(false, |name| {
+                                matches(&segment, &name.chars().collect::<Vec<_>>())
+                            })
+                    })
+                    .collect()
+            }
+            _ => paths
+                .into_iter()
+                .map(|mut path| {
+                    path.push(component);
+                    path
+                })
+                .collect(),
+        };
+    }
+
+    let mut files = paths
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// `dir` and every directory inside it.
+fn directories(dir: &Path) -> Vec<PathBuf> {
+    let mut found = vec![dir.to_path_buf()];
+    for entry in entries(dir) {
+        if entry.is_dir() {
+            found.extend(directories(&entry));
+        }
+    }
+    found
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, _) => name.is_empty(),
+        (Some((&'*', rest)), _) => {
+            matches(rest, name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        }
+        (Some((&'?', rest)), Some((_, name_rest))) => matches(rest, name_rest),
+        (Some((expected, rest)), Some((actual, name_rest))) => {
+            expected == actual && matches(rest, name_rest)
+        }
+        (Some(_), None) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches_str(pattern: &str, name: &str) -> bool {
+        matches(
+            &pattern.chars().collect::<Vec<_>>(),
+            &name.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_matching_names() {
+        assert!(matches_str("*.graphql", "films.graphql"));
+        assert!(matches_str("*.graphql", ".graphql"));
+        assert!(matches_str("film?.graphql", "films.graphql"));
+        assert!(matches_str("*s*", "films"));
+        assert!(!matches_str("*.graphql", "films.json"));
+        assert!(!matches_str("film?.graphql", "film.graphql"));
+    }
+
+    #[test]
+    fn test_expanding_patterns() {
+        let dir = std::env::temp_dir().join("cynic_test_expanding_patterns");
+        std::fs::create_dir_all(dir.join("types/nested")).unwrap();
+        for file in &[
+            "schema.graphql",
+            "types/films.graphql",
+            "types/planets.graphql",
+            "types/notes.txt",
+            "types/nested/people.graphql",
+        ] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+
+        assert_eq!(
+            expand(&dir.join("types/*.graphql")),
+            vec![
+                dir.join("types/films.graphql"),
+                dir.join("types/planets.graphql")
+            ]
+        );
+        assert_eq!(
+            expand(&dir.join("**/*.graphql")),
+            vec![
+                dir.join("schema.graphql"),
+                dir.join("types/films.graphql"),
+                dir.join("types/nested/people.graphql"),
+                dir.join("types/planets.graphql"),
+            ]
+        );
+        assert!(expand(&dir.join("other/*.graphql")).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
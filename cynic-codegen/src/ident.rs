@@ -17,10 +17,15 @@ impl Ident {
     }
 
     pub fn from_proc_macro2(i: &proc_macro2::Ident, rename: impl Into<Option<RenameRule>>) -> Self {
+        // Raw identifiers like `r#type` refer to the GraphQL name without the
+        // prefix.
+        let name = i.to_string();
+        let name = name.trim_start_matches("r#");
+
         if let Some(rename) = rename.into() {
-            Ident::new_spanned(rename.apply(i.to_string()), i.span())
+            Ident::new_spanned(rename.apply(name), i.span())
         } else {
-            Ident::new_spanned(i.to_string(), i.span())
+            Ident::new_spanned(name, i.span())
         }
     }
 
@@ -40,6 +45,16 @@ impl Ident {
         Ident(transform_keywords(s.as_ref().to_snake_case()), None)
     }
 
+    /// Converts a list of GraphQL field names into unique field idents.
+    ///
+    /// Different names can convert to the same ident - e.g. `isPublic` &
+    /// `is_public`.  When that happens the first name keeps the ident & later
+    /// ones get a numeric suffix (`is_public_2`), so the idents only depend on
+    /// the order of the names.
+    pub fn for_fields<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<Self> {
+        make_unique(names.into_iter().map(Ident::for_field).collect(), "_")
+    }
+
     pub fn for_module(s: &str) -> Self {
         let ident = s.to_snake_case();
         if ident == "super" {
@@ -237,6 +252,37 @@ lazy_static! {
     };
 }
 
+/// Adds suffixes to any idents that are duplicates of earlier ones.
+pub fn make_unique(idents: Vec<Ident>, separator: &str) -> Vec<Ident> {
+    let all_idents = idents
+        .iter()
+        .map(|ident| ident.0.clone())
+        .collect::<HashSet<_>>();
+    let mut used = HashSet::new();
+
+    idents
+        .into_iter()
+        .map(|ident| {
+            if used.insert(ident.0.clone()) {
+                return ident;
+            }
+
+            // Keywords already end with an underscore, so don't need another
+            let separator = if ident.0.ends_with('_') {
+                ""
+            } else {
+                separator
+            };
+
+            (2..)
+                .map(|n| format!("{}{}{}", ident.0, separator, n))
+                .find(|candidate| !all_idents.contains(candidate) && used.insert(candidate.clone()))
+                .map(|candidate| Ident(candidate, ident.1))
+                .expect("an unused suffix")
+        })
+        .collect()
+}
+
 fn transform_keywords(mut s: String) -> String {
     let s_ref: &str = &s;
     if KEYWORDS.contains(s_ref) {
@@ -255,4 +301,41 @@ mod tests {
         assert_eq!(transform_keywords("test".to_string()), "test".to_string());
         assert_eq!(transform_keywords("type".to_string()), "type_".to_string());
     }
+
+    #[test]
+    fn test_raw_identifiers() {
+        let raw_ident = proc_macro2::Ident::new_raw("type", Span::call_site());
+
+        assert_eq!(
+            Ident::from_proc_macro2(&raw_ident, None).to_string(),
+            "type"
+        );
+        assert_eq!(Ident::for_field("type").to_string(), "type_");
+        assert_eq!(Ident::for_field("self").to_string(), "self_");
+        assert_eq!(Ident::for_field("async").to_string(), "async_");
+    }
+
+    #[test]
+    fn test_unique_field_idents() {
+        let idents = Ident::for_fields(vec![
+            "isPublic",
+            "is_public",
+            "is_public_2",
+            "IsPublic",
+            "type",
+            "type_",
+        ]);
+
+        assert_eq!(
+            idents.iter().map(Ident::to_string).collect::<Vec<_>>(),
+            vec![
+                "is_public",
+                "is_public_3",
+                "is_public_2",
+                "is_public_4",
+                "type_",
+                "type_2"
+            ]
+        );
+    }
 }
@@ -6,11 +6,25 @@ pub struct InlineFragmentsDeriveInput {
     pub(super) ident: proc_macro2::Ident,
     pub(super) data: darling::ast::Data<SpannedValue<InlineFragmentsDeriveVariant>, ()>,
 
-    pub schema_path: SpannedValue<String>,
+    #[darling(default)]
+    pub schema_path: Option<SpannedValue<String>>,
+    /// The SDL of the schema, for schemas that are written inline rather
+    /// than loaded from `schema_path`.
+    #[darling(default)]
+    pub sdl: Option<SpannedValue<String>>,
     pub query_module: SpannedValue<String>,
     pub graphql_type: SpannedValue<String>,
     #[darling(default)]
     pub argument_struct: Option<syn::Ident>,
+
+    /// A field of the response objects to use instead of `__typename` when
+    /// deciding which variant to decode.
+    #[darling(default)]
+    pub discriminant_field: Option<SpannedValue<String>>,
+    /// A function that takes a `&serde_json::Value` & returns the GraphQL type
+    /// name to decode it as.
+    #[darling(default)]
+    pub discriminant_fn: Option<syn::Path>,
 }
 
 #[derive(darling::FromVariant)]
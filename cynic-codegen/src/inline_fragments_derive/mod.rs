@@ -1,7 +1,7 @@
 use darling::util::SpannedValue;
 use proc_macro2::{Span, TokenStream};
 
-use crate::{load_schema, schema, Ident, TypePath};
+use crate::{schema, suggestions::did_you_mean, Ident, TypePath};
 
 pub mod input;
 
@@ -23,13 +23,28 @@ pub(crate) fn inline_fragments_derive_impl(
 ) -> Result<TokenStream, syn::Error> {
     use quote::{quote, quote_spanned};
 
-    let schema =
-        load_schema(&*input.schema_path).map_err(|e| e.to_syn_error(input.schema_path.span()))?;
+    let discriminant = match (input.discriminant_field, input.discriminant_fn) {
+        (Some(field), Some(_)) => {
+            return Err(syn::Error::new(
+                field.span(),
+                "discriminant_field & discriminant_fn can't be used together",
+            ))
+        }
+        (Some(field), None) => Some(Discriminant::Field((*field).clone())),
+        (None, Some(function)) => Some(Discriminant::Function(function)),
+        (None, None) => None,
+    };
+
+    let schema = schema::load_derive_schema(&input.schema_path, &input.sdl, input.ident.span())?;
 
-    if !find_union_type(&input.graphql_type, &schema) {
+    if !find_union_or_interface_type(&input.graphql_type, &schema) {
         return Err(syn::Error::new(
             input.graphql_type.span(),
-            format!("Could not find a Union type named {}", &*input.graphql_type),
+            format!(
+                "Could not find a Union or Interface type named {}{}",
+                &*input.graphql_type,
+                did_you_mean(union_and_interface_names(&schema), &*input.graphql_type)
+            ),
         ));
     }
 
@@ -46,12 +61,13 @@ pub(crate) fn inline_fragments_derive_impl(
         let inline_fragments_impl = InlineFragmentsImpl {
             target_struct: input.ident.clone(),
             type_lock: TypePath::concat(&[
-                Ident::new_spanned(&*input.query_module, input.query_module.span()).into(),
+                TypePath::for_query_module(&*input.query_module, input.query_module.span()),
                 Ident::for_type(&*input.graphql_type).into(),
             ]),
             argument_struct,
             possible_types: possible_types_from_variants(variants)?,
             graphql_type_name: (*input.graphql_type).clone(),
+            discriminant,
         };
 
         Ok(quote! { #inline_fragments_impl })
@@ -66,7 +82,11 @@ pub(crate) fn inline_fragments_derive_impl(
 fn possible_types_from_variants(
     variants: &[SpannedValue<InlineFragmentsDeriveVariant>],
 ) -> Result<Vec<(syn::Ident, syn::Type)>, syn::Error> {
+    use quote::ToTokens;
+    use std::collections::HashMap;
+
     let mut result = vec![];
+    let mut seen_types = HashMap::new();
     for variant in variants {
         if variant.fields.style != darling::ast::Style::Tuple || variant.fields.fields.len() != 1 {
             return Err(syn::Error::new(
@@ -75,17 +95,40 @@ fn possible_types_from_variants(
             ));
         }
         let field = variant.fields.fields.first().unwrap();
+
+        // Each type can only be decoded by one variant, so any later variants
+        // with the same type would never be used.  We can't tell which GraphQL
+        // type a fragment is on from here, but query_module checks that for
+        // the fragments it contains.
+        let type_string = field.ty.to_token_stream().to_string();
+        if let Some(previous_variant) = seen_types.get(&type_string) {
+            return Err(syn::Error::new(
+                variant.span(),
+                format!(
+                    "This variant is unreachable: {} already handles {}",
+                    previous_variant, type_string
+                ),
+            ));
+        }
+        seen_types.insert(type_string, variant.ident.clone());
+
         result.push((variant.ident.clone(), field.ty.clone()));
     }
     Ok(result)
 }
 
+enum Discriminant {
+    Field(String),
+    Function(syn::Path),
+}
+
 struct InlineFragmentsImpl {
     target_struct: syn::Ident,
     type_lock: TypePath,
     argument_struct: syn::Type,
     possible_types: Vec<(syn::Ident, syn::Type)>,
     graphql_type_name: String,
+    discriminant: Option<Discriminant>,
 }
 
 impl quote::ToTokens for InlineFragmentsImpl {
@@ -99,6 +142,23 @@ impl quote::ToTokens for InlineFragmentsImpl {
         let variants: Vec<_> = self.possible_types.iter().map(|(v, _)| v).collect();
         let graphql_type = proc_macro2::Literal::string(&self.graphql_type_name);
 
+        let discriminant = match &self.discriminant {
+            Some(Discriminant::Field(field)) => {
+                let field = proc_macro2::Literal::string(field);
+                quote! {
+                    fn discriminant(value: &::cynic::serde_json::Value) -> Option<String> {
+                        value[#field].as_str().map(ToString::to_string)
+                    }
+                }
+            }
+            Some(Discriminant::Function(function)) => quote! {
+                fn discriminant(value: &::cynic::serde_json::Value) -> Option<String> {
+                    #function(value)
+                }
+            },
+            None => quote! {},
+        };
+
         tokens.append_all(quote! {
             #[automatically_derived]
             impl ::cynic::InlineFragments for #target_struct {
@@ -125,12 +185,26 @@ impl quote::ToTokens for InlineFragmentsImpl {
                 fn graphql_type() -> String {
                     #graphql_type.to_string()
                 }
+
+                #discriminant
             }
         });
     }
 }
 
-fn find_union_type(name: &str, schema: &schema::Document) -> bool {
+fn union_and_interface_names(schema: &schema::Document) -> impl Iterator<Item = &str> {
+    use graphql_parser::schema::{Definition, TypeDefinition};
+
+    schema.definitions.iter().filter_map(|def| match def {
+        Definition::TypeDefinition(TypeDefinition::Union(union)) => Some(union.name.as_str()),
+        Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+            Some(interface.name.as_str())
+        }
+        _ => None,
+    })
+}
+
+fn find_union_or_interface_type(name: &str, schema: &schema::Document) -> bool {
     for definition in &schema.definitions {
         use graphql_parser::schema::{Definition, TypeDefinition};
         match definition {
@@ -139,8 +213,88 @@ fn find_union_type(name: &str, schema: &schema::Document) -> bool {
                     return true;
                 }
             }
+            Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+                if interface.name == name {
+                    return true;
+                }
+            }
             _ => {}
         }
     }
     return false;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use darling::FromDeriveInput;
+
+    fn variants(input: syn::DeriveInput) -> Vec<SpannedValue<InlineFragmentsDeriveVariant>> {
+        let input = InlineFragmentsDeriveInput::from_derive_input(&input).unwrap();
+        match input.data {
+            darling::ast::Data::Enum(variants) => variants,
+            _ => panic!("Expected an enum"),
+        }
+    }
+
+    #[test]
+    fn test_possible_types_from_variants() {
+        let variants = variants(syn::parse_quote! {
+            #[cynic(schema_path = "", query_module = "", graphql_type = "")]
+            enum Search {
+                Film(Film),
+                Person(Person),
+            }
+        });
+
+        assert_eq!(possible_types_from_variants(&variants).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_unreachable_variants_are_rejected() {
+        let variants = variants(syn::parse_quote! {
+            #[cynic(schema_path = "", query_module = "", graphql_type = "")]
+            enum Search {
+                Film(Film),
+                OtherFilm(Film),
+            }
+        });
+
+        assert!(possible_types_from_variants(&variants).is_err());
+    }
+
+    #[test]
+    fn test_discriminants_are_exclusive() {
+        let input = InlineFragmentsDeriveInput::from_derive_input(&syn::parse_quote! {
+            #[cynic(
+                schema_path = "",
+                query_module = "",
+                graphql_type = "",
+                discriminant_field = "kind",
+                discriminant_fn = "discriminate"
+            )]
+            enum Search {
+                Film(Film),
+            }
+        })
+        .unwrap();
+
+        assert!(inline_fragments_derive_impl(input).is_err());
+    }
+
+    #[test]
+    fn test_finding_unions_and_interfaces() {
+        let schema = crate::schema::parse_schema(
+            r#"
+            interface Node { id: ID! }
+            type Film implements Node { id: ID! }
+            union Search = Film
+            "#,
+        )
+        .unwrap();
+
+        assert!(find_union_or_interface_type("Node", &schema));
+        assert!(find_union_or_interface_type("Search", &schema));
+        assert!(!find_union_or_interface_type("Film", &schema));
+    }
+}
@@ -4,14 +4,16 @@ use syn::spanned::Spanned;
 
 use super::InputObjectDeriveField;
 use crate::{
-    schema::InputValue, type_validation::check_types_are_compatible, FieldType, Ident, TypeIndex,
+    schema::InputValue,
+    type_validation::{check_types_are_compatible, maybe_undefined_inner_type, owned_type},
+    FieldType, Ident, TypeIndex, TypePath,
 };
 
 pub struct FieldSerializer<'a> {
     rust_field: &'a InputObjectDeriveField,
     graphql_field: &'a InputValue,
     graphql_field_type: FieldType,
-    query_module: &'a Ident,
+    query_module: &'a TypePath,
 }
 
 impl<'a> FieldSerializer<'a> {
@@ -19,7 +21,7 @@ impl<'a> FieldSerializer<'a> {
         rust_field: &'a InputObjectDeriveField,
         graphql_field: &'a InputValue,
         type_index: &TypeIndex,
-        query_module: &'a Ident,
+        query_module: &'a TypePath,
     ) -> FieldSerializer<'a> {
         FieldSerializer {
             rust_field,
@@ -31,10 +33,11 @@ impl<'a> FieldSerializer<'a> {
 
     /// Validates the FieldSerializer definition, returning errors if there are any.
     pub fn validate(&self) -> Option<syn::Error> {
-        // First, check for type errors
-        if let Err(e) =
-            check_types_are_compatible(&self.graphql_field_type, &self.rust_field.ty, false)
-        {
+        // First, check for type errors.  Borrowed & MaybeUndefined fields are
+        // checked as if they were the equivalent owned Option.
+        let checked_type = self.checked_type();
+        let rust_type = checked_type.as_ref().unwrap_or(&self.rust_field.ty);
+        if let Err(e) = check_types_are_compatible(&self.graphql_field_type, rust_type, false) {
             return Some(e);
         }
 
@@ -48,6 +51,20 @@ impl<'a> FieldSerializer<'a> {
         None
     }
 
+    /// The type to check this field against, if it can't be passed to the
+    /// type checking function directly.
+    ///
+    /// For borrowed fields this is the owned equivalent, and for
+    /// `MaybeUndefined<T>` it's `Option<T>`.
+    fn checked_type(&self) -> Option<syn::Type> {
+        if let Some(inner) = maybe_undefined_inner_type(&self.rust_field.ty) {
+            let inner = owned_type(inner).unwrap_or_else(|| inner.clone());
+            return Some(syn::parse_quote! { Option<#inner> });
+        }
+
+        owned_type(&self.rust_field.ty)
+    }
+
     pub fn type_check_fn(&self) -> TokenStream {
         // The check_types_are_compatible call in validate only checks for Option
         // and Vec wrappers - we don't have access to any info
@@ -68,7 +85,7 @@ impl<'a> FieldSerializer<'a> {
 
         quote! {
             fn #rust_field_name<#generic_param_definition>(data: &#arg_type) ->
-                Result<::cynic::serde_json::Value, ::cynic::SerializeError> {
+                Result<::cynic::__private::serde_json::Value, ::cynic::SerializeError> {
                     data.serialize()
                 }
         }
@@ -82,8 +99,29 @@ impl<'a> FieldSerializer<'a> {
 
         // For each field we just call our type checking function with the current field
         // and insert it into the output Map.
-        let insert_call = quote_spanned! { field_span =>
-            #output_struct.insert(#gql_field_name.to_string(), #rust_field_name(&self.#rust_field_name)?);
+        //
+        // Some fields can't be passed to the type checking function, so instead
+        // we make sure it accepts their checked type and serialize the field directly.
+        let insert_call = if let Some(checked_type) = self.checked_type() {
+            quote_spanned! { field_span =>
+                let _: fn(&#checked_type) -> Result<::cynic::__private::serde_json::Value, ::cynic::SerializeError> = #rust_field_name;
+                #output_struct.insert(#gql_field_name.to_string(), self.#rust_field_name.serialize()?);
+            }
+        } else {
+            quote_spanned! { field_span =>
+                #output_struct.insert(#gql_field_name.to_string(), #rust_field_name(&self.#rust_field_name)?);
+            }
+        };
+
+        // Undefined fields are always left out.
+        let insert_call = if self.is_maybe_undefined() {
+            quote! {
+                if !self.#rust_field_name.is_undefined() {
+                    #insert_call
+                }
+            }
+        } else {
+            insert_call
         };
 
         if let Some(skip_check_fn) = &self.rust_field.skip_serializing_if {
@@ -97,4 +135,8 @@ impl<'a> FieldSerializer<'a> {
             insert_call
         }
     }
+
+    pub fn is_maybe_undefined(&self) -> bool {
+        maybe_undefined_inner_type(&self.rust_field.ty).is_some()
+    }
 }
@@ -3,12 +3,18 @@ use darling::util::SpannedValue;
 use crate::ident::RenameAll;
 
 #[derive(darling::FromDeriveInput)]
-#[darling(attributes(cynic), supports(struct_named))]
+#[darling(attributes(cynic), supports(struct_named, enum_newtype))]
 pub struct InputObjectDeriveInput {
     pub(super) ident: proc_macro2::Ident,
-    pub(super) data: darling::ast::Data<(), InputObjectDeriveField>,
+    pub(super) generics: syn::Generics,
+    pub(super) data: darling::ast::Data<InputObjectDeriveVariant, InputObjectDeriveField>,
 
-    pub schema_path: SpannedValue<String>,
+    #[darling(default)]
+    pub schema_path: Option<SpannedValue<String>>,
+    /// The SDL of the schema, for schemas that are written inline rather
+    /// than loaded from `schema_path`.
+    #[darling(default)]
+    pub sdl: Option<SpannedValue<String>>,
     pub query_module: SpannedValue<String>,
     pub graphql_type: SpannedValue<String>,
 
@@ -17,6 +23,9 @@ pub struct InputObjectDeriveInput {
 
     #[darling(default)]
     pub(super) rename_all: Option<RenameAll>,
+
+    #[darling(default)]
+    pub(super) impl_serialize: bool,
 }
 
 #[derive(Debug, darling::FromField)]
@@ -31,3 +40,15 @@ pub struct InputObjectDeriveField {
     #[darling(default)]
     pub(super) rename: Option<SpannedValue<String>>,
 }
+
+/// A variant of an enum InputObject, which is used for `@oneOf`
+/// InputObjects.
+#[derive(Debug, darling::FromVariant)]
+#[darling(attributes(cynic))]
+pub struct InputObjectDeriveVariant {
+    pub(super) ident: proc_macro2::Ident,
+    pub(super) fields: darling::ast::Fields<InputObjectDeriveField>,
+
+    #[darling(default)]
+    pub(super) rename: Option<SpannedValue<String>>,
+}
@@ -4,14 +4,22 @@ use syn::spanned::Spanned;
 
 use crate::{
     ident::{RenameAll, RenameRule},
-    load_schema,
-    schema::{Definition, Document, InputObjectType, InputValue, TypeDefinition},
-    Ident, TypeIndex,
+    schema::{
+        load_derive_schema, Definition, Document, InputObjectType, InputObjectTypeExt, InputValue,
+        SchemaSource, TypeDefinition,
+    },
+    serde_serialize::serde_serialize_impl,
+    suggestions::did_you_mean,
+    type_validation::{is_option_type, maybe_undefined_inner_type},
+    Ident, TypeIndex, TypePath,
 };
 
 mod field_serializer;
 use field_serializer::FieldSerializer;
 
+mod one_of;
+use one_of::one_of_enum_impl;
+
 pub(crate) mod input;
 
 use input::InputObjectDeriveField;
@@ -23,8 +31,7 @@ pub fn input_object_derive(ast: &syn::DeriveInput) -> Result<TokenStream, syn::E
     let enum_span = ast.span();
 
     match InputObjectDeriveInput::from_derive_input(ast) {
-        Ok(input) => load_schema(&*input.schema_path)
-            .map_err(|e| e.to_syn_error(input.schema_path.span()))
+        Ok(input) => load_derive_schema(&input.schema_path, &input.sdl, enum_span)
             .and_then(|schema| input_object_derive_impl(input, &schema, enum_span))
             .or_else(|e| Ok(e.to_compile_error())),
         Err(e) => Ok(e.write_errors()),
@@ -47,11 +54,18 @@ pub fn input_object_derive_impl(
         None
     });
     if input_object_def.is_none() {
+        let schema_source = SchemaSource::from_attributes(
+            &input.schema_path,
+            &input.sdl,
+            input.graphql_type.span(),
+        )?;
         return Err(syn::Error::new(
             input.graphql_type.span(),
             format!(
-                "Could not find an InputObject named {} in {}",
-                *input.graphql_type, *input.schema_path
+                "Could not find an InputObject named {} in {}{}",
+                *input.graphql_type,
+                schema_source.name(),
+                did_you_mean(input_object_names(schema), &*input.graphql_type)
             ),
         ));
     }
@@ -59,13 +73,20 @@ pub fn input_object_derive_impl(
 
     let type_index = TypeIndex::for_schema(&schema);
 
+    if let darling::ast::Data::Enum(variants) = &input.data {
+        return one_of_enum_impl(&input, variants, input_object_def, &type_index, struct_span);
+    }
+
     if let darling::ast::Data::Struct(fields) = &input.data {
         let ident = &input.ident;
+        let generics = &input.generics;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         let input_marker_ident = Ident::for_type(&*input.graphql_type);
-        let query_module = Ident::for_module(&input.query_module);
+        let query_module =
+            TypePath::for_query_module(&input.query_module, input.query_module.span());
         let input_object_name = ident.to_string();
 
-        let pairs = match join_fields(
+        let mut pairs = match join_fields(
             &fields.fields,
             input_object_def,
             &input_object_name,
@@ -77,6 +98,16 @@ pub fn input_object_derive_impl(
             Err(error_tokens) => return Ok(error_tokens),
         };
 
+        // join_fields returns fields in an arbitrary order, so we sort them to make sure
+        // we always serialize fields in the same order.
+        pairs.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+
+        let default_impl = if all_fields_optional(&pairs, input_object_def) {
+            Some(default_impl(ident, generics, &pairs))
+        } else {
+            None
+        };
+
         let field_serializers = pairs
             .into_iter()
             .map(|(rust_field, graphql_field)| {
@@ -108,36 +139,221 @@ pub fn input_object_derive_impl(
 
         let map_len = field_serializers.len();
 
+        // The type system can't stop more than one field of a @oneOf struct
+        // being set, so we check it when serializing.
+        let one_of_check = if input_object_def.is_one_of() {
+            let message = format!(
+                "Exactly one field of the @oneOf InputObject {} must be set",
+                input_object_def.name
+            );
+            Some(quote! {
+                if output.values().filter(|value| !value.is_null()).count() != 1 {
+                    return Err(#message.into());
+                }
+            })
+        } else {
+            None
+        };
+
+        let serde_impl = if input.impl_serialize {
+            Some(serde_serialize_impl(ident, generics))
+        } else {
+            None
+        };
+
+        let into_argument_impls = into_argument_impls(ident, generics);
+
         Ok(quote! {
             #[automatically_derived]
-            impl ::cynic::InputObject<#query_module::#input_marker_ident> for #ident {}
+            impl #impl_generics ::cynic::InputObject<#query_module::#input_marker_ident> for #ident #ty_generics #where_clause {}
 
             #[automatically_derived]
-            impl ::cynic::SerializableArgument for #ident {
-                fn serialize(&self) -> Result<::cynic::serde_json::Value, ::cynic::SerializeError> {
+            impl #impl_generics ::cynic::SerializableArgument for #ident #ty_generics #where_clause {
+                fn serialize(&self) -> Result<::cynic::__private::serde_json::Value, ::cynic::SerializeError> {
                     use ::cynic::{Scalar, Enum, SerializableArgument};
                     #(
                         #typecheck_funcs
                     )*
 
-                    let mut output = ::cynic::serde_json::map::Map::with_capacity(#map_len);
+                    let mut output = ::cynic::__private::serde_json::map::Map::with_capacity(#map_len);
 
                     #(#field_inserts)*
 
-                    Ok(::cynic::serde_json::Value::Object(output))
+                    #one_of_check
+
+                    Ok(::cynic::__private::serde_json::Value::Object(output))
                 }
             }
 
-            ::cynic::impl_into_argument_for_options!(#ident);
+            #into_argument_impls
+
+            #serde_impl
+
+            #default_impl
         })
     } else {
-        Err(syn::Error::new(
-            struct_span,
-            format!("Enum can only be derived from an enum"),
-        ))
+        unreachable!("InputObject enums are handled by one_of_enum_impl")
     }
 }
 
+/// Checks whether every field of an InputObject is optional, both in the
+/// GraphQL schema and on the rust struct (as an `Option` or `MaybeUndefined`).
+fn all_fields_optional(
+    pairs: &[(&InputObjectDeriveField, &InputValue)],
+    input_object_def: &InputObjectType,
+) -> bool {
+    use crate::schema::TypeExt;
+
+    input_object_def
+        .fields
+        .iter()
+        .all(|value| !value.value_type.is_required())
+        && pairs.iter().all(|(rust_field, _)| {
+            is_option_type(&rust_field.ty) || maybe_undefined_inner_type(&rust_field.ty).is_some()
+        })
+}
+
+/// Generates a `Default` impl & an `is_empty` function for InputObjects
+/// where every field is optional.
+fn default_impl(
+    ident: &proc_macro2::Ident,
+    generics: &syn::Generics,
+    pairs: &[(&InputObjectDeriveField, &InputValue)],
+) -> TokenStream {
+    use quote::quote;
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let field_idents = pairs
+        .iter()
+        .map(|(rust_field, _)| rust_field.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+
+    let empty_checks = pairs.iter().map(|(rust_field, _)| {
+        let ident = rust_field.ident.as_ref().unwrap();
+        if maybe_undefined_inner_type(&rust_field.ty).is_some() {
+            quote! { self.#ident.is_undefined() }
+        } else {
+            quote! { self.#ident.is_none() }
+        }
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::default::Default for #ident #ty_generics #where_clause {
+            fn default() -> Self {
+                #ident {
+                    #(#field_idents: ::core::default::Default::default(),)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns true if none of the fields of this InputObject have been set.
+            pub fn is_empty(&self) -> bool {
+                true #(&& #empty_checks)*
+            }
+        }
+    }
+}
+
+/// Generates the `IntoArgument` impls that let an InputObject be passed to
+/// optional & list arguments.
+///
+/// `impl_into_argument_for_options!` can't be used for InputObjects with
+/// generics, so we write these out in full for them.
+fn into_argument_impls(ident: &proc_macro2::Ident, generics: &syn::Generics) -> TokenStream {
+    use quote::quote;
+
+    if generics.params.is_empty() {
+        return quote! {
+            ::cynic::impl_into_argument_for_options!(#ident);
+        };
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ty = quote! { #ident #ty_generics };
+
+    let mut ref_generics = generics.clone();
+    ref_generics
+        .params
+        .insert(0, syn::parse_quote! { '__cynic_ref });
+    let (ref_impl_generics, _, _) = ref_generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::cynic::IntoArgument<Option<#ty>> for #ty #where_clause {
+            type Output = Option<#ty>;
+
+            fn into_argument(self) -> Option<#ty> {
+                Some(self)
+            }
+        }
+
+        #[automatically_derived]
+        impl #ref_impl_generics ::cynic::IntoArgument<#ty> for &'__cynic_ref #ty #where_clause {
+            type Output = &'__cynic_ref #ty;
+
+            fn into_argument(self) -> &'__cynic_ref #ty {
+                self
+            }
+        }
+
+        #[automatically_derived]
+        impl #ref_impl_generics ::cynic::IntoArgument<Option<#ty>> for &'__cynic_ref #ty #where_clause {
+            type Output = Option<&'__cynic_ref #ty>;
+
+            fn into_argument(self) -> Option<&'__cynic_ref #ty> {
+                Some(self)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::cynic::IntoArgument<Vec<#ty>> for #ty #where_clause {
+            type Output = Vec<#ty>;
+
+            fn into_argument(self) -> Vec<#ty> {
+                vec![self]
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::cynic::IntoArgument<Vec<Option<#ty>>> for #ty #where_clause {
+            type Output = Vec<Option<#ty>>;
+
+            fn into_argument(self) -> Vec<Option<#ty>> {
+                vec![Some(self)]
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::cynic::IntoArgument<Option<Vec<#ty>>> for #ty #where_clause {
+            type Output = Option<Vec<#ty>>;
+
+            fn into_argument(self) -> Option<Vec<#ty>> {
+                Some(vec![self])
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::cynic::IntoArgument<Option<Vec<Option<#ty>>>> for #ty #where_clause {
+            type Output = Option<Vec<Option<#ty>>>;
+
+            fn into_argument(self) -> Option<Vec<Option<#ty>>> {
+                Some(vec![Some(self)])
+            }
+        }
+    }
+}
+
+fn input_object_names(schema: &Document) -> impl Iterator<Item = &str> {
+    schema.definitions.iter().filter_map(|def| match def {
+        Definition::TypeDefinition(TypeDefinition::InputObject(obj)) => Some(obj.name.as_str()),
+        _ => None,
+    })
+}
+
 fn join_fields<'a>(
     fields: &'a [InputObjectDeriveField],
     input_object_def: &'a InputObjectType,
@@ -178,8 +394,16 @@ fn join_fields<'a>(
                 syn::Error::new(
                     field.ident.span(),
                     format!(
-                        "Could not find a field {} in the GraphQL InputObject {}",
-                        transformed_ident, input_object_name
+                        "Could not find a field {} in the GraphQL InputObject {}{}",
+                        transformed_ident,
+                        input_object_name,
+                        did_you_mean(
+                            input_object_def
+                                .fields
+                                .iter()
+                                .map(|field| field.name.as_str()),
+                            &transformed_ident.to_string()
+                        )
                     ),
                 )
                 .to_compile_error(),
@@ -0,0 +1,167 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+
+use super::input::{InputObjectDeriveInput, InputObjectDeriveVariant};
+use crate::{
+    ident::RenameRule,
+    schema::{InputObjectType, InputObjectTypeExt},
+    serde_serialize::serde_serialize_impl,
+    suggestions::did_you_mean,
+    type_validation::check_types_are_compatible,
+    FieldType, Ident, TypeIndex, TypePath,
+};
+
+/// Generates an InputObject impl for an enum.
+///
+/// This is only supported for `@oneOf` InputObjects: each variant is one of
+/// the fields of the InputObject, so exactly one field is always sent.
+pub fn one_of_enum_impl(
+    input: &InputObjectDeriveInput,
+    variants: &[InputObjectDeriveVariant],
+    input_object_def: &InputObjectType,
+    type_index: &TypeIndex,
+    enum_span: Span,
+) -> Result<TokenStream, syn::Error> {
+    if !input_object_def.is_one_of() {
+        return Err(syn::Error::new(
+            enum_span,
+            format!(
+                "InputObject can only be derived on an enum for @oneOf InputObjects, and {} is not @oneOf.  Did you mean to use a struct?",
+                input_object_def.name
+            ),
+        ));
+    }
+
+    if variants.is_empty() {
+        return Err(syn::Error::new(
+            enum_span,
+            "An InputObject enum needs at least one variant",
+        ));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let input_marker_ident = Ident::for_type(&input_object_def.name);
+    let query_module = TypePath::for_query_module(&input.query_module, input.query_module.span());
+
+    let mut errors = TokenStream::new();
+    let mut serialize_fns = vec![];
+    let mut match_arms = vec![];
+
+    for variant in variants {
+        let graphql_name = Ident::from_proc_macro2(
+            &variant.ident,
+            RenameRule::new(input.rename_all, variant.rename.as_ref()),
+        );
+
+        let graphql_field = input_object_def
+            .fields
+            .iter()
+            .find(|field| Ident::new(&field.name) == graphql_name);
+
+        let graphql_field = match graphql_field {
+            Some(field) => field,
+            None => {
+                errors.extend(
+                    syn::Error::new(
+                        variant.ident.span(),
+                        format!(
+                            "Could not find a field {} in the GraphQL InputObject {}{}",
+                            graphql_name,
+                            input_object_def.name,
+                            did_you_mean(
+                                input_object_def
+                                    .fields
+                                    .iter()
+                                    .map(|field| field.name.as_str()),
+                                &graphql_name.to_string()
+                            )
+                        ),
+                    )
+                    .to_compile_error(),
+                );
+                continue;
+            }
+        };
+
+        // Every field of a @oneOf InputObject is nullable, but a variant is
+        // only used when its field is set - so it holds the required type.
+        let field_type =
+            FieldType::from_schema_type(&graphql_field.value_type, type_index).as_required();
+
+        // darling only accepts newtype variants, so there's always one field.
+        let rust_type = &variant.fields.fields[0].ty;
+        if let Err(e) = check_types_are_compatible(&field_type, rust_type, false) {
+            errors.extend(e.to_compile_error());
+            continue;
+        }
+
+        let generic_param = field_type.generic_parameter(Ident::new("T"));
+        let arg_type = field_type.to_tokens(
+            generic_param.as_ref().map(|p| p.name.clone()),
+            query_module.clone().into(),
+        );
+        let generic_param_definition =
+            generic_param.map(|p| p.to_tokens(query_module.clone().into()));
+
+        let fn_name = format_ident!(
+            "serialize_{}",
+            Ident::for_field(variant.ident.to_string()).to_string()
+        );
+        let variant_ident = &variant.ident;
+        let gql_field_name = proc_macro2::Literal::string(&graphql_field.name);
+
+        serialize_fns.push(quote! {
+            fn #fn_name<#generic_param_definition>(data: &#arg_type) ->
+                Result<::cynic::__private::serde_json::Value, ::cynic::SerializeError> {
+                    data.serialize()
+                }
+        });
+
+        match_arms.push(quote_spanned! { variant_ident.span() =>
+            Self::#variant_ident(value) => {
+                output.insert(#gql_field_name.to_string(), #fn_name(value)?);
+            }
+        });
+    }
+
+    if !errors.is_empty() {
+        return Ok(errors);
+    }
+
+    let serde_impl = if input.impl_serialize {
+        Some(serde_serialize_impl(ident, &input.generics))
+    } else {
+        None
+    };
+
+    let into_argument_impls = super::into_argument_impls(ident, &input.generics);
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::cynic::InputObject<#query_module::#input_marker_ident> for #ident #ty_generics #where_clause {}
+
+        #[automatically_derived]
+        impl #impl_generics ::cynic::SerializableArgument for #ident #ty_generics #where_clause {
+            fn serialize(&self) -> Result<::cynic::__private::serde_json::Value, ::cynic::SerializeError> {
+                use ::cynic::{Scalar, Enum, SerializableArgument};
+                #(
+                    #serialize_fns
+                )*
+
+                let mut output = ::cynic::__private::serde_json::map::Map::with_capacity(1);
+
+                match self {
+                    #(#match_arms)*
+                }
+
+                Ok(::cynic::__private::serde_json::Value::Object(output))
+            }
+        }
+
+        #into_argument_impls
+
+        #serde_impl
+    })
+}
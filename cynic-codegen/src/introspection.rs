@@ -0,0 +1,456 @@
+//! Converts the result of an introspection query into a GraphQL SDL schema,
+//! so schemas can be loaded from servers that don't provide an SDL file.
+
+use serde::Deserialize;
+use std::fmt::Write;
+
+use crate::Error;
+
+/// Converts the JSON result of an introspection query into SDL.
+///
+/// The `IntrospectionQuery` from `cynic-introspection` fetches everything
+/// this needs.
+///
+/// This accepts either a full GraphQL response (`{"data": {"__schema": ...}}`)
+/// or just the `data` (`{"__schema": ...}`).
+pub fn introspection_to_sdl(json: &str) -> Result<String, Error> {
+    let response = serde_json::from_str::<IntrospectionResponse>(json)
+        .map_err(|e| Error::IntrospectionError(e.to_string()))?;
+
+    let schema = match response {
+        IntrospectionResponse::Response { data } => data.schema,
+        IntrospectionResponse::Data(data) => data.schema,
+    };
+
+    Ok(schema.to_sdl())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IntrospectionResponse {
+    Response { data: IntrospectionData },
+    Data(IntrospectionData),
+}
+
+#[derive(Deserialize)]
+struct IntrospectionData {
+    #[serde(rename = "__schema")]
+    schema: Schema,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Schema {
+    query_type: Option<NamedType>,
+    mutation_type: Option<NamedType>,
+    subscription_type: Option<NamedType>,
+    types: Vec<FullType>,
+}
+
+#[derive(Deserialize)]
+struct NamedType {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FullType {
+    kind: TypeKind,
+    name: String,
+    description: Option<String>,
+    fields: Option<Vec<Field>>,
+    input_fields: Option<Vec<InputValue>>,
+    interfaces: Option<Vec<TypeRef>>,
+    enum_values: Option<Vec<EnumValue>>,
+    possible_types: Option<Vec<TypeRef>>,
+    #[serde(default)]
+    is_one_of: bool,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+    List,
+    NonNull,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Field {
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    args: Vec<InputValue>,
+    #[serde(rename = "type")]
+    field_type: TypeRef,
+    #[serde(default)]
+    is_deprecated: bool,
+    deprecation_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InputValue {
+    name: String,
+    description: Option<String>,
+    #[serde(rename = "type")]
+    value_type: TypeRef,
+    default_value: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EnumValue {
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    is_deprecated: bool,
+    deprecation_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypeRef {
+    kind: TypeKind,
+    name: Option<String>,
+    of_type: Option<Box<TypeRef>>,
+}
+
+const BUILTIN_SCALARS: &[&str] = &["String", "Int", "Float", "Boolean", "ID"];
+
+impl Schema {
+    fn to_sdl(&self) -> String {
+        let mut sdl = String::new();
+
+        let roots = [
+            ("query", &self.query_type),
+            ("mutation", &self.mutation_type),
+            ("subscription", &self.subscription_type),
+        ];
+        sdl.push_str("schema {\n");
+        for (operation, root) in roots.iter() {
+            if let Some(root) = root {
+                writeln!(sdl, "  {}: {}", operation, root.name).unwrap();
+            }
+        }
+        sdl.push_str("}\n");
+
+        let types = self.types.iter().filter(|ty| {
+            !ty.name.starts_with("__")
+                && !(ty.kind == TypeKind::Scalar && BUILTIN_SCALARS.contains(&ty.name.as_str()))
+        });
+
+        for ty in types {
+            sdl.push('\n');
+            ty.write_sdl(&mut sdl);
+        }
+
+        sdl
+    }
+}
+
+impl FullType {
+    fn write_sdl(&self, sdl: &mut String) {
+        write_description(sdl, &self.description, "");
+
+        match self.kind {
+            TypeKind::Scalar => writeln!(sdl, "scalar {}", self.name).unwrap(),
+            TypeKind::Object | TypeKind::Interface => {
+                let keyword = if self.kind == TypeKind::Object {
+                    "type"
+                } else {
+                    "interface"
+                };
+                write!(sdl, "{} {}", keyword, self.name).unwrap();
+
+                let interfaces = type_names(&self.interfaces);
+                if !interfaces.is_empty() {
+                    write!(sdl, " implements {}", interfaces.join(" & ")).unwrap();
+                }
+
+                sdl.push_str(" {\n");
+                for field in self.fields.iter().flatten() {
+                    field.write_sdl(sdl);
+                }
+                sdl.push_str("}\n");
+            }
+            TypeKind::Union => writeln!(
+                sdl,
+                "union {} = {}",
+                self.name,
+                type_names(&self.possible_types).join(" | ")
+            )
+            .unwrap(),
+            TypeKind::Enum => {
+                writeln!(sdl, "enum {} {{", self.name).unwrap();
+                for value in self.enum_values.iter().flatten() {
+                    write_description(sdl, &value.description, "  ");
+                    write!(sdl, "  {}", value.name).unwrap();
+                    write_deprecation(sdl, value.is_deprecated, &value.deprecation_reason);
+                    sdl.push('\n');
+                }
+                sdl.push_str("}\n");
+            }
+            TypeKind::InputObject => {
+                let directives = if self.is_one_of { " @oneOf" } else { "" };
+                writeln!(sdl, "input {}{} {{", self.name, directives).unwrap();
+                for field in self.input_fields.iter().flatten() {
+                    write_description(sdl, &field.description, "  ");
+                    writeln!(sdl, "  {}", field.to_sdl()).unwrap();
+                }
+                sdl.push_str("}\n");
+            }
+            // Lists & non nulls only appear in type references
+            TypeKind::List | TypeKind::NonNull => {}
+        }
+    }
+}
+
+impl Field {
+    fn write_sdl(&self, sdl: &mut String) {
+        write_description(sdl, &self.description, "  ");
+        write!(sdl, "  {}", self.name).unwrap();
+
+        if !self.args.is_empty() {
+            let args = self.args.iter().map(InputValue::to_sdl).collect::<Vec<_>>();
+            write!(sdl, "({})", args.join(", ")).unwrap();
+        }
+
+        write!(sdl, ": {}", self.field_type.to_sdl()).unwrap();
+        write_deprecation(sdl, self.is_deprecated, &self.deprecation_reason);
+        sdl.push('\n');
+    }
+}
+
+impl InputValue {
+    fn to_sdl(&self) -> String {
+        // Descriptions on arguments aren't used by codegen, so we don't
+        // bother outputting them.
+        match &self.default_value {
+            Some(default) => format!("{}: {} = {}", self.name, self.value_type.to_sdl(), default),
+            None => format!("{}: {}", self.name, self.value_type.to_sdl()),
+        }
+    }
+}
+
+impl TypeRef {
+    fn to_sdl(&self) -> String {
+        let inner = || {
+            self.of_type
+                .as_ref()
+                .map(|ty| ty.to_sdl())
+                .unwrap_or_default()
+        };
+
+        match self.kind {
+            TypeKind::NonNull => format!("{}!", inner()),
+            TypeKind::List => format!("[{}]", inner()),
+            _ => self.name.clone().unwrap_or_default(),
+        }
+    }
+}
+
+fn type_names(types: &Option<Vec<TypeRef>>) -> Vec<String> {
+    types
+        .iter()
+        .flatten()
+        .filter_map(|ty| ty.name.clone())
+        .collect()
+}
+
+fn write_description(sdl: &mut String, description: &Option<String>, indent: &str) {
+    if let Some(description) = description {
+        writeln!(sdl, "{}\"{}\"", indent, escape_string(description)).unwrap();
+    }
+}
+
+fn write_deprecation(sdl: &mut String, is_deprecated: bool, reason: &Option<String>) {
+    if !is_deprecated {
+        return;
+    }
+
+    match reason {
+        Some(reason) => write!(sdl, " @deprecated(reason: \"{}\")", escape_string(reason)).unwrap(),
+        None => sdl.push_str(" @deprecated"),
+    }
+}
+
+fn escape_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{parse_schema, Definition, FieldExt, TypeDefinition};
+
+    const INTROSPECTION: &str = r#"{
+      "data": {
+        "__schema": {
+          "queryType": { "name": "Query" },
+          "mutationType": null,
+          "subscriptionType": null,
+          "types": [
+            {
+              "kind": "OBJECT",
+              "name": "Query",
+              "description": "The \"root\" query",
+              "fields": [
+                {
+                  "name": "films",
+                  "description": null,
+                  "args": [
+                    {
+                      "name": "first",
+                      "description": null,
+                      "type": { "kind": "SCALAR", "name": "Int", "ofType": null },
+                      "defaultValue": "10"
+                    }
+                  ],
+                  "type": {
+                    "kind": "NON_NULL",
+                    "name": null,
+                    "ofType": {
+                      "kind": "LIST",
+                      "name": null,
+                      "ofType": { "kind": "OBJECT", "name": "Film", "ofType": null }
+                    }
+                  },
+                  "isDeprecated": true,
+                  "deprecationReason": "Use allFilms"
+                }
+              ],
+              "inputFields": null,
+              "interfaces": [],
+              "enumValues": null,
+              "possibleTypes": null
+            },
+            {
+              "kind": "OBJECT",
+              "name": "Film",
+              "description": null,
+              "fields": [
+                {
+                  "name": "title",
+                  "description": null,
+                  "args": [],
+                  "type": { "kind": "SCALAR", "name": "String", "ofType": null },
+                  "isDeprecated": false,
+                  "deprecationReason": null
+                }
+              ],
+              "inputFields": null,
+              "interfaces": [],
+              "enumValues": null,
+              "possibleTypes": null
+            },
+            {
+              "kind": "ENUM",
+              "name": "Episode",
+              "description": null,
+              "fields": null,
+              "inputFields": null,
+              "interfaces": null,
+              "enumValues": [
+                { "name": "NEWHOPE", "description": null, "isDeprecated": false, "deprecationReason": null }
+              ],
+              "possibleTypes": null
+            },
+            {
+              "kind": "SCALAR",
+              "name": "String",
+              "description": "The builtin string type",
+              "fields": null,
+              "inputFields": null,
+              "interfaces": null,
+              "enumValues": null,
+              "possibleTypes": null
+            },
+            {
+              "kind": "OBJECT",
+              "name": "__Type",
+              "description": null,
+              "fields": [],
+              "inputFields": null,
+              "interfaces": [],
+              "enumValues": null,
+              "possibleTypes": null
+            }
+          ],
+          "directives": []
+        }
+      }
+    }"#;
+
+    #[test]
+    fn test_introspection_to_sdl() {
+        let sdl = introspection_to_sdl(INTROSPECTION).unwrap();
+
+        assert_eq!(
+            sdl,
+            r#"schema {
+  query: Query
+}
+
+"The \"root\" query"
+type Query {
+  films(first: Int = 10): [Film]! @deprecated(reason: "Use allFilms")
+}
+
+type Film {
+  title: String
+}
+
+enum Episode {
+  NEWHOPE
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_introspection_sdl_parses() {
+        let document = parse_schema(&introspection_to_sdl(INTROSPECTION).unwrap()).unwrap();
+
+        let query = document.definitions.iter().find_map(|def| match def {
+            Definition::TypeDefinition(TypeDefinition::Object(obj)) if obj.name == "Query" => {
+                Some(obj)
+            }
+            _ => None,
+        });
+
+        assert_eq!(
+            query.unwrap().fields[0].deprecation(),
+            Some("Use allFilms".to_string())
+        );
+    }
+
+    #[test]
+    fn test_introspection_data_without_response() {
+        let data =
+            serde_json::from_str::<serde_json::Value>(INTROSPECTION).unwrap()["data"].to_string();
+
+        assert_eq!(
+            introspection_to_sdl(&data).unwrap(),
+            introspection_to_sdl(INTROSPECTION).unwrap()
+        );
+        assert!(introspection_to_sdl("{}").is_err());
+    }
+}
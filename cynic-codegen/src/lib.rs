@@ -7,36 +7,55 @@ pub mod query_dsl;
 pub mod query_module;
 pub mod scalar_derive;
 
+mod debug_output;
 mod error;
+mod expansion_cache;
 mod field_argument;
 mod field_type;
 mod generic_param;
+mod glob;
 mod ident;
+mod introspection;
 mod module;
 mod schema;
+mod serde_serialize;
+mod suggestions;
 mod type_index;
 mod type_path;
 mod type_validation;
 
-use error::Error;
+pub use debug_output::write_debug_output;
+pub use introspection::introspection_to_sdl;
+
+pub use error::Error;
 use field_argument::FieldArgument;
 use field_type::FieldType;
 use ident::Ident;
 use schema::load_schema;
+pub use schema::SchemaSource;
 use type_index::TypeIndex;
 use type_path::TypePath;
 
 pub fn output_query_dsl<P: AsRef<std::path::Path>>(schema: P, output_path: P) -> Result<(), Error> {
-    use query_dsl::QueryDslParams;
+    output_query_dsl_with_params(
+        query_dsl::QueryDslParams::new(schema.as_ref().to_str().unwrap().to_string()),
+        output_path,
+    )
+}
+
+/// Outputs the query DSL for a schema into `output_path`, using the same
+/// options that `query_dsl!` accepts.
+pub fn output_query_dsl_with_params(
+    params: query_dsl::QueryDslParams,
+    output_path: impl AsRef<std::path::Path>,
+) -> Result<(), Error> {
     use std::io::Write;
 
-    let tokens = query_dsl::query_dsl_from_schema(QueryDslParams {
-        schema_filename: schema.as_ref().to_str().unwrap().to_string(),
-    })?;
+    let tokens = query_dsl::query_dsl_from_schema(params)?;
 
     {
-        let mut out = std::fs::File::create(output_path.as_ref()).unwrap();
-        write!(&mut out, "{}", tokens).unwrap();
+        let mut out = std::fs::File::create(output_path.as_ref())?;
+        write!(&mut out, "{}", tokens)?;
     }
 
     format_code(output_path.as_ref());
@@ -44,6 +63,60 @@ pub fn output_query_dsl<P: AsRef<std::path::Path>>(schema: P, output_path: P) ->
     Ok(())
 }
 
+/// Outputs the query DSL for a schema into `output_dir`, with a separate file
+/// for each GraphQL type.
+///
+/// This writes a `mod.rs` that declares & re-exports every type, so the
+/// output can be used as a module in the same way as the output of
+/// `output_query_dsl`.  This is a lot easier to navigate than a single file
+/// for large schemas.
+pub fn output_query_dsl_per_type<P: AsRef<std::path::Path>>(
+    schema: P,
+    output_dir: P,
+) -> Result<(), Error> {
+    output_query_dsl_per_type_with_params(
+        query_dsl::QueryDslParams::new(schema.as_ref().to_str().unwrap().to_string()),
+        output_dir,
+    )
+}
+
+/// Outputs the query DSL for a schema into a file per type as with
+/// `output_query_dsl_per_type`, using the same options that `query_dsl!`
+/// accepts.
+///
+/// The `module`, `visibility` & `grouped_modules` options aren't supported
+/// here, as the layout of the output is already determined by the files.
+pub fn output_query_dsl_per_type_with_params(
+    params: query_dsl::QueryDslParams,
+    output_dir: impl AsRef<std::path::Path>,
+) -> Result<(), Error> {
+    use query_dsl::QueryDsl;
+    use std::io::Write;
+
+    let document = (*load_schema(&params.schema)?).clone();
+    let document = match &params.types {
+        Some(types) => query_dsl::prune_document(document, types)?,
+        None => document,
+    };
+    let dsl = QueryDsl::from_document(document, &params);
+    let (root, modules) = dsl.type_modules();
+
+    let output_dir = output_dir.as_ref();
+    std::fs::create_dir_all(output_dir)?;
+
+    let files = std::iter::once(("mod".to_string(), root)).chain(modules);
+    for (name, tokens) in files {
+        let path = output_dir.join(format!("{}.rs", name));
+        {
+            let mut out = std::fs::File::create(&path)?;
+            write!(&mut out, "{}", tokens)?;
+        }
+        format_code(&path);
+    }
+
+    Ok(())
+}
+
 #[allow(unused_variables)]
 fn format_code(filename: &std::path::Path) {
     #[cfg(feature = "rustfmt")]
@@ -1,6 +1,9 @@
 use proc_macro2::TokenStream;
 
-use crate::{schema::InputValue, FieldArgument, FieldType, Ident, TypeIndex, TypePath};
+use crate::{
+    schema::{self, FieldExt},
+    FieldArgument, FieldType, Ident, TypeIndex, TypePath,
+};
 
 /// A selection function for a field in our generated DSL
 ///
@@ -17,29 +20,36 @@ pub struct FieldSelector {
     pub argument_structs_path: Ident,
     pub required_args: Vec<FieldArgument>,
     pub selection_builder: TypePath,
+    /// The reason this field is deprecated, if it is deprecated.
+    pub deprecation: Option<String>,
+    /// The schema's description of this field, which we output as docs.
+    pub description: Option<String>,
 }
 
 impl FieldSelector {
     pub fn for_field(
-        name: &str,
+        field: &schema::Field,
+        rust_field_name: Ident,
         field_type: FieldType,
         type_lock: Ident,
         argument_structs_path: Ident,
-        required_args: Vec<InputValue>,
         selection_builder: TypePath,
         type_index: &TypeIndex,
     ) -> FieldSelector {
         FieldSelector {
-            rust_field_name: Ident::for_field(name),
-            query_field_name: name.to_string(),
+            rust_field_name,
+            query_field_name: field.name.clone(),
             field_type,
             type_lock,
             argument_structs_path,
-            required_args: required_args
+            required_args: field
+                .required_arguments()
                 .iter()
                 .map(|v| FieldArgument::from_input_value(v, type_index))
                 .collect(),
             selection_builder,
+            deprecation: field.deprecation(),
+            description: field.description.clone(),
         }
     }
 }
@@ -85,7 +95,17 @@ impl quote::ToTokens for FieldSelector {
 
         let selection_builder = &self.selection_builder;
 
+        let deprecated = self.deprecation.as_ref().map(|reason| {
+            quote! { #[deprecated(note = #reason)] }
+        });
+
+        let doc = self.description.as_ref().map(|description| {
+            quote! { #[doc = #description] }
+        });
+
         tokens.append_all(quote! {
+            #doc
+            #deprecated
             pub fn #rust_field_name<#(#generic_params, )*>(
                 #(#argument_defs, )*
             ) -> #selection_builder {
@@ -27,6 +27,7 @@ impl quote::ToTokens for InputObjectMarker {
 
         tokens.append_all(quote! {
             #[allow(dead_code)]
+            #[doc(hidden)]
             pub struct #name {}
         });
     }
@@ -4,17 +4,21 @@ use crate::{schema, Ident};
 
 /// We generate an InterfaceStruct for each interface in the schema.
 ///
-/// These are output as empty structs that can be used as the TypeLock
-/// in a SelectorStruct.
+/// The struct for the interface itself is output as a SelectorStruct, so that
+/// fields of the interface can be selected.  This just marks each of the
+/// objects that implement the interface as one of its subtypes, so they can be
+/// used in inline fragments.
 #[derive(Debug)]
 pub struct InterfaceStruct {
     pub name: Ident,
+    pub subtypes: Vec<Ident>,
 }
 
 impl InterfaceStruct {
-    pub fn from_interface(interface: &schema::InterfaceType) -> Self {
+    pub fn from_interface(interface: &schema::InterfaceType, implementors: &[&str]) -> Self {
         InterfaceStruct {
             name: Ident::for_type(&interface.name),
+            subtypes: implementors.iter().map(|ty| Ident::for_type(ty)).collect(),
         }
     }
 }
@@ -24,10 +28,12 @@ impl quote::ToTokens for InterfaceStruct {
         use quote::{quote, TokenStreamExt};
 
         let name = &self.name;
+        let subtypes = &self.subtypes;
 
         tokens.append_all(quote! {
-            #[allow(dead_code)]
-            pub struct #name {}
+            #(
+                impl ::cynic::selection_set::HasSubtype<#subtypes> for #name {}
+            )*
         });
     }
 }
@@ -1,20 +1,27 @@
 use proc_macro2::TokenStream;
+use std::collections::BTreeMap;
 
 mod enum_marker;
 mod field_selector;
 mod input_object_marker;
 mod interface_struct;
+mod pruning;
 mod schema_roots;
 mod selection_builder;
 mod selector_struct;
 mod union_struct;
 
 use super::module::Module;
-use crate::{load_schema, schema, Error, TypeIndex};
+use crate::{
+    expansion_cache::ExpansionCache,
+    schema::{self, check_type_references, parse_schema, read_schema, SchemaSource},
+    Error, Ident, TypeIndex, TypePath,
+};
 use enum_marker::EnumMarker;
 pub use field_selector::FieldSelector;
 use input_object_marker::InputObjectMarker;
 use interface_struct::InterfaceStruct;
+pub(crate) use pruning::prune_document;
 use schema_roots::{RootTypes, SchemaRoot};
 use selection_builder::FieldSelectionBuilder;
 pub use selector_struct::SelectorStruct;
@@ -22,31 +29,295 @@ use union_struct::UnionStruct;
 
 #[derive(Debug)]
 pub struct QueryDslParams {
-    pub schema_filename: String,
+    /// The schema to generate the DSL for.
+    pub schema: SchemaSource,
+
+    /// If true, the GraphQL `Float` scalar will be mapped to
+    /// `cynic::OrderedFloat` rather than `f64`.
+    pub ordered_floats: bool,
+
+    /// Scalars that should be mapped to a different rust type, as pairs of
+    /// the GraphQL scalar name & the rust type.  This can override the built
+    /// in scalars as well as custom ones, e.g. `Int` to `i64`.
+    pub scalars: Vec<(String, String)>,
+
+    /// The schema previews to generate fields for.  Fields that require any
+    /// other preview will be left out of the DSL.
+    pub previews: Vec<String>,
+
+    /// The name of a module to generate the DSL into.  If this is `None` the
+    /// DSL is generated directly into the module that invokes the macro.
+    ///
+    /// This is kept as a string, as the `Debug` output of an `Ident` contains
+    /// its span - which would stop the expansion cache from being reused.
+    pub module: Option<String>,
+
+    /// The visibility of the generated DSL, e.g. `pub(crate)`.  Kept as a
+    /// string for the same reason as `module`.
+    pub visibility: Option<String>,
+
+    /// If true, the generated items are grouped into `objects`, `interfaces`,
+    /// `unions`, `inputs` & `enums` submodules.
+    pub grouped_modules: bool,
+
+    /// The objects, interfaces & unions to generate the DSL for.  If this is
+    /// set, any other composite types are left out - along with fields that
+    /// refer to them, and any scalars, enums & input objects that are no
+    /// longer used.
+    pub types: Option<Vec<String>>,
 }
 
 impl QueryDslParams {
-    fn new(schema_filename: String) -> Self {
-        QueryDslParams { schema_filename }
+    pub fn new(schema_filename: String) -> Self {
+        QueryDslParams {
+            schema: SchemaSource::Path(schema_filename),
+            ordered_floats: false,
+            scalars: vec![],
+            previews: vec![],
+            module: None,
+            visibility: None,
+            grouped_modules: false,
+            types: None,
+        }
+    }
+
+    /// A name for the schema, which is the filename unless the schema was
+    /// passed in as SDL.
+    pub fn schema_name(&self) -> std::borrow::Cow<'_, str> {
+        self.schema.name()
     }
 }
 
 impl syn::parse::Parse for QueryDslParams {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        input
-            .parse::<syn::LitStr>()
-            .map(|lit_str| QueryDslParams::new(lit_str.value()))
+        let mut params = QueryDslParams::new(String::new());
+        params.schema = parse_schema_param(input)?;
+
+        while !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let option = input.parse::<syn::Ident>()?;
+            if option == "ordered_floats" {
+                params.ordered_floats = true;
+            } else if option == "scalars" {
+                input.parse::<syn::Token![=]>()?;
+                let content;
+                syn::braced!(content in input);
+                let overrides = content.parse_terminated::<_, syn::Token![,]>(|input| {
+                    let scalar = input.parse::<syn::Ident>()?;
+                    input.parse::<syn::Token![=]>()?;
+                    let rust_type = input.parse::<syn::LitStr>()?;
+                    TypePath::from_rust_type(&rust_type.value())
+                        .map_err(|e| syn::Error::new(rust_type.span(), e))?;
+                    Ok((scalar.to_string(), rust_type.value()))
+                })?;
+                params.scalars = overrides.into_iter().collect();
+            } else if option == "previews" {
+                input.parse::<syn::Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                params.previews = content
+                    .parse_terminated::<_, syn::Token![,]>(|input| input.parse::<syn::LitStr>())?
+                    .into_iter()
+                    .map(|lit| lit.value())
+                    .collect();
+            } else if option == "module" {
+                input.parse::<syn::Token![=]>()?;
+                let name = input.parse::<syn::LitStr>()?;
+                name.parse::<syn::Ident>()?;
+                params.module = Some(name.value());
+            } else if option == "visibility" {
+                input.parse::<syn::Token![=]>()?;
+                let visibility = input.parse::<syn::LitStr>()?;
+                visibility.parse::<syn::Visibility>()?;
+                params.visibility = Some(visibility.value());
+            } else if option == "grouped_modules" {
+                params.grouped_modules = true;
+            } else if option == "types" {
+                input.parse::<syn::Token![=]>()?;
+                let content;
+                syn::bracketed!(content in input);
+                params.types = Some(
+                    content
+                        .parse_terminated::<_, syn::Token![,]>(|input| {
+                            input.parse::<syn::LitStr>()
+                        })?
+                        .into_iter()
+                        .map(|lit| lit.value())
+                        .collect(),
+                );
+            } else {
+                return Err(syn::Error::new(
+                    option.span(),
+                    format!("Unknown query_dsl option: {}", option),
+                ));
+            }
+        }
+
+        Ok(params)
+    }
+}
+
+/// Parses the schema passed to `query_dsl!`, which is either a string literal,
+/// a list of schema files or `sdl = "..."` with the SDL of the schema itself.
+///
+/// Macros aren't expanded before they're passed to us, so something like
+/// `include_str!` is rejected rather than quietly resolving its filename in a
+/// different way to rustc.
+fn parse_schema_param(input: syn::parse::ParseStream) -> syn::Result<SchemaSource> {
+    if input.peek(syn::token::Bracket) {
+        let content;
+        syn::bracketed!(content in input);
+        let filenames = content
+            .parse_terminated::<_, syn::Token![,]>(|input| input.parse::<syn::LitStr>())?
+            .into_iter()
+            .map(|lit| lit.value())
+            .collect::<Vec<_>>();
+
+        return Ok(SchemaSource::Paths(filenames));
+    }
+
+    if input.peek(syn::Ident) && input.peek2(syn::Token![!]) {
+        let macro_name = input.parse::<syn::Ident>()?;
+        return Err(syn::Error::new(
+            macro_name.span(),
+            "query_dsl! can't expand macros - pass the schema filename or sdl = \"...\"",
+        ));
+    }
+
+    if input.peek(syn::Ident) && input.peek2(syn::Token![=]) {
+        let name = input.parse::<syn::Ident>()?;
+        if name != "sdl" {
+            return Err(syn::Error::new(
+                name.span(),
+                "Expected a schema filename or sdl",
+            ));
+        }
+        input.parse::<syn::Token![=]>()?;
+
+        return Ok(SchemaSource::Sdl(input.parse::<syn::LitStr>()?.value()));
     }
+
+    Ok(SchemaSource::Path(input.parse::<syn::LitStr>()?.value()))
 }
 
 pub fn query_dsl_from_schema(input: QueryDslParams) -> Result<TokenStream, Error> {
     use quote::quote;
 
-    let schema_data: QueryDsl = load_schema(input.schema_filename)?.into();
+    let schema = read_schema(&input.schema)?;
+
+    let cache = ExpansionCache::from_env();
+    let cache_key = ExpansionCache::key(&[&schema, &format!("{:?}", input)]);
+    if let Some(tokens) = cache.as_ref().and_then(|cache| cache.get(&cache_key)) {
+        return Ok(tokens);
+    }
+
+    let document = parse_schema(&schema)?;
+    check_type_references(&document)?;
+    check_scalar_overrides(&document, &input.scalars)?;
+
+    let document = match &input.types {
+        Some(types) => prune_document(document, types)?,
+        None => document,
+    };
+
+    let schema_data = QueryDsl::from_document(document, &input);
+
+    let schema_data = if input.grouped_modules {
+        schema_data.grouped_modules()
+    } else {
+        quote! { #schema_data }
+    };
+
+    let visibility = match &input.visibility {
+        Some(visibility) => syn::parse_str::<syn::Visibility>(visibility).map_err(|_| {
+            Error::InvalidOption(format!("{} is not a valid visibility", visibility))
+        })?,
+        None => syn::parse_quote! { pub },
+    };
+
+    let tokens = match (&input.module, &input.visibility) {
+        (Some(module), _) => {
+            let module = quote::format_ident!("{}", module);
+            quote! {
+                #visibility mod #module {
+                    #[allow(unused_imports)]
+                    use super::*;
+
+                    #schema_data
+                }
+            }
+        }
+        // Everything in the DSL is `pub`, so to use a different visibility
+        // we put it in a private module and then re-export it.
+        (None, Some(_)) => quote! {
+            mod __cynic_query_dsl {
+                #[allow(unused_imports)]
+                use super::*;
+
+                #schema_data
+            }
+
+            #[allow(unused_imports)]
+            #visibility use __cynic_query_dsl::*;
+        },
+        (None, None) => schema_data,
+    };
+
+    if let Some(cache) = cache {
+        cache.put(&cache_key, &tokens);
+    }
+
+    Ok(tokens)
+}
+
+/// Checks that every scalar in the `scalars` option is a scalar in the
+/// schema, to catch any typos.
+fn check_scalar_overrides(
+    document: &schema::Document,
+    scalars: &[(String, String)],
+) -> Result<(), Error> {
+    let type_index = TypeIndex::for_schema(document);
+
+    for (scalar, _) in scalars {
+        let is_builtin = ["String", "Int", "Float", "Boolean", "ID"].contains(&scalar.as_str());
+        if !is_builtin && !type_index.is_scalar(scalar) {
+            return Err(Error::InvalidOption(format!(
+                "scalars contains {}, which isn't a scalar in the schema",
+                scalar
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Outputs an `include_bytes!` of each schema file, which makes cargo rebuild
+/// the crate whenever the schema changes.
+///
+/// Globs are expanded when the macro runs, so adding a file that matches one
+/// won't trigger a rebuild by itself.
+///
+/// This is kept separate from `query_dsl_from_schema` as the output contains
+/// an absolute path, which shouldn't be written to files or cached.
+pub fn schema_tracking(schema: &SchemaSource) -> TokenStream {
+    let paths = match schema.file_paths() {
+        Ok(paths) => paths,
+        Err(_) => return TokenStream::new(),
+    };
 
-    Ok(quote! {
-        #schema_data
-    })
+    paths
+        .iter()
+        .filter_map(|path| path.to_str())
+        .map(|path| {
+            quote::quote! {
+                const _: &[u8] = include_bytes!(#path);
+            }
+        })
+        .collect()
 }
 
 #[derive(Debug)]
@@ -58,13 +329,32 @@ pub struct QueryDsl {
     pub enums: Vec<EnumMarker>,
     pub input_objects: Vec<InputObjectMarker>,
     pub schema_roots: Vec<SchemaRoot>,
+    pub previews: Vec<String>,
 }
 
 impl From<schema::Document> for QueryDsl {
     fn from(document: schema::Document) -> Self {
+        QueryDsl::from_document(document, &QueryDslParams::new(String::new()))
+    }
+}
+
+impl QueryDsl {
+    pub(crate) fn from_document(document: schema::Document, params: &QueryDslParams) -> Self {
         use schema::{Definition, TypeDefinition};
 
-        let type_index = TypeIndex::for_schema(&document);
+        // The overrides were checked when params were parsed, so these should
+        // all be valid.
+        let scalar_overrides = params
+            .scalars
+            .iter()
+            .filter_map(|(scalar, rust_type)| {
+                Some((scalar.clone(), TypePath::from_rust_type(rust_type).ok()?))
+            })
+            .collect();
+
+        let type_index = TypeIndex::for_schema(&document)
+            .with_ordered_floats(params.ordered_floats)
+            .with_scalar_overrides(scalar_overrides);
 
         let mut selectors = vec![];
         let mut argument_struct_modules = vec![];
@@ -76,12 +366,25 @@ impl From<schema::Document> for QueryDsl {
 
         let root_types = RootTypes::from_definitions(&document.definitions);
 
+        let mut interface_implementors: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for definition in &document.definitions {
+            if let Definition::TypeDefinition(TypeDefinition::Object(object)) = definition {
+                for interface in &object.implements_interfaces {
+                    interface_implementors
+                        .entry(interface.as_str())
+                        .or_default()
+                        .push(object.name.as_str());
+                }
+            }
+        }
+
         for definition in &document.definitions {
             match definition {
                 Definition::TypeDefinition(TypeDefinition::Object(object)) => {
                     // Ok, so would be nice to restructure this so that the argument structs
                     // are visible at the point we're generating the field_selectors...
-                    let selector = SelectorStruct::from_object(&object, &type_index);
+                    let selector =
+                        SelectorStruct::from_object(&object, &type_index, &params.previews);
                     if !selector.selection_builders.is_empty() {
                         argument_struct_modules.push(Module::new(
                             &object.name,
@@ -100,7 +403,24 @@ impl From<schema::Document> for QueryDsl {
                     unions.push(UnionStruct::from_union(&union));
                 }
                 Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
-                    interfaces.push(InterfaceStruct::from_interface(&interface));
+                    let selector = SelectorStruct::from_object(
+                        &schema::interface_as_object(&interface),
+                        &type_index,
+                        &params.previews,
+                    );
+                    if !selector.selection_builders.is_empty() {
+                        argument_struct_modules.push(Module::new(
+                            &interface.name,
+                            selector.selection_builders.clone(),
+                        ));
+                    }
+                    selectors.push(selector);
+
+                    let implementors = interface_implementors
+                        .get(interface.name.as_str())
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+                    interfaces.push(InterfaceStruct::from_interface(&interface, implementors));
                 }
                 Definition::TypeDefinition(TypeDefinition::Enum(en)) => {
                     enums.push(EnumMarker::from_enum(&en));
@@ -117,6 +437,169 @@ impl From<schema::Document> for QueryDsl {
             interfaces,
             enums,
             schema_roots,
+            previews: params.previews.clone(),
+        }
+    }
+
+    /// A constant listing the previews this DSL was generated with, so they
+    /// can be passed on to the server - e.g. in an `Accept` header.
+    fn previews_const(&self) -> Option<TokenStream> {
+        if self.previews.is_empty() {
+            return None;
+        }
+
+        let previews = &self.previews;
+        Some(quote::quote! {
+            /// The schema previews that this DSL was generated with.
+            #[allow(dead_code)]
+            pub const PREVIEWS: &[&str] = &[#(#previews),*];
+        })
+    }
+}
+
+impl QueryDsl {
+    /// Splits the DSL into a module per GraphQL type.
+    ///
+    /// Returns the contents of a root module, which declares & re-exports
+    /// each of these modules, alongside the name and contents of each
+    /// individual module.  The individual modules `use super::*` so any
+    /// references between types still resolve.
+    pub fn type_modules(&self) -> (TokenStream, BTreeMap<String, TokenStream>) {
+        use quote::{quote, ToTokens};
+
+        let mut modules = BTreeMap::<String, TokenStream>::new();
+        let mut add = |name: &Ident, item: &dyn ToTokens| {
+            item.to_tokens(
+                modules
+                    .entry(Ident::for_module(&name.to_string()).to_string())
+                    .or_default(),
+            );
+        };
+
+        for selector in &self.selectors {
+            add(&selector.name, selector);
+            if !selector.selection_builders.is_empty() {
+                add(
+                    &selector.name,
+                    &Module::new(&selector.graphql_name, selector.selection_builders.clone()),
+                );
+            }
+        }
+        for union in &self.unions {
+            add(&union.name, union);
+        }
+        for interface in &self.interfaces {
+            add(&interface.name, interface);
+        }
+        for input_object in &self.input_objects {
+            add(&input_object.name, input_object);
+        }
+        for en in &self.enums {
+            add(&en.name, en);
+        }
+
+        let declarations = modules.keys().map(|name| {
+            let path = format!("{}.rs", name);
+            let module = quote::format_ident!("__{}", name);
+            quote! {
+                #[path = #path]
+                mod #module;
+                pub use #module::*;
+            }
+        });
+        let schema_roots = &self.schema_roots;
+        let previews = self.previews_const();
+
+        let root = quote! {
+            #previews
+            #(#declarations)*
+            #(#schema_roots)*
+        };
+
+        let modules = modules
+            .into_iter()
+            .map(|(name, contents)| {
+                (
+                    name,
+                    quote! {
+                        #[allow(unused_imports)]
+                        use super::*;
+
+                        #contents
+                    },
+                )
+            })
+            .collect();
+
+        (root, modules)
+    }
+}
+
+impl QueryDsl {
+    /// Outputs the DSL with its items grouped into a module for each kind
+    /// of GraphQL type.
+    ///
+    /// Each module is also re-exported from the root, so the DSL can still
+    /// be used as a `query_module` by the derives.
+    pub fn grouped_modules(&self) -> TokenStream {
+        use quote::quote;
+
+        let selectors = &self.selectors;
+        let argument_struct_modules = &self.argument_struct_modules;
+        let interfaces = &self.interfaces;
+        let unions = &self.unions;
+        let input_objects = &self.input_objects;
+        let enums = &self.enums;
+
+        let groups = vec![
+            (
+                "objects",
+                selectors.is_empty(),
+                quote! {
+                    #(#selectors)*
+                    #(#argument_struct_modules)*
+                },
+            ),
+            (
+                "interfaces",
+                interfaces.is_empty(),
+                quote! { #(#interfaces)* },
+            ),
+            ("unions", unions.is_empty(), quote! { #(#unions)* }),
+            (
+                "inputs",
+                input_objects.is_empty(),
+                quote! { #(#input_objects)* },
+            ),
+            ("enums", enums.is_empty(), quote! { #(#enums)* }),
+        ];
+
+        let modules =
+            groups
+                .into_iter()
+                .filter(|(_, empty, _)| !empty)
+                .map(|(name, _, contents)| {
+                    let name = quote::format_ident!("{}", name);
+                    quote! {
+                        pub mod #name {
+                            #[allow(unused_imports)]
+                            use super::*;
+
+                            #contents
+                        }
+
+                        #[doc(hidden)]
+                        pub use #name::*;
+                    }
+                });
+
+        let schema_roots = &self.schema_roots;
+        let previews = self.previews_const();
+
+        quote! {
+            #previews
+            #(#modules)*
+            #(#schema_roots)*
         }
     }
 }
@@ -132,8 +615,10 @@ impl quote::ToTokens for QueryDsl {
         let interfaces = &self.interfaces;
         let enums = &self.enums;
         let schema_roots = &self.schema_roots;
+        let previews = self.previews_const();
 
         tokens.append_all(quote! {
+            #previews
             #(
                 #unions
             )*
@@ -158,3 +643,365 @@ impl quote::ToTokens for QueryDsl {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn test_parsing_query_dsl_params() {
+        let params: QueryDslParams = syn::parse_quote! { "schema.graphql" };
+        assert_eq!(params.schema, SchemaSource::Path("schema.graphql".into()));
+        assert!(!params.ordered_floats);
+
+        let params: QueryDslParams = syn::parse_quote! { "schema.graphql", ordered_floats };
+        assert!(params.ordered_floats);
+
+        assert!(syn::parse_str::<QueryDslParams>(r#""schema.graphql", other"#).is_err());
+
+        let params: QueryDslParams = syn::parse_quote! {
+            "schema.graphql", ordered_floats, previews = ["antiope", "starfox"]
+        };
+        assert!(params.ordered_floats);
+        assert_eq!(params.previews, vec!["antiope", "starfox"]);
+
+        let params: QueryDslParams = syn::parse_quote! { "schema.graphql", module = "github" };
+        assert_eq!(params.module.unwrap(), "github");
+
+        assert!(
+            syn::parse_str::<QueryDslParams>(r#""schema.graphql", module = "not valid""#).is_err()
+        );
+
+        let params: QueryDslParams = syn::parse_quote! {
+            "schema.graphql", visibility = "pub(crate)", grouped_modules
+        };
+        assert_eq!(params.visibility.unwrap(), "pub(crate)");
+        assert!(params.grouped_modules);
+
+        assert!(
+            syn::parse_str::<QueryDslParams>(r#""schema.graphql", visibility = "public""#).is_err()
+        );
+
+        let params: QueryDslParams = syn::parse_quote! {
+            "schema.graphql", types = ["Query", "Film"]
+        };
+        assert_eq!(params.types.unwrap(), vec!["Query", "Film"]);
+
+        let params: QueryDslParams = syn::parse_quote! {
+            "schema.graphql", scalars = { Int = "i64", DateTime = "chrono::DateTime<chrono::Utc>" }
+        };
+        assert_eq!(
+            params.scalars,
+            vec![
+                ("Int".to_string(), "i64".to_string()),
+                (
+                    "DateTime".to_string(),
+                    "chrono::DateTime<chrono::Utc>".to_string()
+                ),
+            ]
+        );
+
+        assert!(syn::parse_str::<QueryDslParams>(
+            r#""schema.graphql", scalars = { Int = "not a type" }"#
+        )
+        .is_err());
+
+        assert!(syn::parse_str::<QueryDslParams>(r#"include_str!("schema.graphql")"#).is_err());
+
+        let params: QueryDslParams = syn::parse_quote! {
+            ["schema.graphql", "types/*.graphql"], ordered_floats
+        };
+        assert_eq!(
+            params.schema,
+            SchemaSource::Paths(vec!["schema.graphql".into(), "types/*.graphql".into()])
+        );
+        assert!(params.ordered_floats);
+    }
+
+    #[test]
+    fn test_inline_schemas() {
+        let params: QueryDslParams = syn::parse_quote! {
+            sdl = r#"
+                type Query {
+                    film: Film
+                }
+
+                type Film {
+                    title: String
+                }
+            "#,
+            ordered_floats
+        };
+        assert!(params.ordered_floats);
+        assert_matches!(params.schema, SchemaSource::Sdl(_));
+
+        let params: QueryDslParams = syn::parse_quote! { sdl = "scalar Date" };
+        assert_eq!(params.schema, SchemaSource::Sdl("scalar Date".into()));
+
+        let params: QueryDslParams = syn::parse_quote! { "scalar Date" };
+        assert_eq!(params.schema, SchemaSource::Path("scalar Date".into()));
+
+        assert!(syn::parse_str::<QueryDslParams>(r#"schema = "scalar Date""#).is_err());
+
+        let params: QueryDslParams = syn::parse_quote! {
+            sdl = "type Query { film: Film }\ntype Film { title: String }"
+        };
+        assert!(schema_tracking(&params.schema).is_empty());
+
+        let tokens = query_dsl_from_schema(params).unwrap().to_string();
+        assert!(tokens.contains("Film"));
+    }
+
+    #[test]
+    fn test_scalar_overrides() {
+        let schema = crate::schema::parse_schema(
+            "scalar DateTime\ntype Query { count: Int, createdAt: DateTime, title: String }",
+        )
+        .unwrap();
+
+        let mut params = QueryDslParams::new(String::new());
+        params.scalars = vec![
+            ("Int".into(), "i64".into()),
+            ("DateTime".into(), "chrono::DateTime<chrono::Utc>".into()),
+        ];
+        check_scalar_overrides(&schema, &params.scalars).unwrap();
+
+        let dsl = QueryDsl::from_document(schema.clone(), &params);
+        let output = quote::quote! { #dsl }.to_string();
+        assert!(output.contains(&quote::quote! { Option<i64> }.to_string()));
+        assert!(
+            output.contains(&quote::quote! { Option<chrono::DateTime<chrono::Utc> > }.to_string())
+        );
+
+        assert_eq!(
+            check_scalar_overrides(&schema, &[("Date".into(), "String".into())]),
+            Err(Error::InvalidOption(
+                "scalars contains Date, which isn't a scalar in the schema".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_preview_fields() {
+        let schema = crate::schema::parse_schema(
+            r#"
+            type Query {
+                film: String
+                preview: String @preview(toggledBy: "antiope")
+            }
+            "#,
+        )
+        .unwrap();
+
+        let dsl = QueryDsl::from_document(schema.clone(), &QueryDslParams::new(String::new()));
+        let output = quote::quote! { #dsl }.to_string();
+        assert!(output.contains("pub fn film"));
+        assert!(!output.contains("pub fn preview"));
+        assert!(!output.contains("PREVIEWS"));
+
+        let mut params = QueryDslParams::new(String::new());
+        params.previews.push("antiope".into());
+        let dsl = QueryDsl::from_document(schema, &params);
+        let output = quote::quote! { #dsl }.to_string();
+        assert!(output.contains("pub fn preview"));
+        assert!(output.contains(r#"pub const PREVIEWS : & [& str] = & ["antiope"] ;"#));
+    }
+
+    #[test]
+    fn test_deprecated_fields() {
+        let dsl = QueryDsl::from(
+            crate::schema::parse_schema(
+                r#"
+                type Query {
+                    film: String
+                    oldFilm: String @deprecated(reason: "Use film")
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+        let output = quote::quote! { #dsl }.to_string();
+
+        assert!(output.contains(r#"# [deprecated (note = "Use film")] pub fn old_film"#));
+        assert_eq!(output.matches("deprecated").count(), 1);
+    }
+
+    #[test]
+    fn test_descriptions() {
+        let dsl = QueryDsl::from(
+            crate::schema::parse_schema(
+                r#"
+                "The root query"
+                type Query {
+                    "All the films"
+                    films: String
+                    people: String
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+        let output = quote::quote! { #dsl }.to_string();
+
+        assert!(
+            output.contains(r#"# [doc = "The root query"] # [allow (dead_code)] pub struct Query"#)
+        );
+        assert!(output.contains(r#"# [doc = "All the films"] pub fn films"#));
+        assert_eq!(output.matches("# [doc").count(), 10);
+    }
+
+    #[test]
+    fn test_type_modules() {
+        let dsl = QueryDsl::from(
+            crate::schema::parse_schema(
+                r#"
+                type Query {
+                    film(id: ID): Film
+                }
+
+                type Film {
+                    title: String
+                    kind: FilmKind
+                }
+
+                enum FilmKind {
+                    FEATURE
+                    SHORT
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let (root, modules) = dsl.type_modules();
+        let root = root.to_string();
+
+        assert_eq!(
+            modules.keys().collect::<Vec<_>>(),
+            vec!["film", "film_kind", "query"]
+        );
+        assert!(root.contains(r#"# [path = "film_kind.rs"] mod __film_kind ;"#));
+        assert!(root.contains("pub use __film_kind :: * ;"));
+        assert!(root.contains("QueryRoot for Query"));
+
+        // The argument module for `Query.film` should live alongside `Query`
+        assert!(modules["query"].to_string().contains("pub mod query"));
+        assert!(modules["film"].to_string().contains("use super :: * ;"));
+    }
+
+    #[test]
+    fn test_custom_root_types() {
+        let dsl = QueryDsl::from(
+            crate::schema::parse_schema(
+                r#"
+                schema {
+                    query: RootQuery
+                    subscription: RootSubscription
+                }
+
+                type RootQuery {
+                    film: String
+                }
+
+                type RootSubscription {
+                    filmAdded: String
+                }
+
+                type Mutation {
+                    addFilm: String
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let output = quote::quote! { #dsl }.to_string();
+
+        assert!(output.contains("QueryRoot for RootQuery"));
+        assert!(output.contains("SubscriptionRoot for RootSubscription"));
+        assert!(!output.contains("MutationRoot"));
+    }
+
+    #[test]
+    fn test_default_arguments() {
+        let dsl = QueryDsl::from(
+            crate::schema::parse_schema(
+                r#"
+                type Query {
+                    films(first: Int! = 10, after: String): String
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+        let output = quote::quote! { #dsl }.to_string();
+
+        // Arguments with defaults can be left out, so they're optional
+        assert!(output.contains("pub fn films < > ()"));
+        assert!(output.contains(
+            r#"# [doc = "Defaults to `10` on the server if not provided."] pub fn first"#
+        ));
+        assert!(output.contains(":: cynic :: IntoArgument < i32 >"));
+    }
+
+    #[test]
+    fn test_grouped_modules() {
+        let dsl = QueryDsl::from(
+            crate::schema::parse_schema(
+                r#"
+                type Query {
+                    film(id: ID): Film
+                }
+
+                type Film {
+                    title: String
+                    kind: FilmKind
+                }
+
+                enum FilmKind {
+                    FEATURE
+                    SHORT
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let output = dsl.grouped_modules().to_string();
+
+        assert!(output.contains("pub mod objects"));
+        assert!(output.contains("pub use objects :: * ;"));
+        assert!(output.contains("pub mod enums"));
+        assert!(!output.contains("pub mod unions"));
+        assert!(output.contains("QueryRoot for Query"));
+    }
+
+    #[test]
+    fn test_interfaces() {
+        let dsl = QueryDsl::from(
+            crate::schema::parse_schema(
+                r#"
+                type Query {
+                    node(id: ID!): Node
+                }
+
+                interface Node {
+                    id: ID!
+                }
+
+                type Film implements Node {
+                    id: ID!
+                    title: String
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+        let output = quote::quote! { #dsl }.to_string();
+
+        assert!(output.contains("pub struct Node ;"));
+        assert!(output.contains("HasSubtype < Film > for Node"));
+        assert!(!output.contains("HasSubtype < Query > for Node"));
+    }
+}
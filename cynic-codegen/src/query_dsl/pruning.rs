@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+
+use crate::{
+    schema::{Definition, Document, Field, TypeDefinition, TypeExt},
+    Error,
+};
+
+/// Removes everything from a schema that isn't needed to query `types`.
+///
+/// Objects, interfaces & unions that aren't in `types` are removed, along
+/// with any fields, union members & interface implementations that refer
+/// to them.  Scalars, enums & input objects are kept only if something
+/// that's left uses them.
+///
+/// For large schemas this can make the generated DSL a lot smaller.
+pub(crate) fn prune_document(document: Document, types: &[String]) -> Result<Document, Error> {
+    let composite_types = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(TypeDefinition::Object(object)) => Some(&object.name),
+            Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+                Some(&interface.name)
+            }
+            Definition::TypeDefinition(TypeDefinition::Union(union)) => Some(&union.name),
+            _ => None,
+        })
+        .cloned()
+        .collect::<HashSet<_>>();
+
+    for name in types {
+        if !composite_types.contains(name) {
+            return Err(Error::InvalidOption(format!(
+                "types can only contain objects, interfaces & unions from the schema, but {} is not one",
+                name
+            )));
+        }
+    }
+
+    let kept = types.iter().cloned().collect::<HashSet<_>>();
+    let is_wanted = |name: &str| !composite_types.contains(name) || kept.contains(name);
+
+    let mut definitions = vec![];
+    for definition in document.definitions {
+        let definition = match definition {
+            Definition::TypeDefinition(TypeDefinition::Object(mut object)) => {
+                if !kept.contains(&object.name) {
+                    continue;
+                }
+                object
+                    .fields
+                    .retain(|field| is_wanted(field.field_type.inner_name()));
+                object
+                    .implements_interfaces
+                    .retain(|name| kept.contains(name));
+                Definition::TypeDefinition(TypeDefinition::Object(object))
+            }
+            Definition::TypeDefinition(TypeDefinition::Interface(mut interface)) => {
+                if !kept.contains(&interface.name) {
+                    continue;
+                }
+                interface
+                    .fields
+                    .retain(|field| is_wanted(field.field_type.inner_name()));
+                Definition::TypeDefinition(TypeDefinition::Interface(interface))
+            }
+            Definition::TypeDefinition(TypeDefinition::Union(mut union)) => {
+                if !kept.contains(&union.name) {
+                    continue;
+                }
+                union.types.retain(|name| kept.contains(name));
+                Definition::TypeDefinition(TypeDefinition::Union(union))
+            }
+            other => other,
+        };
+        definitions.push(definition);
+    }
+
+    let used_types = used_input_types(&definitions);
+    definitions.retain(|definition| match definition {
+        Definition::TypeDefinition(TypeDefinition::Scalar(scalar)) => {
+            used_types.contains(&scalar.name)
+        }
+        Definition::TypeDefinition(TypeDefinition::Enum(en)) => used_types.contains(&en.name),
+        Definition::TypeDefinition(TypeDefinition::InputObject(input)) => {
+            used_types.contains(&input.name)
+        }
+        _ => true,
+    });
+
+    Ok(Document { definitions })
+}
+
+/// Finds the names of all the types used by the fields of the objects &
+/// interfaces in `definitions`, including any types used by the input
+/// objects they take as arguments.
+fn used_input_types(definitions: &[Definition]) -> HashSet<String> {
+    fn add_fields(fields: &[Field], pending: &mut Vec<String>) {
+        for field in fields {
+            pending.push(field.field_type.inner_name().to_string());
+            for argument in &field.arguments {
+                pending.push(argument.value_type.inner_name().to_string());
+            }
+        }
+    }
+
+    let mut used = HashSet::new();
+    let mut pending = vec![];
+
+    for definition in definitions {
+        match definition {
+            Definition::TypeDefinition(TypeDefinition::Object(object)) => {
+                add_fields(&object.fields, &mut pending)
+            }
+            Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+                add_fields(&interface.fields, &mut pending)
+            }
+            _ => {}
+        }
+    }
+
+    while let Some(name) = pending.pop() {
+        if !used.insert(name.clone()) {
+            continue;
+        }
+
+        let input_object = definitions.iter().find_map(|definition| match definition {
+            Definition::TypeDefinition(TypeDefinition::InputObject(input))
+                if input.name == name =>
+            {
+                Some(input)
+            }
+            _ => None,
+        });
+
+        if let Some(input_object) = input_object {
+            pending.extend(
+                input_object
+                    .fields
+                    .iter()
+                    .map(|field| field.value_type.inner_name().to_string()),
+            );
+        }
+    }
+
+    used
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::parse_schema;
+
+    fn type_names(document: &Document) -> Vec<String> {
+        let mut names = document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::TypeDefinition(type_def) => Some(match type_def {
+                    TypeDefinition::Scalar(ty) => ty.name.clone(),
+                    TypeDefinition::Object(ty) => ty.name.clone(),
+                    TypeDefinition::Interface(ty) => ty.name.clone(),
+                    TypeDefinition::Union(ty) => ty.name.clone(),
+                    TypeDefinition::Enum(ty) => ty.name.clone(),
+                    TypeDefinition::InputObject(ty) => ty.name.clone(),
+                }),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_pruning() {
+        let document = parse_schema(
+            r#"
+            scalar DateTime
+            scalar Unused
+
+            type Query {
+                film(filter: FilmFilter): Film
+                person: Person
+                search: [SearchResult]
+            }
+
+            type Film implements Node {
+                id: ID!
+                title: String
+                director: Person
+            }
+
+            type Person implements Node {
+                id: ID!
+                born: DateTime
+            }
+
+            interface Node {
+                id: ID!
+            }
+
+            union SearchResult = Film | Person
+
+            input FilmFilter {
+                rating: Rating
+            }
+
+            enum Rating {
+                GOOD
+            }
+
+            enum Unrelated {
+                THING
+            }
+            "#,
+        )
+        .unwrap();
+
+        let pruned = prune_document(
+            document.clone(),
+            &[
+                "Query".to_string(),
+                "Film".to_string(),
+                "SearchResult".to_string(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            type_names(&pruned),
+            vec!["Film", "FilmFilter", "Query", "Rating", "SearchResult"]
+        );
+
+        let film = pruned
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::TypeDefinition(TypeDefinition::Object(object))
+                    if object.name == "Film" =>
+                {
+                    Some(object)
+                }
+                _ => None,
+            });
+        let film = film.unwrap();
+        assert_eq!(film.fields.len(), 2);
+        assert!(film.implements_interfaces.is_empty());
+
+        assert!(prune_document(document, &["Rating".to_string()]).is_err());
+    }
+}
@@ -7,6 +7,7 @@ use crate::{schema, Ident};
 enum RootType {
     Query,
     Mutation,
+    Subscription,
 }
 
 #[derive(Debug, PartialEq)]
@@ -29,6 +30,13 @@ impl SchemaRoot {
             ty: RootType::Mutation,
         }
     }
+
+    pub fn for_subscription(name: &Ident) -> Self {
+        SchemaRoot {
+            name: name.clone(),
+            ty: RootType::Subscription,
+        }
+    }
 }
 
 impl quote::ToTokens for SchemaRoot {
@@ -48,44 +56,54 @@ impl quote::ToTokens for SchemaRoot {
                     impl ::cynic::QueryRoot for #name {}
                 }
             }
+            RootType::Subscription => {
+                quote! {
+                    impl ::cynic::SubscriptionRoot for #name {}
+                }
+            }
         });
     }
 }
 
+/// The names of the root operation types of a schema.
+///
+/// These come from the `schema` definition if there is one, otherwise the
+/// types named `Query`, `Mutation` & `Subscription` are used.
 pub struct RootTypes {
-    query: String,
-    mutation: String,
+    query: Option<String>,
+    mutation: Option<String>,
+    subscription: Option<String>,
 }
 
 impl RootTypes {
     pub fn from_definitions(definitions: &[schema::Definition]) -> RootTypes {
         use schema::Definition;
 
-        let mut rv = RootTypes::default();
-
         for definition in definitions {
-            match definition {
-                Definition::SchemaDefinition(schema) => {
-                    if let Some(query_type) = &schema.query {
-                        rv.query = query_type.clone();
-                    }
-                    if let Some(mutation_type) = &schema.mutation {
-                        rv.mutation = mutation_type.clone();
-                    }
-                    break;
-                }
-                _ => {}
+            if let Definition::SchemaDefinition(schema) = definition {
+                // A schema definition lists every root type, so any operations it
+                // leaves out aren't supported by this schema - even if there's a
+                // type with the default name.
+                return RootTypes {
+                    query: schema.query.clone(),
+                    mutation: schema.mutation.clone(),
+                    subscription: schema.subscription.clone(),
+                };
             }
         }
 
-        return rv;
+        RootTypes::default()
     }
 
     pub fn root_from_selector_struct(&self, selector: &SelectorStruct) -> Option<SchemaRoot> {
-        if selector.graphql_name == self.query {
+        let name = Some(&selector.graphql_name);
+
+        if name == self.query.as_ref() {
             Some(SchemaRoot::for_query(&selector.name))
-        } else if selector.graphql_name == self.mutation {
+        } else if name == self.mutation.as_ref() {
             Some(SchemaRoot::for_mutation(&selector.name))
+        } else if name == self.subscription.as_ref() {
+            Some(SchemaRoot::for_subscription(&selector.name))
         } else {
             None
         }
@@ -95,8 +113,9 @@ impl RootTypes {
 impl Default for RootTypes {
     fn default() -> RootTypes {
         RootTypes {
-            query: "Query".to_string(),
-            mutation: "Mutation".to_string(),
+            query: Some("Query".to_string()),
+            mutation: Some("Mutation".to_string()),
+            subscription: Some("Subscription".to_string()),
         }
     }
 }
@@ -18,6 +18,7 @@ pub struct FieldSelectionBuilder {
 
 impl FieldSelectionBuilder {
     pub fn for_field(
+        name: Ident,
         field_name: &str,
         field_type: FieldType,
         type_lock: Ident,
@@ -25,7 +26,7 @@ impl FieldSelectionBuilder {
         type_index: &TypeIndex,
     ) -> FieldSelectionBuilder {
         FieldSelectionBuilder {
-            name: Ident::for_type(format!("{}SelectionBuilder", field_name)),
+            name,
             field_type,
             type_lock,
             optional_args: optional_args
@@ -56,13 +57,24 @@ impl FieldSelectionBuilder {
             let field_type = self
                 .field_type
                 .to_tokens(None, Ident::for_module("super").into());
+            let decodes_to = self.field_type.decodes_to(quote! { T });
+            let custom_selector = self.field_type.selection_set_call(quote! { fields });
             quote! {
                 pub fn select(self) ->
                 ::cynic::selection_set::SelectionSet<'static, #field_type, super::#type_lock> {
                     #[allow(unused_imports)]
                     use ::cynic::selection_set::{string, integer, float, boolean};
 
-                    ::cynic::selection_set::field(#query_field_name, self.args, #selector)
+                    ::cynic::selection_set::field_with_alias(self.alias.as_deref(), #query_field_name, self.args, #selector)
+                }
+
+                /// Selects this field, decoding the scalar with `fields`
+                /// rather than the scalar type from the schema.
+                pub fn select_with<T: 'static + Send + Sync>(
+                    self,
+                    fields: ::cynic::selection_set::SelectionSet<'static, T, ()>
+                ) -> ::cynic::selection_set::SelectionSet<'static, #decodes_to, super::#type_lock> {
+                    ::cynic::selection_set::field_with_alias(self.alias.as_deref(), #query_field_name, self.args, #custom_selector)
                 }
             }
         } else {
@@ -77,7 +89,8 @@ impl FieldSelectionBuilder {
                     fields: ::cynic::selection_set::SelectionSet<'a, T, #argument_type_lock>
                 ) -> ::cynic::selection_set::SelectionSet<'a, #decodes_to, super::#type_lock>
                     {
-                        ::cynic::selection_set::field(
+                        ::cynic::selection_set::field_with_alias(
+                            self.alias.as_deref(),
                             #query_field_name,
                             self.args,
                             #selector
@@ -117,19 +130,38 @@ impl quote::ToTokens for FieldSelectionBuilder {
                 .to_tokens(generic_inner_type, Ident::for_module("super").into())
         });
 
+        let argument_docs = self.optional_args.iter().map(|a| {
+            a.default_value.as_ref().map(|default_value| {
+                let doc = format!(
+                    "Defaults to `{}` on the server if not provided.",
+                    default_value
+                );
+                quote! { #[doc = #doc] }
+            })
+        });
+
         let select_func = self.select_function_tokens();
 
         tokens.append_all(quote! {
             pub struct #name {
-                args: Vec<::cynic::Argument>
+                args: Vec<::cynic::Argument>,
+                alias: Option<String>,
             }
 
             impl #name {
                 pub(super) fn new(args: Vec<::cynic::Argument>) -> Self {
-                    #name { args }
+                    #name { args, alias: None }
+                }
+
+                /// Selects this field under `alias`, so it can be selected
+                /// more than once with different arguments.
+                pub fn alias(mut self, alias: &str) -> Self {
+                    self.alias = Some(alias.to_string());
+                    self
                 }
 
                 #(
+                    #argument_docs
                     pub fn #argument_names #argument_generics(
                         mut self, #argument_names: impl ::cynic::IntoArgument<#argument_types>
                     ) -> Self {
@@ -4,6 +4,7 @@ use super::field_selector::FieldSelector;
 use super::selection_builder::FieldSelectionBuilder;
 
 use crate::{
+    ident::make_unique,
     schema::{self, FieldExt},
     FieldType, Ident, TypeIndex, TypePath,
 };
@@ -19,19 +20,52 @@ pub struct SelectorStruct {
     pub graphql_name: String,
     pub fields: Vec<FieldSelector>,
     pub selection_builders: Vec<FieldSelectionBuilder>,
+    pub description: Option<String>,
 }
 
 impl SelectorStruct {
-    pub fn from_object(obj: &schema::ObjectType, type_index: &TypeIndex) -> Self {
+    /// Builds a SelectorStruct for an object.
+    ///
+    /// Any fields that require a schema preview that isn't in
+    /// `enabled_previews` are left out.
+    pub fn from_object(
+        obj: &schema::ObjectType,
+        type_index: &TypeIndex,
+        enabled_previews: &[String],
+    ) -> Self {
         let name = Ident::for_type(&obj.name);
 
         let mut processed_fields = Vec::with_capacity(obj.fields.len());
         let mut selection_builders = Vec::with_capacity(obj.fields.len());
 
-        for field in &obj.fields {
+        // Fields whose names only differ in case would get the same rust
+        // names, so we make them unique.  The QueryFragment derive does the
+        // same with every field, so this is done before leaving any out.
+        let rust_field_names = Ident::for_fields(obj.fields.iter().map(|f| f.name.as_str()));
+        let selection_builder_names = make_unique(
+            obj.fields
+                .iter()
+                .map(|f| Ident::for_type(format!("{}SelectionBuilder", f.name)))
+                .collect(),
+            "",
+        );
+
+        for ((field, rust_field_name), selection_builder_name) in obj
+            .fields
+            .iter()
+            .zip(rust_field_names)
+            .zip(selection_builder_names)
+        {
+            if let Some(preview) = field.required_preview() {
+                if !enabled_previews.iter().any(|p| p == preview) {
+                    continue;
+                }
+            }
+
             let field_type = FieldType::from_schema_type(&field.field_type, type_index);
 
             let selection_builder = FieldSelectionBuilder::for_field(
+                selection_builder_name,
                 &field.name,
                 field_type.clone(),
                 name.clone(),
@@ -40,11 +74,11 @@ impl SelectorStruct {
             );
 
             processed_fields.push(FieldSelector::for_field(
-                &field.name,
+                field,
+                rust_field_name,
                 field_type,
                 name.clone(),
                 Ident::for_module(&obj.name),
-                field.required_arguments(),
                 TypePath::new(vec![
                     Ident::for_module(&name.to_string()),
                     selection_builder.name.clone(),
@@ -60,6 +94,7 @@ impl SelectorStruct {
             graphql_name: obj.name.clone(),
             fields: processed_fields,
             selection_builders,
+            description: obj.description.clone(),
         }
     }
 }
@@ -70,8 +105,12 @@ impl quote::ToTokens for SelectorStruct {
 
         let name = &self.name;
         let fields = &self.fields;
+        let doc = self.description.as_ref().map(|description| {
+            quote! { #[doc = #description] }
+        });
 
         tokens.append_all(quote! {
+            #doc
             #[allow(dead_code)]
             pub struct #name;
 
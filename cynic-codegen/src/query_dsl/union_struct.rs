@@ -30,6 +30,7 @@ impl quote::ToTokens for UnionStruct {
 
         tokens.append_all(quote! {
             #[allow(dead_code)]
+            #[doc(hidden)]
             pub struct #name {}
 
             #(
@@ -1,18 +1,47 @@
-// TODO: docstring.
+//! The query_module attribute macro.
+//!
+//! This wraps a module of fragments, enums & input objects, gives them all the
+//! same `schema_path` (or `sdl`) & `query_module` and checks any references between
+//! them are for compatible types.
 use darling::{util::SpannedValue, FromMeta};
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
+
+use crate::schema::SchemaSource;
 
 mod utils;
+mod validation;
 
 use utils::Derive;
 
 #[derive(Debug, FromMeta)]
 struct TransformModuleArgs {
-    schema_path: SpannedValue<String>,
+    #[darling(default)]
+    schema_path: Option<SpannedValue<String>>,
+    #[darling(default)]
+    sdl: Option<SpannedValue<String>>,
     // TODO: consider getting rid of query_module at some point (or at least making optional)
     query_module: SpannedValue<String>,
 }
 
+#[derive(Debug, FromMeta)]
+struct SchemaForDerivesArgs {
+    #[darling(default)]
+    file: Option<SpannedValue<String>>,
+    #[darling(default)]
+    sdl: Option<SpannedValue<String>>,
+    module: SpannedValue<String>,
+}
+
+impl From<SchemaForDerivesArgs> for TransformModuleArgs {
+    fn from(args: SchemaForDerivesArgs) -> TransformModuleArgs {
+        TransformModuleArgs {
+            schema_path: args.file,
+            sdl: args.sdl,
+            query_module: args.module,
+        }
+    }
+}
+
 pub fn transform_query_module(
     args: syn::AttributeArgs,
     query_module: syn::ItemMod,
@@ -23,6 +52,20 @@ pub fn transform_query_module(
     }
 }
 
+/// Implements the `schema_for_derives` attribute macro.
+///
+/// This is the same transformation as `query_module`, but with argument names
+/// that are a bit more obvious about what they're for.
+pub fn add_schema_attrs_to_derives(
+    args: syn::AttributeArgs,
+    query_module: syn::ItemMod,
+) -> Result<TokenStream, syn::Error> {
+    match SchemaForDerivesArgs::from_list(&args) {
+        Ok(args) => transform_query_module_impl(args.into(), query_module),
+        Err(e) => Ok(e.write_errors()),
+    }
+}
+
 fn transform_query_module_impl(
     args: TransformModuleArgs,
     query_module: syn::ItemMod,
@@ -37,7 +80,19 @@ fn transform_query_module_impl(
 
     let module_items = module_items
         .into_iter()
-        .map(|item| insert_cynic_attrs(&args, item));
+        .map(|item| insert_cynic_attrs(&args, item))
+        .collect::<Vec<_>>();
+
+    // If the schema fails to load the derives will report that, so we only
+    // need to validate when it succeeds.
+    let mut errors = SchemaSource::from_attributes(&args.schema_path, &args.sdl, Span::call_site())
+        .ok()
+        .and_then(|source| crate::load_schema(&source).ok())
+        .map(|schema| validation::validate_cross_references(&module_items, &schema))
+        .unwrap_or_default();
+    errors.extend(validation::validate_inline_fragments(&module_items));
+    errors.extend(validation::validate_argument_usage(&module_items));
+    let errors = errors.into_iter().map(|e| e.to_compile_error());
 
     let attrs = query_module.attrs;
     let visibility = query_module.vis;
@@ -48,6 +103,8 @@ fn transform_query_module_impl(
         #visibility mod #module_name {
             #(#module_items)*
         }
+
+        #(#errors)*
     })
 }
 
@@ -56,7 +113,7 @@ fn insert_cynic_attrs(args: &TransformModuleArgs, item: syn::Item) -> syn::Item
 
     match utils::find_derives(&item).get(0) {
         None => item,
-        Some(Derive::Scalar) => item,
+        Some(Derive::Scalar) | Some(Derive::FragmentArguments) => item,
         Some(Derive::InlineFragments) | Some(Derive::Enum) => {
             if let Item::Enum(mut en) = item {
                 let attrs = PresentAttributes::from_attributes(&en.attrs);
@@ -94,7 +151,9 @@ impl PresentAttributes {
                 if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
                     for nested in meta_list.nested {
                         if let NestedMeta::Meta(Meta::NameValue(name_val)) = nested {
-                            if name_val.path.is_ident("schema_path") {
+                            if name_val.path.is_ident("schema_path")
+                                || name_val.path.is_ident("sdl")
+                            {
                                 rv.has_schema_path = true;
                             } else if name_val.path.is_ident("query_module") {
                                 rv.has_query_module = true;
@@ -109,10 +168,18 @@ impl PresentAttributes {
 
     fn add_missing_attributes(self, attrs: &mut Vec<syn::Attribute>, args: &TransformModuleArgs) {
         if !self.has_schema_path {
-            let schema_path = proc_macro2::Literal::string(&args.schema_path);
-            attrs.push(syn::parse_quote! {
-                #[cynic(schema_path = #schema_path)]
-            })
+            if let Some(schema_path) = &args.schema_path {
+                let schema_path = proc_macro2::Literal::string(schema_path);
+                attrs.push(syn::parse_quote! {
+                    #[cynic(schema_path = #schema_path)]
+                })
+            }
+            if let Some(sdl) = &args.sdl {
+                let sdl = proc_macro2::Literal::string(sdl);
+                attrs.push(syn::parse_quote! {
+                    #[cynic(sdl = #sdl)]
+                })
+            }
         }
 
         if !self.has_query_module {
@@ -130,7 +197,8 @@ mod tests {
 
     fn args() -> TransformModuleArgs {
         TransformModuleArgs {
-            schema_path: "test.graphql".to_string().into(),
+            schema_path: Some("test.graphql".to_string().into()),
+            sdl: None,
             query_module: "query_dsl".to_string().into(),
         }
     }
@@ -159,6 +227,48 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_schema_for_derives_args() {
+        let args: syn::AttributeArgs = vec![
+            syn::parse_quote! { file = "test.graphql" },
+            syn::parse_quote! { module = "query_dsl" },
+        ];
+
+        let args: TransformModuleArgs = SchemaForDerivesArgs::from_list(&args).unwrap().into();
+
+        assert_eq!(args.schema_path.as_deref().unwrap(), "test.graphql");
+        assert_eq!(*args.query_module, "query_dsl");
+    }
+
+    #[test]
+    fn test_insert_inline_schema_attrs() {
+        let args = TransformModuleArgs {
+            schema_path: None,
+            sdl: Some("scalar Date".to_string().into()),
+            ..args()
+        };
+        let item: syn::Item = syn::parse_quote! {
+            #[derive(cynic::Enum)]
+            enum Test {
+                A
+            }
+        };
+
+        let result = insert_cynic_attrs(&args, item);
+
+        assert_eq!(
+            result,
+            syn::parse_quote! {
+                #[derive(cynic::Enum)]
+                #[cynic(sdl = "scalar Date")]
+                #[cynic(query_module = "query_dsl")]
+                enum Test {
+                    A
+                }
+            }
+        )
+    }
+
     #[test]
     fn test_insert_cynic_attrs_when_already_inserted() {
         let item: syn::Item = syn::parse_quote! {
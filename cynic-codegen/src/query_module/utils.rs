@@ -7,6 +7,7 @@ pub enum Derive {
     Enum,
     Scalar,
     InputObject,
+    FragmentArguments,
 }
 
 pub fn find_derives(item: &Item) -> Vec<Derive> {
@@ -49,6 +50,7 @@ fn derive_for_nested_meta(nested: &NestedMeta) -> Option<Derive> {
                 "Enum" => return Some(Derive::Enum),
                 "Scalar" => return Some(Derive::Scalar),
                 "InputObject" => return Some(Derive::InputObject),
+                "FragmentArguments" => return Some(Derive::FragmentArguments),
                 _ => (),
             }
         }
@@ -0,0 +1,406 @@
+use std::collections::{HashMap, HashSet};
+
+use proc_macro2::{TokenStream, TokenTree};
+use syn::{spanned::Spanned, Item};
+
+use super::utils::{find_derives, Derive};
+use crate::{
+    schema::{Definition, Document, TypeDefinition, TypeExt},
+    Ident,
+};
+
+/// Checks that any QueryFragments inside a query_module that refer to each
+/// other are for compatible GraphQL types.
+///
+/// The type system would catch these problems eventually, but the errors
+/// it produces aren't particularly helpful.  Since we can see the whole
+/// module here we can do a bit better.
+pub fn validate_cross_references(items: &[Item], schema: &Document) -> Vec<syn::Error> {
+    let fragment_types = fragment_types(items);
+
+    let mut errors = vec![];
+
+    for item in items {
+        let st = match item {
+            Item::Struct(st) if find_derives(item).contains(&Derive::QueryFragment) => st,
+            _ => continue,
+        };
+
+        let graphql_type = match fragment_types.get(&st.ident.to_string()) {
+            Some(graphql_type) => graphql_type,
+            None => continue,
+        };
+
+        let object = match find_object(schema, graphql_type) {
+            Some(object) => object,
+            // The derive itself will report this.
+            None => continue,
+        };
+
+        for field in &st.fields {
+            let field_ident = match &field.ident {
+                Some(ident) => ident,
+                None => continue,
+            };
+
+            let referenced_struct = match innermost_type_name(&field.ty) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let referenced_graphql_type = match fragment_types.get(&referenced_struct) {
+                Some(ty) => ty,
+                None => continue,
+            };
+
+            let rust_field_name = Ident::for_field(field_ident.to_string());
+            let schema_field = object
+                .fields
+                .iter()
+                .find(|f| Ident::for_field(&f.name) == rust_field_name);
+
+            if let Some(schema_field) = schema_field {
+                let field_type_name = schema_field.field_type.inner_name();
+                if field_type_name != referenced_graphql_type.as_str() {
+                    errors.push(syn::Error::new(
+                        field.ty.span(),
+                        format!(
+                            "{} is a fragment on {} but the field {} on {} has the type {}",
+                            referenced_struct,
+                            referenced_graphql_type,
+                            schema_field.name,
+                            graphql_type,
+                            field_type_name
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Checks that each variant of any InlineFragments in a query_module is a
+/// fragment on a different GraphQL type.
+///
+/// Only the first variant for each type is ever decoded, so any others are
+/// unreachable.  The derive catches variants with the same rust type, but
+/// only here can we see which GraphQL type each fragment is for.
+pub fn validate_inline_fragments(items: &[Item]) -> Vec<syn::Error> {
+    use quote::ToTokens;
+
+    let fragment_types = fragment_types(items);
+
+    let mut errors = vec![];
+
+    for item in items {
+        let en = match item {
+            Item::Enum(en) if find_derives(item).contains(&Derive::InlineFragments) => en,
+            _ => continue,
+        };
+
+        let mut seen_types = HashMap::new();
+        for variant in &en.variants {
+            let ty = match &variant.fields {
+                syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+                // The derive will report this.
+                _ => continue,
+            };
+
+            let graphql_type = match innermost_type_name(ty).and_then(|n| fragment_types.get(&n)) {
+                Some(graphql_type) => graphql_type,
+                None => continue,
+            };
+
+            let type_string = ty.to_token_stream().to_string();
+            match seen_types.get(graphql_type) {
+                // The derive itself reports variants with the same rust type.
+                Some((_, previous_type)) if *previous_type == type_string => {}
+                Some((previous_variant, _)) => errors.push(syn::Error::new(
+                    variant.span(),
+                    format!(
+                        "This variant is unreachable: {} is also a fragment on {}",
+                        previous_variant, graphql_type
+                    ),
+                )),
+                None => {
+                    seen_types.insert(graphql_type, (variant.ident.to_string(), type_string));
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Checks that every field of the private FragmentArguments structs in a
+/// query_module is used somewhere in the module.
+///
+/// Anything outside the module can't refer to a private struct, so if none
+/// of its fields are accessed here the argument will never be sent.  Fields
+/// starting with an underscore are skipped, as with unused variables.
+pub fn validate_argument_usage(items: &[Item]) -> Vec<syn::Error> {
+    use quote::ToTokens;
+
+    let mut accessed = HashSet::new();
+    for item in items {
+        accessed_fields(item.to_token_stream(), &mut accessed);
+    }
+
+    let mut errors = vec![];
+
+    for item in items {
+        let st = match item {
+            Item::Struct(st) if find_derives(item).contains(&Derive::FragmentArguments) => st,
+            _ => continue,
+        };
+        if st.vis != syn::Visibility::Inherited {
+            continue;
+        }
+
+        for field in &st.fields {
+            let ident = match &field.ident {
+                Some(ident) => ident,
+                None => continue,
+            };
+            let name = ident.to_string();
+            if !name.starts_with('_') && !accessed.contains(&name) {
+                errors.push(syn::Error::new(
+                    ident.span(),
+                    format!(
+                        "The argument {} isn't used by any fragment in this module",
+                        name
+                    ),
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Finds the name of each QueryFragment in a module, and the GraphQL type
+/// that it's a fragment on.
+fn fragment_types(items: &[Item]) -> HashMap<String, String> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Struct(st) if find_derives(item).contains(&Derive::QueryFragment) => {
+                graphql_type_from_attrs(&st.attrs).map(|ty| (st.ident.to_string(), ty))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects every identifier that follows a `.` in `tokens` - i.e. anything
+/// that might be a field access.
+fn accessed_fields(tokens: TokenStream, accessed: &mut HashSet<String>) {
+    let mut after_dot = false;
+    for token in tokens {
+        match token {
+            TokenTree::Group(group) => {
+                accessed_fields(group.stream(), accessed);
+                after_dot = false;
+            }
+            TokenTree::Punct(punct) => after_dot = punct.as_char() == '.',
+            TokenTree::Ident(ident) => {
+                if after_dot {
+                    accessed.insert(ident.to_string());
+                }
+                after_dot = false;
+            }
+            TokenTree::Literal(_) => after_dot = false,
+        }
+    }
+}
+
+fn find_object<'a>(schema: &'a Document, name: &str) -> Option<&'a crate::schema::ObjectType> {
+    schema.definitions.iter().find_map(|def| match def {
+        Definition::TypeDefinition(TypeDefinition::Object(obj)) if obj.name == name => Some(obj),
+        _ => None,
+    })
+}
+
+fn graphql_type_from_attrs(attrs: &[syn::Attribute]) -> Option<String> {
+    use syn::{Lit, Meta, NestedMeta};
+
+    for attr in attrs {
+        if !attr.path.is_ident("cynic") {
+            continue;
+        }
+        if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
+            for nested in meta_list.nested {
+                if let NestedMeta::Meta(Meta::NameValue(name_val)) = nested {
+                    if name_val.path.is_ident("graphql_type") {
+                        if let Lit::Str(lit) = name_val.lit {
+                            return Some(lit.value());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Strips any Option, Vec or Box wrappers from a type and returns the name
+/// of the type inside.
+fn innermost_type_name(ty: &syn::Type) -> Option<String> {
+    use syn::{GenericArgument, PathArguments, Type};
+
+    if let Type::Path(type_path) = ty {
+        let segment = type_path.path.segments.last()?;
+        let name = segment.ident.to_string();
+        if name == "Option" || name == "Vec" || name == "Box" {
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                    return innermost_type_name(inner);
+                }
+            }
+            return None;
+        }
+        return Some(name);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::parse_schema;
+
+    fn schema() -> Document {
+        parse_schema(
+            r#"
+            type Query {
+                film: Film
+                films: [Film!]
+            }
+
+            type Film {
+                title: String
+            }
+
+            type Person {
+                name: String
+            }
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn items() -> Vec<Item> {
+        vec![
+            syn::parse_quote! {
+                #[derive(cynic::QueryFragment)]
+                #[cynic(graphql_type = "Film")]
+                struct Film {
+                    title: Option<String>,
+                }
+            },
+            syn::parse_quote! {
+                #[derive(cynic::QueryFragment)]
+                #[cynic(graphql_type = "Person")]
+                struct Person {
+                    name: Option<String>,
+                }
+            },
+        ]
+    }
+
+    #[test]
+    fn test_compatible_references() {
+        let mut items = items();
+        items.push(syn::parse_quote! {
+            #[derive(cynic::QueryFragment)]
+            #[cynic(graphql_type = "Query")]
+            struct Query {
+                film: Option<Film>,
+                films: Option<Vec<Film>>,
+            }
+        });
+
+        assert!(validate_cross_references(&items, &schema()).is_empty());
+    }
+
+    #[test]
+    fn test_incompatible_references() {
+        let mut items = items();
+        items.push(syn::parse_quote! {
+            #[derive(cynic::QueryFragment)]
+            #[cynic(graphql_type = "Query")]
+            struct Query {
+                film: Option<Person>,
+                films: Option<Vec<Person>>,
+            }
+        });
+
+        assert_eq!(validate_cross_references(&items, &schema()).len(), 2);
+    }
+
+    #[test]
+    fn test_unreachable_inline_fragments() {
+        let mut items = items();
+        items.push(syn::parse_quote! {
+            #[derive(cynic::QueryFragment)]
+            #[cynic(graphql_type = "Film")]
+            struct FilmTitle {
+                title: Option<String>,
+            }
+        });
+        items.push(syn::parse_quote! {
+            #[derive(cynic::InlineFragments)]
+            #[cynic(graphql_type = "Search")]
+            enum Search {
+                Film(Film),
+                Person(Person),
+                FilmTitle(FilmTitle),
+            }
+        });
+
+        let errors = validate_inline_fragments(&items);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "This variant is unreachable: Film is also a fragment on Film"
+        );
+    }
+
+    #[test]
+    fn test_unused_arguments() {
+        let mut items = items();
+        items.push(syn::parse_quote! {
+            #[derive(cynic::FragmentArguments)]
+            struct FilmArguments {
+                id: String,
+                unused: i32,
+                _ignored: i32,
+            }
+        });
+        items.push(syn::parse_quote! {
+            #[derive(cynic::FragmentArguments)]
+            pub struct PublicArguments {
+                unused: i32,
+            }
+        });
+        items.push(syn::parse_quote! {
+            #[derive(cynic::QueryFragment)]
+            #[cynic(graphql_type = "Query", argument_struct = "FilmArguments")]
+            struct Query {
+                #[arguments(id = args.id.clone())]
+                film: Option<Film>,
+            }
+        });
+
+        let errors = validate_argument_usage(&items);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "The argument unused isn't used by any fragment in this module"
+        );
+    }
+}
@@ -1,8 +1,13 @@
 #[derive(darling::FromDeriveInput)]
-#[darling(supports(struct_newtype))]
+#[darling(attributes(cynic), supports(struct_newtype))]
 pub struct ScalarDeriveInput {
     pub(super) ident: proc_macro2::Ident,
     pub(super) data: darling::ast::Data<(), ScalarDeriveField>,
+
+    /// Whether the scalar is sent as a string, which the inner type is
+    /// parsed from with `FromStr`.
+    #[darling(default)]
+    pub(super) string_encoded: bool,
 }
 
 #[derive(darling::FromField)]
@@ -29,14 +29,32 @@ pub fn scalar_derive_impl(input: ScalarDeriveInput) -> Result<TokenStream, syn::
     let ident = input.ident;
     let inner_type = field.ty;
 
+    if input.string_encoded {
+        return Ok(quote! {
+            impl ::cynic::Scalar for #ident {
+                fn decode(value: &::cynic::__private::serde_json::Value) -> Result<Self, ::cynic::DecodeError> {
+                    Ok(#ident(::cynic::scalar::decode_string_encoded::<#inner_type>(value)?))
+                }
+                fn encode(&self) -> Result<::cynic::__private::serde_json::Value, ::cynic::SerializeError> {
+                    ::cynic::scalar::encode_string_encoded(&self.0)
+                }
+            }
+
+            ::cynic::impl_serializable_argument_for_scalar!(#ident);
+        });
+    }
+
     Ok(quote! {
         impl ::cynic::Scalar for #ident {
-            fn decode(value: &::cynic::serde_json::Value) -> Result<Self, ::cynic::DecodeError> {
+            fn decode(value: &::cynic::__private::serde_json::Value) -> Result<Self, ::cynic::DecodeError> {
                 Ok(#ident(<#inner_type as ::cynic::Scalar>::decode(value)?))
             }
-            fn encode(&self) -> Result<::cynic::serde_json::Value, ::cynic::SerializeError> {
+            fn encode(&self) -> Result<::cynic::__private::serde_json::Value, ::cynic::SerializeError> {
                 Ok(self.0.encode()?)
             }
+            fn validate(&self) -> Result<(), String> {
+                self.0.validate()
+            }
         }
 
         ::cynic::impl_serializable_argument_for_scalar!(#ident);
@@ -1,4 +1,16 @@
-use crate::{Error, FieldArgument, TypeIndex};
+use darling::util::SpannedValue;
+use lazy_static::lazy_static;
+use proc_macro2::Span;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    glob, introspection::introspection_to_sdl, type_index::name_for_type, Error, FieldArgument,
+    TypeIndex,
+};
 
 // Alias all the graphql_parser schema types so we don't have to specify generic parameters
 // everywhere
@@ -16,27 +28,870 @@ pub type UnionType = graphql_parser::schema::UnionType<'static, String>;
 pub type InputValue = graphql_parser::schema::InputValue<'static, String>;
 pub type EnumValue = graphql_parser::schema::EnumValue<'static, String>;
 
-/// Loads a schema from a filename, relative to CARGO_MANIFEST_DIR if it's set.
-pub fn load_schema(filename: impl AsRef<std::path::Path>) -> Result<Document, Error> {
+/// Converts an interface into an object with the same fields, so it can be
+/// treated like any other type with fields.
+pub fn interface_as_object(interface: &InterfaceType) -> ObjectType {
+    let mut object = ObjectType::new(interface.name.clone());
+    object.description = interface.description.clone();
+    object.directives = interface.directives.clone();
+    object.fields = interface.fields.clone();
+    object
+}
+
+/// The prefix users can put on a schema path to have it resolved relative to
+/// OUT_DIR.  This lets people use schemas that have been output by a build
+/// script.
+const OUT_DIR_PREFIX: &str = "$OUT_DIR";
+
+/// An environment variable that overrides the directory that schema paths are
+/// resolved relative to.
+const SCHEMA_DIR_VAR: &str = "CYNIC_SCHEMA_DIR";
+
+lazy_static! {
+    /// Schemas that have already been parsed, keyed by their path.
+    ///
+    /// All of the derives in a crate are expanded by the same compiler
+    /// process, so this saves parsing the schema again for every derive.
+    static ref SCHEMA_CACHE: Mutex<HashMap<SchemaKey, CachedSchema>> = Mutex::new(HashMap::new());
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum SchemaKey {
+    Files(Vec<PathBuf>),
+    Inline(String),
+}
+
+struct CachedSchema {
+    /// A hash of the files that `document` was parsed from.  Long running
+    /// processes like rust-analyzer can see a schema change, so we check
+    /// this before re-using the document.
+    contents_hash: u64,
+    document: Arc<Document>,
+}
+
+/// Where a macro should load its schema from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaSource {
+    /// The filename of the schema - see `load_schema`.
+    ///
+    /// This can list several files separated by commas, as the `schema_path`
+    /// of a derive does.
+    Path(String),
+    /// The filenames of a schema that's split across several files.  Unlike
+    /// `Path` these are never split on commas.
+    Paths(Vec<String>),
+    /// The SDL of the schema itself, which is passed to the macros with an
+    /// `sdl` argument.
+    Sdl(String),
+}
+
+impl SchemaSource {
+    /// Picks the schema from the `schema_path` & `sdl` attributes of a derive,
+    /// exactly one of which should be provided.
+    pub(crate) fn from_attributes(
+        schema_path: &Option<SpannedValue<String>>,
+        sdl: &Option<SpannedValue<String>>,
+        span: Span,
+    ) -> Result<SpannedValue<SchemaSource>, syn::Error> {
+        match (schema_path, sdl) {
+            (Some(path), None) => Ok(SpannedValue::new(
+                SchemaSource::Path((**path).clone()),
+                path.span(),
+            )),
+            (None, Some(sdl)) => Ok(SpannedValue::new(
+                SchemaSource::Sdl((**sdl).clone()),
+                sdl.span(),
+            )),
+            (Some(_), Some(sdl)) => Err(syn::Error::new(
+                sdl.span(),
+                "schema_path & sdl can't both be provided",
+            )),
+            (None, None) => Err(syn::Error::new(span, "Missing field `schema_path`")),
+        }
+    }
+
+    /// A name for the schema to use in error messages, which shouldn't
+    /// contain the whole of an inline schema.
+    pub fn name(&self) -> std::borrow::Cow<'_, str> {
+        match self {
+            SchemaSource::Path(filename) => filename.as_str().into(),
+            SchemaSource::Paths(filenames) => filenames.join(", ").into(),
+            SchemaSource::Sdl(_) => "the inline schema".into(),
+        }
+    }
+
+    /// Resolves the files that make up the schema, which there are none of
+    /// for an inline schema.
+    pub(crate) fn file_paths(&self) -> Result<Vec<PathBuf>, Error> {
+        match self {
+            SchemaSource::Path(filename) => schema_file_paths(
+                &filename
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .collect::<Vec<_>>(),
+            ),
+            SchemaSource::Paths(filenames) => schema_file_paths(filenames),
+            SchemaSource::Sdl(_) => Ok(vec![]),
+        }
+    }
+}
+
+/// Loads a schema, reading it from a file if it wasn't given as SDL.
+///
+/// Filenames are relative to CARGO_MANIFEST_DIR if it's set.  Filenames that
+/// start with `$OUT_DIR` are loaded relative to the OUT_DIR of the current
+/// crate instead, and `CYNIC_SCHEMA_DIR` can be set to load all other
+/// filenames relative to a different directory.  Schemas that are split
+/// across several files can be loaded by listing the files - see
+/// `schema_file_paths`.
+///
+/// Parsed schemas are cached for the lifetime of the process, so loading the
+/// same schema again only needs to read the file.
+pub fn load_schema(source: &SchemaSource) -> Result<Arc<Document>, Error> {
+    use std::hash::{Hash, Hasher};
+
+    let (key, contents) = match source {
+        SchemaSource::Sdl(sdl) => (SchemaKey::Inline(sdl.clone()), vec![sdl.clone()]),
+        SchemaSource::Path(_) | SchemaSource::Paths(_) => {
+            let paths = source.file_paths()?;
+            let contents = read_schema_files(&paths)?;
+            (SchemaKey::Files(paths), contents)
+        }
+    };
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    contents.hash(&mut hasher);
+    let contents_hash = hasher.finish();
+
+    let mut cache = SCHEMA_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.get(&key) {
+        if cached.contents_hash == contents_hash {
+            return Ok(Arc::clone(&cached.document));
+        }
+    }
+
+    let sdl = match &key {
+        SchemaKey::Files(paths) => schema_files_sdl(paths, contents)?,
+        SchemaKey::Inline(_) => contents.concat(),
+    };
+    let document = Arc::new(parse_schema(&sdl)?);
+    cache.insert(
+        key,
+        CachedSchema {
+            contents_hash,
+            document: Arc::clone(&document),
+        },
+    );
+
+    Ok(document)
+}
+
+/// Loads the schema named by the `schema_path` or `sdl` attribute of a
+/// derive, reporting any errors against that attribute.
+pub(crate) fn load_derive_schema(
+    schema_path: &Option<SpannedValue<String>>,
+    sdl: &Option<SpannedValue<String>>,
+    span: Span,
+) -> Result<Arc<Document>, syn::Error> {
+    let source = SchemaSource::from_attributes(schema_path, sdl, span)?;
+
+    load_schema(&source).map_err(|e| e.to_syn_error(source.span()))
+}
+
+/// Reads the SDL of a schema, loading any files in the same way as
+/// `load_schema`.
+///
+/// Files with a `.json` extension are expected to contain the result of an
+/// introspection query, and are converted to SDL.
+pub(crate) fn read_schema(source: &SchemaSource) -> Result<String, Error> {
+    if let SchemaSource::Sdl(sdl) = source {
+        return Ok(sdl.clone());
+    }
+
+    let paths = source.file_paths()?;
+    let contents = read_schema_files(&paths)?;
+
+    schema_files_sdl(&paths, contents)
+}
+
+fn read_schema_files(paths: &[PathBuf]) -> Result<Vec<String>, Error> {
+    paths
+        .iter()
+        .map(|pathbuf| {
+            std::fs::read_to_string(pathbuf)
+                .map_err(|_| Error::FileNotFound(pathbuf.to_str().unwrap().to_string()))
+        })
+        .collect()
+}
+
+/// Converts the contents of the files a schema is split across into a single
+/// SDL document.
+///
+/// Type extensions & references are resolved once the whole document has
+/// been parsed, so types can refer to or extend types in any of the files.
+fn schema_files_sdl(paths: &[PathBuf], contents: Vec<String>) -> Result<String, Error> {
+    let sdl = paths
+        .iter()
+        .zip(contents)
+        .map(|(pathbuf, contents)| schema_sdl(pathbuf, contents))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(sdl.join("\n"))
+}
+
+/// Converts the contents of a schema file to SDL, if they're not already.
+fn schema_sdl(pathbuf: &std::path::Path, contents: String) -> Result<String, Error> {
+    if pathbuf.extension().map_or(false, |ext| ext == "json") {
+        return introspection_to_sdl(&contents);
+    }
+
+    Ok(contents)
+}
+
+/// Resolves the filenames of a schema into the files that make up the schema.
+///
+/// A schema can be split across several files, e.g.
+/// `["schema.graphql", "types/*.graphql"]`.  Each name is resolved like any
+/// other schema filename, and can be a glob - see `glob::expand`.
+pub(crate) fn schema_file_paths<S: AsRef<str>>(filenames: &[S]) -> Result<Vec<PathBuf>, Error> {
+    let mut paths = vec![];
+    for name in filenames.iter().map(AsRef::as_ref) {
+        let pathbuf = schema_file_path(std::path::Path::new(name))?;
+        if !glob::is_pattern(name) {
+            paths.push(pathbuf);
+            continue;
+        }
+
+        let matches = glob::expand(&pathbuf);
+        if matches.is_empty() {
+            return Err(Error::FileNotFound(pathbuf.to_str().unwrap().to_string()));
+        }
+        paths.extend(matches);
+    }
+
+    // Root files are often listed alongside a glob that also matches them.
+    let mut unique_paths = Vec::with_capacity(paths.len());
+    for pathbuf in paths {
+        if !unique_paths.contains(&pathbuf) {
+            unique_paths.push(pathbuf);
+        }
+    }
+
+    Ok(unique_paths)
+}
+
+/// Resolves a single schema filename in the same way as `load_schema`.
+pub(crate) fn schema_file_path(filename: &std::path::Path) -> Result<std::path::PathBuf, Error> {
+    let schema_dir = std::env::var_os(SCHEMA_DIR_VAR).filter(|dir| !dir.is_empty());
+    let out_dir = std::env::var_os("OUT_DIR");
+    resolve_schema_path(
+        filename,
+        schema_dir.map(Into::into),
+        out_dir.map(Into::into),
+    )
+}
+
+fn resolve_schema_path(
+    filename: &std::path::Path,
+    schema_dir: Option<std::path::PathBuf>,
+    out_dir: Option<std::path::PathBuf>,
+) -> Result<std::path::PathBuf, Error> {
     use std::path::PathBuf;
     let mut pathbuf = PathBuf::new();
 
-    if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+    if let Ok(relative_path) = filename.strip_prefix(OUT_DIR_PREFIX) {
+        let out_dir = out_dir.ok_or(Error::OutDirNotSet)?;
+        pathbuf.push(out_dir);
+        pathbuf.push(relative_path);
+        return Ok(pathbuf);
+    }
+
+    if let Some(schema_dir) = schema_dir {
+        pathbuf.push(schema_dir);
+    } else if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
         pathbuf.push(manifest_dir);
     } else {
         pathbuf.push(std::env::current_dir()?);
     }
     pathbuf.push(filename);
 
-    let schema = std::fs::read_to_string(&pathbuf)
-        .map_err(|_| Error::FileNotFound(pathbuf.to_str().unwrap().to_string()))?;
-
-    parse_schema(&schema)
+    Ok(pathbuf)
 }
 
 pub(crate) fn parse_schema(schema: &str) -> Result<Document, Error> {
+    let (schema, root_types) = strip_schema_extensions(schema)?;
     let borrowed_schema = graphql_parser::schema::parse_schema::<String>(&schema)?;
-    Ok(schema_into_static(borrowed_schema))
+    let document = merge_schema_extensions(schema_into_static(borrowed_schema), root_types)?;
+    let document = merge_type_extensions(document)?;
+    add_federation_types(document)
+}
+
+/// Removes any `extend schema` definitions from a schema, returning the root
+/// operation types that they declare.
+///
+/// Federation subgraphs use these to `@link` in the federation directives,
+/// but graphql_parser can't parse them.  The directives don't change the
+/// types we generate, and any root operation types are merged into the
+/// schema definition by `merge_schema_extensions`.  The extensions are
+/// replaced with whitespace so the positions in any parse errors are
+/// unchanged.
+fn strip_schema_extensions(
+    schema: &str,
+) -> Result<(std::borrow::Cow<'_, str>, Vec<(String, String)>), Error> {
+    if !schema.contains("extend") {
+        return Ok((schema.into(), vec![]));
+    }
+
+    let tokens = sdl_tokens(schema);
+    let mut root_types = vec![];
+    let mut extensions = vec![];
+    let mut depth = 0;
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let (token, range) = &tokens[index];
+        match token {
+            SdlToken::Punctuator('{') | SdlToken::Punctuator('(') | SdlToken::Punctuator('[') => {
+                depth += 1
+            }
+            SdlToken::Punctuator('}') | SdlToken::Punctuator(')') | SdlToken::Punctuator(']') => {
+                depth -= 1
+            }
+            SdlToken::Name("extend")
+                if depth == 0
+                    && tokens.get(index + 1).map(|(token, _)| token)
+                        == Some(&SdlToken::Name("schema")) =>
+            {
+                let start = range.start;
+                let (end, operations) = schema_extension(schema, &tokens, index + 2)?;
+                extensions.push(start..tokens[end - 1].1.end);
+                root_types.extend(operations);
+                index = end;
+                continue;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    if extensions.is_empty() {
+        return Ok((schema.into(), root_types));
+    }
+
+    let mut output = String::with_capacity(schema.len());
+    let mut position = 0;
+    for range in extensions {
+        output.push_str(&schema[position..range.start]);
+        output.extend(
+            schema[range.clone()]
+                .chars()
+                .map(|c| if c == '\n' { '\n' } else { ' ' }),
+        );
+        position = range.end;
+    }
+    output.push_str(&schema[position..]);
+
+    Ok((output.into(), root_types))
+}
+
+/// Reads the directives & root operation types of the `extend schema`
+/// definition whose tokens start at `index`, returning the index of the first
+/// token after it.
+fn schema_extension(
+    schema: &str,
+    tokens: &[(SdlToken<'_>, std::ops::Range<usize>)],
+    mut index: usize,
+) -> Result<(usize, Vec<(String, String)>), Error> {
+    let token = |index: usize| tokens.get(index).map(|(token, _)| *token);
+    let invalid = |index: usize| {
+        let position = tokens
+            .get(index)
+            .map_or(schema.len(), |(_, range)| range.start);
+        Error::ParseError(format!(
+            "Invalid extend schema definition at byte {}",
+            position
+        ))
+    };
+
+    while token(index) == Some(SdlToken::Punctuator('@')) {
+        match token(index + 1) {
+            Some(SdlToken::Name(_)) => index += 2,
+            _ => return Err(invalid(index + 1)),
+        }
+
+        if token(index) == Some(SdlToken::Punctuator('(')) {
+            let mut depth = 0;
+            loop {
+                match token(index) {
+                    Some(SdlToken::Punctuator('(')) => depth += 1,
+                    Some(SdlToken::Punctuator(')')) => depth -= 1,
+                    None => return Err(invalid(index)),
+                    _ => {}
+                }
+                index += 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut operations = vec![];
+    if token(index) == Some(SdlToken::Punctuator('{')) {
+        index += 1;
+        loop {
+            match (token(index), token(index + 1), token(index + 2)) {
+                (Some(SdlToken::Punctuator('}')), _, _) => break,
+                (
+                    Some(SdlToken::Name(operation)),
+                    Some(SdlToken::Punctuator(':')),
+                    Some(SdlToken::Name(type_name)),
+                ) if ["query", "mutation", "subscription"].contains(&operation) => {
+                    operations.push((operation.to_string(), type_name.to_string()));
+                    index += 3;
+                }
+                _ => return Err(invalid(index)),
+            }
+        }
+        index += 1;
+    }
+
+    Ok((index, operations))
+}
+
+/// Adds the root operation types from any `extend schema` definitions to the
+/// schema definition, creating one if the schema doesn't have one.
+fn merge_schema_extensions(
+    mut document: Document,
+    root_types: Vec<(String, String)>,
+) -> Result<Document, Error> {
+    use graphql_parser::schema::SchemaDefinition;
+
+    if root_types.is_empty() {
+        return Ok(document);
+    }
+
+    let existing = document
+        .definitions
+        .iter()
+        .position(|definition| matches!(definition, Definition::SchemaDefinition(_)));
+    let index = match existing {
+        Some(index) => index,
+        None => {
+            document
+                .definitions
+                .push(Definition::SchemaDefinition(SchemaDefinition {
+                    position: graphql_parser::Pos { line: 1, column: 1 },
+                    directives: vec![],
+                    query: None,
+                    mutation: None,
+                    subscription: None,
+                }));
+            document.definitions.len() - 1
+        }
+    };
+
+    let mut schema = match document.definitions.remove(index) {
+        Definition::SchemaDefinition(schema) => schema,
+        _ => unreachable!(),
+    };
+
+    for (operation, type_name) in root_types {
+        let root_type = match operation.as_str() {
+            "query" => &mut schema.query,
+            "mutation" => &mut schema.mutation,
+            _ => &mut schema.subscription,
+        };
+        if root_type.is_some() {
+            return Err(Error::ParseError(format!(
+                "The {} type of the schema is defined more than once",
+                operation
+            )));
+        }
+        *root_type = Some(type_name);
+    }
+
+    if existing.is_none() {
+        // Without a schema definition the root types are found by name, so we
+        // keep any of those that the extensions didn't replace.
+        let is_defined = |name: &str| {
+            document
+                .definitions
+                .iter()
+                .any(|definition| match definition {
+                    Definition::TypeDefinition(type_def) => name_for_type(type_def) == name,
+                    _ => false,
+                })
+        };
+        let defaults = vec![
+            (&mut schema.query, "Query"),
+            (&mut schema.mutation, "Mutation"),
+            (&mut schema.subscription, "Subscription"),
+        ];
+        for (root_type, name) in defaults {
+            if root_type.is_none() && is_defined(name) {
+                *root_type = Some(name.to_string());
+            }
+        }
+    }
+
+    document
+        .definitions
+        .insert(index, Definition::SchemaDefinition(schema));
+
+    Ok(document)
+}
+
+/// A token of GraphQL SDL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SdlToken<'a> {
+    Name(&'a str),
+    Punctuator(char),
+    /// A string, block string or number.
+    Value,
+}
+
+/// Splits SDL into tokens, along with the byte range that each one covers.
+///
+/// This only needs to be good enough to find the definitions that
+/// graphql_parser can't handle, so it doesn't validate anything - but it does
+/// skip over comments & strings, so their contents are never mistaken for
+/// definitions.
+fn sdl_tokens(schema: &str) -> Vec<(SdlToken<'_>, std::ops::Range<usize>)> {
+    let bytes = schema.as_bytes();
+    let is_name_byte = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_';
+
+    let mut tokens = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        let byte = bytes[index];
+        index += 1;
+
+        let token = match byte {
+            b'#' => {
+                while index < bytes.len() && bytes[index] != b'\n' {
+                    index += 1;
+                }
+                continue;
+            }
+            b'"' if bytes[start..].starts_with(b"\"\"\"") => {
+                index = start + 3;
+                while index < bytes.len() && !bytes[index..].starts_with(b"\"\"\"") {
+                    if bytes[index..].starts_with(b"\\\"\"\"") {
+                        index += 4;
+                    } else {
+                        index += 1;
+                    }
+                }
+                index = (index + 3).min(bytes.len());
+                SdlToken::Value
+            }
+            b'"' => {
+                while index < bytes.len() && bytes[index] != b'"' && bytes[index] != b'\n' {
+                    if bytes[index] == b'\\' {
+                        index += 1;
+                    }
+                    index += 1;
+                }
+                index = (index + 1).min(bytes.len());
+                SdlToken::Value
+            }
+            b'.' if bytes[start..].starts_with(b"...") => {
+                index = start + 3;
+                SdlToken::Punctuator('.')
+            }
+            b'-' | b'0'..=b'9' => {
+                while index < bytes.len()
+                    && (is_name_byte(bytes[index]) || b".+-".contains(&bytes[index]))
+                {
+                    index += 1;
+                }
+                SdlToken::Value
+            }
+            _ if is_name_byte(byte) => {
+                while index < bytes.len() && is_name_byte(bytes[index]) {
+                    index += 1;
+                }
+                SdlToken::Name(&schema[start..index])
+            }
+            _ if byte.is_ascii_whitespace() || byte == b',' => continue,
+            _ if byte.is_ascii() => SdlToken::Punctuator(byte as char),
+            _ => {
+                // Skip the rest of this character - anything outside of ASCII
+                // is either a byte order mark or invalid.
+                while index < bytes.len() && !schema.is_char_boundary(index) {
+                    index += 1;
+                }
+                continue;
+            }
+        };
+
+        tokens.push((token, start..index));
+    }
+
+    tokens
+}
+
+/// Merges any type extensions (`extend type Query { ... }`) in a schema into
+/// the types they extend, so the rest of the codegen doesn't need to know
+/// about them.
+fn merge_type_extensions(mut document: Document) -> Result<Document, Error> {
+    use graphql_parser::schema::TypeExtension;
+
+    let federated = uses_federation(&document);
+
+    let (extensions, mut definitions): (Vec<_>, Vec<_>) = document
+        .definitions
+        .into_iter()
+        .partition(|definition| matches!(definition, Definition::TypeExtension(_)));
+
+    for extension in extensions {
+        let extension = match extension {
+            Definition::TypeExtension(extension) => extension,
+            _ => continue,
+        };
+
+        let name = match &extension {
+            TypeExtension::Scalar(ext) => &ext.name,
+            TypeExtension::Object(ext) => &ext.name,
+            TypeExtension::Interface(ext) => &ext.name,
+            TypeExtension::Union(ext) => &ext.name,
+            TypeExtension::Enum(ext) => &ext.name,
+            TypeExtension::InputObject(ext) => &ext.name,
+        }
+        .clone();
+
+        let extended = definitions.iter_mut().any(|definition| {
+            let type_def = match definition {
+                Definition::TypeDefinition(type_def) => type_def,
+                _ => return false,
+            };
+
+            match (type_def, &extension) {
+                (TypeDefinition::Scalar(def), TypeExtension::Scalar(ext)) if def.name == name => {
+                    def.directives.extend(ext.directives.iter().cloned());
+                }
+                (TypeDefinition::Object(def), TypeExtension::Object(ext)) if def.name == name => {
+                    def.implements_interfaces
+                        .extend(ext.implements_interfaces.iter().cloned());
+                    def.directives.extend(ext.directives.iter().cloned());
+                    def.fields.extend(ext.fields.iter().cloned());
+                }
+                (TypeDefinition::Interface(def), TypeExtension::Interface(ext))
+                    if def.name == name =>
+                {
+                    def.directives.extend(ext.directives.iter().cloned());
+                    def.fields.extend(ext.fields.iter().cloned());
+                }
+                (TypeDefinition::Union(def), TypeExtension::Union(ext)) if def.name == name => {
+                    def.directives.extend(ext.directives.iter().cloned());
+                    def.types.extend(ext.types.iter().cloned());
+                }
+                (TypeDefinition::Enum(def), TypeExtension::Enum(ext)) if def.name == name => {
+                    def.directives.extend(ext.directives.iter().cloned());
+                    def.values.extend(ext.values.iter().cloned());
+                }
+                (TypeDefinition::InputObject(def), TypeExtension::InputObject(ext))
+                    if def.name == name =>
+                {
+                    def.directives.extend(ext.directives.iter().cloned());
+                    def.fields.extend(ext.fields.iter().cloned());
+                }
+                _ => return false,
+            }
+            true
+        });
+
+        if !extended {
+            match extension {
+                // Federation subgraphs extend entities that are defined in
+                // other subgraphs, so these become the only definition.
+                TypeExtension::Object(ext) if federated => {
+                    let mut object = ObjectType::new(ext.name);
+                    object.position = ext.position;
+                    object.implements_interfaces = ext.implements_interfaces;
+                    object.directives = ext.directives;
+                    object.fields = ext.fields;
+                    definitions.push(Definition::TypeDefinition(TypeDefinition::Object(object)));
+                }
+                _ => return Err(Error::UnknownExtension(name)),
+            }
+        }
+    }
+
+    document.definitions = definitions;
+    Ok(document)
+}
+
+/// Checks whether a schema is an Apollo Federation subgraph, i.e. whether
+/// any of its objects have a `@key` or `@extends` directive.
+fn uses_federation(document: &Document) -> bool {
+    use graphql_parser::schema::TypeExtension;
+
+    let is_federation_directive = |d: &graphql_parser::schema::Directive<'static, String>| {
+        d.name == "key" || d.name == "extends"
+    };
+
+    document
+        .definitions
+        .iter()
+        .any(|definition| match definition {
+            Definition::TypeDefinition(TypeDefinition::Object(object)) => {
+                object.directives.iter().any(is_federation_directive)
+            }
+            Definition::TypeExtension(TypeExtension::Object(ext)) => {
+                ext.directives.iter().any(is_federation_directive)
+            }
+            _ => false,
+        })
+}
+
+/// Adds the types & fields that federation subgraphs provide for their
+/// router, if the schema doesn't already define them.
+///
+/// Subgraphs don't usually include these in their SDL, but they can be
+/// queried like any other field - so we add them to let people talk to
+/// subgraphs directly.
+fn add_federation_types(mut document: Document) -> Result<Document, Error> {
+    let entities = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeDefinition(TypeDefinition::Object(object))
+                if object.directives.iter().any(|d| d.name == "key") =>
+            {
+                Some(object.name.clone())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if entities.is_empty() {
+        return Ok(document);
+    }
+
+    let query_type = document
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::SchemaDefinition(schema) => schema.query.clone(),
+            _ => None,
+        })
+        .unwrap_or_else(|| "Query".to_string());
+
+    let federation_sdl = format!(
+        r#"
+        scalar _Any
+        union _Entity = {entities}
+        type _Service {{ sdl: String }}
+        type {query} {{
+            _entities(representations: [_Any!]!): [_Entity]!
+            _service: _Service!
+        }}
+        "#,
+        entities = entities.join(" | "),
+        query = query_type
+    );
+    let federation_types = graphql_parser::schema::parse_schema::<String>(&federation_sdl)?;
+
+    let type_name = |definition: &Definition| match definition {
+        Definition::TypeDefinition(type_def) => Some(name_for_type(type_def).to_string()),
+        _ => None,
+    };
+
+    for definition in schema_into_static(federation_types).definitions {
+        let name = type_name(&definition);
+        let existing = document
+            .definitions
+            .iter_mut()
+            .find(|existing| type_name(existing) == name);
+
+        match (existing, definition) {
+            (
+                Some(Definition::TypeDefinition(TypeDefinition::Object(existing))),
+                Definition::TypeDefinition(TypeDefinition::Object(object)),
+            ) if existing.name == query_type => {
+                for field in object.fields {
+                    if !existing.fields.iter().any(|f| f.name == field.name) {
+                        existing.fields.push(field);
+                    }
+                }
+            }
+            (Some(_), _) => {}
+            (None, definition) => document.definitions.push(definition),
+        }
+    }
+
+    Ok(document)
+}
+
+/// Checks that every type referenced in a schema is defined in it.
+///
+/// The codegen would otherwise output references to types that don't exist,
+/// which fail to compile with some very confusing errors.
+pub(crate) fn check_type_references(document: &Document) -> Result<(), Error> {
+    let index = TypeIndex::for_schema(document);
+    let check = |ty: &Type, location: String| {
+        let name = ty.inner_name();
+        let is_builtin = ["String", "Int", "Float", "Boolean", "ID"].contains(&name);
+        if is_builtin || index.lookup_type(name).is_some() {
+            Ok(())
+        } else {
+            Err(Error::UnknownType(name.to_string(), location))
+        }
+    };
+    let named = |name: &str| Type::NamedType(name.to_string());
+
+    for definition in &document.definitions {
+        let type_def = match definition {
+            Definition::TypeDefinition(type_def) => type_def,
+            _ => continue,
+        };
+
+        match type_def {
+            TypeDefinition::Object(object) => {
+                for interface in &object.implements_interfaces {
+                    check(&named(interface), object.name.clone())?;
+                }
+                check_fields(&object.name, &object.fields, &check)?;
+            }
+            TypeDefinition::Interface(interface) => {
+                check_fields(&interface.name, &interface.fields, &check)?;
+            }
+            TypeDefinition::Union(union) => {
+                for member in &union.types {
+                    check(&named(member), union.name.clone())?;
+                }
+            }
+            TypeDefinition::InputObject(input) => {
+                for field in &input.fields {
+                    check(&field.value_type, format!("{}.{}", input.name, field.name))?;
+                }
+            }
+            TypeDefinition::Scalar(_) | TypeDefinition::Enum(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn check_fields(
+    type_name: &str,
+    fields: &[Field],
+    check: &impl Fn(&Type, String) -> Result<(), Error>,
+) -> Result<(), Error> {
+    for field in fields {
+        check(&field.field_type, format!("{}.{}", type_name, field.name))?;
+        for argument in &field.arguments {
+            check(
+                &argument.value_type,
+                format!("{}.{}({})", type_name, field.name, argument.name),
+            )?;
+        }
+    }
+    Ok(())
 }
 
 fn schema_into_static<'a>(
@@ -54,6 +909,15 @@ fn schema_into_static<'a>(
 pub trait FieldExt {
     fn required_arguments(&self) -> Vec<InputValue>;
     fn optional_arguments(&self) -> Vec<InputValue>;
+
+    /// The schema preview that needs to be enabled to use this field, if any.
+    ///
+    /// Some APIs (notably GitHub) mark these with a
+    /// `@preview(toggledBy: "name")` directive.
+    fn required_preview(&self) -> Option<&str>;
+
+    /// The reason this field is deprecated, if it is deprecated.
+    fn deprecation(&self) -> Option<String>;
 }
 
 impl FieldExt for Field {
@@ -80,6 +944,36 @@ impl FieldExt for Field {
             .map(|a| a.clone())
             .collect()
     }
+
+    fn required_preview(&self) -> Option<&str> {
+        use graphql_parser::schema::Value;
+
+        let directive = self.directives.iter().find(|d| d.name == "preview")?;
+        directive
+            .arguments
+            .iter()
+            .find_map(|(name, value)| match value {
+                Value::String(preview) if name == "toggledBy" => Some(preview.as_str()),
+                _ => None,
+            })
+    }
+
+    fn deprecation(&self) -> Option<String> {
+        use graphql_parser::schema::Value;
+
+        let directive = self.directives.iter().find(|d| d.name == "deprecated")?;
+        let reason = directive
+            .arguments
+            .iter()
+            .find_map(|(name, value)| match value {
+                Value::String(reason) if name == "reason" => Some(reason.clone()),
+                _ => None,
+            });
+
+        // The GraphQL spec gives `reason` a default value, so we use that if
+        // the schema doesn't provide one.
+        Some(reason.unwrap_or_else(|| "No longer supported".to_string()))
+    }
 }
 
 /// Extension trait for the schema Type type
@@ -114,6 +1008,19 @@ impl TypeExt for Type {
     }
 }
 
+/// Extension trait for the schema InputObjectType type
+pub trait InputObjectTypeExt {
+    /// Whether this is a `@oneOf` input object, where exactly one field must
+    /// be provided.
+    fn is_one_of(&self) -> bool;
+}
+
+impl InputObjectTypeExt for InputObjectType {
+    fn is_one_of(&self) -> bool {
+        self.directives.iter().any(|d| d.name == "oneOf")
+    }
+}
+
 pub trait ScalarTypeExt {
     fn is_builtin(&self) -> bool;
 }
@@ -126,3 +1033,434 @@ impl ScalarTypeExt for ScalarType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_schema_path_with_out_dir() {
+        assert_eq!(
+            resolve_schema_path(
+                Path::new("$OUT_DIR/schema.graphql"),
+                None,
+                Some("/tmp/target/out".into())
+            )
+            .unwrap(),
+            PathBuf::from("/tmp/target/out/schema.graphql")
+        );
+        assert_eq!(
+            resolve_schema_path(
+                Path::new("$OUT_DIR/schema.graphql"),
+                Some("/schemas".into()),
+                Some("/tmp/target/out".into())
+            )
+            .unwrap(),
+            PathBuf::from("/tmp/target/out/schema.graphql")
+        );
+
+        assert_eq!(
+            resolve_schema_path(Path::new("$OUT_DIR/schema.graphql"), None, None),
+            Err(Error::OutDirNotSet)
+        );
+    }
+
+    #[test]
+    fn test_inline_schemas() {
+        let sdl = SchemaSource::Sdl("scalar Date".into());
+
+        assert_eq!(sdl.name(), "the inline schema");
+        assert_eq!(
+            SchemaSource::Path("schema.graphql".into()).name(),
+            "schema.graphql"
+        );
+        assert_eq!(
+            SchemaSource::Paths(vec!["schema.graphql".into(), "types/*.graphql".into()]).name(),
+            "schema.graphql, types/*.graphql"
+        );
+
+        let first = load_schema(&sdl).unwrap();
+        let second = load_schema(&sdl).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.definitions.len(), 1);
+
+        assert_eq!(read_schema(&sdl).unwrap(), "scalar Date");
+    }
+
+    #[test]
+    fn test_schema_source_from_attributes() {
+        let value = |s: &str| Some(SpannedValue::new(s.to_string(), Span::call_site()));
+
+        assert_eq!(
+            *SchemaSource::from_attributes(&value("schema.graphql"), &None, Span::call_site())
+                .unwrap(),
+            SchemaSource::Path("schema.graphql".into())
+        );
+        assert_eq!(
+            *SchemaSource::from_attributes(&None, &value("scalar Date"), Span::call_site())
+                .unwrap(),
+            SchemaSource::Sdl("scalar Date".into())
+        );
+        assert!(SchemaSource::from_attributes(
+            &value("schema.graphql"),
+            &value("scalar Date"),
+            Span::call_site()
+        )
+        .is_err());
+        assert!(SchemaSource::from_attributes(&None, &None, Span::call_site()).is_err());
+    }
+
+    #[test]
+    fn test_schemas_split_across_files() {
+        let dir = std::env::temp_dir().join("cynic_test_schemas_split_across_files");
+        std::fs::create_dir_all(dir.join("types")).unwrap();
+        std::fs::write(dir.join("schema.graphql"), "type Query { film: Film }").unwrap();
+        std::fs::write(
+            dir.join("types/film.graphql"),
+            "type Film { title: String }\nextend type Query { planet: Planet }",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("types/planet.graphql"),
+            "type Planet { name: String }",
+        )
+        .unwrap();
+
+        let filename = format!(
+            "{}, {}",
+            dir.join("schema.graphql").display(),
+            dir.join("**/*.graphql").display()
+        );
+
+        assert_eq!(
+            SchemaSource::Path(filename.clone()).file_paths().unwrap(),
+            vec![
+                dir.join("schema.graphql"),
+                dir.join("types/film.graphql"),
+                dir.join("types/planet.graphql"),
+            ]
+        );
+
+        let document = load_schema(&SchemaSource::Path(filename)).unwrap();
+        check_type_references(&document).unwrap();
+        assert_eq!(document.definitions.len(), 3);
+
+        assert_matches!(
+            schema_file_paths(&[format!("{}", dir.join("other/*.graphql").display())]),
+            Err(Error::FileNotFound(_))
+        );
+
+        // Filenames in a list can contain commas, as they're never split.
+        std::fs::write(
+            dir.join("films, planets.graphql"),
+            "type Query { film: String }",
+        )
+        .unwrap();
+        let source = SchemaSource::Paths(vec![format!(
+            "{}",
+            dir.join("films, planets.graphql").display()
+        )]);
+        assert_eq!(
+            source.file_paths().unwrap(),
+            vec![dir.join("films, planets.graphql")]
+        );
+        assert_eq!(load_schema(&source).unwrap().definitions.len(), 1);
+        assert!(SchemaSource::Sdl("scalar Date".into())
+            .file_paths()
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_schema_caching() {
+        let path = std::env::temp_dir().join("cynic_test_load_schema_caching.graphql");
+        let source = SchemaSource::Path(path.to_str().unwrap().to_string());
+        std::fs::write(&path, "type Query { film: String }").unwrap();
+
+        let first = load_schema(&source).unwrap();
+        let second = load_schema(&source).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        std::fs::write(&path, "type Query { films: [String] }").unwrap();
+
+        let third = load_schema(&source).unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(third.definitions.len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_type_extensions() {
+        let document = parse_schema(
+            r#"
+            extend type Query { films: [Film] }
+            type Query { film(id: ID!): Film }
+            type Film { title: String }
+            enum Rating { GOOD }
+            extend enum Rating { BAD }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(document.definitions.len(), 3);
+
+        let index = TypeIndex::for_schema(&document);
+        let query = match index.lookup_type("Query") {
+            Some(TypeDefinition::Object(query)) => query,
+            _ => panic!("Query should be an object"),
+        };
+        let field_names = query
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(field_names, vec!["film", "films"]);
+
+        match index.lookup_type("Rating") {
+            Some(TypeDefinition::Enum(rating)) => assert_eq!(rating.values.len(), 2),
+            _ => panic!("Rating should be an enum"),
+        }
+
+        assert_eq!(
+            parse_schema("extend type Query { films: [String] }"),
+            Err(Error::UnknownExtension("Query".to_string()))
+        );
+        assert_eq!(
+            parse_schema("scalar Query\nextend type Query { films: [String] }"),
+            Err(Error::UnknownExtension("Query".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_federation_schemas() {
+        let document = parse_schema(
+            r#"
+            extend schema
+              @link(url: "https://specs.apollo.dev/federation/v2.0", import: ["@key"])
+
+            type Product @key(fields: "upc") {
+              upc: String!
+              name: String
+            }
+
+            extend type User @key(fields: "id") {
+              id: ID! @external
+              reviews: [Product]
+            }
+
+            extend type Query { topProducts: [Product] }
+            "#,
+        )
+        .unwrap();
+
+        let index = TypeIndex::for_schema(&document);
+        let query = match index.lookup_type("Query") {
+            Some(TypeDefinition::Object(query)) => query,
+            _ => panic!("Query should be an object"),
+        };
+        let field_names = query
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(field_names, vec!["topProducts", "_entities", "_service"]);
+
+        match index.lookup_type("_Entity") {
+            Some(TypeDefinition::Union(entity)) => {
+                assert_eq!(entity.types, vec!["Product", "User"])
+            }
+            _ => panic!("_Entity should be a union"),
+        }
+        assert!(index.is_scalar("_Any"));
+        assert_matches!(
+            index.lookup_type("_Service"),
+            Some(TypeDefinition::Object(_))
+        );
+
+        check_type_references(&document).unwrap();
+    }
+
+    #[test]
+    fn test_strip_schema_extensions() {
+        let schema = "extend schema @link(url: \"a\", import: [\"@key\"])\n  @link(url: \"b\")\ntype Query { a: String } # extend schema";
+
+        let (stripped, root_types) = strip_schema_extensions(schema).unwrap();
+        assert_eq!(
+            stripped,
+            format!(
+                "{}\n{}\ntype Query {{ a: String }} # extend schema",
+                " ".repeat(47),
+                " ".repeat(17)
+            )
+        );
+        assert!(root_types.is_empty());
+
+        let schema = "\"\"\"\nextend schema { query: Other }\n\"\"\"\ntype Query { a: Int }";
+        let (stripped, root_types) = strip_schema_extensions(schema).unwrap();
+        assert_eq!(stripped, schema);
+        assert!(root_types.is_empty());
+
+        let (stripped, root_types) = strip_schema_extensions(
+            "extend schema @link(url: \"a\") { query: Root }\ntype Root { a: Int }",
+        )
+        .unwrap();
+        assert_eq!(
+            stripped,
+            format!("{}\ntype Root {{ a: Int }}", " ".repeat(45))
+        );
+        assert_eq!(root_types, vec![("query".to_string(), "Root".to_string())]);
+
+        assert_matches!(
+            strip_schema_extensions("extend schema { root: Root }"),
+            Err(Error::ParseError(_))
+        );
+    }
+
+    #[test]
+    fn test_schema_extension_root_types() {
+        let root_types = |sdl: &str| {
+            parse_schema(sdl)
+                .unwrap()
+                .definitions
+                .into_iter()
+                .find_map(|definition| match definition {
+                    Definition::SchemaDefinition(schema) => {
+                        Some((schema.query, schema.mutation, schema.subscription))
+                    }
+                    _ => None,
+                })
+        };
+
+        assert_eq!(
+            root_types(
+                "extend schema { query: Root }\ntype Root { a: Int }\ntype Mutation { b: Int }"
+            ),
+            Some((Some("Root".into()), Some("Mutation".into()), None))
+        );
+        assert_eq!(
+            root_types("schema { query: Root }\nextend schema { mutation: Change }\ntype Root { a: Int }\ntype Change { b: Int }"),
+            Some((Some("Root".into()), Some("Change".into()), None))
+        );
+        assert_matches!(
+            parse_schema("schema { query: Root }\nextend schema { query: Other }\ntype Root { a: Int }\ntype Other { a: Int }"),
+            Err(Error::ParseError(_))
+        );
+
+        let document = parse_schema(
+            r#"
+            extend schema @link(url: "https://specs.apollo.dev/federation/v2.0") {
+              query: Root
+            }
+
+            type Root { products: [Product] }
+            type Product @key(fields: "upc") { upc: String! }
+            "#,
+        )
+        .unwrap();
+        let index = TypeIndex::for_schema(&document);
+        assert_matches!(
+            index.lookup_type("Root"),
+            Some(TypeDefinition::Object(root)) if root.fields.iter().any(|f| f.name == "_entities")
+        );
+        assert_eq!(index.lookup_type("Query"), None);
+    }
+
+    #[test]
+    fn test_check_type_references() {
+        let document = parse_schema(
+            "type Query { film(id: ID!): Film }\ntype Film { title: String, rating: Rating }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            check_type_references(&document),
+            Err(Error::UnknownType(
+                "Rating".to_string(),
+                "Film.rating".to_string()
+            ))
+        );
+
+        let document = parse_schema(
+            "type Query { film(filter: FilmFilter): Film }\ntype Film { title: String }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            check_type_references(&document),
+            Err(Error::UnknownType(
+                "FilmFilter".to_string(),
+                "Query.film(filter)".to_string()
+            ))
+        );
+
+        let document =
+            parse_schema("type Query { film: Film }\ntype Film { title: String }").unwrap();
+        assert_eq!(check_type_references(&document), Ok(()));
+    }
+
+    #[test]
+    fn test_schema_dir_override() {
+        assert_eq!(
+            resolve_schema_path(Path::new("schema.graphql"), Some("/schemas".into()), None)
+                .unwrap(),
+            PathBuf::from("/schemas/schema.graphql")
+        );
+        assert_eq!(
+            resolve_schema_path(
+                Path::new("/abs/schema.graphql"),
+                Some("/schemas".into()),
+                None
+            )
+            .unwrap(),
+            PathBuf::from("/abs/schema.graphql")
+        );
+    }
+
+    #[test]
+    fn test_schema_file_path_relative_to_manifest() {
+        let path = schema_file_path(Path::new("schema.graphql")).unwrap();
+
+        assert_eq!(
+            path,
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("schema.graphql")
+        );
+    }
+
+    #[test]
+    fn test_field_deprecation() {
+        let schema = parse_schema(
+            r#"
+            type Query {
+                current: Int
+                old: Int @deprecated(reason: "Use current")
+                older: Int @deprecated
+            }
+            "#,
+        )
+        .unwrap();
+
+        let fields = match &schema.definitions[0] {
+            Definition::TypeDefinition(TypeDefinition::Object(obj)) => &obj.fields,
+            _ => panic!("Expected an object"),
+        };
+
+        let deprecations = fields
+            .iter()
+            .map(|field| field.deprecation())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            deprecations,
+            vec![
+                None,
+                Some("Use current".to_string()),
+                Some("No longer supported".to_string())
+            ]
+        );
+    }
+}
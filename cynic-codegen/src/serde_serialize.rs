@@ -0,0 +1,28 @@
+use proc_macro2::TokenStream;
+
+/// Generates a `serde::Serialize` impl that delegates to `SerializableArgument`.
+///
+/// This is used by the Enum & InputObject derives when `impl_serialize` is
+/// provided, so that users can embed these types in their own JSON payloads.
+pub fn serde_serialize_impl(ident: &proc_macro2::Ident, generics: &syn::Generics) -> TokenStream {
+    use quote::quote;
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics ::cynic::__private::serde::Serialize for #ident #ty_generics #where_clause {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ::cynic::__private::serde::Serializer,
+            {
+                use ::cynic::__private::serde::ser::Error;
+
+                let value = ::cynic::SerializableArgument::serialize(self)
+                    .map_err(|e| S::Error::custom(e.to_string()))?;
+
+                ::cynic::__private::serde::Serialize::serialize(&value, serializer)
+            }
+        }
+    }
+}
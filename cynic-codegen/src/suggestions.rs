@@ -0,0 +1,112 @@
+//! Suggestions for names that couldn't be found in a schema, which are
+//! usually typos or out of date.
+
+/// Finds the names in `candidates` that are close to `name`, closest first.
+///
+/// Names are compared ignoring case & underscores, so `release_date` is
+/// considered the same as `releaseDate`.
+pub fn guess_names<'a>(candidates: impl IntoIterator<Item = &'a str>, name: &str) -> Vec<&'a str> {
+    const MAX_GUESSES: usize = 3;
+
+    let name = normalise(name);
+    let max_distance = std::cmp::max(1, name.chars().count() / 3);
+
+    let mut guesses = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&normalise(candidate), &name), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+
+    guesses.sort_unstable();
+    guesses.dedup();
+
+    guesses
+        .into_iter()
+        .take(MAX_GUESSES)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// A suffix for an error message suggesting the names in `candidates` that
+/// are close to `name`.  This is empty if there are no close names.
+pub fn did_you_mean<'a>(candidates: impl IntoIterator<Item = &'a str>, name: &str) -> String {
+    match guess_names(candidates, name).as_slice() {
+        [] => String::new(),
+        [guess] => format!(".  Did you mean {}?", guess),
+        [guesses @ .., last] => format!(".  Did you mean {} or {}?", guesses.join(", "), last),
+    }
+}
+
+fn normalise(name: &str) -> Vec<char> {
+    name.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The levenshtein distance between two strings.
+fn edit_distance(lhs: &[char], rhs: &[char]) -> usize {
+    let mut previous_row = (0..=rhs.len()).collect::<Vec<_>>();
+
+    for (i, lhs_char) in lhs.iter().enumerate() {
+        let mut row = Vec::with_capacity(rhs.len() + 1);
+        row.push(i + 1);
+
+        for (j, rhs_char) in rhs.iter().enumerate() {
+            let substitution = previous_row[j] + usize::from(lhs_char != rhs_char);
+            let insertion = row[j] + 1;
+            let deletion = previous_row[j + 1] + 1;
+
+            row.push(substitution.min(insertion).min(deletion));
+        }
+
+        previous_row = row;
+    }
+
+    previous_row[rhs.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guessing_names() {
+        let candidates = vec!["releaseDate", "title", "director", "producers", "id"];
+
+        assert_eq!(
+            guess_names(candidates.clone(), "relase_date"),
+            vec!["releaseDate"]
+        );
+        assert_eq!(guess_names(candidates.clone(), "Title"), vec!["title"]);
+        assert_eq!(guess_names(candidates.clone(), "ix"), vec!["id"]);
+        assert!(guess_names(candidates, "episodeId").is_empty());
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(
+            did_you_mean(vec!["Film", "Planet"], "Filn"),
+            ".  Did you mean Film?"
+        );
+        assert_eq!(
+            did_you_mean(vec!["planets", "films", "plane"], "planet"),
+            ".  Did you mean plane or planets?"
+        );
+        assert_eq!(did_you_mean(vec!["Film"], "Starship"), "");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let distance = |lhs: &str, rhs: &str| {
+            edit_distance(
+                &lhs.chars().collect::<Vec<_>>(),
+                &rhs.chars().collect::<Vec<_>>(),
+            )
+        };
+
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("abc", "abc"), 0);
+    }
+}
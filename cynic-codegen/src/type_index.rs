@@ -1,16 +1,23 @@
 use std::collections::HashMap;
 
-use crate::schema::{Definition, Document, TypeDefinition};
+use crate::{
+    schema::{Definition, Document, TypeDefinition},
+    TypePath,
+};
 
 pub struct TypeIndex<'a> {
     //name_to_kind: HashMap<String, Kind>,
     types: HashMap<&'a str, &'a TypeDefinition>,
+    ordered_floats: bool,
+    scalar_overrides: HashMap<String, TypePath>,
 }
 
 impl<'a> TypeIndex<'a> {
     pub fn empty() -> Self {
         TypeIndex {
             types: HashMap::new(),
+            ordered_floats: false,
+            scalar_overrides: HashMap::new(),
         }
     }
 
@@ -25,7 +32,38 @@ impl<'a> TypeIndex<'a> {
             }
         }
 
-        TypeIndex { types }
+        TypeIndex {
+            types,
+            ordered_floats: false,
+            scalar_overrides: HashMap::new(),
+        }
+    }
+
+    /// Makes any types looked up in this index map the GraphQL `Float`
+    /// scalar to `cynic::OrderedFloat` rather than `f64`.
+    pub fn with_ordered_floats(self, ordered_floats: bool) -> Self {
+        TypeIndex {
+            ordered_floats,
+            ..self
+        }
+    }
+
+    pub fn ordered_floats(&self) -> bool {
+        self.ordered_floats
+    }
+
+    /// Makes any types looked up in this index map the given scalars to a
+    /// different rust type, e.g. `Int` to `i64`.
+    pub fn with_scalar_overrides(self, scalar_overrides: HashMap<String, TypePath>) -> Self {
+        TypeIndex {
+            scalar_overrides,
+            ..self
+        }
+    }
+
+    /// The rust type that a scalar has been overridden to use, if any.
+    pub fn scalar_override(&self, name: &str) -> Option<&TypePath> {
+        self.scalar_overrides.get(name)
     }
 
     pub fn lookup_type(&self, name: &str) -> Option<&'a TypeDefinition> {
@@ -54,7 +92,7 @@ impl<'a> TypeIndex<'a> {
     }
 }
 
-fn name_for_type(type_def: &TypeDefinition) -> &str {
+pub(crate) fn name_for_type(type_def: &TypeDefinition) -> &str {
     match type_def {
         TypeDefinition::Scalar(inner) => &inner.name,
         TypeDefinition::Object(inner) => &inner.name,
@@ -11,6 +11,9 @@ pub struct TypePath {
     relative: bool,
     is_void: bool,
     builtin: bool,
+    /// Generic arguments for the last segment of the path, for types that
+    /// were provided by users e.g. `chrono::DateTime<chrono::Utc>`.
+    arguments: Option<TokenStream>,
 }
 
 impl TypePath {
@@ -20,6 +23,7 @@ impl TypePath {
             relative: true,
             is_void: false,
             builtin: false,
+            arguments: None,
         }
     }
 
@@ -29,6 +33,7 @@ impl TypePath {
             relative: false,
             is_void: false,
             builtin: false,
+            arguments: None,
         }
     }
 
@@ -38,15 +43,61 @@ impl TypePath {
             relative: false,
             is_void: false,
             builtin: true,
+            arguments: None,
         }
     }
 
+    /// Parses a rust type provided by a user, e.g. in the `scalars` option of
+    /// `query_dsl!`.
+    ///
+    /// The type is output exactly as it was written, so should be a path that
+    /// is valid wherever it's used.
+    pub fn from_rust_type(ty: &str) -> Result<Self, syn::Error> {
+        use quote::ToTokens;
+        use syn::spanned::Spanned;
+
+        let path = syn::parse_str::<syn::TypePath>(ty)?;
+        if path.qself.is_some() {
+            return Err(syn::Error::new(
+                path.span(),
+                "Qualified paths can't be used for scalars",
+            ));
+        }
+
+        let segment_count = path.path.segments.len();
+        let mut segments = vec![];
+        let mut arguments = None;
+        for (index, segment) in path.path.segments.into_iter().enumerate() {
+            if !segment.arguments.is_empty() {
+                if index + 1 != segment_count {
+                    return Err(syn::Error::new(
+                        segment.span(),
+                        "Only the last segment of a scalar type can have generic arguments",
+                    ));
+                }
+                arguments = Some(segment.arguments.to_token_stream());
+            }
+            segments.push(Ident::from(segment.ident));
+        }
+
+        // These paths are never relative to the query module, but they only
+        // get a leading `::` if the user wrote one.
+        Ok(TypePath {
+            path: segments,
+            relative: false,
+            is_void: false,
+            builtin: path.path.leading_colon.is_none(),
+            arguments,
+        })
+    }
+
     pub fn void() -> Self {
         TypePath {
             path: vec![],
             relative: false,
             is_void: true,
             builtin: false,
+            arguments: None,
         }
     }
 
@@ -69,6 +120,7 @@ impl TypePath {
             relative,
             is_void: false,
             builtin: false,
+            arguments: None,
         }
     }
 
@@ -78,6 +130,31 @@ impl TypePath {
             relative: true,
             is_void: false,
             builtin: false,
+            arguments: None,
+        }
+    }
+
+    /// Builds the path to a query module from the `query_module` attribute of
+    /// a derive.
+    ///
+    /// This can be a path - e.g. `api_client::query_dsl` - so that fragments
+    /// can be built on top of a query_dsl exported from another crate.
+    pub fn for_query_module(module: &str, span: proc_macro2::Span) -> Self {
+        let module = module.trim();
+        let (relative, module) = match module.strip_prefix("::") {
+            Some(rest) => (false, rest),
+            None => (true, module),
+        };
+
+        TypePath {
+            path: module
+                .split("::")
+                .map(|segment| Ident::new_spanned(segment.trim(), span))
+                .collect(),
+            relative,
+            is_void: false,
+            builtin: false,
+            arguments: None,
         }
     }
 
@@ -97,6 +174,7 @@ impl From<Ident> for TypePath {
             relative: true,
             is_void: false,
             builtin: false,
+            arguments: None,
         }
     }
 }
@@ -117,12 +195,63 @@ impl quote::ToTokens for TypePath {
         };
 
         let path = &self.path;
+        let arguments = &self.arguments;
 
         tokens.append_all(quote! {
             #initial
             #(
                  #path
             )::*
+            #arguments
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+    use quote::{quote, ToTokens};
+
+    #[test]
+    fn test_query_module_paths() {
+        let path = |s| TypePath::for_query_module(s, Span::call_site());
+
+        assert_eq!(
+            path("query_dsl").to_token_stream().to_string(),
+            quote! { query_dsl }.to_string()
+        );
+        assert_eq!(
+            path("api_client::query_dsl").to_token_stream().to_string(),
+            quote! { api_client::query_dsl }.to_string()
+        );
+        assert_eq!(
+            path("::api_client::query_dsl")
+                .to_token_stream()
+                .to_string(),
+            quote! { ::api_client::query_dsl }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_rust_type_paths() {
+        let path = |s| {
+            TypePath::from_rust_type(s)
+                .unwrap()
+                .to_token_stream()
+                .to_string()
+        };
+
+        assert_eq!(path("i64"), quote! { i64 }.to_string());
+        assert_eq!(
+            path("chrono::DateTime<chrono::Utc>"),
+            quote! { chrono::DateTime<chrono::Utc> }.to_string()
+        );
+        assert_eq!(
+            path("::my_crate::Timestamp"),
+            quote! { ::my_crate::Timestamp }.to_string()
+        );
+        assert!(TypePath::from_rust_type("chrono<T>::DateTime").is_err());
+        assert!(TypePath::from_rust_type("not a type").is_err());
+    }
+}
@@ -14,12 +14,12 @@ pub fn check_types_are_compatible(
         ParsedType::Unknown => {
             return Err(syn::Error::new(
                 rust_type.span(),
-                "Cynic does not understand this type. Only un-parameterised types, Vecs, Options & Box are accepted currently.",
+                "Cynic does not understand this type. Only un-parameterised types, Vecs, Options, Box, Arc & Rc are accepted currently.",
             ))
         },
-        ParsedType::Box(inner) => {
-            // Box is a transparent container for the purposes of checking compatability
-            // so just recurse
+        ParsedType::Pointer(inner) => {
+            // Pointers are transparent containers for the purposes of checking
+            // compatability so just recurse
             return check_types_are_compatible(gql_type, inner, flattening);
         }
         _ => {}
@@ -28,7 +28,11 @@ pub fn check_types_are_compatible(
     if gql_type.is_nullable() {
         if let ParsedType::Optional(inner) = parsed_type {
             return check_types_are_compatible(&gql_type.as_required(), &inner, flattening);
-        } else if !flattening {
+        } else if flattening {
+            // If we're flattening then the Option can be dropped, but we still
+            // need to check any nested types.
+            return check_types_are_compatible(&gql_type.as_required(), rust_type, flattening);
+        } else {
             // If we're flattening then it's all good.  But otherwise we should return an error.
             return Err(syn::Error::new(
                         rust_type.span(),
@@ -72,12 +76,92 @@ pub fn check_types_are_compatible(
     Ok(())
 }
 
+/// Checks whether a rust type is an `Option`
+pub fn is_option_type(rust_type: &syn::Type) -> bool {
+    optional_inner_type(rust_type).is_some()
+}
+
+/// Takes a type like `Option<T>` and extracts the `T`
+pub fn optional_inner_type(rust_type: &syn::Type) -> Option<&syn::Type> {
+    match parse_type(rust_type) {
+        ParsedType::Optional(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// Takes a type like `MaybeUndefined<T>` and extracts the `T`
+pub fn maybe_undefined_inner_type(rust_type: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(type_path) = rust_type {
+        let last_segment = type_path.path.segments.last()?;
+        if last_segment.ident == "MaybeUndefined" {
+            return extract_generic_argument(last_segment);
+        }
+    }
+
+    None
+}
+
+/// Takes a type that contains `Box`, `Arc` or `Rc` pointers (possibly nested
+/// inside `Option` or `Vec`) and returns it with the pointers removed.
+///
+/// Returns `None` if the type didn't contain any pointers.
+pub fn strip_pointer_types(rust_type: &syn::Type) -> Option<syn::Type> {
+    match parse_type(rust_type) {
+        ParsedType::Pointer(inner) => {
+            Some(strip_pointer_types(inner).unwrap_or_else(|| inner.clone()))
+        }
+        ParsedType::Optional(inner) | ParsedType::List(inner) => {
+            let stripped_inner = strip_pointer_types(inner)?;
+
+            let mut rust_type = rust_type.clone();
+            if let Some(inner) = generic_argument_mut(&mut rust_type) {
+                *inner = stripped_inner;
+            }
+            Some(rust_type)
+        }
+        _ => None,
+    }
+}
+
+/// Takes a type that borrows its data (like `&str`, `&[T]` or `&T`, possibly
+/// nested inside `Option` or `Vec`) and returns the owned equivalent, e.g.
+/// `Option<&[&str]>` becomes `Option<Vec<String>>`.
+///
+/// Returns `None` if the type didn't contain any references.
+pub fn owned_type(rust_type: &syn::Type) -> Option<syn::Type> {
+    if let syn::Type::Reference(reference) = rust_type {
+        return Some(match &*reference.elem {
+            syn::Type::Path(type_path) if type_path.path.is_ident("str") => {
+                syn::parse_quote! { String }
+            }
+            syn::Type::Slice(slice) => {
+                let inner = owned_type(&slice.elem).unwrap_or_else(|| (*slice.elem).clone());
+                syn::parse_quote! { Vec<#inner> }
+            }
+            elem => owned_type(elem).unwrap_or_else(|| elem.clone()),
+        });
+    }
+
+    match parse_type(rust_type) {
+        ParsedType::Optional(inner) | ParsedType::List(inner) | ParsedType::Pointer(inner) => {
+            let owned_inner = owned_type(inner)?;
+
+            let mut rust_type = rust_type.clone();
+            if let Some(inner) = generic_argument_mut(&mut rust_type) {
+                *inner = owned_inner;
+            }
+            Some(rust_type)
+        }
+        _ => None,
+    }
+}
+
 /// A simplified rust type structure
 #[derive(Debug, PartialEq)]
 enum ParsedType<'a> {
     Optional(&'a syn::Type),
     List(&'a syn::Type),
-    Box(&'a syn::Type),
+    Pointer(&'a syn::Type),
     SimpleType,
     Unknown,
 }
@@ -85,9 +169,9 @@ enum ParsedType<'a> {
 fn parse_type<'a>(ty: &'a syn::Type) -> ParsedType<'a> {
     if let syn::Type::Path(type_path) = ty {
         if let Some(last_segment) = type_path.path.segments.last() {
-            if last_segment.ident.to_string() == "Box" {
+            if ["Box", "Arc", "Rc"].contains(&last_segment.ident.to_string().as_str()) {
                 if let Some(inner_type) = extract_generic_argument(last_segment) {
-                    return ParsedType::Box(inner_type);
+                    return ParsedType::Pointer(inner_type);
                 }
 
                 return ParsedType::Unknown;
@@ -128,11 +212,28 @@ fn extract_generic_argument<'a>(segment: &'a syn::PathSegment) -> Option<&'a syn
     None
 }
 
+/// Returns a mutable reference to the `T` in a type like `Vec<T>`
+fn generic_argument_mut(rust_type: &mut syn::Type) -> Option<&mut syn::Type> {
+    if let syn::Type::Path(type_path) = rust_type {
+        let segment = type_path.path.segments.last_mut()?;
+        if let syn::PathArguments::AngleBracketed(angle_bracketed) = &mut segment.arguments {
+            for arg in &mut angle_bracketed.args {
+                if let syn::GenericArgument::Type(inner_type) = arg {
+                    return Some(inner_type);
+                }
+            }
+        }
+    }
+
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{FieldType, Ident};
     use assert_matches::assert_matches;
+    use proc_macro2::TokenStream;
     use quote::quote;
 
     #[test]
@@ -288,4 +389,142 @@ mod tests {
             Err(_)
         );
     }
+
+    #[test]
+    fn test_nested_list_validation() {
+        // [[Int]]
+        let nested_optional_lists = FieldType::List(
+            Box::new(FieldType::List(
+                Box::new(FieldType::Scalar(Ident::new("").into(), true)),
+                true,
+            )),
+            true,
+        );
+        // [String!]!
+        let required_list = FieldType::List(
+            Box::new(FieldType::Scalar(Ident::new("").into(), false)),
+            false,
+        );
+
+        assert_matches!(
+            check_types_are_compatible(
+                &nested_optional_lists,
+                &syn::parse2(quote! { Option<Vec<Option<Vec<Option<i32>>>>> }).unwrap(),
+                false
+            ),
+            Ok(())
+        );
+        assert_matches!(
+            check_types_are_compatible(
+                &nested_optional_lists,
+                &syn::parse2(quote! { Option<Vec<Vec<Option<i32>>>> }).unwrap(),
+                false
+            ),
+            Err(_)
+        );
+        assert_matches!(
+            check_types_are_compatible(
+                &nested_optional_lists,
+                &syn::parse2(quote! { Vec<Vec<i32>> }).unwrap(),
+                true
+            ),
+            Ok(())
+        );
+        assert_matches!(
+            check_types_are_compatible(
+                &nested_optional_lists,
+                &syn::parse2(quote! { Vec<Vec<Vec<i32>>> }).unwrap(),
+                true
+            ),
+            Err(_)
+        );
+        assert_matches!(
+            check_types_are_compatible(
+                &required_list,
+                &syn::parse2(quote! { Vec<String> }).unwrap(),
+                false
+            ),
+            Ok(())
+        );
+        assert_matches!(
+            check_types_are_compatible(
+                &required_list,
+                &syn::parse2(quote! { Vec<Option<String>> }).unwrap(),
+                false
+            ),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_pointers_are_transparent() {
+        let optional_list = FieldType::List(
+            Box::new(FieldType::Scalar(Ident::new("").into(), false)),
+            true,
+        );
+
+        assert_matches!(
+            check_types_are_compatible(
+                &optional_list,
+                &syn::parse2(quote! { Option<Vec<std::sync::Arc<i32>>> }).unwrap(),
+                false
+            ),
+            Ok(())
+        );
+        assert_matches!(
+            check_types_are_compatible(
+                &optional_list,
+                &syn::parse2(quote! { Rc<Option<Vec<i32>>> }).unwrap(),
+                false
+            ),
+            Ok(())
+        );
+        assert_matches!(
+            check_types_are_compatible(
+                &optional_list,
+                &syn::parse2(quote! { Option<Box<i32>> }).unwrap(),
+                false
+            ),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_strip_pointer_types() {
+        let strip = |ty: TokenStream| {
+            strip_pointer_types(&syn::parse2(ty).unwrap()).map(|ty| quote! { #ty }.to_string())
+        };
+
+        assert_eq!(strip(quote! { Option<Vec<i32>> }), None);
+        assert_eq!(strip(quote! { Box<i32> }), Some(quote! { i32 }.to_string()));
+        assert_eq!(
+            strip(quote! { Option<Vec<std::sync::Arc<Film>>> }),
+            Some(quote! { Option<Vec<Film>> }.to_string())
+        );
+        assert_eq!(
+            strip(quote! { Rc<Option<Film>> }),
+            Some(quote! { Option<Film> }.to_string())
+        );
+    }
+
+    #[test]
+    fn test_owned_type() {
+        let owned = |ty: TokenStream| {
+            owned_type(&syn::parse2(ty).unwrap()).map(|ty| quote! { #ty }.to_string())
+        };
+
+        assert_eq!(owned(quote! { Option<Vec<String>> }), None);
+        assert_eq!(
+            owned(quote! { &'a str }),
+            Some(quote! { String }.to_string())
+        );
+        assert_eq!(
+            owned(quote! { &'a [&'a str] }),
+            Some(quote! { Vec<String> }.to_string())
+        );
+        assert_eq!(
+            owned(quote! { Option<&'a Film> }),
+            Some(quote! { Option<Film> }.to_string())
+        );
+    }
 }
@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use cynic_codegen::{introspection_to_sdl, Error};
+
+use crate::IntrospectionQuery;
+
+/// Fetches the schema of the GraphQL server at `url` with an
+/// `IntrospectionQuery`, and writes it to `out_path`.
+///
+/// This is intended to be called from a build script, so the derives always
+/// compile against the latest schema:
+///
+/// ```rust,ignore
+/// fn main() {
+///     let out_dir = std::env::var("OUT_DIR").unwrap();
+///     cynic_introspection::fetch_schema(
+///         "https://example.com/graphql",
+///         &[("Authorization", "Bearer abcd")],
+///         format!("{}/schema.graphql", out_dir),
+///     )
+///     .unwrap();
+/// }
+/// ```
+///
+/// Paths with a `.json` extension get the raw introspection result, and any
+/// other paths get the schema as SDL.
+pub fn fetch_schema(
+    url: &str,
+    headers: &[(&str, &str)],
+    out_path: impl AsRef<Path>,
+) -> Result<(), Error> {
+    let client = reqwest::blocking::Client::new();
+
+    let mut request = client.post(url).json(&IntrospectionQuery::build());
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+
+    let response = request
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| Error::FetchError(e.to_string()))?;
+    let body = response
+        .text()
+        .map_err(|e| Error::FetchError(e.to_string()))?;
+
+    let out_path = out_path.as_ref();
+    std::fs::write(out_path, schema_file_contents(body, out_path)?)?;
+
+    Ok(())
+}
+
+fn schema_file_contents(introspection_result: String, out_path: &Path) -> Result<String, Error> {
+    // We always convert to SDL, so we fail early if the result isn't usable.
+    let sdl = introspection_to_sdl(&introspection_result)?;
+
+    if out_path.extension().map_or(false, |ext| ext == "json") {
+        Ok(introspection_result)
+    } else {
+        Ok(sdl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESULT: &str = r#"{"data": {"__schema": {
+        "queryType": { "kind": "OBJECT", "name": "Query" },
+        "mutationType": null,
+        "subscriptionType": null,
+        "directives": [],
+        "types": [{
+            "kind": "SCALAR", "name": "DateTime", "description": null, "fields": null,
+            "inputFields": null, "interfaces": null, "enumValues": null, "possibleTypes": null
+        }]
+    }}}"#;
+
+    #[test]
+    fn test_schema_file_contents() {
+        assert_eq!(
+            schema_file_contents(RESULT.to_string(), Path::new("schema.json")).unwrap(),
+            RESULT
+        );
+        assert_eq!(
+            schema_file_contents(RESULT.to_string(), Path::new("schema.graphql")).unwrap(),
+            "schema {\n  query: Query\n}\n\nscalar DateTime\n"
+        );
+        assert!(schema_file_contents(
+            r#"{"errors": [{"message": "Unauthorized"}]}"#.to_string(),
+            Path::new("schema.graphql")
+        )
+        .is_err());
+    }
+}
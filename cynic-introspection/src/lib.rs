@@ -0,0 +1,364 @@
+//! GraphQL introspection queries for cynic.
+//!
+//! This crate contains `QueryFragment`s for the introspection types that every
+//! GraphQL server provides, built with cynic itself.  These can be used to
+//! fetch the schema of a running server through any of cynic's HTTP
+//! integrations.
+//!
+//! ```rust,no_run
+//! use cynic_introspection::IntrospectionQuery;
+//!
+//! let operation = IntrospectionQuery::build();
+//! # let response: cynic::GraphQLResponse<serde_json::Value> = todo!();
+//! // Send `operation` to a server with an HTTP client, then:
+//! let schema = operation.decode_response(response).unwrap().data.unwrap().schema;
+//!
+//! for ty in schema.types {
+//!     println!("{:?}", ty.name);
+//! }
+//! ```
+//!
+//! ### Server capabilities
+//!
+//! Older servers don't support some of the introspection fields added in
+//! later versions of the GraphQL specification and will reject queries that
+//! select them.  `IntrospectionQuery` only selects fields that every version
+//! of the specification supports.  `CapabilitiesQuery` can be used to find out
+//! which version of the specification a server supports before deciding what
+//! else to query.
+//!
+//! ### Saving schemas
+//!
+//! The JSON response to an `IntrospectionQuery` can be saved with a `.json`
+//! extension & used as the `schema_path` for cynic's derives.  With the
+//! `fetch-schema` feature, `fetch_schema` runs the query from a build script
+//! & saves the result, optionally converted into SDL.
+
+mod query_dsl {
+    cynic::query_dsl!("src/schema.graphql");
+}
+
+#[cfg(feature = "fetch-schema")]
+mod fetch_schema;
+
+#[cfg(feature = "fetch-schema")]
+pub use fetch_schema::fetch_schema;
+pub use queries::*;
+
+#[cynic::query_module(schema_path = "src/schema.graphql", query_module = "query_dsl")]
+mod queries {
+    use super::query_dsl;
+
+    /// A query that introspects the whole schema of a server.
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "Query")]
+    pub struct IntrospectionQuery {
+        pub schema: Schema,
+    }
+
+    impl IntrospectionQuery {
+        /// Builds an `Operation` that runs this query.
+        pub fn build() -> cynic::Operation<'static, IntrospectionQuery> {
+            use cynic::QueryFragment;
+
+            cynic::Operation::query(IntrospectionQuery::fragment(&())).named("IntrospectionQuery")
+        }
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__Schema")]
+    pub struct Schema {
+        pub query_type: NamedType,
+        pub mutation_type: Option<NamedType>,
+        pub subscription_type: Option<NamedType>,
+        pub types: Vec<Type>,
+        pub directives: Vec<Directive>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__Type")]
+    pub struct NamedType {
+        pub kind: TypeKind,
+        pub name: Option<String>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__Type")]
+    pub struct Type {
+        pub kind: TypeKind,
+        pub name: Option<String>,
+        pub description: Option<String>,
+        #[arguments(include_deprecated = true)]
+        pub fields: Option<Vec<Field>>,
+        pub input_fields: Option<Vec<InputValue>>,
+        pub interfaces: Option<Vec<NamedType>>,
+        #[arguments(include_deprecated = true)]
+        pub enum_values: Option<Vec<EnumValue>>,
+        pub possible_types: Option<Vec<NamedType>>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__Field")]
+    pub struct Field {
+        pub name: String,
+        pub description: Option<String>,
+        pub args: Vec<InputValue>,
+        pub type_: TypeRef,
+        pub is_deprecated: bool,
+        pub deprecation_reason: Option<String>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__InputValue")]
+    pub struct InputValue {
+        pub name: String,
+        pub description: Option<String>,
+        pub type_: TypeRef,
+        pub default_value: Option<String>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__EnumValue")]
+    pub struct EnumValue {
+        pub name: String,
+        pub description: Option<String>,
+        pub is_deprecated: bool,
+        pub deprecation_reason: Option<String>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__Directive")]
+    pub struct Directive {
+        pub name: String,
+        pub description: Option<String>,
+        pub locations: Vec<DirectiveLocation>,
+        pub args: Vec<InputValue>,
+    }
+
+    /// A reference to a type, including any list or non-null wrappers.
+    ///
+    /// This is selected seven levels deep - enough for anything up to
+    /// `[[[Int!]!]!]`.
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__Type")]
+    pub struct TypeRef {
+        pub kind: TypeKind,
+        pub name: Option<String>,
+        #[cynic(recurse = "6")]
+        pub of_type: Option<Box<TypeRef>>,
+    }
+
+    #[derive(cynic::Enum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cynic(graphql_type = "__TypeKind", rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum TypeKind {
+        Scalar,
+        Object,
+        Interface,
+        Union,
+        Enum,
+        InputObject,
+        List,
+        NonNull,
+    }
+
+    #[derive(cynic::Enum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cynic(
+        graphql_type = "__DirectiveLocation",
+        rename_all = "SCREAMING_SNAKE_CASE"
+    )]
+    pub enum DirectiveLocation {
+        Query,
+        Mutation,
+        Subscription,
+        Field,
+        FragmentDefinition,
+        FragmentSpread,
+        InlineFragment,
+        VariableDefinition,
+        Schema,
+        Scalar,
+        Object,
+        FieldDefinition,
+        ArgumentDefinition,
+        Interface,
+        Union,
+        Enum,
+        EnumValue,
+        InputObject,
+        InputFieldDefinition,
+    }
+
+    /// A query that finds out which introspection features a server
+    /// supports.
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "Query")]
+    pub struct CapabilitiesQuery {
+        /// The `__Directive` type.
+        #[arguments(name = "__Directive".to_string())]
+        pub type_: Option<FieldNames>,
+    }
+
+    impl CapabilitiesQuery {
+        /// Builds an `Operation` that runs this query.
+        pub fn build() -> cynic::Operation<'static, CapabilitiesQuery> {
+            use cynic::QueryFragment;
+
+            cynic::Operation::query(CapabilitiesQuery::fragment(&())).named("CapabilitiesQuery")
+        }
+
+        /// The version of the GraphQL specification the server supports.
+        pub fn specification_version(&self) -> SpecificationVersion {
+            let fields = self.type_.as_ref().and_then(|ty| ty.fields.as_ref());
+
+            match fields {
+                Some(fields) if fields.iter().any(|f| f.name == "isRepeatable") => {
+                    SpecificationVersion::October2021
+                }
+                _ => SpecificationVersion::June2018,
+            }
+        }
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__Type")]
+    pub struct FieldNames {
+        #[arguments(include_deprecated = true)]
+        pub fields: Option<Vec<FieldName>>,
+    }
+
+    #[derive(cynic::QueryFragment, Debug, Clone)]
+    #[cynic(graphql_type = "__Field")]
+    pub struct FieldName {
+        pub name: String,
+    }
+}
+
+/// The versions of the GraphQL specification that differ in what they support
+/// for introspection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpecificationVersion {
+    /// The June 2018 specification, or something earlier.
+    June2018,
+    /// The October 2021 specification, which added `isRepeatable`,
+    /// `specifiedByURL` & schema descriptions.
+    October2021,
+}
+
+impl TypeRef {
+    /// The name of the named type inside any list or non-null wrappers.
+    pub fn inner_name(&self) -> Option<&str> {
+        self.name
+            .as_deref()
+            .or_else(|| self.of_type.as_ref().and_then(|t| t.inner_name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_introspection_query() {
+        let operation = IntrospectionQuery::build();
+
+        assert!(operation.query.starts_with("query IntrospectionQuery"));
+        assert!(operation.query.contains("__schema {"));
+        assert!(operation.query.contains("fields(includeDeprecated: $_"));
+    }
+
+    #[test]
+    fn test_decoding_introspection_response() {
+        let operation = IntrospectionQuery::build();
+        let named = |name: &str| json!({ "kind": "SCALAR", "name": name, "ofType": null });
+        let wrapped = |kind: &str, inner| json!({ "kind": kind, "name": null, "ofType": inner });
+        let deeply_nested = wrapped(
+            "LIST",
+            wrapped(
+                "NON_NULL",
+                wrapped(
+                    "LIST",
+                    wrapped(
+                        "NON_NULL",
+                        wrapped("LIST", wrapped("NON_NULL", named("Int"))),
+                    ),
+                ),
+            ),
+        );
+
+        let schema = operation
+            .decode_response(cynic::GraphQLResponse {
+                data: Some(json!({
+                    "__schema": {
+                        "queryType": { "kind": "OBJECT", "name": "Query" },
+                        "mutationType": null,
+                        "subscriptionType": null,
+                        "directives": [],
+                        "types": [{
+                            "kind": "OBJECT",
+                            "name": "Query",
+                            "description": null,
+                            "fields": [{
+                                "name": "hello",
+                                "description": null,
+                                "args": [{
+                                    "name": "ids",
+                                    "description": null,
+                                    "type": deeply_nested,
+                                    "defaultValue": null
+                                }],
+                                "type": {
+                                    "kind": "NON_NULL",
+                                    "name": null,
+                                    "ofType": named("String")
+                                },
+                                "isDeprecated": false,
+                                "deprecationReason": null
+                            }],
+                            "inputFields": null,
+                            "interfaces": [],
+                            "enumValues": null,
+                            "possibleTypes": null
+                        }]
+                    }
+                })),
+                errors: None,
+                extensions: None,
+            })
+            .unwrap()
+            .data
+            .unwrap()
+            .schema;
+
+        assert_eq!(schema.query_type.name.as_deref(), Some("Query"));
+
+        let field = &schema.types[0].fields.as_ref().unwrap()[0];
+        assert_eq!(field.type_.kind, TypeKind::NonNull);
+        assert_eq!(field.type_.inner_name(), Some("String"));
+        assert_eq!(field.args[0].type_.kind, TypeKind::List);
+        assert_eq!(field.args[0].type_.inner_name(), Some("Int"));
+    }
+
+    #[test]
+    fn test_specification_version() {
+        let query = |fields: Vec<&str>| CapabilitiesQuery {
+            type_: Some(FieldNames {
+                fields: Some(
+                    fields
+                        .into_iter()
+                        .map(|name| FieldName { name: name.into() })
+                        .collect(),
+                ),
+            }),
+        };
+
+        assert_eq!(
+            query(vec!["name", "args"]).specification_version(),
+            SpecificationVersion::June2018
+        );
+        assert_eq!(
+            query(vec!["name", "isRepeatable"]).specification_version(),
+            SpecificationVersion::October2021
+        );
+    }
+}
@@ -4,16 +4,34 @@ use proc_macro::TokenStream;
 
 use cynic_codegen::{
     enum_derive, fragment_arguments_derive, fragment_derive, inline_fragments_derive,
-    input_object_derive, query_dsl, query_module, scalar_derive,
+    input_object_derive, query_dsl, query_module, scalar_derive, write_debug_output,
 };
 
 #[proc_macro]
 pub fn query_dsl(input: TokenStream) -> TokenStream {
+    // Errors loading the schema are reported against the schema filename,
+    // which is the first token of the input.
+    let schema_span = input
+        .clone()
+        .into_iter()
+        .next()
+        .map(|token| proc_macro2::Span::from(token.span()))
+        .unwrap_or_else(proc_macro2::Span::call_site);
+
     let input = syn::parse_macro_input!(input as query_dsl::QueryDslParams);
 
-    let rv = query_dsl::query_dsl_from_schema(input).unwrap().into();
+    let schema_name = input.schema_name().to_string();
+    let tracking = query_dsl::schema_tracking(&input.schema);
+    let rv: TokenStream = match query_dsl::query_dsl_from_schema(input) {
+        Ok(dsl) => quote::quote! {
+            #dsl
+            #tracking
+        }
+        .into(),
+        Err(e) => e.to_syn_error(schema_span).to_compile_error().into(),
+    };
 
-    //eprintln!("{}", rv);
+    write_debug_output("query_dsl", &schema_name, || rv.to_string());
 
     rv
 }
@@ -22,25 +40,29 @@ pub fn query_dsl(input: TokenStream) -> TokenStream {
 pub fn query_fragment_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    let rv = match fragment_derive::fragment_derive(&ast) {
+    let rv: TokenStream = match fragment_derive::fragment_derive(&ast) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     };
 
-    //eprintln!("{}", rv);
+    write_debug_output("QueryFragment", &ast.ident.to_string(), || rv.to_string());
 
     rv
 }
 
-#[proc_macro_derive(FragmentArguments)]
+#[proc_macro_derive(FragmentArguments, attributes(cynic))]
 pub fn fragment_arguments_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    let rv = match fragment_arguments_derive::fragment_arguments_derive(&ast) {
+    let rv: TokenStream = match fragment_arguments_derive::fragment_arguments_derive(&ast) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     };
 
+    write_debug_output("FragmentArguments", &ast.ident.to_string(), || {
+        rv.to_string()
+    });
+
     rv
 }
 
@@ -48,12 +70,12 @@ pub fn fragment_arguments_derive(input: TokenStream) -> TokenStream {
 pub fn inline_fragments_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    let rv = match inline_fragments_derive::inline_fragments_derive(&ast) {
+    let rv: TokenStream = match inline_fragments_derive::inline_fragments_derive(&ast) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     };
 
-    //eprintln!("{}", rv);
+    write_debug_output("InlineFragments", &ast.ident.to_string(), || rv.to_string());
 
     rv
 }
@@ -62,26 +84,26 @@ pub fn inline_fragments_derive(input: TokenStream) -> TokenStream {
 pub fn enum_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    let rv = match enum_derive::enum_derive(&ast) {
+    let rv: TokenStream = match enum_derive::enum_derive(&ast) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     };
 
-    //eprintln!("{}", rv);
+    write_debug_output("Enum", &ast.ident.to_string(), || rv.to_string());
 
     rv
 }
 
-#[proc_macro_derive(Scalar)]
+#[proc_macro_derive(Scalar, attributes(cynic))]
 pub fn scalar_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    let rv = match scalar_derive::scalar_derive(&ast) {
+    let rv: TokenStream = match scalar_derive::scalar_derive(&ast) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     };
 
-    //eprintln!("{}", rv);
+    write_debug_output("Scalar", &ast.ident.to_string(), || rv.to_string());
 
     rv
 }
@@ -90,12 +112,12 @@ pub fn scalar_derive(input: TokenStream) -> TokenStream {
 pub fn input_object_derive(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
-    let rv = match input_object_derive::input_object_derive(&ast) {
+    let rv: TokenStream = match input_object_derive::input_object_derive(&ast) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     };
 
-    //eprintln!("{}", rv);
+    write_debug_output("InputObject", &ast.ident.to_string(), || rv.to_string());
 
     rv
 }
@@ -104,13 +126,31 @@ pub fn input_object_derive(input: TokenStream) -> TokenStream {
 pub fn query_module(attrs: TokenStream, input: TokenStream) -> TokenStream {
     let module = syn::parse_macro_input!(input as syn::ItemMod);
     let attrs = syn::parse_macro_input!(attrs as syn::AttributeArgs);
+    let module_name = module.ident.to_string();
 
     let rv: TokenStream = match query_module::transform_query_module(attrs, module) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     };
 
-    // eprintln!("{}", rv);
+    write_debug_output("query_module", &module_name, || rv.to_string());
+
+    rv
+}
+
+#[proc_macro_attribute]
+pub fn schema_for_derives(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    let module = syn::parse_macro_input!(input as syn::ItemMod);
+    let attrs = syn::parse_macro_input!(attrs as syn::AttributeArgs);
+
+    let module_name = module.ident.to_string();
+
+    let rv: TokenStream = match query_module::add_schema_attrs_to_derives(attrs, module) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    };
+
+    write_debug_output("schema_for_derives", &module_name, || rv.to_string());
 
     rv
 }
@@ -126,10 +126,21 @@ pub fn document_to_fragment_structs(
 
                         lines.push(format!("        #[arguments({})]", arguments_string));
                     }
-                    // TODO: print out arguments
+                    // Aliased fields are named after their alias, which
+                    // the derive then uses as the alias in the query.
+                    let field_name = match field.alias {
+                        Some(alias) => {
+                            lines.push(format!(
+                                "        #[cynic(rename = \"{}\", alias)]",
+                                field.name
+                            ));
+                            alias
+                        }
+                        None => field.name,
+                    };
                     lines.push(format!(
                         "        pub {}: {},",
-                        field.name.to_snake_case(),
+                        field_name.to_snake_case(),
                         field.field_type.type_spec(&type_index)
                     ))
                 }
@@ -163,6 +174,26 @@ pub fn document_to_fragment_structs(
 
                 lines.push("    }\n".into());
             }
+            PotentialStruct::InputObject(input_object) if input_object.is_one_of => {
+                lines.push("    #[derive(cynic::InputObject, Debug)]".into());
+                lines.push(format!(
+                    "    #[cynic(graphql_type = \"{}\", rename_all=\"camelCase\")]",
+                    input_object.name
+                ));
+                lines.push(format!("    pub enum {} {{", input_object.name));
+
+                // Exactly one field of a @oneOf InputObject is set, so each
+                // field becomes a variant.
+                for field in input_object.fields {
+                    lines.push(format!(
+                        "        {}({}),",
+                        field.name.to_pascal_case(),
+                        field.value_type.required_type_spec(&type_index)
+                    ))
+                }
+
+                lines.push("    }\n".into());
+            }
             PotentialStruct::InputObject(input_object) => {
                 lines.push("    #[derive(cynic::InputObject, Debug)]".into());
                 lines.push(format!(
@@ -194,7 +225,10 @@ pub fn document_to_fragment_structs(
     // Output any custom scalars we need.
     for def in &schema.definitions {
         match def {
-            schema::Definition::TypeDefinition(schema::TypeDefinition::Scalar(scalar)) => {
+            // Federation's _Any scalar is provided by cynic.
+            schema::Definition::TypeDefinition(schema::TypeDefinition::Scalar(scalar))
+                if scalar.name != "_Any" =>
+            {
                 lines.push("    #[derive(cynic::Scalar, Debug)]".into());
                 lines.push(format!(
                     "    pub struct {}(String);\n",
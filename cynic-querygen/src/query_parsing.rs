@@ -8,6 +8,8 @@ use crate::{value_ext::ValueExt, Error, GraphPath, TypeExt, TypeIndex};
 #[derive(Debug, PartialEq)]
 pub struct Field<'a> {
     pub name: &'a str,
+    /// The alias of this field in the query, if it has one.
+    pub alias: Option<&'a str>,
     pub field_type: &'a Type<'a>,
 
     pub arguments: Vec<FieldArgument<'a>>,
@@ -85,6 +87,7 @@ impl<'a> ArgumentStruct<'a> {
                 .iter()
                 .map(|var| Field {
                     name: var.name,
+                    alias: None,
                     field_type: &var.var_type,
                     arguments: vec![],
                 })
@@ -115,6 +118,8 @@ impl PotentialStruct<'_> {
 pub struct InputObject<'a> {
     pub name: String,
     pub fields: Vec<InputValue<'a>>,
+    /// Whether this is a `@oneOf` InputObject, which is output as an enum.
+    pub is_one_of: bool,
 }
 
 pub fn parse_query_document<'a>(
@@ -264,6 +269,7 @@ fn selection_set_to_structs<'a, 'b>(
 
                 this_fragment.fields.push(Field {
                     name: field.name,
+                    alias: field.alias,
                     field_type: &schema_field.field_type,
                     arguments: field
                         .arguments
@@ -388,6 +394,7 @@ fn structs_from_type_name<'a>(
             rv.push(PotentialStruct::InputObject(InputObject {
                 name: type_name.to_string(),
                 fields: input_object.fields.clone(),
+                is_one_of: input_object.directives.iter().any(|d| d.name == "oneOf"),
             }));
 
             rv.extend(
@@ -7,6 +7,9 @@ use crate::{schema::TypeDefinition, TypeIndex};
 pub trait TypeExt<'a> {
     fn inner_name(&self) -> &str;
     fn type_spec(&self, type_index: &TypeIndex<'a>) -> Cow<'a, str>;
+    /// The type spec without any outer `Option`, for values that are known
+    /// to be present.
+    fn required_type_spec(&self, type_index: &TypeIndex<'a>) -> Cow<'a, str>;
     fn is_required(&self) -> bool;
 }
 
@@ -15,6 +18,10 @@ impl<'a> TypeExt<'a> for Type<'a, &'a str> {
         type_spec_imp(self, true, type_index)
     }
 
+    fn required_type_spec(&self, type_index: &TypeIndex<'a>) -> Cow<'a, str> {
+        type_spec_imp(self, false, type_index)
+    }
+
     fn inner_name(&self) -> &str {
         match self {
             Type::NamedType(s) => s,
@@ -53,6 +60,7 @@ fn type_spec_imp<'a>(
         Type::NamedType("Float") => Cow::Borrowed("f64"),
         Type::NamedType("Boolean") => Cow::Borrowed("bool"),
         Type::NamedType("ID") => Cow::Borrowed("cynic::Id"),
+        Type::NamedType("_Any") => Cow::Borrowed("cynic::federation::Any"),
         Type::NamedType(s) => match type_index.lookup_type(s) {
             Some(TypeDefinition::Enum(_)) => Cow::Owned(s.to_pascal_case()),
             Some(TypeDefinition::Object(_)) => Cow::Owned(s.to_pascal_case()),
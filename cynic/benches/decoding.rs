@@ -0,0 +1,85 @@
+//! Benchmarks comparing serde_json & simd-json decoding of a large response.
+//!
+//! Run with `cargo bench --features simd-json`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+mod query_dsl {
+    cynic::query_dsl!("../examples/examples/starwars.schema.graphql");
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "../examples/examples/starwars.schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Film"
+)]
+struct Film {
+    title: Option<String>,
+    director: Option<String>,
+    opening_crawl: Option<String>,
+    producers: Option<Vec<Option<String>>>,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "../examples/examples/starwars.schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "FilmsConnection"
+)]
+struct FilmsConnection {
+    films: Option<Vec<Option<Film>>>,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    schema_path = "../examples/examples/starwars.schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Root"
+)]
+struct AllFilmsQuery {
+    all_films: Option<FilmsConnection>,
+}
+
+fn large_response() -> Vec<u8> {
+    let films = (0..10_000)
+        .map(|i| {
+            serde_json::json!({
+                "title": format!("Episode {}", i),
+                "director": "George Lucas",
+                "openingCrawl": "It is a period of civil war. ".repeat(20),
+                "producers": ["Gary Kurtz", "Rick McCallum"]
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_vec(&serde_json::json!({
+        "data": { "allFilms": { "films": films } }
+    }))
+    .unwrap()
+}
+
+fn decoding_benchmark(c: &mut Criterion) {
+    use cynic::QueryFragment;
+
+    let operation = cynic::Operation::query(AllFilmsQuery::fragment(&()));
+    let body = large_response();
+
+    c.bench_function("serde_json decode", |b| {
+        b.iter(|| {
+            let response = serde_json::from_slice(black_box(&body)).unwrap();
+            operation.decode_response(response).unwrap()
+        })
+    });
+
+    c.bench_function("simd-json decode", |b| {
+        b.iter_batched_ref(
+            || body.clone(),
+            |body| operation.decode_response_slice(black_box(body)).unwrap(),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, decoding_benchmark);
+criterion_main!(benches);
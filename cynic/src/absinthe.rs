@@ -0,0 +1,391 @@
+//! Running subscriptions against [Absinthe][absinthe] servers.
+//!
+//! Absinthe - the GraphQL library most Elixir backends use - sends
+//! subscriptions over Phoenix channels rather than a dedicated GraphQL
+//! websocket protocol.  Subscriptions are pushed to a control channel, and
+//! the server replies with an id for the subscription that its responses are
+//! then published under.
+//!
+//! `Connection` implements `graphql_transport_ws::Protocol`, so it can be
+//! used with `SubscriptionStream::with_protocol`:
+//!
+//! ```rust,ignore
+//! let url = absinthe::socket_url("wss://example.com/socket");
+//! let (sink, incoming) = connect_websocket(&url).await;
+//!
+//! let mut stream = SubscriptionStream::with_protocol(
+//!     absinthe::Connection::new(),
+//!     FilmsAddedSubscription::build(()),
+//!     None,
+//!     incoming.map(|message| message.into_text()),
+//!     move |text| sink.send(text),
+//! );
+//! ```
+//!
+//! Phoenix closes connections that don't send a heartbeat every so often -
+//! every 60 seconds by default - so `ping_message` should be sent at least
+//! that often.
+//!
+//! [absinthe]: https://hexdocs.pm/absinthe_phoenix
+
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    graphql_transport_ws::{Event, Protocol, ProtocolError},
+    GraphQLError, GraphQLResponse, StreamingOperation,
+};
+
+/// The version of the Phoenix channels message format this module speaks.
+pub const VSN: &str = "2.0.0";
+
+/// The channel subscriptions are sent to.
+pub const CONTROL_TOPIC: &str = "__absinthe__:control";
+
+/// The ref of the message that joins the control channel.
+const JOIN_REF: &str = "1";
+
+/// A Phoenix channels message: `[join_ref, ref, topic, event, payload]`.
+type Message = (Option<String>, Option<String>, String, String, Value);
+
+#[derive(serde::Deserialize)]
+struct Reply {
+    status: String,
+    #[serde(default)]
+    response: Value,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionReply {
+    subscription_id: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ErrorReply {
+    errors: Vec<GraphQLError>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionData {
+    result: GraphQLResponse<Value>,
+    subscription_id: String,
+}
+
+/// The URL of the websocket for a Phoenix socket mounted at `endpoint`.
+///
+/// Any params the socket expects - an authentication token, for example -
+/// should be appended to the query string of this URL.
+pub fn socket_url(endpoint: &str) -> String {
+    format!("{}/websocket?vsn={}", endpoint.trim_end_matches('/'), VSN)
+}
+
+/// The client side of a connection to an Absinthe server.
+///
+/// This only tracks the state of the protocol - it's up to the caller to
+/// send & receive messages.  `init_message` should be sent as soon as the
+/// websocket is open, and subscriptions started once `handle_message` has
+/// returned `Event::Acknowledged`.
+///
+/// The ids of subscriptions are the refs of the messages that started them,
+/// rather than the ids Absinthe gives them.
+#[derive(Debug)]
+pub struct Connection {
+    acknowledged: bool,
+    next_ref: u64,
+    /// Subscriptions the server hasn't replied to yet.
+    pending: HashSet<String>,
+    /// The ids of the subscriptions the server has started, keyed by the id
+    /// Absinthe gave them.
+    subscriptions: HashMap<String, String>,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Connection {
+            acknowledged: false,
+            // The first ref is used to join the control channel.
+            next_ref: 1,
+            pending: HashSet::new(),
+            subscriptions: HashMap::new(),
+        }
+    }
+}
+
+impl Connection {
+    pub fn new() -> Self {
+        Connection::default()
+    }
+
+    /// Whether the server has acknowledged this connection.
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledged
+    }
+
+    fn handle_reply(
+        &mut self,
+        reference: Option<String>,
+        payload: Value,
+    ) -> Result<Event, ProtocolError> {
+        let reply = serde_json::from_value::<Reply>(payload)?;
+        let reference = reference.unwrap_or_default();
+
+        if !self.acknowledged {
+            if reference != JOIN_REF {
+                return Err(ProtocolError::NotAcknowledged("phx_reply"));
+            }
+            if reply.status != "ok" {
+                return Err(ProtocolError::ConnectionRejected(Some(reply.response)));
+            }
+
+            self.acknowledged = true;
+            return Ok(Event::Acknowledged);
+        }
+
+        if self.pending.remove(&reference) {
+            if reply.status != "ok" {
+                return Ok(Event::Error {
+                    id: reference,
+                    errors: serde_json::from_value::<ErrorReply>(reply.response)?.errors,
+                });
+            }
+
+            let reply = serde_json::from_value::<SubscriptionReply>(reply.response)?;
+            self.subscriptions
+                .insert(reply.subscription_id, reference.clone());
+            return Ok(Event::Started { id: reference });
+        }
+
+        // Anything else is the reply to an unsubscribe, which uses the ref of
+        // the subscription it stopped.
+        self.subscriptions.retain(|_, id| *id != reference);
+        Ok(Event::Complete { id: reference })
+    }
+}
+
+impl Protocol for Connection {
+    /// Phoenix doesn't use a websocket subprotocol, so this is empty and
+    /// none should be requested.
+    fn subprotocol(&self) -> &'static str {
+        ""
+    }
+
+    /// Joins the control channel, with `payload` as the channel params.
+    fn init_message(&self, payload: Option<Value>) -> String {
+        encode(
+            Some(JOIN_REF),
+            Some(JOIN_REF),
+            CONTROL_TOPIC,
+            "phx_join",
+            payload.unwrap_or_else(|| json!({})),
+        )
+    }
+
+    /// A heartbeat, which the server will reply to.
+    fn ping_message(&self) -> Option<String> {
+        Some(encode(
+            None,
+            Some("heartbeat"),
+            "phoenix",
+            "heartbeat",
+            json!({}),
+        ))
+    }
+
+    fn subscribe<ResponseData>(
+        &mut self,
+        operation: &StreamingOperation<'_, ResponseData>,
+    ) -> Result<(String, String), serde_json::Error> {
+        let payload = serde_json::to_value(operation)?;
+
+        self.next_ref += 1;
+        let id = self.next_ref.to_string();
+        self.pending.insert(id.clone());
+
+        let message = encode(Some(JOIN_REF), Some(&id), CONTROL_TOPIC, "doc", payload);
+        Ok((id, message))
+    }
+
+    /// Subscriptions the server hasn't started yet can't be stopped, so the
+    /// server will ignore this for them.
+    fn complete_message(&self, id: &str) -> String {
+        let subscription_id = self
+            .subscriptions
+            .iter()
+            .find(|(_, subscription)| *subscription == id)
+            .map(|(subscription_id, _)| subscription_id.as_str());
+
+        encode(
+            Some(JOIN_REF),
+            Some(id),
+            CONTROL_TOPIC,
+            "unsubscribe",
+            json!({ "subscriptionId": subscription_id }),
+        )
+    }
+
+    fn handle_message(&mut self, text: &str) -> Result<Event, ProtocolError> {
+        let (_, reference, topic, event, payload) = serde_json::from_str::<Message>(text)?;
+
+        match (topic.as_str(), event.as_str()) {
+            ("phoenix", "phx_reply") => Ok(Event::Pong),
+            (CONTROL_TOPIC, "phx_reply") => self.handle_reply(reference, payload),
+            (CONTROL_TOPIC, "phx_error") | (CONTROL_TOPIC, "phx_close") => {
+                Err(ProtocolError::ConnectionClosed(Some(payload)))
+            }
+            (_, "subscription:data") => {
+                if !self.acknowledged {
+                    return Err(ProtocolError::NotAcknowledged("subscription:data"));
+                }
+
+                let data = serde_json::from_value::<SubscriptionData>(payload)?;
+                let id = match self.subscriptions.get(&data.subscription_id) {
+                    Some(id) => id.clone(),
+                    // This is for a subscription we didn't start.
+                    None => data.subscription_id,
+                };
+                Ok(Event::Next {
+                    id,
+                    payload: data.result,
+                })
+            }
+            // Anything else is a part of Phoenix that subscriptions don't
+            // use, which needs no reply.
+            _ => Ok(Event::KeepAlive),
+        }
+    }
+}
+
+fn encode(
+    join_ref: Option<&str>,
+    reference: Option<&str>,
+    topic: &str,
+    event: &str,
+    payload: Value,
+) -> String {
+    // Messages only contain strings & JSON values, so can't fail to
+    // serialize.
+    serde_json::to_string(&(join_ref, reference, topic, event, payload))
+        .expect("Message should always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    use crate::testing::fixtures::subscription;
+
+    fn acknowledged() -> Connection {
+        let mut connection = Connection::new();
+        connection
+            .handle_message(
+                r#"["1","1","__absinthe__:control","phx_reply",{"status":"ok","response":{}}]"#,
+            )
+            .unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_socket_url() {
+        assert_eq!(
+            socket_url("wss://example.com/socket/"),
+            "wss://example.com/socket/websocket?vsn=2.0.0"
+        );
+    }
+
+    #[test]
+    fn test_connection_messages() {
+        let mut connection = Connection::new();
+
+        assert_eq!(
+            connection.init_message(Some(json!({ "token": "abcd" }))),
+            r#"["1","1","__absinthe__:control","phx_join",{"token":"abcd"}]"#
+        );
+        assert_eq!(
+            connection.ping_message().unwrap(),
+            r#"[null,"heartbeat","phoenix","heartbeat",{}]"#
+        );
+
+        let (id, message) = connection.subscribe(&subscription("filmAdded")).unwrap();
+        assert_eq!(id, "2");
+        let message = serde_json::from_str::<Value>(&message).unwrap();
+        assert_eq!(message[3], "doc");
+        assert_eq!(
+            message[4],
+            serde_json::to_value(&subscription("filmAdded")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_handling_messages() {
+        let mut connection = Connection::new();
+
+        assert_matches!(
+            connection.handle_message(r#"[null,null,"__absinthe__:doc:1","subscription:data",{"result":{"data":null},"subscriptionId":"__absinthe__:doc:1"}]"#),
+            Err(ProtocolError::NotAcknowledged("subscription:data"))
+        );
+        assert_matches!(
+            connection.handle_message(
+                r#"["1","1","__absinthe__:control","phx_reply",{"status":"ok","response":{}}]"#
+            ),
+            Ok(Event::Acknowledged)
+        );
+
+        let (id, _) = connection.subscribe(&subscription("filmAdded")).unwrap();
+        assert_matches!(
+            connection.handle_message(r#"["1","2","__absinthe__:control","phx_reply",{"status":"ok","response":{"subscriptionId":"__absinthe__:doc:1"}}]"#),
+            Ok(Event::Started { id: started }) => assert_eq!(started, id)
+        );
+        assert_matches!(
+            connection.handle_message(r#"[null,null,"__absinthe__:doc:1","subscription:data",{"result":{"data":{"filmAdded":"Jaws"}},"subscriptionId":"__absinthe__:doc:1"}]"#),
+            Ok(Event::Next { id: next, payload }) => {
+                assert_eq!(next, id);
+                assert_eq!(payload.data, Some(json!({ "filmAdded": "Jaws" })));
+            }
+        );
+        assert_matches!(
+            connection.handle_message(
+                r#"[null,"heartbeat","phoenix","phx_reply",{"status":"ok","response":{}}]"#
+            ),
+            Ok(Event::Pong)
+        );
+
+        assert_eq!(
+            connection.complete_message(&id),
+            r#"["1","2","__absinthe__:control","unsubscribe",{"subscriptionId":"__absinthe__:doc:1"}]"#
+        );
+        assert_matches!(
+            connection.handle_message(r#"["1","2","__absinthe__:control","phx_reply",{"status":"ok","response":{}}]"#),
+            Ok(Event::Complete { id: completed }) => assert_eq!(completed, id)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut connection = acknowledged();
+
+        let (id, _) = connection.subscribe(&subscription("filmAdded")).unwrap();
+        assert_matches!(
+            connection.handle_message(r#"["1","2","__absinthe__:control","phx_reply",{"status":"error","response":{"errors":[{"message":"oops"}]}}]"#),
+            Ok(Event::Error { id: failed, errors }) => {
+                assert_eq!(failed, id);
+                assert_eq!(errors[0].message, "oops");
+            }
+        );
+
+        assert_matches!(
+            connection.handle_message(r#"["1","1","__absinthe__:control","phx_error",{}]"#),
+            Err(ProtocolError::ConnectionClosed(_))
+        );
+
+        let mut connection = Connection::new();
+        assert_matches!(
+            connection.handle_message(r#"["1","1","__absinthe__:control","phx_reply",{"status":"error","response":{"reason":"unauthorized"}}]"#),
+            Err(ProtocolError::ConnectionRejected(Some(response))) => {
+                assert_eq!(response, json!({ "reason": "unauthorized" }))
+            }
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Running subscriptions against [AWS AppSync][appsync]'s realtime endpoint.
+//!
+//! AppSync speaks a variant of the legacy `subscriptions-transport-ws`
+//! protocol.  The differences are mostly around authorization: the headers
+//! AppSync authorizes with are sent base64 encoded in the URL when
+//! connecting, and again alongside every subscription.  Subscriptions are
+//! also sent as a JSON string rather than an object, and confirmed by the
+//! server before any data arrives.
+//!
+//! `Connection` implements `graphql_transport_ws::Protocol`, so it can be
+//! used with `SubscriptionStream::with_protocol`:
+//!
+//! ```rust,ignore
+//! let connection = appsync::Connection::with_api_key(
+//!     "example.appsync-api.eu-west-1.amazonaws.com",
+//!     api_key,
+//! );
+//! let url = connection.connection_url(&appsync::realtime_endpoint(
+//!     "https://example.appsync-api.eu-west-1.amazonaws.com/graphql",
+//! ));
+//! let (sink, incoming) = connect_websocket(&url, appsync::SUBPROTOCOL).await;
+//!
+//! let mut stream = SubscriptionStream::with_protocol(
+//!     connection,
+//!     FilmsAddedSubscription::build(()),
+//!     None,
+//!     incoming.map(|message| message.into_text()),
+//!     move |text| sink.send(text),
+//! );
+//! ```
+//!
+//! AppSync also used to support MQTT over websockets, but that's deprecated
+//! and isn't supported here.
+//!
+//! [appsync]: https://docs.aws.amazon.com/appsync/latest/devguide/real-time-websocket-client.html
+
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::{
+    graphql_transport_ws::{Event, Protocol, ProtocolError},
+    GraphQLError, GraphQLResponse, StreamingOperation,
+};
+
+/// The websocket subprotocol that should be requested when connecting.
+pub const SUBPROTOCOL: &str = "graphql-ws";
+
+/// A message sent from the client to the server.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    ConnectionInit,
+    Start { id: String, payload: Value },
+    Stop { id: String },
+}
+
+/// A message sent from the server to the client.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    ConnectionAck {
+        #[serde(default)]
+        payload: Option<AckPayload>,
+    },
+    ConnectionError {
+        #[serde(default)]
+        payload: Option<Value>,
+    },
+    #[serde(rename = "ka")]
+    KeepAlive,
+    StartAck {
+        id: String,
+    },
+    Data {
+        id: String,
+        payload: GraphQLResponse<Value>,
+    },
+    /// Errors without an `id` are about the connection rather than a
+    /// subscription.
+    Error {
+        #[serde(default)]
+        id: Option<String>,
+        payload: Value,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AckPayload {
+    pub connection_timeout_ms: Option<u64>,
+}
+
+#[derive(serde::Deserialize)]
+struct ErrorPayload {
+    errors: Vec<GraphQLError>,
+}
+
+/// The realtime endpoint of an AppSync API, given its GraphQL endpoint.
+///
+/// APIs on a custom domain serve the realtime endpoint from `/realtime`
+/// under the GraphQL endpoint.
+pub fn realtime_endpoint(graphql_endpoint: &str) -> String {
+    let endpoint = graphql_endpoint.replacen("https://", "wss://", 1);
+
+    if endpoint.contains(".appsync-api.") {
+        endpoint.replacen(".appsync-api.", ".appsync-realtime-api.", 1)
+    } else {
+        format!("{}/realtime", endpoint.trim_end_matches('/'))
+    }
+}
+
+/// The client side of an AppSync realtime connection.
+///
+/// This only tracks the state of the protocol - it's up to the caller to
+/// open the websocket with `connection_url` & send & receive messages.
+/// `init_message` should be sent as soon as the websocket is open, and
+/// subscriptions started once `handle_message` has returned
+/// `Event::Acknowledged`.
+#[derive(Debug)]
+pub struct Connection {
+    authorization: Value,
+    acknowledged: bool,
+    connection_timeout: Option<Duration>,
+    next_id: u64,
+}
+
+impl Connection {
+    /// Creates a connection that authorizes with `authorization`: an object
+    /// of the headers AppSync expects for the API's authorization mode.
+    ///
+    /// The same headers are sent with every subscription.
+    pub fn new(authorization: Value) -> Self {
+        Connection {
+            authorization,
+            acknowledged: false,
+            connection_timeout: None,
+            next_id: 0,
+        }
+    }
+
+    /// Creates a connection that authorizes with an API key.
+    ///
+    /// `host` is the host of the API's GraphQL endpoint, not the realtime
+    /// endpoint.
+    pub fn with_api_key(host: &str, api_key: &str) -> Self {
+        Connection::new(json!({ "host": host, "x-api-key": api_key }))
+    }
+
+    /// Creates a connection that authorizes with a token from a Cognito user
+    /// pool, an OpenID Connect provider or a Lambda authorizer.
+    ///
+    /// `host` is the host of the API's GraphQL endpoint, not the realtime
+    /// endpoint.
+    pub fn with_token(host: &str, token: &str) -> Self {
+        Connection::new(json!({ "host": host, "Authorization": token }))
+    }
+
+    /// The URL the websocket should be opened with, given the API's realtime
+    /// endpoint.
+    pub fn connection_url(&self, realtime_endpoint: &str) -> String {
+        format!(
+            "{}?header={}&payload={}",
+            realtime_endpoint,
+            base64::encode(self.authorization.to_string()),
+            base64::encode("{}")
+        )
+    }
+
+    /// Whether the server has acknowledged this connection.
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledged
+    }
+
+    /// How long the server can go without sending a keep alive before the
+    /// connection should be considered dead.
+    ///
+    /// This is sent by the server when it acknowledges the connection.
+    pub fn connection_timeout(&self) -> Option<Duration> {
+        self.connection_timeout
+    }
+}
+
+impl Protocol for Connection {
+    fn subprotocol(&self) -> &'static str {
+        SUBPROTOCOL
+    }
+
+    /// AppSync doesn't accept a payload here - the authorization headers are
+    /// sent in the `connection_url` instead - so `payload` is ignored.
+    fn init_message(&self, _payload: Option<Value>) -> String {
+        encode(&ClientMessage::ConnectionInit)
+    }
+
+    /// AppSync has no pings - the server sends keep alives instead.
+    fn ping_message(&self) -> Option<String> {
+        None
+    }
+
+    fn subscribe<ResponseData>(
+        &mut self,
+        operation: &StreamingOperation<'_, ResponseData>,
+    ) -> Result<(String, String), serde_json::Error> {
+        let data = serde_json::to_string(operation)?;
+
+        self.next_id += 1;
+        let id = self.next_id.to_string();
+
+        let message = encode(&ClientMessage::Start {
+            id: id.clone(),
+            payload: json!({
+                "data": data,
+                "extensions": { "authorization": self.authorization },
+            }),
+        });
+        Ok((id, message))
+    }
+
+    fn complete_message(&self, id: &str) -> String {
+        encode(&ClientMessage::Stop { id: id.to_string() })
+    }
+
+    fn handle_message(&mut self, text: &str) -> Result<Event, ProtocolError> {
+        let message = serde_json::from_str::<ServerMessage>(text)?;
+
+        if !self.acknowledged {
+            match message {
+                ServerMessage::ConnectionAck { .. }
+                | ServerMessage::ConnectionError { .. }
+                | ServerMessage::KeepAlive
+                | ServerMessage::Error { id: None, .. } => {}
+                ServerMessage::StartAck { .. } => {
+                    return Err(ProtocolError::NotAcknowledged("start_ack"))
+                }
+                ServerMessage::Data { .. } => return Err(ProtocolError::NotAcknowledged("data")),
+                ServerMessage::Error { .. } => return Err(ProtocolError::NotAcknowledged("error")),
+                ServerMessage::Complete { .. } => {
+                    return Err(ProtocolError::NotAcknowledged("complete"))
+                }
+            }
+        }
+
+        Ok(match message {
+            ServerMessage::ConnectionAck { payload } => {
+                self.acknowledged = true;
+                self.connection_timeout = payload
+                    .and_then(|payload| payload.connection_timeout_ms)
+                    .map(Duration::from_millis);
+                Event::Acknowledged
+            }
+            ServerMessage::ConnectionError { payload } => {
+                return Err(ProtocolError::ConnectionRejected(payload))
+            }
+            ServerMessage::KeepAlive => Event::KeepAlive,
+            ServerMessage::StartAck { id } => Event::Started { id },
+            ServerMessage::Data { id, payload } => Event::Next { id, payload },
+            ServerMessage::Error { id: None, payload } => {
+                return Err(ProtocolError::ConnectionRejected(Some(payload)))
+            }
+            ServerMessage::Error {
+                id: Some(id),
+                payload,
+            } => Event::Error {
+                id,
+                errors: serde_json::from_value::<ErrorPayload>(payload)?.errors,
+            },
+            ServerMessage::Complete { id } => Event::Complete { id },
+        })
+    }
+}
+
+fn encode(message: &ClientMessage) -> String {
+    // ClientMessages only contain strings & JSON values, so can't fail to
+    // serialize.
+    serde_json::to_string(message).expect("ClientMessage should always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    use crate::testing::fixtures::subscription;
+
+    const HOST: &str = "example.appsync-api.eu-west-1.amazonaws.com";
+
+    #[test]
+    fn test_connection_url() {
+        assert_eq!(
+            realtime_endpoint(&format!("https://{}/graphql", HOST)),
+            "wss://example.appsync-realtime-api.eu-west-1.amazonaws.com/graphql"
+        );
+        assert_eq!(
+            realtime_endpoint("https://api.example.com/graphql"),
+            "wss://api.example.com/graphql/realtime"
+        );
+
+        let connection = Connection::with_api_key(HOST, "da2-abcd");
+        let url = connection.connection_url("wss://example.com/graphql");
+        let header = url
+            .strip_prefix("wss://example.com/graphql?header=")
+            .unwrap()
+            .strip_suffix("&payload=e30=")
+            .unwrap();
+
+        assert_eq!(
+            serde_json::from_slice::<Value>(&base64::decode(header).unwrap()).unwrap(),
+            json!({ "host": HOST, "x-api-key": "da2-abcd" })
+        );
+    }
+
+    #[test]
+    fn test_connection_messages() {
+        let mut connection = Connection::with_token(HOST, "eyJ");
+
+        assert_eq!(
+            connection.init_message(Some(json!({ "ignored": true }))),
+            r#"{"type":"connection_init"}"#
+        );
+        assert_eq!(connection.ping_message(), None);
+
+        let (id, message) = connection.subscribe(&subscription("filmAdded")).unwrap();
+        let message = serde_json::from_str::<Value>(&message).unwrap();
+        assert_eq!(message["type"], "start");
+        assert_eq!(message["id"], "1");
+        assert_eq!(
+            message["payload"]["extensions"]["authorization"],
+            json!({ "host": HOST, "Authorization": "eyJ" })
+        );
+
+        let data = message["payload"]["data"].as_str().unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(data).unwrap(),
+            serde_json::to_value(&subscription("filmAdded")).unwrap()
+        );
+
+        assert_eq!(
+            connection.complete_message(&id),
+            r#"{"type":"stop","id":"1"}"#
+        );
+    }
+
+    #[test]
+    fn test_handling_messages() {
+        let mut connection = Connection::with_api_key(HOST, "da2-abcd");
+
+        assert_matches!(
+            connection.handle_message(r#"{"type":"start_ack","id":"1"}"#),
+            Err(ProtocolError::NotAcknowledged("start_ack"))
+        );
+        assert_matches!(
+            connection.handle_message(
+                r#"{"type":"connection_ack","payload":{"connectionTimeoutMs":300000}}"#
+            ),
+            Ok(Event::Acknowledged)
+        );
+        assert_eq!(
+            connection.connection_timeout(),
+            Some(Duration::from_secs(300))
+        );
+
+        assert_matches!(
+            connection.handle_message(r#"{"type":"ka"}"#),
+            Ok(Event::KeepAlive)
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"start_ack","id":"1"}"#),
+            Ok(Event::Started { id }) => assert_eq!(id, "1")
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"data","id":"1","payload":{"data":{"filmAdded":"Jaws"}}}"#),
+            Ok(Event::Next { payload, .. }) => {
+                assert_eq!(payload.data, Some(json!({ "filmAdded": "Jaws" })));
+            }
+        );
+        assert_matches!(
+            connection.handle_message(
+                r#"{"type":"error","id":"1","payload":{"errors":[{"errorType":"UnauthorizedException","message":"oops"}]}}"#
+            ),
+            Ok(Event::Error { errors, .. }) => assert_eq!(errors[0].message, "oops")
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"complete","id":"1"}"#),
+            Ok(Event::Complete { id }) => assert_eq!(id, "1")
+        );
+    }
+
+    #[test]
+    fn test_rejected_connections() {
+        let mut connection = Connection::with_api_key(HOST, "da2-abcd");
+
+        assert_matches!(
+            connection.handle_message(
+                r#"{"type":"connection_error","payload":{"errors":[{"errorType":"UnauthorizedException"}]}}"#
+            ),
+            Err(ProtocolError::ConnectionRejected(Some(_)))
+        );
+        assert_matches!(
+            connection.handle_message(
+                r#"{"type":"error","payload":{"errors":[{"message":"Too many subscriptions"}]}}"#
+            ),
+            Err(ProtocolError::ConnectionRejected(Some(payload))) => {
+                assert_eq!(payload["errors"][0]["message"], "Too many subscriptions")
+            }
+        );
+    }
+}
@@ -1,17 +1,29 @@
-use crate::SerializeError;
+use std::sync::Arc;
 
+use crate::{upload::Upload, SerializeError};
+
+#[derive(Clone)]
 pub struct Argument {
     pub(crate) name: String,
-    pub(crate) serialize_result: Result<serde_json::Value, SerializeError>,
+    // We store errors in an Arc so that Arguments (and therefore Operations)
+    // can be cloned.
+    pub(crate) serialize_result:
+        Result<serde_json::Value, Arc<dyn std::error::Error + Send + Sync>>,
     pub(crate) type_: String,
+    /// Any files in the value, which are sent separately from the JSON, along
+    /// with their paths within the value.
+    pub(crate) uploads: Arc<[(String, Upload)]>,
 }
 
 impl Argument {
     pub fn new(name: &str, gql_type: &str, value: impl SerializableArgument) -> Argument {
+        let (serialize_result, uploads) = crate::upload::serialize_argument(&value);
+
         Argument {
             name: name.to_string(),
-            serialize_result: value.serialize(),
+            serialize_result: serialize_result.map_err(Arc::from),
             type_: gql_type.to_string(),
+            uploads: uploads.into(),
         }
     }
 }
@@ -43,6 +55,15 @@ impl<T: SerializableArgument> SerializableArgument for Vec<T> {
     }
 }
 
+impl<T: SerializableArgument> SerializableArgument for [T] {
+    fn serialize(&self) -> Result<serde_json::Value, SerializeError> {
+        self.iter()
+            .map(|s| s.serialize())
+            .collect::<Result<Vec<_>, _>>()
+            .map(serde_json::Value::Array)
+    }
+}
+
 impl<T: SerializableArgument> SerializableArgument for Option<T> {
     fn serialize(&self) -> Result<serde_json::Value, SerializeError> {
         match self {
@@ -58,13 +79,13 @@ impl<T: SerializableArgument> SerializableArgument for Box<T> {
     }
 }
 
-impl<T: SerializableArgument> SerializableArgument for &T {
+impl<T: SerializableArgument + ?Sized> SerializableArgument for &T {
     fn serialize(&self) -> Result<serde_json::Value, SerializeError> {
         (*self).serialize()
     }
 }
 
-impl SerializableArgument for &str {
+impl SerializableArgument for str {
     fn serialize(&self) -> Result<serde_json::Value, SerializeError> {
         Ok(serde_json::Value::String(self.to_string()))
     }
@@ -84,6 +105,8 @@ macro_rules! impl_serializable_argument_for_scalar {
 }
 
 impl_serializable_argument_for_scalar!(i32);
+impl_serializable_argument_for_scalar!(i64);
+impl_serializable_argument_for_scalar!(u64);
 impl_serializable_argument_for_scalar!(f64);
 impl_serializable_argument_for_scalar!(String);
 impl_serializable_argument_for_scalar!(bool);
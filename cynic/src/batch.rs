@@ -0,0 +1,180 @@
+//! Sending several operations in a single request.
+//!
+//! Many servers accept a JSON array of operations in a single POST, and
+//! respond with an array of responses in the same order.  A `BatchOperation`
+//! builds these requests, and each operation added to it returns a
+//! `BatchEntry` that can decode its own response out of the batch:
+//!
+//! ```rust,ignore
+//! let mut batch = BatchOperation::new();
+//! let films = batch.add(AllFilmsQuery::build(()));
+//! let people = batch.add(AllPeopleQuery::build(()));
+//!
+//! let mut response = client.post(url).run_graphql_batch(&batch)?;
+//! let films = films.decode_response(&mut response)?;
+//! let people = people.decode_response(&mut response)?;
+//! ```
+
+use serde_json::Value;
+
+use crate::{GraphQLResponse, Operation};
+
+/// A batch of operations, to be sent in a single request.
+///
+/// This serializes as an array of operations.
+#[derive(Clone, Default, serde::Serialize)]
+#[serde(transparent)]
+pub struct BatchOperation {
+    operations: Vec<Operation<'static, Value>>,
+}
+
+impl BatchOperation {
+    pub fn new() -> Self {
+        BatchOperation::default()
+    }
+
+    /// Adds an operation to the batch, returning a `BatchEntry` that can
+    /// decode its response.
+    pub fn add<'a, ResponseData: 'a>(
+        &mut self,
+        operation: Operation<'a, ResponseData>,
+    ) -> BatchEntry<'a, ResponseData> {
+        self.operations.push(operation.untyped());
+
+        BatchEntry {
+            index: self.operations.len() - 1,
+            operation,
+        }
+    }
+
+    /// The operations in this batch.
+    pub fn operations(&self) -> &[Operation<'static, Value>] {
+        &self.operations
+    }
+
+    /// The number of operations in this batch.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Whether there are any operations in this batch.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+/// An operation that's been added to a `BatchOperation`.
+pub struct BatchEntry<'a, ResponseData> {
+    index: usize,
+    operation: Operation<'a, ResponseData>,
+}
+
+impl<'a, ResponseData: 'a> BatchEntry<'a, ResponseData> {
+    /// Takes the response to this operation out of the response to the
+    /// batch, and decodes it.
+    pub fn decode_response(
+        &self,
+        response: &mut BatchResponse,
+    ) -> Result<GraphQLResponse<ResponseData>, BatchError> {
+        let response = response
+            .responses
+            .get_mut(self.index)
+            .and_then(Option::take)
+            .ok_or(BatchError::MissingResponse(self.index))?;
+
+        Ok(self.operation.decode_response(response)?)
+    }
+}
+
+/// The response to a `BatchOperation`.
+///
+/// This deserializes from an array of responses.
+#[derive(Debug, serde::Deserialize)]
+#[serde(from = "Vec<GraphQLResponse<Value>>")]
+pub struct BatchResponse {
+    responses: Vec<Option<GraphQLResponse<Value>>>,
+}
+
+impl BatchResponse {
+    /// The number of responses in the batch.
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    /// The response to each operation in the batch, or `None` if it's been
+    /// taken by a `BatchEntry`.
+    pub(crate) fn responses(&self) -> impl Iterator<Item = Option<&GraphQLResponse<Value>>> {
+        self.responses.iter().map(Option::as_ref)
+    }
+}
+
+impl From<Vec<GraphQLResponse<Value>>> for BatchResponse {
+    fn from(responses: Vec<GraphQLResponse<Value>>) -> Self {
+        BatchResponse {
+            responses: responses.into_iter().map(Some).collect(),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BatchError {
+    #[error("The batch response did not contain a response for operation {0}")]
+    MissingResponse(usize),
+    #[error("Error decoding GraphQL response: {0}")]
+    DecodeError(#[from] json_decode::DecodeError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use crate::{
+        selection_set::{field, integer, string},
+        testing::fixtures::Root,
+    };
+
+    #[test]
+    fn test_batches() {
+        let mut batch = BatchOperation::new();
+        let title = batch.add(Operation::query(field::<_, Root, ()>(
+            "title",
+            vec![],
+            string(),
+        )));
+        let count = batch.add(Operation::query(field::<_, Root, ()>(
+            "count",
+            vec![],
+            integer(),
+        )));
+
+        let body = serde_json::to_value(&batch).unwrap();
+        assert_eq!(body.as_array().unwrap().len(), 2);
+        assert_eq!(body[1]["query"], json!(batch.operations()[1].query));
+
+        let mut response: BatchResponse = serde_json::from_value(json!([
+            { "data": { "title": "A New Hope" } },
+            { "data": { "count": 6 } }
+        ]))
+        .unwrap();
+
+        assert_eq!(count.decode_response(&mut response).unwrap().data, Some(6));
+        assert_eq!(
+            title
+                .decode_response(&mut response)
+                .unwrap()
+                .data
+                .as_deref(),
+            Some("A New Hope")
+        );
+        assert_matches!(
+            title.decode_response(&mut response),
+            Err(BatchError::MissingResponse(0))
+        );
+    }
+}
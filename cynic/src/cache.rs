@@ -0,0 +1,384 @@
+//! A simple cache of operation responses.
+//!
+//! `ResponseCache` stores whole responses, keyed by an operation's document &
+//! variables.  This is a lot less clever than a normalized cache, but is
+//! often enough for read heavy clients that run the same queries repeatedly.
+//!
+//! Responses are cached for a default TTL, unless the server sends Apollo
+//! style cache hints in the `extensions` of its response - in which case the
+//! smallest `maxAge` of the hints is used:
+//!
+//! ```json
+//! { "cacheControl": { "version": 1, "hints": [{ "path": ["film"], "maxAge": 60 }] } }
+//! ```
+//!
+//! Once a response expires it can still be served for a configurable
+//! `stale_while_revalidate` period while a fresh response is fetched.
+//! `ResponseCache::lookup` reports whether an entry is `Fresh` or `Stale`, so
+//! async clients can serve stale responses while revalidating in the
+//! background.  `ResponseCache::run` is a simpler blocking helper.
+//!
+//! Mutations & responses with errors are never cached.  Entries are removed
+//! once they're past their stale-while-revalidate period.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::{GraphQLResponse, Operation};
+
+/// The result of looking up an operation in a `ResponseCache`.
+#[derive(Debug)]
+pub enum CacheLookup {
+    /// The cached response is within its TTL.
+    Fresh(GraphQLResponse<Value>),
+    /// The cached response has expired, but is still within the
+    /// stale-while-revalidate period.  It can be used, but should be
+    /// refreshed.
+    Stale(GraphQLResponse<Value>),
+    /// There is no usable cached response.
+    Miss,
+}
+
+/// A cache of responses with a TTL.
+///
+/// `ResponseCache` is cheap to clone - clones share the same entries.
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+    entries: Arc<Mutex<HashMap<CacheKey, Entry>>>,
+    default_ttl: Duration,
+    stale_while_revalidate: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    query: String,
+    variables: String,
+}
+
+#[derive(Debug)]
+struct Entry {
+    data: Value,
+    expires_at: Instant,
+}
+
+impl ResponseCache {
+    /// Creates a ResponseCache that caches responses for `default_ttl`,
+    /// unless the server provides cache hints.
+    pub fn new(default_ttl: Duration) -> Self {
+        ResponseCache {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            default_ttl,
+            stale_while_revalidate: Duration::from_secs(0),
+        }
+    }
+
+    /// Keeps serving responses for `period` after they expire, while they're
+    /// being revalidated.
+    pub fn with_stale_while_revalidate(mut self, period: Duration) -> Self {
+        self.stale_while_revalidate = period;
+        self
+    }
+
+    /// Looks up the cached response for `operation`.
+    pub fn lookup<ResponseData>(&self, operation: &Operation<'_, ResponseData>) -> CacheLookup {
+        self.lookup_at(operation, Instant::now())
+    }
+
+    /// Caches `response` for `operation`, honouring any cache hints in its
+    /// `extensions`.
+    pub fn store<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+        response: &GraphQLResponse<Value>,
+    ) {
+        self.store_at(operation, response, Instant::now())
+    }
+
+    /// Removes every cached response.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Runs `operation` using a cached response if there's a usable one, and
+    /// `send` otherwise.
+    ///
+    /// Stale responses are revalidated before this returns, but are used if
+    /// revalidation fails.
+    pub fn run<'a, ResponseData: 'a, E>(
+        &self,
+        operation: &Operation<'a, ResponseData>,
+        send: impl FnOnce(&Operation<'a, ResponseData>) -> Result<GraphQLResponse<Value>, E>,
+    ) -> Result<GraphQLResponse<ResponseData>, CacheError<E>> {
+        let stale = match self.lookup(operation) {
+            CacheLookup::Fresh(response) => return Ok(operation.decode_response(response)?),
+            CacheLookup::Stale(response) => Some(response),
+            CacheLookup::Miss => None,
+        };
+
+        let response = match (send(operation), stale) {
+            (Ok(response), _) => {
+                self.store(operation, &response);
+                response
+            }
+            (Err(_), Some(stale)) => stale,
+            (Err(e), None) => return Err(CacheError::Transport(e)),
+        };
+
+        Ok(operation.decode_response(response)?)
+    }
+
+    fn lookup_at<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+        now: Instant,
+    ) -> CacheLookup {
+        let key = match cache_key(operation) {
+            Some(key) => key,
+            None => return CacheLookup::Miss,
+        };
+
+        let mut entries = self.lock();
+        let entry = match entries.get(&key) {
+            Some(entry) => entry,
+            None => return CacheLookup::Miss,
+        };
+
+        if !self.is_usable(entry, now) {
+            entries.remove(&key);
+            return CacheLookup::Miss;
+        }
+
+        let response = GraphQLResponse {
+            data: Some(entry.data.clone()),
+            errors: None,
+            extensions: None,
+        };
+
+        if now < entry.expires_at {
+            CacheLookup::Fresh(response)
+        } else {
+            CacheLookup::Stale(response)
+        }
+    }
+
+    /// Whether `entry` can still be served, either fresh or stale.
+    fn is_usable(&self, entry: &Entry, now: Instant) -> bool {
+        now < entry.expires_at + self.stale_while_revalidate
+    }
+
+    fn store_at<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+        response: &GraphQLResponse<Value>,
+        now: Instant,
+    ) {
+        let has_errors = response.errors.as_ref().map_or(false, |e| !e.is_empty());
+        let (key, data) = match (cache_key(operation), &response.data) {
+            (Some(key), Some(data)) if !has_errors => (key, data.clone()),
+            _ => return,
+        };
+
+        let ttl = response
+            .extensions
+            .as_ref()
+            .and_then(cache_hint_max_age)
+            .unwrap_or(self.default_ttl);
+
+        let mut entries = self.lock();
+        entries.retain(|_, entry| self.is_usable(entry, now));
+
+        if ttl == Duration::from_secs(0) {
+            entries.remove(&key);
+            return;
+        }
+
+        entries.insert(
+            key,
+            Entry {
+                data,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<CacheKey, Entry>> {
+        self.entries
+            .lock()
+            .expect("ResponseCache mutex was poisoned")
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CacheError<E> {
+    #[error("Error sending operation: {0}")]
+    Transport(E),
+    #[error("Error decoding GraphQL response: {0}")]
+    DecodeError(#[from] json_decode::DecodeError),
+}
+
+fn cache_key<ResponseData>(operation: &Operation<'_, ResponseData>) -> Option<CacheKey> {
+    if operation.is_mutation() {
+        return None;
+    }
+
+    Some(CacheKey {
+        query: operation.query.clone(),
+        variables: serde_json::to_string(&operation.variables).ok()?,
+    })
+}
+
+/// Finds the smallest `maxAge` in any Apollo style cache hints.
+fn cache_hint_max_age(extensions: &Value) -> Option<Duration> {
+    extensions["cacheControl"]["hints"]
+        .as_array()?
+        .iter()
+        .filter_map(|hint| hint["maxAge"].as_u64())
+        .min()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use crate::testing::fixtures::{film_query, mutation};
+
+    fn response() -> GraphQLResponse<Value> {
+        GraphQLResponse {
+            data: Some(json!({ "film": "A New Hope" })),
+            errors: None,
+            extensions: None,
+        }
+    }
+
+    #[test]
+    fn test_responses_expire() {
+        let cache = ResponseCache::new(Duration::from_secs(10))
+            .with_stale_while_revalidate(Duration::from_secs(5));
+        let now = Instant::now();
+
+        cache.store_at(&film_query(1), &response(), now);
+
+        assert_matches!(cache.lookup_at(&film_query(1), now), CacheLookup::Fresh(_));
+        assert_matches!(cache.lookup_at(&film_query(2), now), CacheLookup::Miss);
+
+        let later = now + Duration::from_secs(12);
+        assert_matches!(
+            cache.lookup_at(&film_query(1), later),
+            CacheLookup::Stale(_)
+        );
+
+        let much_later = now + Duration::from_secs(16);
+        assert_matches!(
+            cache.lookup_at(&film_query(1), much_later),
+            CacheLookup::Miss
+        );
+    }
+
+    #[test]
+    fn test_cache_hints() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        let extensions = json!({
+            "cacheControl": {
+                "version": 1,
+                "hints": [
+                    { "path": ["film"], "maxAge": 30 },
+                    { "path": ["film", "title"], "maxAge": 5 }
+                ]
+            }
+        });
+        let response = GraphQLResponse {
+            extensions: Some(extensions),
+            ..response()
+        };
+        cache.store_at(&film_query(1), &response, now);
+
+        let later = now + Duration::from_secs(6);
+        assert_matches!(cache.lookup_at(&film_query(1), later), CacheLookup::Miss);
+    }
+
+    #[test]
+    fn test_expired_entries_are_removed() {
+        let cache = ResponseCache::new(Duration::from_secs(10))
+            .with_stale_while_revalidate(Duration::from_secs(5));
+        let now = Instant::now();
+
+        cache.store_at(&film_query(1), &response(), now);
+        cache.store_at(&film_query(2), &response(), now + Duration::from_secs(10));
+        assert_eq!(cache.lock().len(), 2);
+
+        cache.store_at(&film_query(3), &response(), now + Duration::from_secs(16));
+        assert_eq!(cache.lock().len(), 2);
+
+        assert_matches!(
+            cache.lookup_at(&film_query(2), now + Duration::from_secs(26)),
+            CacheLookup::Miss
+        );
+        assert_eq!(cache.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_mutations_and_errors_arent_cached() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+
+        let mutation = mutation("createFilm", vec![]);
+        cache.store(&mutation, &response());
+        assert_matches!(cache.lookup(&mutation), CacheLookup::Miss);
+
+        let error_response = GraphQLResponse {
+            data: Some(json!({ "film": null })),
+            errors: Some(vec![crate::GraphQLError::new(
+                "oops".into(),
+                None,
+                None,
+                None,
+            )]),
+            extensions: None,
+        };
+        cache.store(&film_query(1), &error_response);
+        assert_matches!(cache.lookup(&film_query(1)), CacheLookup::Miss);
+    }
+
+    #[test]
+    fn test_run() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+
+        let first = cache
+            .run(&film_query(1), |_| Ok::<_, ()>(response()))
+            .unwrap();
+        assert_eq!(first.data.as_deref(), Some("A New Hope"));
+
+        let second = cache
+            .run(&film_query(1), |_| -> Result<_, ()> {
+                panic!("Should be cached")
+            })
+            .unwrap();
+        assert_eq!(second.data.as_deref(), Some("A New Hope"));
+
+        let uncacheable = || GraphQLResponse {
+            extensions: Some(json!({
+                "cacheControl": { "version": 1, "hints": [{ "path": ["film"], "maxAge": 0 }] }
+            })),
+            ..response()
+        };
+        let mut calls = 0;
+        for _ in 0..2 {
+            cache
+                .run(&film_query(2), |_| {
+                    calls += 1;
+                    Ok::<_, ()>(uncacheable())
+                })
+                .unwrap();
+        }
+        assert_eq!(calls, 2);
+    }
+}
@@ -0,0 +1,665 @@
+//! Running operations over any transport.
+//!
+//! cynic has integrations for a few HTTP clients, but operations can be sent
+//! with anything that implements `GraphQlClient`.  Implementations only need
+//! to send a request body & hand back the response - serializing operations,
+//! checking responses for errors & decoding are handled by
+//! `GraphQlClient::run_graphql`:
+//!
+//! ```rust,ignore
+//! struct MyClient { /* ... */ }
+//!
+//! impl cynic::client::GraphQlClient for MyClient {
+//!     type Error = MyHttpError;
+//!
+//!     fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, MyHttpError>> {
+//!         Box::pin(async move {
+//!             let response = self.post("/graphql", request.headers, request.body).await?;
+//!             Ok(Response {
+//!                 status: response.status,
+//!                 headers: response.headers,
+//!                 body: response.body,
+//!             })
+//!         })
+//!     }
+//! }
+//!
+//! let response = client.run_graphql(FilmDirectorQuery::build(())).await?;
+//! ```
+//!
+//! With the `reqwest` or `surf` features enabled, `http::ReqwestClient` &
+//! `http::SurfClient` are implementations for those HTTP clients.  The
+//! `ReqwestExt` & `SurfExt` traits run operations through the same
+//! implementation.
+//!
+//! Transports whose futures aren't `Send` - like `http::FetchRequest` with
+//! the `wasm` feature - can implement `LocalGraphQlClient` instead.
+
+use std::{future::Future, pin::Pin};
+
+use crate::{
+    batch::{BatchOperation, BatchResponse},
+    middleware::{self, Middleware, MiddlewareError},
+    GraphQLResponse, Operation,
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A `BoxFuture` that doesn't need to be `Send`, for `LocalGraphQlClient`.
+pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// A request that a `GraphQlClient` should send to the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    /// The headers to send, including the `Content-Type` of the body.
+    pub headers: Vec<(String, String)>,
+    /// The body of the request - an operation serialized as JSON, or a
+    /// multipart form if it contains uploads.
+    pub body: Vec<u8>,
+}
+
+/// The response a `GraphQlClient` received from the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// The HTTP status of the response.  Transports that don't have statuses
+    /// should use 200.
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ClientError<E: std::fmt::Display + std::fmt::Debug> {
+    #[error("Error sending GraphQL request: {0}")]
+    TransportError(E),
+    #[error("Error decoding GraphQL response: {0}")]
+    DecodeError(#[from] json_decode::DecodeError),
+    #[error("Error serializing GraphQL variables: {0}")]
+    SerializeError(#[from] serde_json::Error),
+    #[error("Error in middleware: {0}")]
+    MiddlewareError(#[from] MiddlewareError),
+    #[error("The server responded with HTTP status {status}")]
+    HttpError { status: u16, body: String },
+    #[error("The server did not send a valid GraphQL response: {0}")]
+    InvalidResponse(serde_json::Error),
+    /// This is only returned by transports that support `graphql-sse`
+    /// subscriptions.
+    #[error("Error in graphql-sse subscription: {0}")]
+    SseError(#[from] crate::graphql_sse::ProtocolError),
+}
+
+/// Errors from the transport classify themselves, so that a policy retries
+/// timeouts but not e.g. a request that couldn't be built.
+impl<E> crate::retry::RetryableError for ClientError<E>
+where
+    E: std::fmt::Display + std::fmt::Debug + crate::retry::RetryableError,
+{
+    fn http_status(&self) -> Option<u16> {
+        match self {
+            ClientError::HttpError { status, .. } => Some(*status),
+            ClientError::TransportError(error) => error.http_status(),
+            _ => None,
+        }
+    }
+
+    fn is_transport_error(&self) -> bool {
+        match self {
+            ClientError::TransportError(error) => error.is_transport_error(),
+            _ => false,
+        }
+    }
+}
+
+/// A transport that can send GraphQL requests to a server.
+pub trait GraphQlClient: Sync {
+    /// The errors the transport can fail with.
+    type Error: std::fmt::Display + std::fmt::Debug + Send;
+
+    /// Sends `request` to the server & returns its response.
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Self::Error>>;
+
+    /// Runs an operation & decodes the response.
+    fn run_graphql<'c, 'a: 'c, ResponseData: 'a>(
+        &'c self,
+        operation: Operation<'a, ResponseData>,
+    ) -> BoxFuture<'c, Result<GraphQLResponse<ResponseData>, ClientError<Self::Error>>> {
+        self.run_graphql_with_middleware(operation, &())
+    }
+
+    /// Runs an operation & decodes the response, calling the hooks in
+    /// `middleware` around the request.
+    fn run_graphql_with_middleware<'c, 'a: 'c, ResponseData: 'a>(
+        &'c self,
+        operation: Operation<'a, ResponseData>,
+        middleware: &'c dyn Middleware,
+    ) -> BoxFuture<'c, Result<GraphQLResponse<ResponseData>, ClientError<Self::Error>>> {
+        Box::pin(run_operation(operation, middleware, move |request| {
+            self.send(request)
+        }))
+    }
+}
+
+/// A transport whose futures can't be sent between threads - such as the
+/// browser `fetch` API when compiling to WASM.
+///
+/// This works exactly like `GraphQlClient` otherwise.
+pub trait LocalGraphQlClient {
+    /// The errors the transport can fail with.
+    type Error: std::fmt::Display + std::fmt::Debug;
+
+    /// Sends `request` to the server & returns its response.
+    fn send(&self, request: Request) -> LocalBoxFuture<'_, Result<Response, Self::Error>>;
+
+    /// Runs an operation & decodes the response.
+    fn run_graphql<'c, 'a: 'c, ResponseData: 'a>(
+        &'c self,
+        operation: Operation<'a, ResponseData>,
+    ) -> LocalBoxFuture<'c, Result<GraphQLResponse<ResponseData>, ClientError<Self::Error>>> {
+        self.run_graphql_with_middleware(operation, &())
+    }
+
+    /// Runs an operation & decodes the response, calling the hooks in
+    /// `middleware` around the request.
+    fn run_graphql_with_middleware<'c, 'a: 'c, ResponseData: 'a>(
+        &'c self,
+        operation: Operation<'a, ResponseData>,
+        middleware: &'c dyn Middleware,
+    ) -> LocalBoxFuture<'c, Result<GraphQLResponse<ResponseData>, ClientError<Self::Error>>> {
+        Box::pin(run_operation(operation, middleware, move |request| {
+            self.send(request)
+        }))
+    }
+}
+
+/// Runs an operation, using `send` to send its request.
+///
+/// The returned future is `Send` whenever `send`'s is, so this is shared by
+/// `GraphQlClient` & `LocalGraphQlClient`.
+fn run_operation<'c, 'a: 'c, ResponseData: 'a, E, F>(
+    operation: Operation<'a, ResponseData>,
+    middleware: &'c dyn Middleware,
+    send: impl FnOnce(Request) -> F + 'c,
+) -> impl Future<Output = Result<GraphQLResponse<ResponseData>, ClientError<E>>> + 'c
+where
+    E: std::fmt::Display + std::fmt::Debug + 'c,
+    F: Future<Output = Result<Response, E>> + 'c,
+{
+    let span = crate::logging::operation_span(&operation);
+    crate::logging::instrument(span, async move {
+        crate::logging::log_operation(&operation);
+
+        let request = crate::logging::serializing(|| request_for::<_, E>(&operation, middleware))?;
+        let response = send(request).await.map_err(ClientError::TransportError)?;
+
+        let body = handle_response::<_, E>(&operation, &response, middleware)?;
+        Ok(decode_response(&operation, body)?)
+    })
+}
+
+/// Builds the request that runs `operation`.
+///
+/// Operations that contain uploads are sent as `multipart/form-data`.  With
+/// the `compression` feature, any other bodies are gzipped if they're larger
+/// than the operation's compression threshold.
+pub fn request_for<ResponseData, E: std::fmt::Display + std::fmt::Debug>(
+    operation: &Operation<'_, ResponseData>,
+    middleware: &dyn Middleware,
+) -> Result<Request, ClientError<E>> {
+    let mut headers = operation_headers(operation, middleware)?;
+
+    if let Some(multipart) = crate::upload::MultipartRequest::for_operation(operation)? {
+        let (content_type, body) = multipart.to_form_data()?;
+        headers.insert(0, ("Content-Type".to_string(), content_type));
+
+        return Ok(Request { headers, body });
+    }
+
+    headers.insert(
+        0,
+        ("Content-Type".to_string(), "application/json".to_string()),
+    );
+    let body = serde_json::to_vec(operation)?;
+
+    #[cfg(feature = "compression")]
+    let body = match operation.compression_threshold {
+        Some(threshold) if body.len() > threshold => {
+            headers.insert(1, ("Content-Encoding".to_string(), "gzip".to_string()));
+            gzip(&body)
+        }
+        _ => body,
+    };
+
+    Ok(Request { headers, body })
+}
+
+/// Builds the request that runs every operation in `batch`.
+///
+/// The headers of each operation are sent once, and with the `compression`
+/// feature the body is gzipped if it's larger than the lowest compression
+/// threshold of any operation in the batch.
+pub fn batch_request_for<E: std::fmt::Display + std::fmt::Debug>(
+    batch: &BatchOperation,
+    middleware: &dyn Middleware,
+) -> Result<Request, ClientError<E>> {
+    let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+    for operation in batch.operations() {
+        for header in operation_headers(operation, middleware)? {
+            if !headers.contains(&header) {
+                headers.push(header);
+            }
+        }
+    }
+    let body = serde_json::to_vec(batch)?;
+
+    #[cfg(feature = "compression")]
+    let threshold = batch
+        .operations()
+        .iter()
+        .filter_map(|operation| operation.compression_threshold)
+        .min();
+    #[cfg(feature = "compression")]
+    let body = match threshold {
+        Some(threshold) if body.len() > threshold => {
+            headers.insert(1, ("Content-Encoding".to_string(), "gzip".to_string()));
+            gzip(&body)
+        }
+        _ => body,
+    };
+
+    Ok(Request { headers, body })
+}
+
+/// The headers to send with `operation`, other than those describing the
+/// body.
+pub(crate) fn operation_headers<ResponseData>(
+    operation: &Operation<'_, ResponseData>,
+    middleware: &dyn Middleware,
+) -> Result<Vec<(String, String)>, MiddlewareError> {
+    let mut headers = vec![];
+    if let Some(key) = &operation.idempotency_key {
+        headers.push(("Idempotency-Key".to_string(), key.clone()));
+    }
+    headers.extend(middleware::request_headers(middleware, operation)?);
+
+    Ok(headers)
+}
+
+/// Parses the body of a response.
+///
+/// Bodies that aren't GraphQL responses are reported as an `HttpError` if the
+/// status was unsuccessful - proxies often send an HTML page with a 502, for
+/// example.
+pub fn parse_response<E: std::fmt::Display + std::fmt::Debug>(
+    response: &Response,
+) -> Result<GraphQLResponse<serde_json::Value>, ClientError<E>> {
+    parse_body(response)
+}
+
+/// Parses the body of a response to a batch, as with `parse_response`.
+pub fn parse_batch_response<E: std::fmt::Display + std::fmt::Debug>(
+    response: &Response,
+) -> Result<BatchResponse, ClientError<E>> {
+    parse_body(response)
+}
+
+fn parse_body<T: serde::de::DeserializeOwned, E: std::fmt::Display + std::fmt::Debug>(
+    response: &Response,
+) -> Result<T, ClientError<E>> {
+    match serde_json::from_slice(&response.body) {
+        Ok(body) => Ok(body),
+        Err(_) if !(200..300).contains(&response.status) => Err(ClientError::HttpError {
+            status: response.status,
+            body: String::from_utf8_lossy(&response.body).into_owned(),
+        }),
+        Err(e) => Err(ClientError::InvalidResponse(e)),
+    }
+}
+
+/// Records the status of `response`, parses its body & passes it to
+/// `middleware`.
+pub(crate) fn handle_response<ResponseData, E: std::fmt::Display + std::fmt::Debug>(
+    operation: &Operation<'_, ResponseData>,
+    response: &Response,
+    middleware: &dyn Middleware,
+) -> Result<GraphQLResponse<serde_json::Value>, ClientError<E>> {
+    crate::logging::record_status(response.status);
+
+    let body = parse_response(response)?;
+    middleware::inspect_response(
+        middleware,
+        operation,
+        response.status,
+        &response.headers,
+        &body,
+    );
+
+    Ok(body)
+}
+
+/// Records the status of a response to `batch`, parses its body & passes
+/// the response to each operation to `middleware`.
+pub(crate) fn handle_batch_response<E: std::fmt::Display + std::fmt::Debug>(
+    batch: &BatchOperation,
+    response: &Response,
+    middleware: &dyn Middleware,
+) -> Result<BatchResponse, ClientError<E>> {
+    crate::logging::record_status(response.status);
+
+    let body = parse_batch_response(response)?;
+    for (operation, operation_response) in batch.operations().iter().zip(body.responses()) {
+        if let Some(operation_response) = operation_response {
+            middleware::inspect_response(
+                middleware,
+                operation,
+                response.status,
+                &response.headers,
+                operation_response,
+            );
+        }
+    }
+
+    Ok(body)
+}
+
+/// Decodes a response, logging it first if any logging features are enabled.
+pub(crate) fn decode_response<ResponseData>(
+    operation: &Operation<'_, ResponseData>,
+    response: GraphQLResponse<serde_json::Value>,
+) -> Result<GraphQLResponse<ResponseData>, json_decode::DecodeError> {
+    crate::logging::log_response(operation, &response);
+    crate::logging::decoding(response, |response| operation.decode_response(response))
+}
+
+/// Compresses a request body with gzip.
+#[cfg(feature = "compression")]
+fn gzip(body: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    // Writing into a Vec can't fail.
+    encoder.write_all(body).expect("Failed to compress body");
+    encoder.finish().expect("Failed to compress body")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+    use std::{
+        sync::Mutex,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    use crate::testing::fixtures::query;
+
+    struct FakeClient {
+        response: Result<Response, String>,
+        requests: Mutex<Vec<Request>>,
+    }
+
+    impl FakeClient {
+        fn new(status: u16, body: &str) -> Self {
+            FakeClient {
+                response: Ok(Response {
+                    status,
+                    headers: vec![],
+                    body: body.as_bytes().to_vec(),
+                }),
+                requests: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    impl GraphQlClient for FakeClient {
+        type Error = String;
+
+        fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, String>> {
+            self.requests.lock().unwrap().push(request);
+            Box::pin(std::future::ready(self.response.clone()))
+        }
+    }
+
+    fn block_on<T>(mut future: LocalBoxFuture<'_, T>) -> T {
+        fn noop_waker() -> Waker {
+            fn clone(_: *const ()) -> RawWaker {
+                RawWaker::new(std::ptr::null(), &VTABLE)
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+            unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+        }
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("FakeClient futures should always be ready"),
+        }
+    }
+
+    #[test]
+    fn test_running_operations() {
+        let client = FakeClient::new(200, r#"{"data": {"film": "A New Hope"}}"#);
+
+        let response = block_on(client.run_graphql(query("film", vec![]))).unwrap();
+        assert_eq!(response.data, Some("A New Hope".to_string()));
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(
+            requests[0].headers,
+            vec![("Content-Type".to_string(), "application/json".to_string())]
+        );
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&requests[0].body).unwrap(),
+            serde_json::to_value(&query("film", vec![])).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_local_clients() {
+        // Rc isn't Sync, so this couldn't be a GraphQlClient.
+        struct LocalClient(std::rc::Rc<Response>);
+
+        impl LocalGraphQlClient for LocalClient {
+            type Error = String;
+
+            fn send(&self, _: Request) -> LocalBoxFuture<'_, Result<Response, String>> {
+                Box::pin(std::future::ready(Ok((*self.0).clone())))
+            }
+        }
+
+        let client = LocalClient(std::rc::Rc::new(Response {
+            status: 502,
+            headers: vec![],
+            body: b"<html>Bad Gateway</html>".to_vec(),
+        }));
+        assert_matches!(
+            block_on(LocalGraphQlClient::run_graphql(
+                &client,
+                query("film", vec![])
+            )),
+            Err(ClientError::HttpError { status: 502, .. })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let client = FakeClient::new(502, "<html>Bad Gateway</html>");
+        assert_matches!(
+            block_on(client.run_graphql(query("film", vec![]))),
+            Err(ClientError::HttpError { status: 502, body }) => {
+                assert_eq!(body, "<html>Bad Gateway</html>")
+            }
+        );
+
+        let client = FakeClient::new(200, "not json");
+        assert_matches!(
+            block_on(client.run_graphql(query("film", vec![]))),
+            Err(ClientError::InvalidResponse(_))
+        );
+
+        let client = FakeClient::new(200, r#"{"data": {"film": 1}}"#);
+        assert_matches!(
+            block_on(client.run_graphql(query("film", vec![]))),
+            Err(ClientError::DecodeError(_))
+        );
+
+        let client = FakeClient {
+            response: Err("connection refused".into()),
+            requests: Mutex::new(vec![]),
+        };
+        assert_matches!(
+            block_on(client.run_graphql(query("film", vec![]))),
+            Err(ClientError::TransportError(e)) => assert_eq!(e, "connection refused")
+        );
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        use crate::retry::{RetryPolicy, RetryableError};
+
+        #[derive(Debug)]
+        struct TransportError(Option<u16>);
+
+        impl std::fmt::Display for TransportError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "transport error")
+            }
+        }
+
+        impl RetryableError for TransportError {
+            fn http_status(&self) -> Option<u16> {
+                self.0
+            }
+
+            fn is_transport_error(&self) -> bool {
+                self.0.is_none()
+            }
+        }
+
+        let policy = RetryPolicy::new(3);
+        let error = ClientError::TransportError;
+
+        assert!(policy.is_retryable(&error(TransportError(None))));
+        assert!(policy.is_retryable(&error(TransportError(Some(503)))));
+        assert!(!policy.is_retryable(&error(TransportError(Some(400)))));
+        assert!(
+            policy.is_retryable(&ClientError::<TransportError>::HttpError {
+                status: 502,
+                body: String::new(),
+            })
+        );
+        assert!(
+            !policy.is_retryable(&ClientError::<TransportError>::InvalidResponse(
+                serde_json::from_str::<()>("").unwrap_err()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_graphql_errors_with_unsuccessful_statuses() {
+        let response = Response {
+            status: 400,
+            headers: vec![],
+            body: json!({ "errors": [{ "message": "oops" }] })
+                .to_string()
+                .into_bytes(),
+        };
+
+        assert_matches!(
+            parse_response::<String>(&response),
+            Ok(GraphQLResponse { errors: Some(errors), .. }) => {
+                assert_eq!(errors[0].message, "oops")
+            }
+        );
+    }
+
+    #[test]
+    fn test_batches() {
+        let mut batch = BatchOperation::new();
+        batch.add(query("film", vec![]).with_idempotency_key("abc"));
+        batch.add(query("film", vec![]));
+
+        let auth = middleware::before_request(|request| {
+            request.add_header("Authorization", "Bearer token");
+            Ok(())
+        });
+        let request = batch_request_for::<String>(&batch, &auth).unwrap();
+        assert_eq!(
+            request.headers,
+            vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("Idempotency-Key".to_string(), "abc".to_string()),
+                ("Authorization".to_string(), "Bearer token".to_string()),
+            ]
+        );
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&request.body).unwrap(),
+            serde_json::to_value(&batch).unwrap()
+        );
+
+        let response = Response {
+            status: 502,
+            headers: vec![],
+            body: b"<html>Bad Gateway</html>".to_vec(),
+        };
+        assert_matches!(
+            parse_batch_response::<String>(&response),
+            Err(ClientError::HttpError { status: 502, .. })
+        );
+    }
+
+    #[test]
+    fn test_requests_with_uploads() {
+        use crate::{testing::fixtures::mutation, upload::Upload, Argument};
+
+        let operation = mutation(
+            "upload",
+            vec![Argument::new(
+                "file",
+                "Upload!",
+                Upload::new("a.txt", "hello"),
+            )],
+        )
+        .with_idempotency_key("abc");
+
+        let request = request_for::<_, String>(&operation, &()).unwrap();
+        assert_eq!(request.headers.len(), 2);
+        assert!(request.headers[0]
+            .1
+            .starts_with("multipart/form-data; boundary="));
+        assert_eq!(
+            request.headers[1],
+            ("Idempotency-Key".to_string(), "abc".to_string())
+        );
+        assert!(String::from_utf8_lossy(&request.body).contains("filename=\"a.txt\""));
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compressed_requests() {
+        use std::io::Read;
+
+        let operation = query("film", vec![]);
+        let body = serde_json::to_vec(&operation).unwrap();
+
+        let request =
+            request_for::<_, String>(&operation.clone().compress_above(body.len()), &()).unwrap();
+        assert_eq!(request.body, body);
+
+        let request =
+            request_for::<_, String>(&operation.compress_above(body.len() - 1), &()).unwrap();
+        assert_eq!(
+            request.headers[1],
+            ("Content-Encoding".to_string(), "gzip".to_string())
+        );
+
+        let mut decompressed = vec![];
+        flate2::read::GzDecoder::new(request.body.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, body);
+    }
+}
@@ -0,0 +1,653 @@
+//! Utilities for debugging responses that don't decode the way you expect.
+//!
+//! `render_response` walks the selections of a query alongside the JSON a
+//! server returned, and renders them side by side:
+//!
+//! ```text
+//! film                                    {object}
+//!   title                                 "A New Hope"
+//!   director                              <missing>  <-- expected by query
+//!   producers                             [2 items]
+//! ```
+//!
+//! `Operation::debug_response` does the same for an operation, and also
+//! includes any GraphQL or decode errors.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The column that values are rendered at.
+const VALUE_COLUMN: usize = 40;
+
+/// The maximum length of any JSON snippets we output.
+const MAX_SNIPPET_LENGTH: usize = 60;
+
+/// Renders the selections in `query` alongside the values found for them in
+/// `data`.
+///
+/// If `query` contains several operations, `operation_name` can be used to
+/// pick one, otherwise the first operation is used.
+pub fn render_response(query: &str, operation_name: Option<&str>, data: &Value) -> String {
+    let selections = parse_operation(query, operation_name);
+
+    let mut output = String::new();
+    render_selections(&selections, data, 0, &mut output);
+    output
+}
+
+#[derive(Debug, PartialEq)]
+enum Selection {
+    Field {
+        response_key: String,
+        children: Vec<Selection>,
+    },
+    Fragment {
+        label: String,
+        type_condition: Option<String>,
+        children: Vec<Selection>,
+    },
+    UnknownSpread {
+        label: String,
+    },
+}
+
+fn render_selections(selections: &[Selection], value: &Value, depth: usize, output: &mut String) {
+    for selection in selections {
+        match selection {
+            Selection::Field {
+                response_key,
+                children,
+            } => render_field(
+                response_key,
+                children,
+                value.get(response_key),
+                depth,
+                output,
+            ),
+            Selection::Fragment {
+                label,
+                type_condition,
+                children,
+            } => match value.get("__typename").and_then(Value::as_str) {
+                Some(typename) if type_condition.as_deref().map_or(false, |c| c != typename) => {
+                    line(
+                        output,
+                        depth,
+                        label,
+                        &format!("(skipped: __typename is {})", typename),
+                    )
+                }
+                _ => {
+                    line(output, depth, label, "");
+                    render_selections(children, value, depth + 1, output);
+                }
+            },
+            Selection::UnknownSpread { label } => line(
+                output,
+                depth,
+                label,
+                "<missing>  <-- no fragment definition",
+            ),
+        }
+    }
+}
+
+fn render_field(
+    key: &str,
+    children: &[Selection],
+    value: Option<&Value>,
+    depth: usize,
+    output: &mut String,
+) {
+    let value = match value {
+        Some(value) => value,
+        None => return line(output, depth, key, "<missing>  <-- expected by query"),
+    };
+
+    if children.is_empty() {
+        return match value {
+            Value::Object(_) | Value::Array(_) if !is_scalar_list(value) => line(
+                output,
+                depth,
+                key,
+                &format!("{}  <-- expected a scalar", snippet(value)),
+            ),
+            _ => line(output, depth, key, &snippet(value)),
+        };
+    }
+
+    match value {
+        Value::Null => line(output, depth, key, "null"),
+        Value::Object(_) => {
+            line(output, depth, key, "{object}");
+            render_selections(children, value, depth + 1, output);
+        }
+        Value::Array(items) => {
+            line(output, depth, key, &format!("[{} items]", items.len()));
+            if let Some(first) = items.iter().find(|item| !item.is_null()) {
+                line(output, depth + 1, "(first item)", "");
+                render_selections(children, first, depth + 2, output);
+            }
+        }
+        _ => line(
+            output,
+            depth,
+            key,
+            &format!("{}  <-- expected an object", snippet(value)),
+        ),
+    }
+}
+
+fn is_scalar_list(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.iter().all(|item| match item {
+            Value::Object(_) => false,
+            Value::Array(_) => is_scalar_list(item),
+            _ => true,
+        }),
+        _ => false,
+    }
+}
+
+fn line(output: &mut String, depth: usize, label: &str, description: &str) {
+    let label = format!("{:indent$}{}", "", label, indent = depth * 2);
+    let line = format!("{:width$}{}", label, description, width = VALUE_COLUMN);
+    output.push_str(line.trim_end());
+    output.push('\n');
+}
+
+fn snippet(value: &Value) -> String {
+    let json = value.to_string();
+    if json.chars().count() <= MAX_SNIPPET_LENGTH {
+        return json;
+    }
+
+    let truncated = json
+        .chars()
+        .take(MAX_SNIPPET_LENGTH - 3)
+        .collect::<String>();
+    format!("{}...", truncated)
+}
+
+/// Parses the selections out of a query document.
+///
+/// This only understands the subset of GraphQL that cynic generates, which
+/// is enough for debugging output.  Any fragment spreads are replaced with
+/// the selections of the matching fragment definition.
+fn parse_operation(query: &str, operation_name: Option<&str>) -> Vec<Selection> {
+    let tokens = tokenize(query);
+
+    let mut parser = Parser {
+        tokens: &tokens,
+        fragments: HashMap::new(),
+    };
+    let mut operations = vec![];
+
+    let mut position = 0;
+    while position < tokens.len() {
+        match tokens[position] {
+            Token::Name("fragment") => {
+                if let (Some(Token::Name(name)), Some(Token::Name(type_condition))) =
+                    (tokens.get(position + 1), tokens.get(position + 3))
+                {
+                    if let Some(start) = parser.selection_set_start(position) {
+                        parser.fragments.insert(*name, (*type_condition, start));
+                    }
+                }
+            }
+            ref token if is_operation_keyword(token) || *token == Token::OpenBrace => {
+                let name = match (token, tokens.get(position + 1)) {
+                    (Token::Name(_), Some(Token::Name(name))) => Some(*name),
+                    _ => None,
+                };
+                if let Some(start) = parser.selection_set_start(position) {
+                    operations.push((name, start));
+                }
+            }
+            _ => {}
+        }
+
+        position = match parser.selection_set_start(position) {
+            Some(start) => parser.selection_set_end(start),
+            None => break,
+        };
+    }
+
+    let operation = match operation_name {
+        Some(operation_name) => operations
+            .iter()
+            .find(|(name, _)| *name == Some(operation_name))
+            .or_else(|| operations.first()),
+        None => operations.first(),
+    };
+
+    match operation {
+        Some((_, start)) => parser.parse_selections(&mut { *start }, &mut vec![]),
+        None => vec![],
+    }
+}
+
+fn is_operation_keyword(token: &Token) -> bool {
+    match token {
+        Token::Name(name) => *name == "query" || *name == "mutation" || *name == "subscription",
+        _ => false,
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token<'a>],
+
+    /// The type condition of each fragment definition in the document, &
+    /// the position of the first token inside its selection set.
+    fragments: HashMap<&'a str, (&'a str, usize)>,
+}
+
+impl<'a> Parser<'a> {
+    /// Finds the position just after the next opening brace.
+    fn selection_set_start(&self, position: usize) -> Option<usize> {
+        self.tokens[position..]
+            .iter()
+            .position(|t| *t == Token::OpenBrace)
+            .map(|index| position + index + 1)
+    }
+
+    /// Finds the position just after the brace that closes the selection set
+    /// starting at `start`.
+    fn selection_set_end(&self, start: usize) -> usize {
+        let mut depth = 1;
+        let mut position = start;
+        while depth > 0 && position < self.tokens.len() {
+            match self.tokens[position] {
+                Token::OpenBrace => depth += 1,
+                Token::CloseBrace => depth -= 1,
+                _ => {}
+            }
+            position += 1;
+        }
+        position
+    }
+
+    fn parse_selections(&self, position: &mut usize, spreads: &mut Vec<&'a str>) -> Vec<Selection> {
+        let mut selections = vec![];
+
+        while let Some(token) = self.tokens.get(*position) {
+            *position += 1;
+            match token {
+                Token::CloseBrace => break,
+                Token::Spread => {
+                    let type_condition = match self.tokens.get(*position) {
+                        Some(Token::Name("on")) => match self.tokens.get(*position + 1) {
+                            Some(Token::Name(name)) => {
+                                *position += 2;
+                                Some(name.to_string())
+                            }
+                            _ => break,
+                        },
+                        Some(Token::Name(name)) => {
+                            *position += 1;
+                            self.skip_directives(position);
+                            selections.push(self.parse_spread(*name, spreads));
+                            continue;
+                        }
+                        // An inline fragment without a type condition, which
+                        // must have some directives.
+                        _ => None,
+                    };
+
+                    let mut label = String::from("...");
+                    if let Some(type_condition) = &type_condition {
+                        label.push_str(" on ");
+                        label.push_str(type_condition);
+                    }
+                    for directive in self.skip_directives(position) {
+                        label.push_str(" @");
+                        label.push_str(directive);
+                    }
+
+                    if self.tokens.get(*position) != Some(&Token::OpenBrace) {
+                        break;
+                    }
+                    *position += 1;
+
+                    selections.push(Selection::Fragment {
+                        label,
+                        type_condition,
+                        children: self.parse_selections(position, spreads),
+                    });
+                }
+                Token::Name(name) => {
+                    if self.tokens.get(*position) == Some(&Token::Colon) {
+                        // This is an aliased field, so `name` is the alias &
+                        // we can skip past the actual field name.
+                        *position += 2;
+                    }
+                    self.skip_directives(position);
+
+                    let mut children = vec![];
+                    if self.tokens.get(*position) == Some(&Token::OpenBrace) {
+                        *position += 1;
+                        children = self.parse_selections(position, spreads);
+                    }
+
+                    selections.push(Selection::Field {
+                        response_key: name.to_string(),
+                        children,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        selections
+    }
+
+    /// Skips past any directives at `position`, returning their names.
+    fn skip_directives(&self, position: &mut usize) -> Vec<&'a str> {
+        let mut directives = vec![];
+        while let Some(Token::Directive(name)) = self.tokens.get(*position) {
+            directives.push(*name);
+            *position += 1;
+        }
+        directives
+    }
+
+    fn parse_spread(&self, name: &'a str, spreads: &mut Vec<&'a str>) -> Selection {
+        let label = format!("...{}", name);
+
+        let (type_condition, start) = match self.fragments.get(name) {
+            // Fragments can't contain themselves, but we'd rather not
+            // overflow the stack on a broken document.
+            Some(fragment) if !spreads.contains(&name) => *fragment,
+            _ => {
+                return Selection::UnknownSpread { label };
+            }
+        };
+
+        spreads.push(name);
+        let children = self.parse_selections(&mut { start }, spreads);
+        spreads.pop();
+
+        Selection::Fragment {
+            label: format!("{} on {}", label, type_condition),
+            type_condition: Some(type_condition.to_string()),
+            children,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Name(&'a str),
+    OpenBrace,
+    CloseBrace,
+    Colon,
+    Spread,
+    Directive(&'a str),
+}
+
+fn tokenize(query: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::OpenBrace),
+            '}' => tokens.push(Token::CloseBrace),
+            ':' => tokens.push(Token::Colon),
+            '.' => {
+                // Skip the other two dots of the spread
+                chars.next();
+                chars.next();
+                tokens.push(Token::Spread);
+            }
+            '(' => {
+                // Arguments are only ever variables or string labels, so we
+                // can skip straight to the closing paren.
+                let mut in_string = false;
+                for (_, c) in &mut chars {
+                    match c {
+                        '"' => in_string = !in_string,
+                        ')' if !in_string => break,
+                        _ => {}
+                    }
+                }
+            }
+            '@' => tokens.push(Token::Directive(take_name(
+                query,
+                start + 1,
+                start + 1,
+                &mut chars,
+            ))),
+            c if c.is_alphanumeric() || c == '_' => tokens.push(Token::Name(take_name(
+                query,
+                start,
+                start + c.len_utf8(),
+                &mut chars,
+            ))),
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+/// Consumes the rest of a name that starts at `start`, and has been read up
+/// to `end` so far.
+fn take_name<'a>(
+    query: &'a str,
+    start: usize,
+    mut end: usize,
+    chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
+) -> &'a str {
+    while let Some((i, c)) = chars.peek() {
+        if !(c.is_alphanumeric() || *c == '_') {
+            break;
+        }
+        end = i + c.len_utf8();
+        chars.next();
+    }
+    &query[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const QUERY: &str = r#"query Query($_0: ID) {
+  film(id: $_0) {
+    title
+    director
+    producers
+    characters {
+      name
+    }
+    ... on Film {
+      episodeID
+    }
+  }
+}
+"#;
+
+    #[test]
+    fn test_parsing_selections() {
+        let field = |name: &str, children| Selection::Field {
+            response_key: name.to_string(),
+            children,
+        };
+
+        assert_eq!(
+            parse_operation(QUERY, None),
+            vec![field(
+                "film",
+                vec![
+                    field("title", vec![]),
+                    field("director", vec![]),
+                    field("producers", vec![]),
+                    field("characters", vec![field("name", vec![])]),
+                    Selection::Fragment {
+                        label: "... on Film".into(),
+                        type_condition: Some("Film".into()),
+                        children: vec![field("episodeID", vec![])]
+                    }
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_rendering_a_response() {
+        let data = json!({
+            "film": {
+                "__typename": "Film",
+                "title": "A New Hope",
+                "producers": ["Gary Kurtz", "Rick McCallum"],
+                "characters": [{"name": "Luke"}, {"name": "Leia"}],
+                "episodeID": 4
+            }
+        });
+
+        assert_eq!(
+            render_response(QUERY, None, &data),
+            r#"film                                    {object}
+  title                                 "A New Hope"
+  director                              <missing>  <-- expected by query
+  producers                             ["Gary Kurtz","Rick McCallum"]
+  characters                            [2 items]
+    (first item)
+      name                              "Luke"
+  ... on Film
+    episodeID                           4
+"#
+        );
+    }
+
+    #[test]
+    fn test_rendering_named_fragments() {
+        let query = r#"query Query($_0: String) {
+  film {
+    ...FilmFields
+  }
+  sequel {
+    ...FilmFields
+  }
+}
+fragment FilmFields on Film {
+  title(lang: $_0)
+  director
+}
+"#;
+        let data = json!({
+            "film": {"title": "A New Hope", "director": "George Lucas"},
+            "sequel": {"__typename": "Person", "name": "Luke"}
+        });
+
+        assert_eq!(
+            render_response(query, None, &data),
+            r#"film                                    {object}
+  ...FilmFields on Film
+    title                               "A New Hope"
+    director                            "George Lucas"
+sequel                                  {object}
+  ...FilmFields on Film                 (skipped: __typename is Person)
+"#
+        );
+    }
+
+    #[test]
+    fn test_fragment_definitions_before_the_operation() {
+        let query = "fragment F on Film { title ...Unknown } query Q { film { ...F } }";
+
+        assert_eq!(
+            render_response(query, None, &json!({"film": {"title": "Jaws"}})),
+            r#"film                                    {object}
+  ...F on Film
+    title                               "Jaws"
+    ...Unknown                          <missing>  <-- no fragment definition
+"#
+        );
+    }
+
+    #[test]
+    fn test_rendering_conditional_fragments() {
+        let query = r#"query Query($_0: Boolean!, $_1: Boolean!) {
+  film {
+    ... @include(if: $_0) {
+      director
+    }
+    ... on Film @skip(if: $_1) {
+      openingCrawl
+    }
+    title
+  }
+}
+"#;
+        let data = json!({"film": {"director": "George Lucas", "title": "A New Hope"}});
+
+        assert_eq!(
+            render_response(query, None, &data),
+            r#"film                                    {object}
+  ... @include
+    director                            "George Lucas"
+  ... on Film @skip
+    openingCrawl                        <missing>  <-- expected by query
+  title                                 "A New Hope"
+"#
+        );
+    }
+
+    #[test]
+    fn test_rendering_incremental_delivery() {
+        let query = r#"query Query($_0: Int) {
+  film {
+    title
+    ... @defer(label: "details (all of them)") {
+      director
+      characters(first: 5) @stream(initialCount: $_0) {
+        name
+      }
+    }
+    ... @defer {
+      openingCrawl
+    }
+    firstPlanets: planets @stream(initialCount: 0)
+  }
+}
+"#;
+        // Fragments without a type condition apply whatever the __typename.
+        let data = json!({
+            "film": {
+                "__typename": "Film",
+                "title": "A New Hope",
+                "director": "George Lucas",
+                "characters": [{"name": "Luke"}],
+                "firstPlanets": ["Tatooine"]
+            }
+        });
+
+        assert_eq!(
+            render_response(query, None, &data),
+            r#"film                                    {object}
+  title                                 "A New Hope"
+  ... @defer
+    director                            "George Lucas"
+    characters                          [1 items]
+      (first item)
+        name                            "Luke"
+  ... @defer
+    openingCrawl                        <missing>  <-- expected by query
+  firstPlanets                          ["Tatooine"]
+"#
+        );
+    }
+
+    #[test]
+    fn test_rendering_with_operation_name() {
+        let query = "query One { one } query Two { two }";
+
+        assert_eq!(
+            render_response(query, Some("Two"), &json!({"two": 2})),
+            "two                                     2\n"
+        );
+    }
+}
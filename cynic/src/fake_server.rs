@@ -0,0 +1,878 @@
+//! An in-process fake of a GraphQL server, for testing.
+//!
+//! A `FakeServer` is built from a schema, and can respond to any operation
+//! that's valid against that schema with fake data of the right shape.  This
+//! makes it possible to run end to end tests of code that uses cynic without
+//! a real server:
+//!
+//! ```rust
+//! # use cynic::fake_server::FakeServer;
+//! let server = FakeServer::new(
+//!     r#"
+//!     type Query { films: [Film!]! }
+//!     type Film { title: String, releaseDate: Date }
+//!     scalar Date
+//!     "#,
+//! )
+//! .unwrap()
+//! .with_generator("Date", |_| serde_json::json!("1977-05-25"));
+//!
+//! let response = server.respond_to("query { films { title releaseDate } }", None);
+//! # assert!(response.errors.is_none());
+//! ```
+//!
+//! Responses can be decoded with `Operation::decode_response`, or
+//! `FakeServer::run` can be used to do that directly.
+//!
+//! The data is deterministic: `Int`s are `1`, `Float`s are `1.5`,
+//! `Boolean`s are `true` and `String`s & `ID`s are the name of the field
+//! they're in.  Enums use their first value and custom scalars are treated
+//! as strings.  Objects with an abstract type are resolved as the first type
+//! that implements them.  Any of this can be overridden for a particular
+//! scalar or enum with `FakeServer::with_generator`.
+//!
+//! The variables of operations are checked against the schema the same way
+//! a real server would check them, so responses contain an error if an
+//! argument didn't serialize to something the server would accept.
+
+use std::collections::HashMap;
+
+use graphql_parser::{
+    query::{self, Definition, OperationDefinition, Selection, SelectionSet, TypeCondition},
+    schema,
+};
+use serde_json::Value;
+
+use crate::{GraphQLError, GraphQLResponse, Operation};
+
+#[derive(thiserror::Error, Debug)]
+pub enum FakeServerError {
+    #[error("Could not parse the schema: {0}")]
+    InvalidSchema(#[from] schema::ParseError),
+}
+
+/// The context a generator is called in.
+#[derive(Debug, Clone, Copy)]
+pub struct FakeContext<'a> {
+    /// The name of the type being generated.
+    pub type_name: &'a str,
+    /// The name of the field the value is for.
+    pub field_name: &'a str,
+    /// The index of the value in the list that contains it, if any.
+    pub list_index: Option<usize>,
+}
+
+type Generator = Box<dyn Fn(&FakeContext<'_>) -> Value + Send + Sync>;
+
+/// A fake GraphQL server that responds to operations with generated data.
+pub struct FakeServer {
+    query_type: String,
+    mutation_type: String,
+    types: HashMap<String, FakeType>,
+    generators: HashMap<String, Generator>,
+    list_length: usize,
+}
+
+#[derive(Debug)]
+enum FakeType {
+    Scalar,
+    Enum(Vec<String>),
+    Object {
+        fields: HashMap<String, TypeRef>,
+        interfaces: Vec<String>,
+    },
+    Interface,
+    Union(Vec<String>),
+    InputObject {
+        fields: HashMap<String, InputField>,
+        one_of: bool,
+    },
+}
+
+#[derive(Debug)]
+struct InputField {
+    field_type: TypeRef,
+    has_default: bool,
+}
+
+#[derive(Debug, Clone)]
+enum TypeRef {
+    Named(String),
+    List(Box<TypeRef>),
+    NonNull(Box<TypeRef>),
+}
+
+impl FakeServer {
+    /// Creates a FakeServer for the schema in `schema`, which should be
+    /// in the GraphQL schema definition language.
+    pub fn new(schema: &str) -> Result<Self, FakeServerError> {
+        use schema::{Definition, TypeDefinition};
+
+        let document = graphql_parser::parse_schema::<String>(schema)?;
+
+        let mut query_type = "Query".to_string();
+        let mut mutation_type = "Mutation".to_string();
+        let mut types = HashMap::new();
+
+        for definition in document.definitions {
+            match definition {
+                Definition::SchemaDefinition(schema) => {
+                    if let Some(name) = schema.query {
+                        query_type = name;
+                    }
+                    if let Some(name) = schema.mutation {
+                        mutation_type = name;
+                    }
+                }
+                Definition::TypeDefinition(TypeDefinition::Scalar(scalar)) => {
+                    types.insert(scalar.name, FakeType::Scalar);
+                }
+                Definition::TypeDefinition(TypeDefinition::Enum(en)) => {
+                    let values = en.values.into_iter().map(|v| v.name).collect();
+                    types.insert(en.name, FakeType::Enum(values));
+                }
+                Definition::TypeDefinition(TypeDefinition::Object(object)) => {
+                    let object_type = FakeType::Object {
+                        fields: fields(object.fields),
+                        interfaces: object.implements_interfaces,
+                    };
+                    types.insert(object.name, object_type);
+                }
+                Definition::TypeDefinition(TypeDefinition::Interface(interface)) => {
+                    types.insert(interface.name, FakeType::Interface);
+                }
+                Definition::TypeDefinition(TypeDefinition::Union(union)) => {
+                    types.insert(union.name, FakeType::Union(union.types));
+                }
+                Definition::TypeDefinition(TypeDefinition::InputObject(input)) => {
+                    let input_type = FakeType::InputObject {
+                        one_of: input.directives.iter().any(|d| d.name == "oneOf"),
+                        fields: input
+                            .fields
+                            .iter()
+                            .map(|field| {
+                                let input_field = InputField {
+                                    field_type: TypeRef::from(&field.value_type),
+                                    has_default: field.default_value.is_some(),
+                                };
+                                (field.name.clone(), input_field)
+                            })
+                            .collect(),
+                    };
+                    types.insert(input.name, input_type);
+                }
+                _ => {}
+            }
+        }
+
+        for scalar in &["Int", "Float", "String", "Boolean", "ID"] {
+            types.insert(scalar.to_string(), FakeType::Scalar);
+        }
+
+        Ok(FakeServer {
+            query_type,
+            mutation_type,
+            types,
+            generators: HashMap::new(),
+            list_length: 2,
+        })
+    }
+
+    /// Uses `generator` to produce all the values of the scalar or enum
+    /// named `type_name`.
+    pub fn with_generator(
+        mut self,
+        type_name: impl Into<String>,
+        generator: impl Fn(&FakeContext<'_>) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        self.generators
+            .insert(type_name.into(), Box::new(generator));
+        self
+    }
+
+    /// Sets the number of items in every generated list.  Defaults to 2.
+    pub fn with_list_length(mut self, list_length: usize) -> Self {
+        self.list_length = list_length;
+        self
+    }
+
+    /// Responds to `operation` and decodes the response.
+    pub fn run<'a, ResponseData: 'a>(
+        &self,
+        operation: &Operation<'a, ResponseData>,
+    ) -> Result<GraphQLResponse<ResponseData>, json_decode::DecodeError> {
+        operation.decode_response(self.respond(operation))
+    }
+
+    /// Responds to `operation` with fake data.
+    ///
+    /// The variables of `operation` are checked against the schema first.
+    pub fn respond<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+    ) -> GraphQLResponse<Value> {
+        let mut variables = serde_json::Map::new();
+        for (name, argument) in &operation.variables {
+            match &argument.serialize_result {
+                Ok(value) => {
+                    variables.insert(name.clone(), value.clone());
+                }
+                Err(e) => {
+                    return error_response(format!(
+                        "Could not serialize the variable ${}: {}",
+                        name, e
+                    ))
+                }
+            }
+        }
+
+        self.respond_to_with_variables(
+            &operation.query,
+            operation.operation_name.as_deref(),
+            &variables,
+        )
+    }
+
+    /// Responds to the operation called `operation_name` in `document` with
+    /// fake data.  If `operation_name` is `None` the first operation in the
+    /// document is used.
+    ///
+    /// The variables of the operation aren't checked.  Use
+    /// `respond_to_with_variables` to check them.
+    pub fn respond_to(
+        &self,
+        document: &str,
+        operation_name: Option<&str>,
+    ) -> GraphQLResponse<Value> {
+        self.respond_to_document(document, operation_name, None)
+    }
+
+    /// Like `respond_to`, but checks `variables` against the variable
+    /// definitions of the operation before responding.
+    pub fn respond_to_with_variables(
+        &self,
+        document: &str,
+        operation_name: Option<&str>,
+        variables: &serde_json::Map<String, Value>,
+    ) -> GraphQLResponse<Value> {
+        self.respond_to_document(document, operation_name, Some(variables))
+    }
+
+    fn respond_to_document(
+        &self,
+        document: &str,
+        operation_name: Option<&str>,
+        variables: Option<&serde_json::Map<String, Value>>,
+    ) -> GraphQLResponse<Value> {
+        let document = match graphql_parser::parse_query::<&str>(document) {
+            Ok(document) => document,
+            Err(e) => return error_response(format!("Could not parse the query: {}", e)),
+        };
+
+        let operation = document
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Operation(operation) => Some(operation),
+                _ => None,
+            })
+            .find(|operation| match operation_name {
+                Some(name) => self::operation_name(operation) == Some(name),
+                None => true,
+            });
+
+        let (root_type, variable_definitions, selection_set) = match operation {
+            Some(OperationDefinition::SelectionSet(selection_set)) => {
+                (&self.query_type, &[][..], selection_set)
+            }
+            Some(OperationDefinition::Query(query)) => (
+                &self.query_type,
+                &query.variable_definitions[..],
+                &query.selection_set,
+            ),
+            Some(OperationDefinition::Mutation(mutation)) => (
+                &self.mutation_type,
+                &mutation.variable_definitions[..],
+                &mutation.selection_set,
+            ),
+            Some(OperationDefinition::Subscription(_)) => {
+                return error_response("FakeServer doesn't support subscriptions".into())
+            }
+            None => return error_response("Could not find the operation in the document".into()),
+        };
+
+        if let Some(variables) = variables {
+            if let Err(message) = self.check_variables(variable_definitions, variables) {
+                return error_response(message);
+            }
+        }
+
+        let resolver = Resolver {
+            server: self,
+            fragments: document
+                .definitions
+                .iter()
+                .filter_map(|definition| match definition {
+                    Definition::Fragment(fragment) => Some((fragment.name, fragment)),
+                    _ => None,
+                })
+                .collect(),
+        };
+
+        match resolver.resolve_object(root_type, selection_set) {
+            Ok(data) => GraphQLResponse {
+                data: Some(data),
+                errors: None,
+                extensions: None,
+            },
+            Err(message) => error_response(message),
+        }
+    }
+
+    fn check_variables(
+        &self,
+        definitions: &[query::VariableDefinition<'_, &str>],
+        variables: &serde_json::Map<String, Value>,
+    ) -> Result<(), String> {
+        for name in variables.keys() {
+            if !definitions
+                .iter()
+                .any(|definition| definition.name == name.as_str())
+            {
+                return Err(format!("The variable ${} is not defined", name));
+            }
+        }
+
+        for definition in definitions {
+            let variable_type = TypeRef::from(&definition.var_type);
+            match variables.get(definition.name) {
+                Some(value) => self
+                    .check_input(&variable_type, value)
+                    .map_err(|e| format!("The variable ${} is invalid: {}", definition.name, e))?,
+                None => {
+                    if let TypeRef::NonNull(_) = variable_type {
+                        if definition.default_value.is_none() {
+                            return Err(format!("The variable ${} is required", definition.name));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `value` could be coerced into an input of `input_type`.
+    fn check_input(&self, input_type: &TypeRef, value: &Value) -> Result<(), String> {
+        let type_name = match input_type {
+            TypeRef::NonNull(inner) => {
+                if value.is_null() {
+                    return Err("expected a value, found null".into());
+                }
+                return self.check_input(inner, value);
+            }
+            _ if value.is_null() => return Ok(()),
+            TypeRef::List(inner) => {
+                return match value {
+                    Value::Array(items) => items
+                        .iter()
+                        .try_for_each(|item| self.check_input(inner, item)),
+                    // Single values are accepted where a list is expected
+                    _ => self.check_input(inner, value),
+                };
+            }
+            TypeRef::Named(type_name) => type_name.as_str(),
+        };
+
+        let valid = match self.types.get(type_name) {
+            Some(FakeType::Scalar) => match type_name {
+                "Int" => value
+                    .as_i64()
+                    .map_or(false, |i| (i32::MIN as i64..=i32::MAX as i64).contains(&i)),
+                "Float" => value.is_number(),
+                "String" => value.is_string(),
+                "Boolean" => value.is_boolean(),
+                "ID" => value.is_string() || value.is_i64(),
+                // We don't know how custom scalars are represented
+                _ => true,
+            },
+            Some(FakeType::Enum(values)) => value
+                .as_str()
+                .map_or(false, |value| values.iter().any(|v| v == value)),
+            Some(FakeType::InputObject { fields, one_of }) => {
+                return self.check_input_object(type_name, fields, *one_of, value)
+            }
+            Some(_) | None => return Err(format!("{} is not an input type", type_name)),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("expected {}, found {}", type_name, value))
+        }
+    }
+
+    fn check_input_object(
+        &self,
+        type_name: &str,
+        fields: &HashMap<String, InputField>,
+        one_of: bool,
+        value: &Value,
+    ) -> Result<(), String> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| format!("expected {}, found {}", type_name, value))?;
+
+        for name in object.keys() {
+            if !fields.contains_key(name) {
+                return Err(format!("{} has no field {}", type_name, name));
+            }
+        }
+
+        for (name, field) in fields {
+            match object.get(name) {
+                Some(value) => self
+                    .check_input(&field.field_type, value)
+                    .map_err(|e| format!("{}.{}: {}", type_name, name, e))?,
+                None => {
+                    if let TypeRef::NonNull(_) = field.field_type {
+                        if !field.has_default {
+                            return Err(format!("{}.{} is required", type_name, name));
+                        }
+                    }
+                }
+            }
+        }
+
+        if one_of && object.values().filter(|value| !value.is_null()).count() != 1 {
+            return Err(format!("exactly one field of {} must be set", type_name));
+        }
+
+        Ok(())
+    }
+
+    fn possible_types<'b>(&'b self, type_name: &'b str) -> Vec<&'b str> {
+        match self.types.get(type_name) {
+            Some(FakeType::Union(members)) => members.iter().map(String::as_str).collect(),
+            Some(FakeType::Interface) => {
+                let mut implementors = self
+                    .types
+                    .iter()
+                    .filter(|(_, ty)| match ty {
+                        FakeType::Object { interfaces, .. } => {
+                            interfaces.iter().any(|i| i == type_name)
+                        }
+                        _ => false,
+                    })
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>();
+
+                // Sort so we always pick the same type
+                implementors.sort();
+                implementors
+            }
+            _ => vec![type_name],
+        }
+    }
+}
+
+fn fields(fields: Vec<schema::Field<'_, String>>) -> HashMap<String, TypeRef> {
+    fields
+        .into_iter()
+        .map(|field| (field.name, TypeRef::from(&field.field_type)))
+        .collect()
+}
+
+impl<'a, T: schema::Text<'a>> From<&schema::Type<'a, T>> for TypeRef {
+    fn from(ty: &schema::Type<'a, T>) -> Self {
+        match ty {
+            schema::Type::NamedType(name) => TypeRef::Named(name.as_ref().to_string()),
+            schema::Type::ListType(inner) => TypeRef::List(Box::new(TypeRef::from(&**inner))),
+            schema::Type::NonNullType(inner) => TypeRef::NonNull(Box::new(TypeRef::from(&**inner))),
+        }
+    }
+}
+
+fn operation_name<'a>(operation: &OperationDefinition<'a, &'a str>) -> Option<&'a str> {
+    match operation {
+        OperationDefinition::SelectionSet(_) => None,
+        OperationDefinition::Query(query) => query.name,
+        OperationDefinition::Mutation(mutation) => mutation.name,
+        OperationDefinition::Subscription(subscription) => subscription.name,
+    }
+}
+
+fn error_response(message: String) -> GraphQLResponse<Value> {
+    GraphQLResponse {
+        data: None,
+        errors: Some(vec![GraphQLError::new(message, None, None, None)]),
+        extensions: None,
+    }
+}
+
+struct Resolver<'a> {
+    server: &'a FakeServer,
+    fragments: HashMap<&'a str, &'a query::FragmentDefinition<'a, &'a str>>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve_object(
+        &self,
+        type_name: &str,
+        selection_set: &SelectionSet<'a, &'a str>,
+    ) -> Result<Value, String> {
+        let possible_types = self.server.possible_types(type_name);
+        let concrete_type = possible_types
+            .first()
+            .ok_or_else(|| format!("Nothing in the schema implements {}", type_name))?;
+
+        let mut output = serde_json::Map::new();
+        self.resolve_selections(concrete_type, selection_set, &mut output)?;
+        Ok(Value::Object(output))
+    }
+
+    fn resolve_selections(
+        &self,
+        concrete_type: &str,
+        selection_set: &SelectionSet<'a, &'a str>,
+        output: &mut serde_json::Map<String, Value>,
+    ) -> Result<(), String> {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let key = field.alias.unwrap_or(field.name);
+
+                    let value = if field.name == "__typename" {
+                        Value::String(concrete_type.to_string())
+                    } else {
+                        let field_type = match self.server.types.get(concrete_type) {
+                            Some(FakeType::Object { fields, .. }) => fields.get(field.name),
+                            _ => None,
+                        }
+                        .ok_or_else(|| {
+                            format!(
+                                "The field {} does not exist on {}",
+                                field.name, concrete_type
+                            )
+                        })?;
+
+                        self.resolve_value(field_type, field.name, None, &field.selection_set)?
+                    };
+
+                    output.insert(key.to_string(), value);
+                }
+                Selection::InlineFragment(fragment) => {
+                    let applies = match &fragment.type_condition {
+                        Some(TypeCondition::On(condition)) => {
+                            self.fragment_applies(condition, concrete_type)
+                        }
+                        None => true,
+                    };
+                    if applies {
+                        self.resolve_selections(concrete_type, &fragment.selection_set, output)?;
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    let fragment = self
+                        .fragments
+                        .get(spread.fragment_name)
+                        .ok_or_else(|| format!("Unknown fragment {}", spread.fragment_name))?;
+
+                    let TypeCondition::On(condition) = fragment.type_condition;
+                    if self.fragment_applies(condition, concrete_type) {
+                        self.resolve_selections(concrete_type, &fragment.selection_set, output)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fragment_applies(&self, condition: &str, concrete_type: &str) -> bool {
+        self.server
+            .possible_types(condition)
+            .contains(&concrete_type)
+    }
+
+    fn resolve_value(
+        &self,
+        field_type: &TypeRef,
+        field_name: &str,
+        list_index: Option<usize>,
+        selection_set: &SelectionSet<'a, &'a str>,
+    ) -> Result<Value, String> {
+        let type_name = match field_type {
+            TypeRef::NonNull(inner) => {
+                return self.resolve_value(inner, field_name, list_index, selection_set)
+            }
+            TypeRef::List(inner) => {
+                return (0..self.server.list_length)
+                    .map(|i| self.resolve_value(inner, field_name, Some(i), selection_set))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::Array);
+            }
+            TypeRef::Named(type_name) => type_name.as_str(),
+        };
+
+        if let Some(generator) = self.server.generators.get(type_name) {
+            return Ok(generator(&FakeContext {
+                type_name,
+                field_name,
+                list_index,
+            }));
+        }
+
+        match self.server.types.get(type_name) {
+            Some(FakeType::Object { .. })
+            | Some(FakeType::Interface)
+            | Some(FakeType::Union(_)) => self.resolve_object(type_name, selection_set),
+            Some(FakeType::Enum(values)) => values
+                .first()
+                .map(|value| Value::String(value.clone()))
+                .ok_or_else(|| format!("The enum {} has no values", type_name)),
+            Some(FakeType::Scalar) => Ok(match type_name {
+                "Int" => Value::from(1),
+                "Float" => Value::from(1.5),
+                "Boolean" => Value::Bool(true),
+                _ => Value::String(field_name.to_string()),
+            }),
+            Some(FakeType::InputObject { .. }) | None => {
+                Err(format!("{} is not an output type", type_name))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA: &str = r#"
+        schema { query: Root }
+
+        type Root {
+            films(first: Int): [Film!]!
+            node(id: ID!): Node
+            search: [SearchResult]
+            filteredFilms(filter: FilmFilter!): [Film!]!
+        }
+
+        interface Node { id: ID! }
+
+        type Film implements Node {
+            id: ID!
+            title: String
+            episode: Int
+            rating: Rating
+            releasedAt: DateTime
+        }
+
+        type Person implements Node {
+            id: ID!
+            name: String!
+        }
+
+        union SearchResult = Person | Film
+
+        enum Rating { PG, R }
+
+        input FilmFilter {
+            rating: Rating
+            ids: [ID!]
+            release: ReleaseFilter
+        }
+
+        input ReleaseFilter @oneOf { before: DateTime, after: DateTime }
+
+        scalar DateTime
+    "#;
+
+    #[test]
+    fn test_resolving_objects() {
+        let server = FakeServer::new(SCHEMA).unwrap();
+
+        let response = server.respond_to(
+            "query Films($first: Int) { films(first: $first) { id title episode rating releasedAt } }",
+            None,
+        );
+
+        assert!(response.errors.is_none());
+
+        let film = json!({
+            "id": "id",
+            "title": "title",
+            "episode": 1,
+            "rating": "PG",
+            "releasedAt": "releasedAt"
+        });
+        assert_eq!(response.data, Some(json!({ "films": [film, film] })));
+    }
+
+    #[test]
+    fn test_resolving_abstract_types() {
+        let server = FakeServer::new(SCHEMA).unwrap();
+
+        let response = server.respond_to(
+            r#"
+            query {
+                node(id: "1") {
+                    __typename
+                    ... on Film { title }
+                    ... on Person { name }
+                }
+                search { ...PersonFields }
+            }
+
+            fragment PersonFields on Person { __typename name }
+            "#,
+            None,
+        );
+
+        assert_eq!(
+            response.data,
+            Some(json!({
+                "node": { "__typename": "Film", "title": "title" },
+                "search": [
+                    { "__typename": "Person", "name": "name" },
+                    { "__typename": "Person", "name": "name" }
+                ]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_generators() {
+        let server = FakeServer::new(SCHEMA)
+            .unwrap()
+            .with_list_length(3)
+            .with_generator("ID", |context| {
+                json!(format!("film-{}", context.list_index.unwrap()))
+            });
+
+        let response = server.respond_to("{ films { id } }", None);
+
+        assert_eq!(
+            response.data,
+            Some(json!({
+                "films": [{ "id": "film-0" }, { "id": "film-1" }, { "id": "film-2" }]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let server = FakeServer::new(SCHEMA).unwrap();
+
+        let response = server.respond_to("{ films { director } }", None);
+
+        assert!(response.data.is_none());
+        assert_eq!(
+            response.errors.unwrap()[0].message,
+            "The field director does not exist on Film"
+        );
+    }
+
+    #[test]
+    fn test_running_operations() {
+        use crate::{
+            selection_set::{field, string, vec},
+            testing::fixtures::Root,
+        };
+
+        let operation = Operation::query(field::<_, Root, ()>(
+            "films",
+            vec![],
+            vec(field::<_, (), ()>("title", vec![], string())),
+        ));
+
+        let server = FakeServer::new(SCHEMA).unwrap();
+
+        assert_eq!(
+            server.run(&operation).unwrap().data,
+            Some(vec!["title".to_string(), "title".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_checking_variables() {
+        let server = FakeServer::new(SCHEMA).unwrap();
+        let query = "query Films($filter: FilmFilter!) { filteredFilms(filter: $filter) { id } }";
+
+        let error_for = |variables: Value| {
+            let variables = variables.as_object().unwrap().clone();
+            server
+                .respond_to_with_variables(query, None, &variables)
+                .errors
+                .map(|errors| errors[0].message.clone())
+        };
+
+        assert_eq!(
+            error_for(json!({
+                "filter": { "rating": "PG", "ids": ["1", 2], "release": { "before": "1990" } }
+            })),
+            None
+        );
+        assert_eq!(
+            error_for(json!({ "filter": { "ids": "1" } })),
+            None,
+            "single values should be accepted for lists"
+        );
+        assert_eq!(
+            error_for(json!({})),
+            Some("The variable $filter is required".into())
+        );
+        assert_eq!(
+            error_for(json!({ "filter": { "rating": "Pg" } })),
+            Some(r#"The variable $filter is invalid: FilmFilter.rating: expected Rating, found "Pg""#.into())
+        );
+        assert_eq!(
+            error_for(json!({ "filter": { "ids": [1.5] } })),
+            Some("The variable $filter is invalid: FilmFilter.ids: expected ID, found 1.5".into())
+        );
+        assert_eq!(
+            error_for(json!({ "filter": { "director": "Lucas" } })),
+            Some("The variable $filter is invalid: FilmFilter has no field director".into())
+        );
+        assert_eq!(
+            error_for(json!({
+                "filter": { "release": { "before": "1990", "after": "1980" } }
+            })),
+            Some("The variable $filter is invalid: FilmFilter.release: exactly one field of ReleaseFilter must be set".into())
+        );
+        assert_eq!(
+            error_for(json!({ "filter": {}, "first": 1 })),
+            Some("The variable $first is not defined".into())
+        );
+    }
+
+    #[test]
+    fn test_running_operations_with_variables() {
+        use crate::{
+            selection_set::{field, string, vec},
+            testing::fixtures::Root,
+            Argument,
+        };
+
+        let films = |first: Argument| {
+            Operation::query(field::<_, Root, ()>(
+                "films",
+                vec![first],
+                vec(field::<_, (), ()>("title", vec![], string())),
+            ))
+        };
+
+        let server = FakeServer::new(SCHEMA).unwrap();
+
+        let response = server.respond(&films(Argument::new("first", "Int", 2)));
+        assert!(response.errors.is_none());
+
+        let response = server.respond(&films(Argument::new("first", "Int", "2")));
+        assert!(response.data.is_none());
+        assert_eq!(
+            response.errors.unwrap()[0].message,
+            r#"The variable $_0 is invalid: expected Int, found "2""#
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Support for querying [Apollo Federation][federation] subgraphs directly.
+//!
+//! Subgraph schemas can be used with cynic like any other schema.  If a
+//! schema uses federation directives like `@key`, cynic adds the types &
+//! fields that subgraphs provide for their router where the schema doesn't
+//! already define them:
+//!
+//! ```graphql
+//! scalar _Any
+//! union _Entity = # Every type with a @key directive
+//! type _Service { sdl: String }
+//!
+//! type Query {
+//!   _entities(representations: [_Any!]!): [_Entity]!
+//!   _service: _Service!
+//! }
+//! ```
+//!
+//! The `_Any` scalar is always mapped to `cynic::federation::Any`, which
+//! holds the JSON representation of an entity:
+//!
+//! ```rust,ignore
+//! #[derive(cynic::QueryFragment)]
+//! #[cynic(graphql_type = "Query", argument_struct = "EntitiesArguments")]
+//! struct EntitiesQuery {
+//!     #[cynic(rename = "_entities")]
+//!     #[arguments(representations = args.representations.clone())]
+//!     entities: Vec<Option<Entity>>,
+//! }
+//!
+//! let representations = vec![Any::entity("Product", json!({ "upc": "1" }))];
+//! ```
+//!
+//! `Entity` here is an `InlineFragments` derive for the `_Entity` union,
+//! with a variant for each type being fetched.
+//!
+//! [federation]: https://www.apollographql.com/docs/federation/
+
+use serde_json::Value;
+
+use crate::SerializeError;
+
+/// The `_Any` scalar, which holds the representation of an entity.
+///
+/// A representation is a JSON object containing the `__typename` of the
+/// entity and the fields of one of its keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Any(pub Value);
+
+impl Any {
+    /// A representation of the entity `typename` with the key `fields`.
+    ///
+    /// `fields` should be a JSON object - anything else is ignored.
+    pub fn entity(typename: &str, fields: Value) -> Any {
+        let mut representation = match fields {
+            Value::Object(fields) => fields,
+            _ => serde_json::Map::new(),
+        };
+        representation.insert("__typename".into(), typename.into());
+
+        Any(Value::Object(representation))
+    }
+}
+
+impl crate::Scalar for Any {
+    fn decode(value: &Value) -> Result<Self, json_decode::DecodeError> {
+        Ok(Any(value.clone()))
+    }
+
+    fn encode(&self) -> Result<Value, SerializeError> {
+        Ok(self.0.clone())
+    }
+}
+
+crate::impl_serializable_argument_for_scalar!(Any);
+crate::impl_into_argument_for_options!(Any);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scalar;
+    use serde_json::json;
+
+    #[test]
+    fn test_entity_representations() {
+        let representation = Any::entity("Product", json!({ "upc": "1" }));
+
+        assert_eq!(
+            representation.encode().unwrap(),
+            json!({ "__typename": "Product", "upc": "1" })
+        );
+        assert_eq!(Any::decode(&representation.0).unwrap(), representation);
+    }
+}
@@ -3,6 +3,7 @@ use crate::Argument;
 pub enum OperationType {
     Query,
     Mutation,
+    Subscription,
 }
 
 pub enum Field {
@@ -10,8 +11,32 @@ pub enum Field {
     Leaf(String, Vec<Argument>),
     Composite(String, Vec<Argument>, Vec<Field>),
     InlineFragment(String, Vec<Field>),
+    /// A spread of a named fragment: the fragment name, its type condition &
+    /// the fields it selects.
+    FragmentSpread(String, String, Vec<Field>),
+    /// An inline fragment without a type condition that has an `@include` or
+    /// `@skip` directive: the name of the directive, its `if` argument & the
+    /// fields it selects.
+    ConditionalFragment(&'static str, Argument, Vec<Field>),
+    /// An inline fragment with a `@defer` directive: the label of the
+    /// fragment & the fields it selects.
+    DeferredFragment(Option<String>, Vec<Field>),
+    /// A list field with a `@stream` directive: the field & the
+    /// `initialCount` argument.
+    Streamed(Box<Field>, Argument),
+    /// A field with an alias: the alias & the field.
+    Aliased(String, Box<Field>),
 }
 
+/// The seperator we put between fields in a query.
+///
+/// When the `pretty-print` feature is disabled we output queries on a single
+/// line without indentation, which saves some code size for WASM builds.
+#[cfg(feature = "pretty-print")]
+const SEPERATOR: &str = "\n";
+#[cfg(not(feature = "pretty-print"))]
+const SEPERATOR: &str = " ";
+
 impl Field {
     pub(crate) fn query<'a>(
         self,
@@ -19,71 +44,497 @@ impl Field {
         indent_size: usize,
         arguments_out: &mut Vec<Argument>,
     ) -> String {
+        let (indent, indent_size) = if cfg!(feature = "pretty-print") {
+            (indent, indent_size)
+        } else {
+            (0, 0)
+        };
+
         match self {
-            Field::Leaf(field_name, args) => {
-                let arguments = handle_field_arguments(args, arguments_out);
-                format!(
-                    "{:indent$}{field_name}{arguments}\n",
-                    "",
-                    field_name = field_name,
-                    arguments = arguments,
-                    indent = indent
-                )
+            field @ Field::Leaf(..) | field @ Field::Composite(..) | field @ Field::Aliased(..) => {
+                match field.into_parts() {
+                    Ok((field_name, args, child_fields)) => field_query(
+                        field_name,
+                        args,
+                        None,
+                        child_fields,
+                        indent,
+                        indent_size,
+                        arguments_out,
+                    ),
+                    // Aliases can only be used on fields
+                    Err(other) => other.query(indent, indent_size, arguments_out),
+                }
             }
-            Field::Composite(field_name, args, child_fields) => {
-                let arguments = handle_field_arguments(args, arguments_out);
-                let child_query: String = child_fields
+            Field::Streamed(field, initial_count) => match field.into_parts() {
+                Ok((field_name, args, child_fields)) => field_query(
+                    field_name,
+                    args,
+                    Some(("stream", initial_count)),
+                    child_fields,
+                    indent,
+                    indent_size,
+                    arguments_out,
+                ),
+                // @stream can only be used on fields
+                Err(other) => other.query(indent, indent_size, arguments_out),
+            },
+            Field::InlineFragment(type_name, child_fields) => {
+                let child_query: String = merge_fields(child_fields)
                     .into_iter()
                     .map(|f| f.query(indent + indent_size, indent_size, arguments_out))
                     .collect();
 
                 format!(
-                    "{0:indent$}{field_name}{arguments} {{\n{child_query}{0:indent$}}}\n",
+                    "{0:indent$}... on {type_name} {{{sep}{child_query}{0:indent$}}}{sep}",
                     "",
-                    field_name = field_name,
+                    type_name = type_name,
                     child_query = child_query,
                     indent = indent,
-                    arguments = arguments
+                    sep = SEPERATOR
                 )
             }
-            Field::InlineFragment(type_name, child_fields) => {
-                let child_query: String = child_fields
+            Field::ConditionalFragment(directive, condition, child_fields) => {
+                let arguments = handle_field_arguments(vec![condition], arguments_out);
+                let child_query: String = merge_fields(child_fields)
                     .into_iter()
                     .map(|f| f.query(indent + indent_size, indent_size, arguments_out))
                     .collect();
 
                 format!(
-                    "{0:indent$}... on {type_name} {{\n{child_query}{0:indent$}}}\n",
+                    "{0:indent$}... @{directive}{arguments} {{{sep}{child_query}{0:indent$}}}{sep}",
                     "",
-                    type_name = type_name,
+                    directive = directive,
+                    arguments = arguments,
                     child_query = child_query,
-                    indent = indent
+                    indent = indent,
+                    sep = SEPERATOR
                 )
             }
-            Field::Root(fields, operation_type) => {
-                let child_query: String = fields
+            Field::DeferredFragment(label, child_fields) => {
+                let child_query: String = merge_fields(child_fields)
                     .into_iter()
                     .map(|f| f.query(indent + indent_size, indent_size, arguments_out))
                     .collect();
 
-                let arguments = handle_query_arguments(arguments_out);
-
-                let operation_def = match operation_type {
-                    OperationType::Query => "query Query",
-                    OperationType::Mutation => "mutation Mutation",
+                let label = match label {
+                    Some(label) => format!("(label: {})", serde_json::Value::String(label)),
+                    None => "".to_string(),
                 };
 
                 format!(
-                    "{operation_def}{arguments} {{\n{child_query}}}\n",
-                    operation_def = operation_def,
-                    arguments = arguments,
-                    child_query = child_query
+                    "{0:indent$}... @defer{label} {{{sep}{child_query}{0:indent$}}}{sep}",
+                    "",
+                    label = label,
+                    child_query = child_query,
+                    indent = indent,
+                    sep = SEPERATOR
                 )
             }
+            Field::FragmentSpread(fragment_name, _, _) => format!(
+                "{:indent$}...{fragment_name}{sep}",
+                "",
+                fragment_name = fragment_name,
+                indent = indent,
+                sep = SEPERATOR
+            ),
+            field @ Field::Root(..) => {
+                let (operation, fragment_definitions) =
+                    field.operation_query(indent, indent_size, arguments_out);
+
+                fragment_definitions
+                    .into_iter()
+                    .fold(operation, |query, (_, definition)| query + &definition)
+            }
+        }
+    }
+
+    /// Outputs an operation, returning the definitions of any named fragments
+    /// it uses separately from the operation itself.
+    ///
+    /// This lets an `OperationDocument` output each fragment definition once,
+    /// even if several of its operations use them.
+    pub(crate) fn operation_query(
+        self,
+        indent: usize,
+        indent_size: usize,
+        arguments_out: &mut Vec<Argument>,
+    ) -> (String, Vec<FragmentText>) {
+        let (indent, indent_size) = if cfg!(feature = "pretty-print") {
+            (indent, indent_size)
+        } else {
+            (0, 0)
+        };
+
+        let (fields, operation_type) = match self {
+            Field::Root(fields, operation_type) => (fields, operation_type),
+            other => return (other.query(indent, indent_size, arguments_out), vec![]),
+        };
+
+        let mut fragments = vec![];
+        let fields = extract_fragments(fields, &mut fragments);
+
+        let child_query: String = merge_fields(fields)
+            .into_iter()
+            .map(|f| f.query(indent + indent_size, indent_size, arguments_out))
+            .collect();
+
+        let fragment_definitions = fragments
+            .into_iter()
+            .map(|fragment| {
+                let name = fragment.name.clone();
+                (name, fragment.query(indent_size, arguments_out))
+            })
+            .collect();
+
+        let arguments = handle_query_arguments(arguments_out);
+
+        let operation_def = match operation_type {
+            OperationType::Query => "query Query",
+            OperationType::Mutation => "mutation Mutation",
+            OperationType::Subscription => "subscription Subscription",
+        };
+
+        let operation = format!(
+            "{operation_def}{arguments} {{{sep}{child_query}}}{sep}",
+            operation_def = operation_def,
+            arguments = arguments,
+            child_query = child_query,
+            sep = SEPERATOR
+        );
+
+        (operation, fragment_definitions)
+    }
+}
+
+impl Field {
+    /// The key this field will have in a response, if it's a field rather
+    /// than a fragment.
+    pub(crate) fn response_key(&self) -> Option<&str> {
+        match self {
+            Field::Leaf(name, _) | Field::Composite(name, _, _) => Some(name),
+            Field::Streamed(field, _) => field.response_key(),
+            Field::Aliased(alias, _) => Some(alias),
+            _ => None,
+        }
+    }
+
+    /// Splits a field into the name it's output with, its arguments & its
+    /// child fields.  Anything that isn't a field is returned as an error.
+    fn into_parts(self) -> Result<(String, Vec<Argument>, Option<Vec<Field>>), Field> {
+        match self {
+            Field::Leaf(field_name, args) => Ok((field_name, args, None)),
+            Field::Composite(field_name, args, child_fields) => {
+                Ok((field_name, args, Some(child_fields)))
+            }
+            Field::Aliased(alias, field) => {
+                field.into_parts().map(|(field_name, args, child_fields)| {
+                    (format!("{}: {}", alias, field_name), args, child_fields)
+                })
+            }
+            other => Err(other),
+        }
+    }
+}
+
+/// Outputs a field, along with its arguments, an optional directive & any
+/// child fields.
+fn field_query(
+    field_name: String,
+    args: Vec<Argument>,
+    directive: Option<(&'static str, Argument)>,
+    child_fields: Option<Vec<Field>>,
+    indent: usize,
+    indent_size: usize,
+    arguments_out: &mut Vec<Argument>,
+) -> String {
+    let arguments = handle_field_arguments(args, arguments_out);
+    let directive = match directive {
+        Some((name, argument)) => format!(
+            " @{}{}",
+            name,
+            handle_field_arguments(vec![argument], arguments_out)
+        ),
+        None => "".to_string(),
+    };
+
+    match child_fields {
+        None => format!(
+            "{:indent$}{field_name}{arguments}{directive}{sep}",
+            "",
+            field_name = field_name,
+            arguments = arguments,
+            directive = directive,
+            indent = indent,
+            sep = SEPERATOR
+        ),
+        Some(child_fields) => {
+            let child_query: String = merge_fields(child_fields)
+                .into_iter()
+                .map(|f| f.query(indent + indent_size, indent_size, arguments_out))
+                .collect();
+
+            format!(
+                "{0:indent$}{field_name}{arguments}{directive} {{{sep}{child_query}{0:indent$}}}{sep}",
+                "",
+                field_name = field_name,
+                arguments = arguments,
+                directive = directive,
+                child_query = child_query,
+                indent = indent,
+                sep = SEPERATOR
+            )
         }
     }
 }
 
+/// The name of a named fragment & the text of its definition.
+pub(crate) type FragmentText = (String, String);
+
+/// The definition of a named fragment, which is output after the operation
+/// that uses it.
+struct FragmentDefinition {
+    name: String,
+    type_condition: String,
+    fields: Vec<Field>,
+}
+
+impl FragmentDefinition {
+    fn query(self, indent_size: usize, arguments_out: &mut Vec<Argument>) -> String {
+        let child_query: String = merge_fields(self.fields)
+            .into_iter()
+            .map(|f| f.query(indent_size, indent_size, arguments_out))
+            .collect();
+
+        format!(
+            "fragment {name} on {type_condition} {{{sep}{child_query}}}{sep}",
+            name = self.name,
+            type_condition = self.type_condition,
+            child_query = child_query,
+            sep = SEPERATOR
+        )
+    }
+}
+
+/// Moves the fields of any fragment spreads into `definitions`, leaving the
+/// spreads themselves empty.
+///
+/// GraphQL requires fragment names to be unique, so if the same name is used
+/// for fragments that select different things (e.g. because they were built
+/// with different arguments) then the later fragments are inlined instead.
+fn extract_fragments(fields: Vec<Field>, definitions: &mut Vec<FragmentDefinition>) -> Vec<Field> {
+    fields
+        .into_iter()
+        .map(|field| match field {
+            Field::Composite(name, args, children) => {
+                Field::Composite(name, args, extract_fragments(children, definitions))
+            }
+            Field::InlineFragment(type_name, children) => {
+                Field::InlineFragment(type_name, extract_fragments(children, definitions))
+            }
+            Field::ConditionalFragment(directive, condition, children) => {
+                Field::ConditionalFragment(
+                    directive,
+                    condition,
+                    extract_fragments(children, definitions),
+                )
+            }
+            Field::DeferredFragment(label, children) => {
+                Field::DeferredFragment(label, extract_fragments(children, definitions))
+            }
+            Field::Streamed(field, initial_count) => Field::Streamed(
+                Box::new(
+                    extract_fragments(vec![*field], definitions)
+                        .pop()
+                        .expect("extract_fragments to return the field it was given"),
+                ),
+                initial_count,
+            ),
+            Field::Aliased(alias, field) => Field::Aliased(
+                alias,
+                Box::new(
+                    extract_fragments(vec![*field], definitions)
+                        .pop()
+                        .expect("extract_fragments to return the field it was given"),
+                ),
+            ),
+            Field::FragmentSpread(name, type_condition, children) => {
+                let children = extract_fragments(children, definitions);
+
+                match definitions.iter().find(|def| def.name == name) {
+                    None => {
+                        definitions.push(FragmentDefinition {
+                            name: name.clone(),
+                            type_condition: type_condition.clone(),
+                            fields: children,
+                        });
+                        Field::FragmentSpread(name, type_condition, vec![])
+                    }
+                    Some(existing)
+                        if existing.type_condition == type_condition
+                            && fields_identical(&existing.fields, &children) =>
+                    {
+                        Field::FragmentSpread(name, type_condition, vec![])
+                    }
+                    Some(_) => Field::InlineFragment(type_condition, children),
+                }
+            }
+            other => other,
+        })
+        .collect()
+}
+
+/// Merges any duplicate selections in a list of fields.
+///
+/// Composing fragments can easily lead to the same field being selected
+/// more than once.  That's valid GraphQL as long as the selections can be
+/// merged, but some servers reject it anyway - so we merge them ourselves.
+/// Fields are only merged if they have the same name & arguments - any that
+/// differ are left alone.
+fn merge_fields(fields: Vec<Field>) -> Vec<Field> {
+    let mut merged: Vec<Field> = Vec::with_capacity(fields.len());
+
+    for field in fields {
+        let existing = merged
+            .iter()
+            .position(|existing| existing.can_merge(&field));
+
+        match existing {
+            None => merged.push(field),
+            Some(index) => match (&mut merged[index], field) {
+                (Field::Composite(_, _, existing_children), Field::Composite(_, _, children))
+                | (
+                    Field::InlineFragment(_, existing_children),
+                    Field::InlineFragment(_, children),
+                )
+                | (
+                    Field::ConditionalFragment(_, _, existing_children),
+                    Field::ConditionalFragment(_, _, children),
+                )
+                | (
+                    Field::DeferredFragment(_, existing_children),
+                    Field::DeferredFragment(_, children),
+                ) => {
+                    existing_children.extend(children);
+                }
+                (Field::Aliased(_, existing_field), Field::Aliased(_, field)) => {
+                    if let (
+                        Field::Composite(_, _, existing_children),
+                        Field::Composite(_, _, children),
+                    ) = (&mut **existing_field, *field)
+                    {
+                        existing_children.extend(children);
+                    }
+                }
+                // Leaves can just be dropped as they're identical
+                _ => {}
+            },
+        }
+    }
+
+    merged
+}
+
+impl Field {
+    fn can_merge(&self, other: &Field) -> bool {
+        match (self, other) {
+            (Field::Leaf(name, args), Field::Leaf(other_name, other_args))
+            | (Field::Composite(name, args, _), Field::Composite(other_name, other_args, _)) => {
+                name == other_name && arguments_match(args, other_args)
+            }
+            (Field::InlineFragment(type_name, _), Field::InlineFragment(other_type_name, _)) => {
+                type_name == other_type_name
+            }
+            (
+                Field::ConditionalFragment(directive, condition, _),
+                Field::ConditionalFragment(other_directive, other_condition, _),
+            ) => {
+                directive == other_directive
+                    && arguments_match(
+                        std::slice::from_ref(condition),
+                        std::slice::from_ref(other_condition),
+                    )
+            }
+            (Field::DeferredFragment(label, _), Field::DeferredFragment(other_label, _)) => {
+                label == other_label
+            }
+            (Field::Aliased(alias, field), Field::Aliased(other_alias, other_field)) => {
+                alias == other_alias && field.can_merge(other_field)
+            }
+            (Field::FragmentSpread(..), Field::FragmentSpread(..))
+            | (Field::Streamed(..), Field::Streamed(..)) => self.is_identical(other),
+            _ => false,
+        }
+    }
+
+    /// Whether two fields select exactly the same things.
+    fn is_identical(&self, other: &Field) -> bool {
+        match (self, other) {
+            (Field::Leaf(name, args), Field::Leaf(other_name, other_args)) => {
+                name == other_name && arguments_match(args, other_args)
+            }
+            (
+                Field::Composite(name, args, children),
+                Field::Composite(other_name, other_args, other_children),
+            ) => {
+                name == other_name
+                    && arguments_match(args, other_args)
+                    && fields_identical(children, other_children)
+            }
+            (
+                Field::InlineFragment(type_name, children),
+                Field::InlineFragment(other_type_name, other_children),
+            ) => type_name == other_type_name && fields_identical(children, other_children),
+            (
+                Field::ConditionalFragment(_, _, children),
+                Field::ConditionalFragment(_, _, other_children),
+            ) => self.can_merge(other) && fields_identical(children, other_children),
+            (
+                Field::DeferredFragment(label, children),
+                Field::DeferredFragment(other_label, other_children),
+            ) => label == other_label && fields_identical(children, other_children),
+            (
+                Field::Streamed(field, initial_count),
+                Field::Streamed(other_field, other_initial_count),
+            ) => {
+                field.is_identical(other_field)
+                    && arguments_match(
+                        std::slice::from_ref(initial_count),
+                        std::slice::from_ref(other_initial_count),
+                    )
+            }
+            (Field::Aliased(alias, field), Field::Aliased(other_alias, other_field)) => {
+                alias == other_alias && field.is_identical(other_field)
+            }
+            (
+                Field::FragmentSpread(name, type_condition, children),
+                Field::FragmentSpread(other_name, other_type_condition, other_children),
+            ) => {
+                name == other_name
+                    && type_condition == other_type_condition
+                    && fields_identical(children, other_children)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn fields_identical(lhs: &[Field], rhs: &[Field]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| lhs.is_identical(rhs))
+}
+
+fn arguments_match(lhs: &[Argument], rhs: &[Argument]) -> bool {
+    lhs.len() == rhs.len()
+        && lhs.iter().zip(rhs).all(|(lhs, rhs)| {
+            lhs.name == rhs.name
+                && lhs.type_ == rhs.type_
+                && match (&lhs.serialize_result, &rhs.serialize_result) {
+                    (Ok(lhs), Ok(rhs)) => lhs == rhs,
+                    _ => false,
+                }
+        })
+}
+
 /// Extracts any argument values & returns a string to be used in a query.
 fn handle_field_arguments<'a>(
     arguments: Vec<Argument>,
@@ -131,6 +582,7 @@ mod tests {
     use serde_json::json;
 
     #[test]
+    #[cfg(feature = "pretty-print")]
     fn test_query_building() {
         let fields = Field::Composite(
             "test_struct".to_string(),
@@ -154,6 +606,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "pretty-print")]
     fn test_inline_fragments() {
         let fields = Field::Composite(
             "test".to_string(),
@@ -178,6 +631,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "pretty-print")]
     fn test_query_with_arguments() {
         let fields = Field::Composite(
             "test_struct".to_string(),
@@ -215,4 +669,246 @@ mod tests {
             vec!["Bool!", "Bool!"]
         );
     }
+
+    #[test]
+    #[cfg(feature = "pretty-print")]
+    fn test_duplicate_fields_are_merged() {
+        let fields = Field::Composite(
+            "test_struct".to_string(),
+            vec![],
+            vec![
+                Field::Leaf("field_one".to_string(), vec![]),
+                Field::Composite(
+                    "nested".to_string(),
+                    vec![],
+                    vec![Field::Leaf("a_string".to_string(), vec![])],
+                ),
+                Field::Leaf("field_one".to_string(), vec![]),
+                Field::Composite(
+                    "nested".to_string(),
+                    vec![],
+                    vec![
+                        Field::Leaf("a_string".to_string(), vec![]),
+                        Field::Leaf("an_int".to_string(), vec![]),
+                    ],
+                ),
+            ],
+        );
+        let mut arguments = vec![];
+
+        assert_eq!(
+            fields.query(0, 2, &mut arguments),
+            "test_struct {\n  field_one\n  nested {\n    a_string\n    an_int\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "pretty-print")]
+    fn test_fields_with_different_arguments_arent_merged() {
+        let fields = Field::Composite(
+            "test_struct".to_string(),
+            vec![],
+            vec![
+                Field::Leaf(
+                    "field_one".to_string(),
+                    vec![Argument::new("an_arg", "Bool!", true)],
+                ),
+                Field::Leaf(
+                    "field_one".to_string(),
+                    vec![Argument::new("an_arg", "Bool!", true)],
+                ),
+                Field::Leaf(
+                    "field_one".to_string(),
+                    vec![Argument::new("an_arg", "Bool!", false)],
+                ),
+            ],
+        );
+        let mut arguments = vec![];
+
+        assert_eq!(
+            fields.query(0, 2, &mut arguments),
+            "test_struct {\n  field_one(an_arg: $_0)\n  field_one(an_arg: $_1)\n}\n"
+        );
+        assert_eq!(arguments.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "pretty-print")]
+    fn test_named_fragments() {
+        let film_fields = |lang: &str| {
+            Field::FragmentSpread(
+                "FilmFields".to_string(),
+                "Film".to_string(),
+                vec![Field::Leaf(
+                    "title".to_string(),
+                    vec![Argument::new("lang", "String", lang)],
+                )],
+            )
+        };
+        let fields = Field::Root(
+            vec![
+                Field::Composite("film".to_string(), vec![], vec![film_fields("en")]),
+                Field::Composite("sequel".to_string(), vec![], vec![film_fields("en")]),
+                Field::Composite("prequel".to_string(), vec![], vec![film_fields("fr")]),
+            ],
+            OperationType::Query,
+        );
+        let mut arguments = vec![];
+
+        assert_eq!(
+            fields.query(0, 2, &mut arguments),
+            "query Query($_0: String, $_1: String) {\n  film {\n    ...FilmFields\n  }\n  sequel {\n    ...FilmFields\n  }\n  prequel {\n    ... on Film {\n      title(lang: $_0)\n    }\n  }\n}\nfragment FilmFields on Film {\n  title(lang: $_1)\n}\n"
+        );
+        assert_eq!(
+            arguments
+                .iter()
+                .map(|a| a.serialize_result.as_ref().unwrap())
+                .collect::<Vec<_>>(),
+            vec![&json!("fr"), &json!("en")]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "pretty-print")]
+    fn test_conditional_fragments() {
+        let fields = Field::Composite(
+            "film".to_string(),
+            vec![],
+            vec![
+                Field::Leaf("title".to_string(), vec![]),
+                Field::ConditionalFragment(
+                    "include",
+                    Argument::new("if", "Boolean!", true),
+                    vec![Field::Leaf("director".to_string(), vec![])],
+                ),
+                Field::ConditionalFragment(
+                    "include",
+                    Argument::new("if", "Boolean!", true),
+                    vec![Field::Leaf("producers".to_string(), vec![])],
+                ),
+                Field::ConditionalFragment(
+                    "skip",
+                    Argument::new("if", "Boolean!", true),
+                    vec![Field::Leaf("openingCrawl".to_string(), vec![])],
+                ),
+            ],
+        );
+        let mut arguments = vec![];
+
+        assert_eq!(
+            fields.query(0, 2, &mut arguments),
+            "film {\n  title\n  ... @include(if: $_0) {\n    director\n    producers\n  }\n  ... @skip(if: $_1) {\n    openingCrawl\n  }\n}\n"
+        );
+        assert_eq!(arguments.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "pretty-print")]
+    fn test_incremental_directives() {
+        let fields = Field::Composite(
+            "film".to_string(),
+            vec![],
+            vec![
+                Field::Leaf("title".to_string(), vec![]),
+                Field::DeferredFragment(
+                    Some("details".to_string()),
+                    vec![Field::Leaf("director".to_string(), vec![])],
+                ),
+                Field::DeferredFragment(
+                    Some("details".to_string()),
+                    vec![Field::Streamed(
+                        Box::new(Field::Composite(
+                            "characters".to_string(),
+                            vec![Argument::new("first", "Int", 10)],
+                            vec![Field::Leaf("name".to_string(), vec![])],
+                        )),
+                        Argument::new("initialCount", "Int!", 2),
+                    )],
+                ),
+                Field::DeferredFragment(
+                    None,
+                    vec![Field::Leaf("openingCrawl".to_string(), vec![])],
+                ),
+            ],
+        );
+        let mut arguments = vec![];
+
+        assert_eq!(
+            fields.query(0, 2, &mut arguments),
+            "film {\n  title\n  ... @defer(label: \"details\") {\n    director\n    characters(first: $_0) @stream(initialCount: $_1) {\n      name\n    }\n  }\n  ... @defer {\n    openingCrawl\n  }\n}\n"
+        );
+        assert_eq!(arguments.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "pretty-print")]
+    fn test_aliased_fields() {
+        let aliased = |alias: &str, field| Field::Aliased(alias.to_string(), Box::new(field));
+        let fields = Field::Composite(
+            "film".to_string(),
+            vec![],
+            vec![
+                aliased(
+                    "hero",
+                    Field::Composite(
+                        "character".to_string(),
+                        vec![],
+                        vec![Field::Leaf("name".to_string(), vec![])],
+                    ),
+                ),
+                aliased(
+                    "hero",
+                    Field::Composite(
+                        "character".to_string(),
+                        vec![],
+                        vec![Field::Leaf("height".to_string(), vec![])],
+                    ),
+                ),
+                aliased(
+                    "villain",
+                    Field::Composite(
+                        "character".to_string(),
+                        vec![],
+                        vec![Field::Leaf("name".to_string(), vec![])],
+                    ),
+                ),
+                Field::Streamed(
+                    Box::new(aliased(
+                        "firstPlanets",
+                        Field::Leaf("planets".to_string(), vec![]),
+                    )),
+                    Argument::new("initialCount", "Int!", 1),
+                ),
+            ],
+        );
+        let mut arguments = vec![];
+
+        assert_eq!(
+            fields.query(0, 2, &mut arguments),
+            "film {\n  hero: character {\n    name\n    height\n  }\n  villain: character {\n    name\n  }\n  firstPlanets: planets @stream(initialCount: $_0)\n}\n"
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "pretty-print"))]
+    fn test_compact_query_building() {
+        let fields = Field::Composite(
+            "test_struct".to_string(),
+            vec![Argument::new("an_arg", "Bool!", false)],
+            vec![
+                Field::Leaf("field_one".to_string(), vec![]),
+                Field::Composite(
+                    "nested".to_string(),
+                    vec![],
+                    vec![Field::Leaf("a_string".to_string(), vec![])],
+                ),
+            ],
+        );
+        let mut arguments = vec![];
+
+        assert_eq!(
+            fields.query(0, 2, &mut arguments),
+            "test_struct(an_arg: $_0) { field_one nested { a_string } } "
+        );
+    }
 }
@@ -0,0 +1,329 @@
+//! Running subscriptions with the `graphql-sse` protocol.
+//!
+//! Some servers send the responses to subscriptions as
+//! [Server-Sent Events][sse], using the [graphql-sse][protocol] protocol:
+//! the subscription is POSTed with an `Accept: text/event-stream` header,
+//! and the server replies with a `next` event for each response followed by
+//! a `complete` event.
+//!
+//! `Connection` implements the protocol without doing any IO itself, so it
+//! can be used with any HTTP client: send the body it returns, and pass it
+//! each chunk of the response as it arrives.  If the response ends before
+//! the server has sent `complete`, make the request again with the headers
+//! from `Connection::reconnect_headers` to resume where it left off.
+//!
+//! With the `reqwest` feature enabled, `ReqwestExt::run_graphql_sse` takes
+//! care of all of this:
+//!
+//! ```rust,ignore
+//! let mut subscription = client
+//!     .post(url)
+//!     .run_graphql_sse(FilmsAddedSubscription::build(()))
+//!     .await?;
+//!
+//! while let Some(response) = subscription.next().await {
+//!     println!("{:?}", response?.data);
+//! }
+//! ```
+//!
+//! [sse]: https://html.spec.whatwg.org/multipage/server-sent-events.html
+//! [protocol]: https://github.com/enisdenjo/graphql-sse/blob/master/PROTOCOL.md
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{GraphQLResponse, StreamingOperation};
+
+/// The `Accept` header that should be sent with subscriptions.
+pub const ACCEPT: &str = "text/event-stream";
+
+/// A single event from a `text/event-stream` response.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServerSentEvent {
+    /// The type of the event, which is empty if the server didn't send one.
+    pub event: String,
+    /// The data of the event.  Multiple `data` lines are joined with
+    /// newlines.
+    pub data: String,
+    /// The id of the most recent event that had one.
+    pub id: Option<String>,
+}
+
+/// Parses a `text/event-stream` response into `ServerSentEvent`s.
+///
+/// Chunks of the response can be pushed in as they arrive, with no need to
+/// split them on line or event boundaries.
+#[derive(Debug, Default)]
+pub struct EventStreamParser {
+    buffer: Vec<u8>,
+    event: String,
+    data: Option<String>,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl EventStreamParser {
+    pub fn new() -> Self {
+        EventStreamParser::default()
+    }
+
+    /// Adds a chunk of the response to the parser.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// The next complete event in the response, if there is one.
+    pub fn next_event(&mut self) -> Option<ServerSentEvent> {
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line = self.buffer.drain(..=end).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(&['\r', '\n'][..]);
+
+            if line.is_empty() {
+                if let Some(event) = self.dispatch() {
+                    return Some(event);
+                }
+                continue;
+            }
+
+            self.handle_line(line);
+        }
+
+        None
+    }
+
+    /// The id of the last event the server sent, which should be sent in a
+    /// `Last-Event-ID` header when reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// How long the server has asked clients to wait before reconnecting.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Discards any partially received event, ready for a new response.
+    ///
+    /// The last event id & retry delay are kept.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.event.clear();
+        self.data = None;
+    }
+
+    fn handle_line(&mut self, line: &str) {
+        // Lines starting with a colon are comments, often used as keep
+        // alives.
+        if line.starts_with(':') {
+            return;
+        }
+
+        let (field, value) = match line.find(':') {
+            Some(index) => {
+                let value = &line[index + 1..];
+                (&line[..index], value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.event = value.to_string(),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn dispatch(&mut self) -> Option<ServerSentEvent> {
+        let event = std::mem::take(&mut self.event);
+        let data = self.data.take();
+
+        if data.is_none() && event.is_empty() {
+            return None;
+        }
+
+        Some(ServerSentEvent {
+            event,
+            data: data.unwrap_or_default(),
+            id: self.last_event_id.clone(),
+        })
+    }
+}
+
+/// Something that happened on a `Connection`.
+#[derive(Debug)]
+pub enum Event {
+    /// A response to the subscription.
+    Next(GraphQLResponse<Value>),
+    /// The subscription has finished.  The server won't send any more
+    /// responses.
+    Complete,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProtocolError {
+    #[error("Could not parse event from server: {0}")]
+    InvalidEvent(#[from] serde_json::Error),
+    #[error("Server sent an unknown {0} event")]
+    UnknownEvent(String),
+}
+
+/// The client side of a `graphql-sse` subscription.
+///
+/// This only tracks the state of the protocol - it's up to the caller to
+/// make requests & read responses.
+#[derive(Debug, Default)]
+pub struct Connection {
+    parser: EventStreamParser,
+    complete: bool,
+}
+
+impl Connection {
+    pub fn new() -> Self {
+        Connection::default()
+    }
+
+    /// The body to POST to the server to start `operation`.
+    pub fn request_body<ResponseData>(
+        &self,
+        operation: &StreamingOperation<'_, ResponseData>,
+    ) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(operation)
+    }
+
+    /// The headers to send when reconnecting after a response ended before
+    /// the subscription completed.
+    pub fn reconnect_headers(&self) -> Vec<(&'static str, String)> {
+        match self.parser.last_event_id() {
+            Some(id) => vec![("Last-Event-ID", id.to_string())],
+            None => vec![],
+        }
+    }
+
+    /// How long the server has asked clients to wait before reconnecting.
+    pub fn retry_delay(&self) -> Option<Duration> {
+        self.parser.retry()
+    }
+
+    /// Whether the server has completed the subscription.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Prepares for a new response after reconnecting.
+    pub fn reconnected(&mut self) {
+        self.parser.reset();
+    }
+
+    /// Handles a chunk of the response from the server.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.parser.push(chunk);
+    }
+
+    /// The next event in the chunks that have been pushed, if there is one.
+    pub fn next_event(&mut self) -> Option<Result<Event, ProtocolError>> {
+        while let Some(event) = self.parser.next_event() {
+            match event.event.as_str() {
+                "next" => {
+                    return Some(
+                        serde_json::from_str(&event.data)
+                            .map(Event::Next)
+                            .map_err(ProtocolError::from),
+                    )
+                }
+                "complete" => {
+                    self.complete = true;
+                    return Some(Ok(Event::Complete));
+                }
+                // Events without a type are usually keep alives.
+                "" => {}
+                other => return Some(Err(ProtocolError::UnknownEvent(other.to_string()))),
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    #[test]
+    fn test_parsing_events_across_chunks() {
+        let mut parser = EventStreamParser::new();
+
+        parser.push(b": keep alive\n\nevent: next\nid: 1\nda");
+        assert_eq!(parser.next_event(), None);
+
+        parser.push(b"ta: {\"a\":\r\ndata:1}\r\n\r\nretry: 2000\nevent: complete\ndata:\n\n");
+        assert_eq!(
+            parser.next_event(),
+            Some(ServerSentEvent {
+                event: "next".into(),
+                data: "{\"a\":\n1}".into(),
+                id: Some("1".into()),
+            })
+        );
+        assert_eq!(
+            parser.next_event(),
+            Some(ServerSentEvent {
+                event: "complete".into(),
+                data: "".into(),
+                id: Some("1".into()),
+            })
+        );
+        assert_eq!(parser.next_event(), None);
+        assert_eq!(parser.retry(), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_connection_events() {
+        let mut connection = Connection::new();
+        assert!(connection.reconnect_headers().is_empty());
+
+        connection.push(b"event: next\nid: 5\ndata: {\"data\":{\"filmAdded\":\"Jaws\"}}\n\n");
+        connection.push(b"event: next\ndata: {\"data\":{\"filmAdded\":\"Alien");
+
+        assert_matches!(
+            connection.next_event(),
+            Some(Ok(Event::Next(response))) => {
+                assert_eq!(response.data, Some(json!({ "filmAdded": "Jaws" })))
+            }
+        );
+        assert_matches!(connection.next_event(), None);
+
+        // The response ended half way through an event.
+        connection.reconnected();
+        assert_eq!(
+            connection.reconnect_headers(),
+            vec![("Last-Event-ID", "5".to_string())]
+        );
+
+        connection.push(b"event: next\ndata: {\"data\":null}\n\nevent: complete\n\n");
+        assert_matches!(connection.next_event(), Some(Ok(Event::Next(_))));
+        assert_matches!(connection.next_event(), Some(Ok(Event::Complete)));
+        assert!(connection.is_complete());
+
+        connection.push(b"event: other\ndata: 1\n\n");
+        assert_matches!(
+            connection.next_event(),
+            Some(Err(ProtocolError::UnknownEvent(event))) => assert_eq!(event, "other")
+        );
+    }
+}
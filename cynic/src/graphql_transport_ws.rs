@@ -0,0 +1,701 @@
+//! Running subscriptions with the `graphql-transport-ws` protocol.
+//!
+//! Subscriptions are usually sent over a websocket, using the
+//! [graphql-transport-ws][protocol] protocol.  `Connection` implements the
+//! protocol without doing any IO itself, so it can be used with any
+//! websocket library: send the messages it returns, and pass it any text
+//! messages you receive.
+//!
+//! With the `graphql-ws` feature enabled, `SubscriptionStream` takes care
+//! of this for a single subscription, and provides a `Stream` of decoded
+//! responses:
+//!
+//! ```rust,ignore
+//! let (sink, incoming) = connect_websocket("wss://example.com/graphql").await;
+//!
+//! let mut stream = SubscriptionStream::new(
+//!     FilmsAddedSubscription::build(()),
+//!     incoming.map(|message| message.into_text()),
+//!     move |text| sink.send(text),
+//! );
+//!
+//! while let Some(response) = stream.next().await {
+//!     println!("{:?}", response?.data);
+//! }
+//! ```
+//!
+//! Some older servers use the legacy `subscriptions-transport-ws` protocol
+//! instead.  That's implemented in the `subscriptions_transport_ws` module,
+//! and either protocol can be used with `SubscriptionStream::with_protocol`
+//! as they both implement `Protocol`.
+//!
+//! [protocol]: https://github.com/enisdenjo/graphql-ws/blob/master/PROTOCOL.md
+
+use serde_json::Value;
+
+use crate::{GraphQLError, GraphQLResponse, StreamingOperation};
+
+/// The websocket subprotocol that should be requested when connecting.
+pub const SUBPROTOCOL: &str = "graphql-transport-ws";
+
+/// A message sent from the client to the server.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    ConnectionInit {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<Value>,
+    },
+    Ping,
+    Pong,
+    Subscribe {
+        id: String,
+        payload: Value,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+/// A message sent from the server to the client.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    ConnectionAck,
+    Ping,
+    Pong,
+    Next {
+        id: String,
+        payload: GraphQLResponse<Value>,
+    },
+    Error {
+        id: String,
+        payload: Vec<GraphQLError>,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+/// A websocket subscription protocol.
+///
+/// This is implemented by the `Connection` in this module, by
+/// `subscriptions_transport_ws::Connection` for the legacy protocol, and by
+/// `appsync::Connection` & `absinthe::Connection` for AWS AppSync & Absinthe
+/// servers.
+pub trait Protocol {
+    /// The websocket subprotocol that should be requested when connecting.
+    fn subprotocol(&self) -> &'static str;
+
+    /// The message that starts the connection, with an optional payload
+    /// that servers often use for authentication.
+    fn init_message(&self, payload: Option<Value>) -> String;
+
+    /// A message that checks the connection is still alive, if the protocol
+    /// supports one.
+    fn ping_message(&self) -> Option<String>;
+
+    /// Starts `operation`, returning the id of the subscription and the
+    /// message that should be sent to the server.
+    fn subscribe<ResponseData>(
+        &mut self,
+        operation: &StreamingOperation<'_, ResponseData>,
+    ) -> Result<(String, String), serde_json::Error>;
+
+    /// The message that stops the subscription `id`.
+    fn complete_message(&self, id: &str) -> String;
+
+    /// Handles a text message from the server.
+    fn handle_message(&mut self, text: &str) -> Result<Event, ProtocolError>;
+}
+
+/// Something that happened on a `Connection`.
+#[derive(Debug)]
+pub enum Event {
+    /// The server has acknowledged the connection, so subscriptions can be
+    /// started.
+    Acknowledged,
+    /// The server expects this message to be sent in reply.
+    Reply(String),
+    /// The server replied to a ping.
+    Pong,
+    /// The server sent a keep alive message, which needs no reply.
+    KeepAlive,
+    /// The server has started the subscription `id`.  Only some protocols
+    /// confirm subscriptions like this.
+    Started { id: String },
+    /// A response for the subscription `id`.
+    Next {
+        id: String,
+        payload: GraphQLResponse<Value>,
+    },
+    /// The subscription `id` failed.  The server won't send any more
+    /// responses for it.
+    Error {
+        id: String,
+        errors: Vec<GraphQLError>,
+    },
+    /// The subscription `id` has finished.
+    Complete { id: String },
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProtocolError {
+    #[error("Could not parse message from server: {0}")]
+    InvalidMessage(#[from] serde_json::Error),
+    #[error("Server sent a {0} message before acknowledging the connection")]
+    NotAcknowledged(&'static str),
+    #[error("Server rejected the connection")]
+    ConnectionRejected(Option<Value>),
+    #[error("Server closed the connection")]
+    ConnectionClosed(Option<Value>),
+}
+
+/// The client side of a `graphql-transport-ws` connection.
+///
+/// This only tracks the state of the protocol - it's up to the caller to
+/// send & receive messages.  `init_message` should be sent as soon as the
+/// websocket is open, and subscriptions started once `handle_message` has
+/// returned `Event::Acknowledged`.
+#[derive(Debug, Default)]
+pub struct Connection {
+    acknowledged: bool,
+    next_id: u64,
+}
+
+impl Connection {
+    pub fn new() -> Self {
+        Connection::default()
+    }
+
+    /// The message that starts the connection, with an optional payload
+    /// that servers often use for authentication.
+    pub fn init_message(&self, payload: Option<Value>) -> String {
+        encode(&ClientMessage::ConnectionInit { payload })
+    }
+
+    /// Whether the server has acknowledged this connection.
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledged
+    }
+
+    /// Starts `operation`, returning the id of the subscription and the
+    /// message that should be sent to the server.
+    pub fn subscribe<ResponseData>(
+        &mut self,
+        operation: &StreamingOperation<'_, ResponseData>,
+    ) -> Result<(String, String), serde_json::Error> {
+        let payload = serde_json::to_value(operation)?;
+
+        self.next_id += 1;
+        let id = self.next_id.to_string();
+
+        let message = encode(&ClientMessage::Subscribe {
+            id: id.clone(),
+            payload,
+        });
+        Ok((id, message))
+    }
+
+    /// The message that stops the subscription `id`.
+    pub fn complete_message(&self, id: &str) -> String {
+        encode(&ClientMessage::Complete { id: id.to_string() })
+    }
+
+    /// A ping message, which the server should reply to with a pong.
+    pub fn ping_message(&self) -> String {
+        encode(&ClientMessage::Ping)
+    }
+
+    /// Handles a text message from the server.
+    pub fn handle_message(&mut self, text: &str) -> Result<Event, ProtocolError> {
+        let message = serde_json::from_str::<ServerMessage>(text)?;
+
+        if !self.acknowledged {
+            match message {
+                ServerMessage::ConnectionAck | ServerMessage::Ping | ServerMessage::Pong => {}
+                ServerMessage::Next { .. } => return Err(ProtocolError::NotAcknowledged("next")),
+                ServerMessage::Error { .. } => return Err(ProtocolError::NotAcknowledged("error")),
+                ServerMessage::Complete { .. } => {
+                    return Err(ProtocolError::NotAcknowledged("complete"))
+                }
+            }
+        }
+
+        Ok(match message {
+            ServerMessage::ConnectionAck => {
+                self.acknowledged = true;
+                Event::Acknowledged
+            }
+            ServerMessage::Ping => Event::Reply(encode(&ClientMessage::Pong)),
+            ServerMessage::Pong => Event::Pong,
+            ServerMessage::Next { id, payload } => Event::Next { id, payload },
+            ServerMessage::Error { id, payload } => Event::Error {
+                id,
+                errors: payload,
+            },
+            ServerMessage::Complete { id } => Event::Complete { id },
+        })
+    }
+}
+
+impl Protocol for Connection {
+    fn subprotocol(&self) -> &'static str {
+        SUBPROTOCOL
+    }
+
+    fn init_message(&self, payload: Option<Value>) -> String {
+        Connection::init_message(self, payload)
+    }
+
+    fn ping_message(&self) -> Option<String> {
+        Some(Connection::ping_message(self))
+    }
+
+    fn subscribe<ResponseData>(
+        &mut self,
+        operation: &StreamingOperation<'_, ResponseData>,
+    ) -> Result<(String, String), serde_json::Error> {
+        Connection::subscribe(self, operation)
+    }
+
+    fn complete_message(&self, id: &str) -> String {
+        Connection::complete_message(self, id)
+    }
+
+    fn handle_message(&mut self, text: &str) -> Result<Event, ProtocolError> {
+        Connection::handle_message(self, text)
+    }
+}
+
+fn encode(message: &ClientMessage) -> String {
+    // ClientMessages only contain strings & JSON values, so can't fail to
+    // serialize.
+    serde_json::to_string(message).expect("ClientMessage should always serialize")
+}
+
+#[cfg(feature = "graphql-ws")]
+pub use stream::{SubscriptionError, SubscriptionStream};
+
+#[cfg(feature = "graphql-ws")]
+mod stream {
+    use futures_core::Stream;
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use super::{Connection, Event, Protocol, ProtocolError};
+    use crate::{GraphQLError, GraphQLResponse, StreamingOperation};
+
+    #[derive(thiserror::Error, Debug)]
+    pub enum SubscriptionError {
+        #[error("{0}")]
+        Protocol(#[from] ProtocolError),
+        #[error("Could not serialize subscription: {0}")]
+        Serialize(serde_json::Error),
+        #[error("Error decoding GraphQL response: {0}")]
+        Decode(#[from] json_decode::DecodeError),
+        #[error("Subscription failed with {} errors", .0.len())]
+        Errors(Vec<GraphQLError>),
+    }
+
+    /// A `Stream` of the responses to a single subscription.
+    ///
+    /// This takes the text messages received from a websocket, and a
+    /// function that sends text messages to it.  The connection is
+    /// initialised as soon as the stream is created, and the subscription
+    /// is completed if the stream is dropped before the server ends it.
+    ///
+    /// This uses the `graphql-transport-ws` protocol unless another
+    /// `Protocol` is given to `with_protocol`.
+    pub struct SubscriptionStream<'a, ResponseData, Incoming, P: Protocol = Connection> {
+        operation: StreamingOperation<'a, ResponseData>,
+        connection: P,
+        incoming: Incoming,
+        send: Box<dyn FnMut(String) + Send + 'a>,
+        id: Option<String>,
+        finished: bool,
+    }
+
+    impl<'a, ResponseData: 'a, Incoming> SubscriptionStream<'a, ResponseData, Incoming>
+    where
+        Incoming: Stream<Item = String> + Unpin,
+    {
+        pub fn new(
+            operation: StreamingOperation<'a, ResponseData>,
+            incoming: Incoming,
+            send: impl FnMut(String) + Send + 'a,
+        ) -> Self {
+            SubscriptionStream::with_init_payload(operation, None, incoming, send)
+        }
+
+        /// Creates a SubscriptionStream that sends `payload` when
+        /// initialising the connection.
+        pub fn with_init_payload(
+            operation: StreamingOperation<'a, ResponseData>,
+            payload: Option<serde_json::Value>,
+            incoming: Incoming,
+            send: impl FnMut(String) + Send + 'a,
+        ) -> Self {
+            SubscriptionStream::with_protocol(Connection::new(), operation, payload, incoming, send)
+        }
+    }
+
+    impl<'a, ResponseData: 'a, Incoming, P: Protocol> SubscriptionStream<'a, ResponseData, Incoming, P>
+    where
+        Incoming: Stream<Item = String> + Unpin,
+    {
+        /// Creates a SubscriptionStream that speaks the protocol of `connection`,
+        /// sending `payload` when initialising the connection.
+        pub fn with_protocol(
+            connection: P,
+            operation: StreamingOperation<'a, ResponseData>,
+            payload: Option<serde_json::Value>,
+            incoming: Incoming,
+            send: impl FnMut(String) + Send + 'a,
+        ) -> Self {
+            let mut send = Box::new(send);
+            send(connection.init_message(payload));
+
+            SubscriptionStream {
+                operation,
+                connection,
+                incoming,
+                send,
+                id: None,
+                finished: false,
+            }
+        }
+
+        fn handle_event(
+            &mut self,
+            event: Event,
+        ) -> Option<Option<Result<GraphQLResponse<ResponseData>, SubscriptionError>>> {
+            match event {
+                Event::Acknowledged => {
+                    match self.connection.subscribe(&self.operation) {
+                        Ok((id, message)) => {
+                            self.id = Some(id);
+                            (self.send)(message);
+                        }
+                        Err(e) => return Some(self.finish(Err(SubscriptionError::Serialize(e)))),
+                    }
+                    None
+                }
+                Event::Reply(message) => {
+                    (self.send)(message);
+                    None
+                }
+                Event::Pong | Event::KeepAlive | Event::Started { .. } => None,
+                Event::Next { id, payload } if self.is_current(&id) => Some(Some(
+                    self.operation
+                        .decode_response(payload)
+                        .map_err(SubscriptionError::from),
+                )),
+                Event::Error { id, errors } if self.is_current(&id) => {
+                    Some(self.finish(Err(SubscriptionError::Errors(errors))))
+                }
+                Event::Complete { id } if self.is_current(&id) => {
+                    self.finished = true;
+                    Some(None)
+                }
+                // Anything else is for a subscription we didn't start.
+                _ => None,
+            }
+        }
+
+        fn is_current(&self, id: &str) -> bool {
+            self.id.as_deref() == Some(id)
+        }
+
+        fn finish(
+            &mut self,
+            result: Result<GraphQLResponse<ResponseData>, SubscriptionError>,
+        ) -> Option<Result<GraphQLResponse<ResponseData>, SubscriptionError>> {
+            self.finished = true;
+            Some(result)
+        }
+    }
+
+    impl<'a, ResponseData: 'a, Incoming, P> Stream for SubscriptionStream<'a, ResponseData, Incoming, P>
+    where
+        Incoming: Stream<Item = String> + Unpin,
+        P: Protocol + Unpin,
+    {
+        type Item = Result<GraphQLResponse<ResponseData>, SubscriptionError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+
+            loop {
+                if this.finished {
+                    return Poll::Ready(None);
+                }
+
+                let text = match Pin::new(&mut this.incoming).poll_next(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(None) => {
+                        this.finished = true;
+                        return Poll::Ready(None);
+                    }
+                    Poll::Ready(Some(text)) => text,
+                };
+
+                let event = match this.connection.handle_message(&text) {
+                    Ok(event) => event,
+                    Err(e) => return Poll::Ready(this.finish(Err(e.into()))),
+                };
+
+                if let Some(item) = this.handle_event(event) {
+                    return Poll::Ready(item);
+                }
+            }
+        }
+    }
+
+    impl<'a, ResponseData, Incoming, P: Protocol> Drop
+        for SubscriptionStream<'a, ResponseData, Incoming, P>
+    {
+        fn drop(&mut self) {
+            if let (Some(id), false) = (&self.id, self.finished) {
+                let message = self.connection.complete_message(id);
+                (self.send)(message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use crate::testing::fixtures::subscription;
+
+    #[test]
+    fn test_connection_messages() {
+        let mut connection = Connection::new();
+
+        assert_eq!(
+            connection.init_message(Some(json!({ "token": "abcd" }))),
+            r#"{"type":"connection_init","payload":{"token":"abcd"}}"#
+        );
+
+        let (id, message) = connection.subscribe(&subscription("filmAdded")).unwrap();
+        assert_eq!(id, "1");
+        let message = serde_json::from_str::<Value>(&message).unwrap();
+        assert_eq!(message["type"], "subscribe");
+        assert_eq!(message["id"], "1");
+        assert!(message["payload"]["query"]
+            .as_str()
+            .unwrap()
+            .starts_with("subscription Subscription"));
+
+        assert_eq!(
+            connection.complete_message(&id),
+            r#"{"type":"complete","id":"1"}"#
+        );
+    }
+
+    #[test]
+    fn test_handling_messages() {
+        let mut connection = Connection::new();
+
+        assert_matches!(
+            connection.handle_message(r#"{"type":"next","id":"1","payload":{"data":null}}"#),
+            Err(ProtocolError::NotAcknowledged("next"))
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"connection_ack"}"#),
+            Ok(Event::Acknowledged)
+        );
+        assert!(connection.is_acknowledged());
+
+        assert_matches!(
+            connection.handle_message(r#"{"type":"ping"}"#),
+            Ok(Event::Reply(message)) => assert_eq!(message, r#"{"type":"pong"}"#)
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"next","id":"1","payload":{"data":{"filmAdded":"Jaws"}}}"#),
+            Ok(Event::Next { id, payload }) => {
+                assert_eq!(id, "1");
+                assert_eq!(payload.data, Some(json!({ "filmAdded": "Jaws" })));
+            }
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"error","id":"1","payload":[{"message":"oops"}]}"#),
+            Ok(Event::Error { errors, .. }) => assert_eq!(errors[0].message, "oops")
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"unknown"}"#),
+            Err(ProtocolError::InvalidMessage(_))
+        );
+    }
+
+    #[cfg(feature = "graphql-ws")]
+    mod stream {
+        use super::*;
+        use futures_core::Stream;
+        use std::{
+            collections::VecDeque,
+            pin::Pin,
+            sync::{Arc, Mutex},
+            task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+        };
+
+        /// Incoming messages from a websocket that stays open once they've
+        /// all been received.
+        struct Incoming(VecDeque<String>);
+
+        impl Stream for Incoming {
+            type Item = String;
+
+            fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<String>> {
+                match self.0.pop_front() {
+                    Some(message) => Poll::Ready(Some(message)),
+                    None => Poll::Pending,
+                }
+            }
+        }
+
+        fn noop_waker() -> Waker {
+            fn clone(_: *const ()) -> RawWaker {
+                RawWaker::new(std::ptr::null(), &VTABLE)
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+            unsafe { Waker::from_raw(clone(std::ptr::null())) }
+        }
+
+        fn collect<S: Stream + Unpin>(stream: &mut S) -> Vec<S::Item> {
+            let waker = noop_waker();
+            let mut cx = Context::from_waker(&waker);
+            let mut items = Vec::new();
+            while let Poll::Ready(Some(item)) = Pin::new(&mut *stream).poll_next(&mut cx) {
+                items.push(item);
+            }
+            items
+        }
+
+        #[test]
+        fn test_subscription_stream() {
+            let sent = Arc::new(Mutex::new(Vec::new()));
+            let incoming = Incoming(
+                vec![
+                    r#"{"type":"connection_ack"}"#,
+                    r#"{"type":"ping"}"#,
+                    r#"{"type":"next","id":"1","payload":{"data":{"filmAdded":"Jaws"}}}"#,
+                    r#"{"type":"next","id":"2","payload":{"data":{"filmAdded":"Other"}}}"#,
+                    r#"{"type":"next","id":"1","payload":{"data":{"filmAdded":"Alien"}}}"#,
+                    r#"{"type":"complete","id":"1"}"#,
+                    r#"{"type":"next","id":"1","payload":{"data":{"filmAdded":"Late"}}}"#,
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            );
+
+            let mut stream = SubscriptionStream::new(subscription("filmAdded"), incoming, {
+                let sent = Arc::clone(&sent);
+                move |message| sent.lock().unwrap().push(message)
+            });
+
+            let films = collect(&mut stream)
+                .into_iter()
+                .map(|response| response.unwrap().data.unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(films, vec!["Jaws".to_string(), "Alien".to_string()]);
+
+            drop(stream);
+
+            let sent = sent
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| serde_json::from_str::<Value>(message).unwrap()["type"].clone())
+                .collect::<Vec<_>>();
+            assert_eq!(sent, vec!["connection_init", "subscribe", "pong"]);
+        }
+
+        #[test]
+        fn test_dropping_completes_subscription() {
+            let sent = Arc::new(Mutex::new(Vec::new()));
+            let incoming = Incoming(vec![r#"{"type":"connection_ack"}"#.to_string()].into());
+
+            let mut stream = SubscriptionStream::new(subscription("filmAdded"), incoming, {
+                let sent = Arc::clone(&sent);
+                move |message| sent.lock().unwrap().push(message)
+            });
+            assert!(collect(&mut stream).is_empty());
+            drop(stream);
+
+            let sent = sent.lock().unwrap();
+            assert_eq!(sent.len(), 3);
+            assert_eq!(sent[2], r#"{"type":"complete","id":"1"}"#);
+        }
+
+        #[test]
+        fn test_legacy_protocol() {
+            let sent = Arc::new(Mutex::new(Vec::new()));
+            let incoming = Incoming(
+                vec![
+                    r#"{"type":"connection_ack"}"#,
+                    r#"{"type":"ka"}"#,
+                    r#"{"type":"data","id":"1","payload":{"data":{"filmAdded":"Jaws"}}}"#,
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            );
+
+            let mut stream = SubscriptionStream::with_protocol(
+                crate::subscriptions_transport_ws::Connection::new(),
+                subscription("filmAdded"),
+                None,
+                incoming,
+                {
+                    let sent = Arc::clone(&sent);
+                    move |message| sent.lock().unwrap().push(message)
+                },
+            );
+
+            let films = collect(&mut stream)
+                .into_iter()
+                .map(|response| response.unwrap().data.unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(films, vec!["Jaws".to_string()]);
+            drop(stream);
+
+            let sent = sent
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|message| serde_json::from_str::<Value>(message).unwrap()["type"].clone())
+                .collect::<Vec<_>>();
+            assert_eq!(sent, vec!["connection_init", "start", "stop"]);
+        }
+
+        #[test]
+        fn test_subscription_errors() {
+            let incoming = Incoming(
+                vec![
+                    r#"{"type":"connection_ack"}"#,
+                    r#"{"type":"error","id":"1","payload":[{"message":"oops"}]}"#,
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            );
+
+            let mut stream = SubscriptionStream::new(subscription("filmAdded"), incoming, |_| {});
+
+            assert_matches!(
+                collect(&mut stream).as_slice(),
+                [Err(SubscriptionError::Errors(errors))] => assert_eq!(errors.len(), 1)
+            );
+        }
+    }
+}
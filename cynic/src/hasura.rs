@@ -0,0 +1,476 @@
+//! Helpers for Hasura's streaming subscriptions.
+//!
+//! Hasura generates a `_stream` field for each table, which streams rows in
+//! batches starting after the `initial_value` of a cursor column.  The
+//! server tracks the cursor while a subscription is running, but it's up to
+//! the client to carry on from the last row it received if it has to
+//! subscribe again.  A `CursorSubscription` keeps track of this, given a
+//! function that builds the subscription for a cursor:
+//!
+//! ```rust,ignore
+//! impl Batch for MessagesSubscription {
+//!     type Row = Message;
+//!     type Cursor = i32;
+//!
+//!     fn cursor(row: &Message) -> i32 {
+//!         row.id
+//!     }
+//!
+//!     fn into_rows(self) -> Vec<Message> {
+//!         self.messages_stream
+//!     }
+//! }
+//!
+//! let mut subscription = CursorSubscription::new(0, |id| {
+//!     MessagesSubscription::build(&MessagesArguments {
+//!         cursor: vec![StreamCursor::new("id", id, CursorOrdering::Asc)],
+//!     })
+//! });
+//! ```
+//!
+//! With the `graphql-ws` feature enabled, `CursorSubscription::into_stream`
+//! returns a `Stream` of every row, that subscribes again from the latest
+//! cursor whenever a subscription ends.
+
+use serde_json::Value;
+use std::marker::PhantomData;
+
+use crate::{
+    GraphQLError, GraphQLResponse, SerializableArgument, SerializeError, StreamingOperation,
+};
+
+/// The order a `_stream` field returns rows in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorOrdering {
+    Asc,
+    Desc,
+}
+
+/// The value of the `cursor` argument of a `_stream` field, which streams
+/// rows after the one where `column` is `initial_value`.
+///
+/// Hasura's cursor arguments are lists, so this usually needs to be passed
+/// inside a `Vec`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamCursor<T> {
+    pub column: String,
+    pub initial_value: T,
+    pub ordering: CursorOrdering,
+}
+
+impl<T> StreamCursor<T> {
+    pub fn new(column: impl Into<String>, initial_value: T, ordering: CursorOrdering) -> Self {
+        StreamCursor {
+            column: column.into(),
+            initial_value,
+            ordering,
+        }
+    }
+}
+
+impl<T: serde::Serialize> SerializableArgument for StreamCursor<T> {
+    fn serialize(&self) -> Result<Value, SerializeError> {
+        let mut initial_value = serde_json::Map::new();
+        initial_value.insert(
+            self.column.clone(),
+            serde_json::to_value(&self.initial_value)?,
+        );
+
+        let ordering = match self.ordering {
+            CursorOrdering::Asc => "ASC",
+            CursorOrdering::Desc => "DESC",
+        };
+
+        Ok(serde_json::json!({
+            "initial_value": initial_value,
+            "ordering": ordering,
+        }))
+    }
+}
+
+/// A batch of rows from a `_stream` field.
+///
+/// This is usually implemented for the `QueryFragment` of a streaming
+/// subscription, pulling the rows out of the field it contains.
+pub trait Batch {
+    type Row;
+    type Cursor: Clone;
+
+    /// The value of the cursor column of `row`.
+    fn cursor(row: &Self::Row) -> Self::Cursor;
+
+    /// The rows in this batch.
+    fn into_rows(self) -> Vec<Self::Row>;
+}
+
+/// Runs a Hasura streaming subscription, tracking the cursor of the last
+/// row received.
+///
+/// `CursorSubscription` doesn't start any subscriptions itself: `operation`
+/// returns the subscription that carries on from the latest cursor, and each
+/// response is passed back into `handle_response`.  `into_stream` does this
+/// for you.
+pub struct CursorSubscription<'a, ResponseData: Batch, Build> {
+    build: Build,
+    cursor: ResponseData::Cursor,
+    phantom: PhantomData<fn() -> StreamingOperation<'a, ResponseData>>,
+}
+
+impl<'a, ResponseData, Build> CursorSubscription<'a, ResponseData, Build>
+where
+    ResponseData: Batch + 'a,
+    Build: Fn(ResponseData::Cursor) -> StreamingOperation<'a, ResponseData>,
+{
+    /// Creates a CursorSubscription from a function that builds the
+    /// subscription, given the cursor to pass as its `initial_value`.
+    ///
+    /// The first subscription starts from `initial_cursor`.
+    pub fn new(initial_cursor: ResponseData::Cursor, build: Build) -> Self {
+        CursorSubscription {
+            build,
+            cursor: initial_cursor,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The cursor of the last row received.
+    pub fn cursor(&self) -> &ResponseData::Cursor {
+        &self.cursor
+    }
+
+    /// The subscription that streams rows after the last one received.
+    pub fn operation(&self) -> StreamingOperation<'a, ResponseData> {
+        (self.build)(self.cursor.clone())
+    }
+
+    /// Handles a response to the subscription returned by `operation`,
+    /// returning the rows it contained.
+    pub fn handle_response<E>(
+        &mut self,
+        response: Result<GraphQLResponse<ResponseData>, E>,
+    ) -> Result<Vec<ResponseData::Row>, StreamingError<E>> {
+        let response = response.map_err(StreamingError::Subscription)?;
+        if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
+            return Err(StreamingError::Errors(errors));
+        }
+
+        let rows = match response.data {
+            Some(batch) => batch.into_rows(),
+            None => vec![],
+        };
+        if let Some(row) = rows.last() {
+            self.cursor = ResponseData::cursor(row);
+        }
+
+        Ok(rows)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StreamingError<E> {
+    #[error("{0}")]
+    Subscription(E),
+    #[error("Batch failed with {} errors", .0.len())]
+    Errors(Vec<GraphQLError>),
+}
+
+#[cfg(feature = "graphql-ws")]
+pub use stream::CursorStream;
+
+#[cfg(feature = "graphql-ws")]
+mod stream {
+    use futures_core::Stream;
+    use std::{
+        collections::VecDeque,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use super::{Batch, CursorSubscription, StreamingError};
+    use crate::{GraphQLResponse, StreamingOperation};
+
+    /// The number of subscriptions in a row that can end without sending any
+    /// rows before a `CursorStream` gives up.
+    const MAX_EMPTY_SUBSCRIPTIONS: u32 = 3;
+
+    impl<'a, ResponseData, Build> CursorSubscription<'a, ResponseData, Build>
+    where
+        ResponseData: Batch + 'a,
+        Build: Fn(ResponseData::Cursor) -> StreamingOperation<'a, ResponseData>,
+    {
+        /// Converts this into a `Stream` of rows, that uses `subscribe` to
+        /// start each subscription - usually by creating a
+        /// `SubscriptionStream` on a new websocket.
+        pub fn into_stream<Subscribe, S, E>(
+            self,
+            subscribe: Subscribe,
+        ) -> CursorStream<'a, ResponseData, Build, Subscribe, S>
+        where
+            Subscribe: FnMut(StreamingOperation<'a, ResponseData>) -> S,
+            S: Stream<Item = Result<GraphQLResponse<ResponseData>, E>>,
+        {
+            CursorStream {
+                subscription: self,
+                subscribe,
+                current: None,
+                received_rows: false,
+                empty_subscriptions: 0,
+                rows: VecDeque::new(),
+                finished: false,
+            }
+        }
+    }
+
+    /// A `Stream` of every row from a Hasura streaming subscription.
+    ///
+    /// `_stream` subscriptions don't end by themselves, so whenever one does,
+    /// usually because the websocket was closed, the stream subscribes again
+    /// from the latest cursor.  The stream ends after the first error,
+    /// or if several subscriptions in a row end without sending any rows.
+    pub struct CursorStream<'a, ResponseData: Batch, Build, Subscribe, S> {
+        subscription: CursorSubscription<'a, ResponseData, Build>,
+        subscribe: Subscribe,
+        current: Option<S>,
+        received_rows: bool,
+        empty_subscriptions: u32,
+        rows: VecDeque<ResponseData::Row>,
+        finished: bool,
+    }
+
+    impl<'a, ResponseData, Build, Subscribe, S> CursorStream<'a, ResponseData, Build, Subscribe, S>
+    where
+        ResponseData: Batch + 'a,
+    {
+        /// The cursor of the last row received.
+        pub fn cursor(&self) -> &ResponseData::Cursor {
+            &self.subscription.cursor
+        }
+    }
+
+    impl<'a, ResponseData, Build, Subscribe, S, E> Stream
+        for CursorStream<'a, ResponseData, Build, Subscribe, S>
+    where
+        ResponseData: Batch + 'a,
+        ResponseData::Row: Unpin,
+        ResponseData::Cursor: Unpin,
+        Build: Fn(ResponseData::Cursor) -> StreamingOperation<'a, ResponseData> + Unpin,
+        Subscribe: FnMut(StreamingOperation<'a, ResponseData>) -> S + Unpin,
+        S: Stream<Item = Result<GraphQLResponse<ResponseData>, E>> + Unpin,
+    {
+        type Item = Result<ResponseData::Row, StreamingError<E>>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+
+            loop {
+                if let Some(row) = this.rows.pop_front() {
+                    return Poll::Ready(Some(Ok(row)));
+                }
+
+                if this.finished {
+                    return Poll::Ready(None);
+                }
+
+                if this.current.is_none() {
+                    this.current = Some((this.subscribe)(this.subscription.operation()));
+                    this.received_rows = false;
+                }
+
+                let response = match this.current.as_mut().map(|s| Pin::new(s).poll_next(cx)) {
+                    Some(Poll::Ready(Some(response))) => response,
+                    Some(Poll::Ready(None)) => {
+                        this.current = None;
+                        if this.received_rows {
+                            this.empty_subscriptions = 0;
+                        } else {
+                            this.empty_subscriptions += 1;
+                            this.finished = this.empty_subscriptions >= MAX_EMPTY_SUBSCRIPTIONS;
+                        }
+                        continue;
+                    }
+                    _ => return Poll::Pending,
+                };
+
+                match this.subscription.handle_response(response) {
+                    Ok(rows) => {
+                        this.received_rows |= !rows.is_empty();
+                        this.rows.extend(rows);
+                    }
+                    Err(e) => {
+                        this.finished = true;
+                        this.current = None;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use crate::{
+        selection_set::{field, integer, map, map2, string, vec},
+        testing::fixtures::Root,
+        Argument,
+    };
+
+    #[derive(Debug)]
+    struct Messages(Vec<(i32, String)>);
+
+    impl Batch for Messages {
+        type Row = (i32, String);
+        type Cursor = i32;
+
+        fn cursor(row: &(i32, String)) -> i32 {
+            row.0
+        }
+
+        fn into_rows(self) -> Vec<(i32, String)> {
+            self.0
+        }
+    }
+
+    fn messages_subscription(id: i32) -> StreamingOperation<'static, Messages> {
+        StreamingOperation::subscription(map(
+            Messages,
+            field::<_, Root, ()>(
+                "messages_stream",
+                vec![Argument::new(
+                    "cursor",
+                    "[messages_stream_cursor_input]!",
+                    vec![StreamCursor::new("id", id, CursorOrdering::Asc)],
+                )],
+                vec(map2(
+                    |id, text| (id, text),
+                    field("id", vec![], integer()),
+                    field("text", vec![], string()),
+                )),
+            ),
+        ))
+    }
+
+    fn batch(rows: &[(i32, &str)]) -> GraphQLResponse<Messages> {
+        GraphQLResponse {
+            data: Some(Messages(
+                rows.iter()
+                    .map(|(id, text)| (*id, text.to_string()))
+                    .collect(),
+            )),
+            errors: None,
+            extensions: None,
+        }
+    }
+
+    #[test]
+    fn test_cursor_arguments() {
+        let operation = serde_json::to_value(&messages_subscription(12)).unwrap();
+
+        assert_eq!(
+            operation["variables"]["_0"],
+            json!([{ "initial_value": { "id": 12 }, "ordering": "ASC" }])
+        );
+    }
+
+    #[test]
+    fn test_tracking_cursors() {
+        let mut subscription = CursorSubscription::new(0, messages_subscription);
+
+        let rows = subscription
+            .handle_response(Ok::<_, ()>(batch(&[(1, "a"), (2, "b")])))
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(*subscription.cursor(), 2);
+
+        subscription
+            .handle_response(Ok::<_, ()>(batch(&[])))
+            .unwrap();
+        assert_eq!(*subscription.cursor(), 2);
+
+        let variables =
+            serde_json::to_value(&subscription.operation()).unwrap()["variables"].clone();
+        assert_eq!(variables["_0"][0]["initial_value"], json!({ "id": 2 }));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut subscription = CursorSubscription::new(0, messages_subscription);
+
+        assert_matches!(
+            subscription.handle_response(Err("closed")),
+            Err(StreamingError::Subscription("closed"))
+        );
+        assert_matches!(
+            subscription.handle_response(Ok::<_, ()>(GraphQLResponse {
+                data: None,
+                errors: Some(vec![GraphQLError::new("oops".into(), None, None, None)]),
+                extensions: None,
+            })),
+            Err(StreamingError::Errors(errors)) => assert_eq!(errors[0].message, "oops")
+        );
+    }
+
+    #[cfg(feature = "graphql-ws")]
+    #[test]
+    fn test_cursor_stream() {
+        use futures_core::Stream;
+        use std::{
+            collections::VecDeque,
+            pin::Pin,
+            task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+        };
+
+        fn noop_waker() -> Waker {
+            fn clone(_: *const ()) -> RawWaker {
+                RawWaker::new(std::ptr::null(), &VTABLE)
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+            unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+        }
+
+        struct Responses(VecDeque<GraphQLResponse<Messages>>);
+
+        impl Stream for Responses {
+            type Item = Result<GraphQLResponse<Messages>, ()>;
+
+            fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+                Poll::Ready(self.get_mut().0.pop_front().map(Ok))
+            }
+        }
+
+        // Each subscription sends one batch, then the connection drops.
+        let mut cursors = vec![];
+        let mut stream =
+            CursorSubscription::new(0, messages_subscription).into_stream(|operation| {
+                let variables = serde_json::to_value(&operation).unwrap()["variables"].clone();
+                let cursor = variables["_0"][0]["initial_value"]["id"].as_i64().unwrap() as i32;
+                cursors.push(cursor);
+
+                Responses(match cursor {
+                    0 => vec![batch(&[(1, "a"), (2, "b")])].into(),
+                    2 => vec![batch(&[(3, "c")])].into(),
+                    _ => VecDeque::new(),
+                })
+            });
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut rows = vec![];
+        while let Poll::Ready(Some(row)) = Pin::new(&mut stream).poll_next(&mut cx) {
+            rows.push(row.unwrap().0);
+        }
+        assert_eq!(*stream.cursor(), 3);
+        drop(stream);
+
+        assert_eq!(rows, vec![1, 2, 3]);
+        // Subscriptions that don't send anything are only retried a few times.
+        assert_eq!(cursors, vec![0, 2, 3, 3, 3]);
+    }
+}
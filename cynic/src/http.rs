@@ -4,22 +4,27 @@
 //! heavy dependencies, and there's several options to choose from.
 
 #[cfg(feature = "surf")]
-pub use self::surf_ext::SurfExt;
+pub use self::surf_ext::{SurfClient, SurfExt};
 
 #[cfg(feature = "reqwest")]
-pub use reqwest_ext::ReqwestExt;
+pub use reqwest_ext::{IncrementalReqwestResponse, ReqwestClient, ReqwestExt, SseReqwestResponse};
 
 #[cfg(feature = "reqwest-blocking")]
 pub use reqwest_blocking_ext::ReqwestBlockingExt;
 
+#[cfg(feature = "wasm")]
+pub use fetch_ext::{CynicFetchError, FetchError, FetchRequest};
+
 #[cfg(feature = "surf")]
 mod surf_ext {
-    use serde_json::json;
-    use std::{future::Future, pin::Pin};
-
-    use crate::{GraphQLResponse, Operation};
+    use std::sync::Mutex;
 
-    type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+    use crate::{
+        batch::{BatchOperation, BatchResponse},
+        client::{self, BoxFuture, ClientError, GraphQlClient},
+        middleware::Middleware,
+        GraphQLResponse, Operation,
+    };
 
     /// An extension trait for surf::RequestBuilder.
     ///
@@ -80,40 +85,289 @@ mod surf_ext {
             self,
             operation: Operation<'a, ResponseData>,
         ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, surf::Error>>;
+
+        /// Runs a GraphQL query as with `run_graphql`, calling the hooks of
+        /// `middleware` before the request is sent & after the response is
+        /// received.
+        fn run_graphql_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, surf::Error>>;
+
+        /// Runs a GraphQL query as with `run_graphql`, but sends the bare
+        /// query document with an `application/graphql` content type.  Any
+        /// variables and the operation name are sent in the URL query string.
+        fn run_graphql_raw<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, surf::Error>>;
+
+        /// Runs a GraphQL query as with `run_graphql_raw`, calling the hooks
+        /// of `middleware` before the request is sent & after the response is
+        /// received.
+        fn run_graphql_raw_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, surf::Error>>;
+
+        /// Sends every operation in a `BatchOperation` in a single request.
+        ///
+        /// The response to each operation can be decoded with the
+        /// `BatchEntry` returned when it was added to the batch.
+        fn run_graphql_batch(
+            self,
+            batch: &BatchOperation,
+        ) -> BoxFuture<'_, Result<BatchResponse, surf::Error>>;
+
+        /// Sends a batch as with `run_graphql_batch`, calling the hooks of
+        /// `middleware` for each operation in the batch.
+        fn run_graphql_batch_with_middleware<'a>(
+            self,
+            batch: &'a BatchOperation,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<BatchResponse, surf::Error>>;
     }
 
     impl SurfExt for surf::RequestBuilder {
         fn run_graphql<'a, ResponseData: 'a>(
             self,
             operation: Operation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, surf::Error>> {
+            self.run_graphql_with_middleware(operation, &())
+        }
+
+        fn run_graphql_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
         ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, surf::Error>> {
             Box::pin(async move {
-                self.body(json!(&operation))
-                    .recv_json::<GraphQLResponse<serde_json::Value>>()
+                let client = BuilderClient(Mutex::new(Some(self)));
+                client
+                    .run_graphql_with_middleware(operation, middleware)
                     .await
-                    .and_then(|response| operation.decode_response(response).map_err(|e| e.into()))
+                    .map_err(into_surf_error)
+            })
+        }
+
+        fn run_graphql_raw<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, surf::Error>> {
+            self.run_graphql_raw_with_middleware(operation, &())
+        }
+
+        fn run_graphql_raw_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, surf::Error>> {
+            let span = crate::logging::operation_span(&operation);
+            Box::pin(crate::logging::instrument(span, async move {
+                crate::logging::log_operation(&operation);
+
+                let parameters =
+                    crate::logging::serializing(|| operation.query_string_parameters())?;
+                let builder = self
+                    .query(&parameters)?
+                    .content_type("application/graphql")
+                    .body(operation.query.clone());
+                let builder =
+                    with_headers(builder, client::operation_headers(&operation, middleware)?);
+                let response = into_response(builder.await?).await?;
+
+                let body = client::handle_response(&operation, &response, middleware)
+                    .map_err(into_surf_error)?;
+                Ok(client::decode_response(&operation, body)?)
+            }))
+        }
+
+        fn run_graphql_batch(
+            self,
+            batch: &BatchOperation,
+        ) -> BoxFuture<'_, Result<BatchResponse, surf::Error>> {
+            self.run_graphql_batch_with_middleware(batch, &())
+        }
+
+        fn run_graphql_batch_with_middleware<'a>(
+            self,
+            batch: &'a BatchOperation,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<BatchResponse, surf::Error>> {
+            Box::pin(async move {
+                for operation in batch.operations() {
+                    crate::logging::log_operation(operation);
+                }
+
+                let request =
+                    client::batch_request_for(batch, middleware).map_err(into_surf_error)?;
+                let response = send(self, request).await?;
+
+                client::handle_batch_response(batch, &response, middleware).map_err(into_surf_error)
             })
         }
     }
+
+    /// A `GraphQlClient` that POSTs operations to a URL with surf.
+    ///
+    /// This doesn't support everything `SurfExt` does, but can be used
+    /// anywhere that accepts any `GraphQlClient`.
+    #[derive(Clone, Debug)]
+    pub struct SurfClient {
+        client: surf::Client,
+        url: String,
+    }
+
+    impl SurfClient {
+        pub fn new(client: surf::Client, url: impl Into<String>) -> Self {
+            SurfClient {
+                client,
+                url: url.into(),
+            }
+        }
+    }
+
+    impl GraphQlClient for SurfClient {
+        type Error = surf::Error;
+
+        fn send(
+            &self,
+            request: client::Request,
+        ) -> BoxFuture<'_, Result<client::Response, surf::Error>> {
+            Box::pin(send(self.client.post(&self.url), request))
+        }
+    }
+
+    /// Sends a single request with a `RequestBuilder`, so that `SurfExt` can
+    /// run operations with the same implementation as any other client.
+    struct BuilderClient(Mutex<Option<surf::RequestBuilder>>);
+
+    impl GraphQlClient for BuilderClient {
+        type Error = surf::Error;
+
+        fn send(
+            &self,
+            request: client::Request,
+        ) -> BoxFuture<'_, Result<client::Response, surf::Error>> {
+            let builder = self
+                .0
+                .lock()
+                .unwrap()
+                .take()
+                .expect("BuilderClient to only send one request");
+
+            Box::pin(send(builder, request))
+        }
+    }
+
+    async fn send(
+        builder: surf::RequestBuilder,
+        request: client::Request,
+    ) -> Result<client::Response, surf::Error> {
+        let builder = with_headers(builder.body(request.body), request.headers);
+
+        into_response(builder.await?).await
+    }
+
+    async fn into_response(mut response: surf::Response) -> Result<client::Response, surf::Error> {
+        let headers = response
+            .iter()
+            .map(|(name, values)| (name.to_string(), values.last().to_string()))
+            .collect();
+
+        Ok(client::Response {
+            status: response.status().into(),
+            headers,
+            body: response.body_bytes().await?,
+        })
+    }
+
+    fn with_headers(
+        mut builder: surf::RequestBuilder,
+        headers: Vec<(String, String)>,
+    ) -> surf::RequestBuilder {
+        for (name, value) in headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        builder
+    }
+
+    /// surf doesn't turn unsuccessful statuses into errors, so only IO errors
+    /// like timeouts & connection failures are retried.
+    impl crate::retry::RetryableError for surf::Error {
+        fn http_status(&self) -> Option<u16> {
+            None
+        }
+
+        fn is_transport_error(&self) -> bool {
+            self.downcast_ref::<std::io::Error>().is_some()
+        }
+    }
+
+    /// Converts the errors from a `GraphQlClient` into a `surf::Error`.
+    ///
+    /// Errors from surf itself are returned as they are, and anything else is
+    /// wrapped so it can be obtained with `downcast_ref`.
+    fn into_surf_error(error: ClientError<surf::Error>) -> surf::Error {
+        match error {
+            ClientError::TransportError(error) => error,
+            ClientError::DecodeError(error) => error.into(),
+            ClientError::SerializeError(error) => error.into(),
+            ClientError::MiddlewareError(error) => error.into(),
+            error => error.into(),
+        }
+    }
 }
 
+/// The errors the reqwest integrations can fail with.
 #[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
-#[derive(thiserror::Error, Debug)]
-pub enum CynicReqwestError {
-    #[error("Error making HTTP request: {0}")]
-    ReqwestError(#[from] reqwest::Error),
-    #[error("Error decoding GraphQL response: {0}")]
-    DecodeError(#[from] json_decode::DecodeError),
+pub type CynicReqwestError = crate::client::ClientError<reqwest::Error>;
+
+#[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
+impl From<reqwest::Error> for crate::client::ClientError<reqwest::Error> {
+    fn from(error: reqwest::Error) -> Self {
+        crate::client::ClientError::TransportError(error)
+    }
+}
+
+#[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
+impl crate::retry::RetryableError for reqwest::Error {
+    fn http_status(&self) -> Option<u16> {
+        self.status().map(|status| status.as_u16())
+    }
+
+    fn is_transport_error(&self) -> bool {
+        self.is_timeout() || self.is_request()
+    }
+}
+
+/// Copies the headers of a reqwest response, skipping any that aren't valid
+/// strings.
+#[cfg(any(feature = "reqwest", feature = "reqwest-blocking"))]
+fn response_headers(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
 }
 
 #[cfg(feature = "reqwest")]
 mod reqwest_ext {
     use super::CynicReqwestError;
-    use std::{future::Future, pin::Pin};
-
-    use crate::{GraphQLResponse, Operation};
+    use std::sync::Mutex;
 
-    type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+    #[cfg(feature = "persisted-queries")]
+    use crate::persisted_queries::PersistedQueries;
+    use crate::{
+        batch::{BatchOperation, BatchResponse},
+        client::{self, BoxFuture, GraphQlClient},
+        graphql_sse::{self, Connection},
+        incremental::{IncrementalResponse, MultipartMixed},
+        middleware::{self, Middleware},
+        GraphQLResponse, Operation, StreamingOperation,
+    };
 
     /// An extension trait for reqwest::RequestBuilder.
     ///
@@ -175,33 +429,621 @@ mod reqwest_ext {
             self,
             operation: Operation<'a, ResponseData>,
         ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>>;
+
+        /// Runs a GraphQL query as with `run_graphql`, calling the hooks of
+        /// `middleware` before the request is sent & after the response is
+        /// received.
+        fn run_graphql_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>>;
+
+        /// Runs a GraphQL query as with `run_graphql`, but sends the bare
+        /// query document with an `application/graphql` content type.  Any
+        /// variables and the operation name are sent in the URL query string.
+        fn run_graphql_raw<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>>;
+
+        /// Runs a GraphQL query as with `run_graphql_raw`, calling the hooks
+        /// of `middleware` before the request is sent & after the response is
+        /// received.
+        fn run_graphql_raw_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>>;
+
+        /// Runs a GraphQL query as with `run_graphql`, but uses Automatic
+        /// Persisted Queries to avoid sending the full query document where
+        /// possible.
+        ///
+        /// The request is sent again with the full document if the server
+        /// doesn't recognise the query - unless the RequestBuilder already
+        /// contains a body that can't be cloned.
+        #[cfg(feature = "persisted-queries")]
+        fn run_graphql_persisted<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            persisted_queries: &'a PersistedQueries,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>>;
+
+        /// Runs a GraphQL query as with `run_graphql_persisted`, calling the
+        /// hooks of `middleware` around each request that's sent.
+        #[cfg(feature = "persisted-queries")]
+        fn run_graphql_persisted_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            persisted_queries: &'a PersistedQueries,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>>;
+
+        /// Sends every operation in a `BatchOperation` in a single request.
+        ///
+        /// The response to each operation can be decoded with the
+        /// `BatchEntry` returned when it was added to the batch.
+        fn run_graphql_batch(
+            self,
+            batch: &BatchOperation,
+        ) -> BoxFuture<'_, Result<BatchResponse, CynicReqwestError>>;
+
+        /// Sends a batch as with `run_graphql_batch`, calling the hooks of
+        /// `middleware` for each operation in the batch.
+        fn run_graphql_batch_with_middleware<'a>(
+            self,
+            batch: &'a BatchOperation,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<BatchResponse, CynicReqwestError>>;
+
+        /// Runs a GraphQL query that uses `@defer` or `@stream`.
+        ///
+        /// This returns as soon as the server starts responding - call `next`
+        /// on the result to wait for each payload the server sends.
+        fn run_graphql_incremental<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<IncrementalReqwestResponse<'a, ResponseData>, CynicReqwestError>>;
+
+        /// Runs a GraphQL query as with `run_graphql_incremental`, calling
+        /// the `before_request` hook of `middleware` before the request is
+        /// sent & the `after_response` hook after each payload is received.
+        fn run_graphql_incremental_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<IncrementalReqwestResponse<'a, ResponseData>, CynicReqwestError>>;
+
+        /// Runs a subscription using the `graphql-sse` protocol.
+        ///
+        /// This returns as soon as the server starts responding - call `next`
+        /// on the result to wait for each response.  If the connection drops
+        /// before the subscription completes it's resumed with a
+        /// `Last-Event-ID` header, unless the RequestBuilder contains a body
+        /// that can't be cloned.
+        fn run_graphql_sse<'a, ResponseData: 'a>(
+            self,
+            operation: StreamingOperation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<SseReqwestResponse<'a, ResponseData>, CynicReqwestError>>;
+
+        /// Runs a subscription as with `run_graphql_sse`, calling the
+        /// `before_request` hook of `middleware` before each request is sent
+        /// (including any to resume the subscription) & the `after_response`
+        /// hook after each response is received.
+        fn run_graphql_sse_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: StreamingOperation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<SseReqwestResponse<'a, ResponseData>, CynicReqwestError>>;
     }
 
     impl ReqwestExt for reqwest::RequestBuilder {
         fn run_graphql<'a, ResponseData: 'a>(
             self,
             operation: Operation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>> {
+            self.run_graphql_with_middleware(operation, &())
+        }
+
+        fn run_graphql_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
         ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>> {
             Box::pin(async move {
-                match self
-                    .json(&operation)
-                    .send()
-                    //.recv_json::<GraphQLResponse<serde_json::Value>>()
+                let client = BuilderClient(Mutex::new(Some(self)));
+                client
+                    .run_graphql_with_middleware(operation, middleware)
                     .await
-                {
-                    Ok(response) => response
-                        .json::<GraphQLResponse<serde_json::Value>>()
-                        .await
-                        .map_err(CynicReqwestError::ReqwestError)
-                        .and_then(|gql_response| {
-                            operation
-                                .decode_response(gql_response)
-                                .map_err(CynicReqwestError::DecodeError)
-                        }),
-                    Err(e) => Err(CynicReqwestError::ReqwestError(e)),
+            })
+        }
+
+        fn run_graphql_raw<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>> {
+            self.run_graphql_raw_with_middleware(operation, &())
+        }
+
+        fn run_graphql_raw_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>> {
+            let span = crate::logging::operation_span(&operation);
+            Box::pin(crate::logging::instrument(span, async move {
+                crate::logging::log_operation(&operation);
+
+                let parameters =
+                    crate::logging::serializing(|| operation.query_string_parameters())?;
+                let builder = self
+                    .query(&parameters)
+                    .header(reqwest::header::CONTENT_TYPE, "application/graphql")
+                    .body(operation.query.clone());
+                let response =
+                    with_headers(builder, client::operation_headers(&operation, middleware)?)
+                        .send()
+                        .await?;
+                let gql_response = recv_response(response, &operation, middleware).await?;
+
+                Ok(client::decode_response(&operation, gql_response)?)
+            }))
+        }
+
+        #[cfg(feature = "persisted-queries")]
+        fn run_graphql_persisted<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            persisted_queries: &'a PersistedQueries,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>> {
+            self.run_graphql_persisted_with_middleware(operation, persisted_queries, &())
+        }
+
+        #[cfg(feature = "persisted-queries")]
+        fn run_graphql_persisted_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            persisted_queries: &'a PersistedQueries,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<GraphQLResponse<ResponseData>, CynicReqwestError>> {
+            let span = crate::logging::operation_span(&operation);
+            Box::pin(crate::logging::instrument(span, async move {
+                crate::logging::log_operation(&operation);
+
+                let retry = self.try_clone();
+                let body =
+                    crate::logging::serializing(|| persisted_queries.request_body(&operation))?;
+                let headers = client::operation_headers(&operation, middleware)?;
+                let response = with_headers(self, headers.clone())
+                    .json(&body)
+                    .send()
+                    .await?;
+                let mut gql_response = recv_response(response, &operation, middleware).await?;
+
+                if persisted_queries.handle_response(&operation, &gql_response) {
+                    if let Some(retry) = retry {
+                        let body = crate::logging::serializing(|| {
+                            persisted_queries.registration_body(&operation)
+                        })?;
+                        let response = with_headers(retry, headers).json(&body).send().await?;
+                        gql_response = recv_response(response, &operation, middleware).await?;
+                        persisted_queries.handle_response(&operation, &gql_response);
+                    }
+                }
+
+                Ok(client::decode_response(&operation, gql_response)?)
+            }))
+        }
+
+        fn run_graphql_batch(
+            self,
+            batch: &BatchOperation,
+        ) -> BoxFuture<'_, Result<BatchResponse, CynicReqwestError>> {
+            self.run_graphql_batch_with_middleware(batch, &())
+        }
+
+        fn run_graphql_batch_with_middleware<'a>(
+            self,
+            batch: &'a BatchOperation,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<BatchResponse, CynicReqwestError>> {
+            Box::pin(async move {
+                for operation in batch.operations() {
+                    crate::logging::log_operation(operation);
                 }
+
+                let request = client::batch_request_for::<reqwest::Error>(batch, middleware)?;
+                let response = send(self, request).await?;
+
+                client::handle_batch_response(batch, &response, middleware)
             })
         }
+
+        fn run_graphql_incremental<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<IncrementalReqwestResponse<'a, ResponseData>, CynicReqwestError>>
+        {
+            self.run_graphql_incremental_with_middleware(operation, &())
+        }
+
+        fn run_graphql_incremental_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<IncrementalReqwestResponse<'a, ResponseData>, CynicReqwestError>>
+        {
+            Box::pin(async move {
+                crate::logging::log_operation(&operation);
+
+                let request = client::request_for::<_, reqwest::Error>(&operation, middleware)?;
+                let response = with_request(self, request)
+                    .header(
+                        reqwest::header::ACCEPT,
+                        "multipart/mixed; deferSpec=20220824, application/json",
+                    )
+                    .send()
+                    .await?;
+
+                let parser = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|content_type| content_type.to_str().ok())
+                    .and_then(MultipartMixed::from_content_type);
+
+                Ok(IncrementalReqwestResponse {
+                    status: response.status().as_u16(),
+                    headers: super::response_headers(response.headers()),
+                    response: Some(response),
+                    parser,
+                    incremental: IncrementalResponse::new(operation),
+                    middleware,
+                })
+            })
+        }
+
+        fn run_graphql_sse<'a, ResponseData: 'a>(
+            self,
+            operation: StreamingOperation<'a, ResponseData>,
+        ) -> BoxFuture<'a, Result<SseReqwestResponse<'a, ResponseData>, CynicReqwestError>>
+        {
+            self.run_graphql_sse_with_middleware(operation, &())
+        }
+
+        fn run_graphql_sse_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: StreamingOperation<'a, ResponseData>,
+            middleware: &'a dyn Middleware,
+        ) -> BoxFuture<'a, Result<SseReqwestResponse<'a, ResponseData>, CynicReqwestError>>
+        {
+            Box::pin(async move {
+                let connection = Connection::new();
+                let body = connection.request_body(&operation)?;
+
+                let reconnect = self.try_clone();
+                let response =
+                    send_sse_request(self, &body, &connection, &operation, middleware).await?;
+
+                Ok(SseReqwestResponse {
+                    status: response.status().as_u16(),
+                    headers: super::response_headers(response.headers()),
+                    operation,
+                    connection,
+                    body,
+                    reconnect,
+                    reconnects: 0,
+                    response: Some(response),
+                    middleware,
+                })
+            })
+        }
+    }
+
+    /// The number of times in a row a `graphql-sse` subscription is resumed
+    /// without receiving any responses before giving up.
+    const MAX_SSE_RECONNECTS: u32 = 3;
+
+    /// A subscription started with `run_graphql_sse`.
+    pub struct SseReqwestResponse<'a, ResponseData> {
+        operation: StreamingOperation<'a, ResponseData>,
+        connection: Connection,
+        body: serde_json::Value,
+        reconnect: Option<reqwest::RequestBuilder>,
+        reconnects: u32,
+        response: Option<reqwest::Response>,
+        status: u16,
+        headers: Vec<(String, String)>,
+        middleware: &'a dyn Middleware,
+    }
+
+    impl<'a, ResponseData: 'a> SseReqwestResponse<'a, ResponseData> {
+        /// Waits for the next response from the server.
+        ///
+        /// Returns `None` once the subscription has completed.
+        pub async fn next(
+            &mut self,
+        ) -> Option<Result<GraphQLResponse<ResponseData>, CynicReqwestError>> {
+            loop {
+                match self.connection.next_event() {
+                    Some(Ok(graphql_sse::Event::Next(response))) => {
+                        self.reconnects = 0;
+                        middleware::inspect_response(
+                            self.middleware,
+                            self.operation.as_operation(),
+                            self.status,
+                            &self.headers,
+                            &response,
+                        );
+                        return Some(
+                            self.operation
+                                .decode_response(response)
+                                .map_err(CynicReqwestError::from),
+                        );
+                    }
+                    Some(Ok(graphql_sse::Event::Complete)) => {
+                        self.response = None;
+                        return None;
+                    }
+                    Some(Err(e)) => {
+                        self.response = None;
+                        return Some(Err(e.into()));
+                    }
+                    None => {}
+                }
+
+                let response = self.response.as_mut()?;
+                match response.chunk().await {
+                    Ok(Some(chunk)) => self.connection.push(&chunk),
+                    // The connection dropped before the subscription
+                    // completed, so try to resume it.
+                    Ok(None) | Err(_) => {
+                        if let Err(e) = self.reconnect().await {
+                            return Some(Err(e));
+                        }
+                    }
+                }
+            }
+        }
+
+        async fn reconnect(&mut self) -> Result<(), CynicReqwestError> {
+            self.response = None;
+
+            if self.reconnects >= MAX_SSE_RECONNECTS {
+                return Ok(());
+            }
+            let builder = match self.reconnect.as_ref().and_then(|b| b.try_clone()) {
+                Some(builder) => builder,
+                None => return Ok(()),
+            };
+
+            self.reconnects += 1;
+            self.connection.reconnected();
+            let response = send_sse_request(
+                builder,
+                &self.body,
+                &self.connection,
+                &self.operation,
+                self.middleware,
+            )
+            .await?;
+
+            self.status = response.status().as_u16();
+            self.headers = super::response_headers(response.headers());
+            self.response = Some(response);
+
+            Ok(())
+        }
+    }
+
+    /// Sends a `graphql-sse` request, checking the server accepted it.
+    async fn send_sse_request<ResponseData>(
+        mut builder: reqwest::RequestBuilder,
+        body: &serde_json::Value,
+        connection: &Connection,
+        operation: &StreamingOperation<'_, ResponseData>,
+        middleware: &dyn Middleware,
+    ) -> Result<reqwest::Response, CynicReqwestError> {
+        builder = with_headers(
+            builder,
+            client::operation_headers(operation.as_operation(), middleware)?,
+        );
+        for (name, value) in connection.reconnect_headers() {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder
+            .header(reqwest::header::ACCEPT, graphql_sse::ACCEPT)
+            .json(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(CynicReqwestError::HttpError {
+                status: status.as_u16(),
+                body: response.text().await?,
+            });
+        }
+
+        Ok(response)
+    }
+
+    /// The response to an operation sent with `run_graphql_incremental`.
+    pub struct IncrementalReqwestResponse<'a, ResponseData> {
+        response: Option<reqwest::Response>,
+        parser: Option<MultipartMixed>,
+        incremental: IncrementalResponse<'a, ResponseData>,
+        status: u16,
+        headers: Vec<(String, String)>,
+        middleware: &'a dyn Middleware,
+    }
+
+    impl<'a, ResponseData: 'a> IncrementalReqwestResponse<'a, ResponseData> {
+        /// Waits for the next payload from the server, and returns the
+        /// response including everything that's been delivered so far.
+        ///
+        /// Returns `None` once the server has sent every payload.
+        pub async fn next(
+            &mut self,
+        ) -> Option<Result<GraphQLResponse<ResponseData>, CynicReqwestError>> {
+            let payload = match self.next_payload().await {
+                Ok(Some(payload)) => payload,
+                Ok(None) => return None,
+                Err(e) => {
+                    self.response = None;
+                    return Some(Err(e));
+                }
+            };
+
+            if let Err(e) = self.incremental.apply(payload) {
+                self.response = None;
+                return Some(Err(e.into()));
+            }
+
+            if !self.incremental.has_next() {
+                self.response = None;
+            }
+
+            let response = self.incremental.raw_response();
+            let operation = self.incremental.operation();
+            middleware::inspect_response(
+                self.middleware,
+                operation,
+                self.status,
+                &self.headers,
+                &response,
+            );
+
+            Some(
+                operation
+                    .decode_response(response)
+                    .map_err(CynicReqwestError::from),
+            )
+        }
+
+        async fn next_payload(&mut self) -> Result<Option<serde_json::Value>, CynicReqwestError> {
+            let parser = match &mut self.parser {
+                Some(parser) => parser,
+                // Servers that don't support incremental delivery send a
+                // single JSON response.
+                None => {
+                    return match self.response.take() {
+                        Some(response) => Ok(Some(response.json().await?)),
+                        None => Ok(None),
+                    }
+                }
+            };
+
+            while let Some(response) = &mut self.response {
+                if let Some(payload) = parser.next_payload() {
+                    return Ok(Some(payload?));
+                }
+
+                match response.chunk().await? {
+                    Some(chunk) => parser.push(&chunk),
+                    None => self.response = None,
+                }
+            }
+
+            Ok(None)
+        }
+    }
+
+    /// A `GraphQlClient` that POSTs operations to a URL with reqwest.
+    ///
+    /// This doesn't support everything `ReqwestExt` does, but can be used
+    /// anywhere that accepts any `GraphQlClient`.
+    #[derive(Clone, Debug)]
+    pub struct ReqwestClient {
+        client: reqwest::Client,
+        url: String,
+    }
+
+    impl ReqwestClient {
+        pub fn new(client: reqwest::Client, url: impl Into<String>) -> Self {
+            ReqwestClient {
+                client,
+                url: url.into(),
+            }
+        }
+    }
+
+    impl GraphQlClient for ReqwestClient {
+        type Error = reqwest::Error;
+
+        fn send(
+            &self,
+            request: client::Request,
+        ) -> BoxFuture<'_, Result<client::Response, reqwest::Error>> {
+            Box::pin(send(self.client.post(&self.url), request))
+        }
+    }
+
+    /// Sends a single request with a `RequestBuilder`, so that `ReqwestExt`
+    /// can run operations with the same implementation as any other client.
+    struct BuilderClient(Mutex<Option<reqwest::RequestBuilder>>);
+
+    impl GraphQlClient for BuilderClient {
+        type Error = reqwest::Error;
+
+        fn send(
+            &self,
+            request: client::Request,
+        ) -> BoxFuture<'_, Result<client::Response, reqwest::Error>> {
+            let builder = self
+                .0
+                .lock()
+                .unwrap()
+                .take()
+                .expect("BuilderClient to only send one request");
+
+            Box::pin(send(builder, request))
+        }
+    }
+
+    async fn send(
+        builder: reqwest::RequestBuilder,
+        request: client::Request,
+    ) -> Result<client::Response, reqwest::Error> {
+        into_response(with_request(builder, request).send().await?).await
+    }
+
+    async fn into_response(
+        response: reqwest::Response,
+    ) -> Result<client::Response, reqwest::Error> {
+        Ok(client::Response {
+            status: response.status().as_u16(),
+            headers: super::response_headers(response.headers()),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+
+    /// Records the status of `response`, parses its body & passes it to
+    /// `middleware`.
+    async fn recv_response<ResponseData>(
+        response: reqwest::Response,
+        operation: &Operation<'_, ResponseData>,
+        middleware: &dyn Middleware,
+    ) -> Result<GraphQLResponse<serde_json::Value>, CynicReqwestError> {
+        client::handle_response(operation, &into_response(response).await?, middleware)
+    }
+
+    fn with_request(
+        builder: reqwest::RequestBuilder,
+        request: client::Request,
+    ) -> reqwest::RequestBuilder {
+        with_headers(builder.body(request.body), request.headers)
+    }
+
+    fn with_headers(
+        mut builder: reqwest::RequestBuilder,
+        headers: Vec<(String, String)>,
+    ) -> reqwest::RequestBuilder {
+        for (name, value) in headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        builder
     }
 }
 
@@ -209,7 +1051,15 @@ mod reqwest_ext {
 mod reqwest_blocking_ext {
     use super::CynicReqwestError;
 
-    use crate::{GraphQLResponse, Operation};
+    #[cfg(feature = "persisted-queries")]
+    use crate::persisted_queries::PersistedQueries;
+    use crate::{
+        batch::{BatchOperation, BatchResponse},
+        client,
+        middleware::Middleware,
+        retry::RetryPolicy,
+        GraphQLResponse, Operation,
+    };
 
     /// An extension trait for reqwest::blocking::RequestBuilder.
     ///
@@ -268,6 +1118,84 @@ mod reqwest_blocking_ext {
             self,
             operation: Operation<'a, ResponseData>,
         ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError>;
+
+        /// Runs a GraphQL query as with `run_graphql`, calling the hooks of
+        /// `middleware` before the request is sent & after the response is
+        /// received.
+        fn run_graphql_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &dyn Middleware,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError>;
+
+        /// Runs a GraphQL query as with `run_graphql`, retrying any
+        /// transient failures according to `policy`.
+        ///
+        /// The request is only sent once if the RequestBuilder contains a
+        /// body that can't be cloned.
+        fn run_graphql_with_retries<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            policy: &RetryPolicy,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError>;
+
+        /// Runs a GraphQL query as with `run_graphql`, but sends the bare
+        /// query document with an `application/graphql` content type.  Any
+        /// variables and the operation name are sent in the URL query string.
+        fn run_graphql_raw<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError>;
+
+        /// Runs a GraphQL query as with `run_graphql_raw`, calling the hooks
+        /// of `middleware` before the request is sent & after the response is
+        /// received.
+        fn run_graphql_raw_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &dyn Middleware,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError>;
+
+        /// Runs a GraphQL query as with `run_graphql`, but uses Automatic
+        /// Persisted Queries to avoid sending the full query document where
+        /// possible.
+        ///
+        /// The request is sent again with the full document if the server
+        /// doesn't recognise the query - unless the RequestBuilder already
+        /// contains a body that can't be cloned.
+        #[cfg(feature = "persisted-queries")]
+        fn run_graphql_persisted<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            persisted_queries: &PersistedQueries,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError>;
+
+        /// Runs a GraphQL query as with `run_graphql_persisted`, calling the
+        /// hooks of `middleware` around each request that's sent.
+        #[cfg(feature = "persisted-queries")]
+        fn run_graphql_persisted_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            persisted_queries: &PersistedQueries,
+            middleware: &dyn Middleware,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError>;
+
+        /// Sends every operation in a `BatchOperation` in a single request.
+        ///
+        /// The response to each operation can be decoded with the
+        /// `BatchEntry` returned when it was added to the batch.
+        fn run_graphql_batch(
+            self,
+            batch: &BatchOperation,
+        ) -> Result<BatchResponse, CynicReqwestError>;
+
+        /// Sends a batch as with `run_graphql_batch`, calling the hooks of
+        /// `middleware` for each operation in the batch.
+        fn run_graphql_batch_with_middleware(
+            self,
+            batch: &BatchOperation,
+            middleware: &dyn Middleware,
+        ) -> Result<BatchResponse, CynicReqwestError>;
     }
 
     impl ReqwestBlockingExt for reqwest::blocking::RequestBuilder {
@@ -275,15 +1203,319 @@ mod reqwest_blocking_ext {
             self,
             operation: Operation<'a, ResponseData>,
         ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError> {
-            self.json(&operation)
-                .send()
-                .and_then(|response| response.json::<GraphQLResponse<serde_json::Value>>())
-                .map_err(CynicReqwestError::ReqwestError)
-                .and_then(|gql_response| {
-                    operation
-                        .decode_response(gql_response)
-                        .map_err(CynicReqwestError::DecodeError)
-                })
+            self.run_graphql_with_middleware(operation, &())
         }
+
+        fn run_graphql_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &dyn Middleware,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError> {
+            crate::logging::in_span(crate::logging::operation_span(&operation), || {
+                crate::logging::log_operation(&operation);
+
+                let request = crate::logging::serializing(|| {
+                    client::request_for::<_, reqwest::Error>(&operation, middleware)
+                })?;
+                let response = into_response(with_request(self, request).send()?)?;
+                let gql_response = client::handle_response(&operation, &response, middleware)?;
+
+                Ok(client::decode_response(&operation, gql_response)?)
+            })
+        }
+
+        fn run_graphql_with_retries<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            policy: &RetryPolicy,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError> {
+            if self.try_clone().is_none() {
+                return self.run_graphql(operation);
+            }
+
+            policy.run(&operation, |operation| {
+                // We checked above that this builder can be cloned.
+                let builder = self
+                    .try_clone()
+                    .expect("RequestBuilder could not be cloned");
+                builder.run_graphql(operation.clone())
+            })
+        }
+
+        fn run_graphql_raw<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError> {
+            self.run_graphql_raw_with_middleware(operation, &())
+        }
+
+        fn run_graphql_raw_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &dyn Middleware,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError> {
+            crate::logging::in_span(crate::logging::operation_span(&operation), || {
+                crate::logging::log_operation(&operation);
+
+                let parameters =
+                    crate::logging::serializing(|| operation.query_string_parameters())?;
+                let builder = self
+                    .query(&parameters)
+                    .header(reqwest::header::CONTENT_TYPE, "application/graphql")
+                    .body(operation.query.clone());
+                let response =
+                    with_headers(builder, client::operation_headers(&operation, middleware)?)
+                        .send()?;
+                let gql_response = recv_response(response, &operation, middleware)?;
+
+                Ok(client::decode_response(&operation, gql_response)?)
+            })
+        }
+
+        #[cfg(feature = "persisted-queries")]
+        fn run_graphql_persisted<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            persisted_queries: &PersistedQueries,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError> {
+            self.run_graphql_persisted_with_middleware(operation, persisted_queries, &())
+        }
+
+        #[cfg(feature = "persisted-queries")]
+        fn run_graphql_persisted_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            persisted_queries: &PersistedQueries,
+            middleware: &dyn Middleware,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicReqwestError> {
+            crate::logging::in_span(crate::logging::operation_span(&operation), || {
+                crate::logging::log_operation(&operation);
+
+                let retry = self.try_clone();
+                let body =
+                    crate::logging::serializing(|| persisted_queries.request_body(&operation))?;
+                let headers = client::operation_headers(&operation, middleware)?;
+                let response = with_headers(self, headers.clone()).json(&body).send()?;
+                let mut gql_response = recv_response(response, &operation, middleware)?;
+
+                if persisted_queries.handle_response(&operation, &gql_response) {
+                    if let Some(retry) = retry {
+                        let body = crate::logging::serializing(|| {
+                            persisted_queries.registration_body(&operation)
+                        })?;
+                        let response = with_headers(retry, headers).json(&body).send()?;
+                        gql_response = recv_response(response, &operation, middleware)?;
+                        persisted_queries.handle_response(&operation, &gql_response);
+                    }
+                }
+
+                Ok(client::decode_response(&operation, gql_response)?)
+            })
+        }
+
+        fn run_graphql_batch(
+            self,
+            batch: &BatchOperation,
+        ) -> Result<BatchResponse, CynicReqwestError> {
+            self.run_graphql_batch_with_middleware(batch, &())
+        }
+
+        fn run_graphql_batch_with_middleware(
+            self,
+            batch: &BatchOperation,
+            middleware: &dyn Middleware,
+        ) -> Result<BatchResponse, CynicReqwestError> {
+            for operation in batch.operations() {
+                crate::logging::log_operation(operation);
+            }
+
+            let request = client::batch_request_for::<reqwest::Error>(batch, middleware)?;
+            let response = into_response(with_request(self, request).send()?)?;
+
+            client::handle_batch_response(batch, &response, middleware)
+        }
+    }
+
+    fn into_response(
+        response: reqwest::blocking::Response,
+    ) -> Result<client::Response, reqwest::Error> {
+        Ok(client::Response {
+            status: response.status().as_u16(),
+            headers: super::response_headers(response.headers()),
+            body: response.bytes()?.to_vec(),
+        })
+    }
+
+    /// Records the status of `response`, parses its body & passes it to
+    /// `middleware`.
+    fn recv_response<ResponseData>(
+        response: reqwest::blocking::Response,
+        operation: &Operation<'_, ResponseData>,
+        middleware: &dyn Middleware,
+    ) -> Result<GraphQLResponse<serde_json::Value>, CynicReqwestError> {
+        client::handle_response(operation, &into_response(response)?, middleware)
+    }
+
+    fn with_request(
+        builder: reqwest::blocking::RequestBuilder,
+        request: client::Request,
+    ) -> reqwest::blocking::RequestBuilder {
+        with_headers(builder.body(request.body), request.headers)
+    }
+
+    fn with_headers(
+        mut builder: reqwest::blocking::RequestBuilder,
+        headers: Vec<(String, String)>,
+    ) -> reqwest::blocking::RequestBuilder {
+        for (name, value) in headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        builder
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod fetch_ext {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    use crate::{
+        client::{self, ClientError, LocalBoxFuture, LocalGraphQlClient},
+        middleware::Middleware,
+        GraphQLResponse, Operation,
+    };
+
+    /// The errors `FetchRequest` can fail with.
+    pub type CynicFetchError = ClientError<FetchError>;
+
+    /// An error from the `fetch` API.
+    #[derive(thiserror::Error, Debug)]
+    #[error("{0}")]
+    pub struct FetchError(pub String);
+
+    /// `fetch` only fails if the request couldn't be sent, so these are
+    /// always retryable.
+    impl crate::retry::RetryableError for FetchError {
+        fn http_status(&self) -> Option<u16> {
+            None
+        }
+
+        fn is_transport_error(&self) -> bool {
+            true
+        }
+    }
+
+    impl From<JsValue> for FetchError {
+        fn from(error: JsValue) -> Self {
+            FetchError(error.as_string().unwrap_or_else(|| format!("{:?}", error)))
+        }
+    }
+
+    /// A request sent with the browser `fetch` API, for use from WASM.
+    ///
+    /// ```rust,ignore
+    /// use cynic::http::FetchRequest;
+    ///
+    /// let response = FetchRequest::post("https://swapi-graphql.netlify.com/.netlify/functions/index")
+    ///     .header("Authorization", "Bearer token")
+    ///     .run_graphql(FilmDirectorQuery::build(()))
+    ///     .await?;
+    /// ```
+    ///
+    /// This needs a browser `window`, so can't be used from web workers.
+    /// It's a `LocalGraphQlClient`, so can be used anywhere that accepts one.
+    #[derive(Clone, Debug)]
+    pub struct FetchRequest {
+        url: String,
+        headers: Vec<(String, String)>,
+    }
+
+    impl FetchRequest {
+        /// Creates a request that POSTs operations to `url`.
+        pub fn post(url: impl Into<String>) -> Self {
+            FetchRequest {
+                url: url.into(),
+                headers: vec![],
+            }
+        }
+
+        /// Adds a header to the request.
+        pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+            self.headers.push((name.into(), value.into()));
+            self
+        }
+
+        /// Runs a GraphQL query, decodes & returns the result.
+        pub async fn run_graphql<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicFetchError> {
+            LocalGraphQlClient::run_graphql(&self, operation).await
+        }
+
+        /// Runs a GraphQL query as with `run_graphql`, calling the hooks of
+        /// `middleware` before the request is sent & after the response is
+        /// received.
+        pub async fn run_graphql_with_middleware<'a, ResponseData: 'a>(
+            self,
+            operation: Operation<'a, ResponseData>,
+            middleware: &dyn Middleware,
+        ) -> Result<GraphQLResponse<ResponseData>, CynicFetchError> {
+            LocalGraphQlClient::run_graphql_with_middleware(&self, operation, middleware).await
+        }
+    }
+
+    impl LocalGraphQlClient for FetchRequest {
+        type Error = FetchError;
+
+        fn send(
+            &self,
+            request: client::Request,
+        ) -> LocalBoxFuture<'_, Result<client::Response, FetchError>> {
+            Box::pin(async move { Ok(fetch(&self.url, &self.headers, request).await?) })
+        }
+    }
+
+    /// POSTs `request` to `url` with the `fetch` API.
+    async fn fetch(
+        url: &str,
+        headers: &[(String, String)],
+        request: client::Request,
+    ) -> Result<client::Response, JsValue> {
+        let body = js_sys::Uint8Array::from(request.body.as_slice());
+        let mut init = web_sys::RequestInit::new();
+        init.method("POST");
+        init.body(Some(body.as_ref()));
+
+        let fetch_request = web_sys::Request::new_with_str_and_init(url, &init)?;
+        for (name, value) in headers.iter().chain(&request.headers) {
+            fetch_request.headers().append(name, value)?;
+        }
+
+        let window =
+            web_sys::window().ok_or_else(|| JsValue::from_str("fetch needs a browser window"))?;
+        let response: web_sys::Response = JsFuture::from(window.fetch_with_request(&fetch_request))
+            .await?
+            .dyn_into()?;
+
+        let mut response_headers = vec![];
+        if let Some(entries) = js_sys::try_iter(&response.headers())? {
+            for entry in entries {
+                let entry = js_sys::Array::from(&entry?);
+                if let (Some(name), Some(value)) =
+                    (entry.get(0).as_string(), entry.get(1).as_string())
+                {
+                    response_headers.push((name, value));
+                }
+            }
+        }
+
+        let body = JsFuture::from(response.array_buffer()?).await?;
+
+        Ok(client::Response {
+            status: response.status(),
+            headers: response_headers,
+            body: js_sys::Uint8Array::new(&body).to_vec(),
+        })
     }
 }
@@ -1,5 +1,10 @@
 use crate::SerializeError;
 
+/// A GraphQL `ID`.
+///
+/// IDs are always sent to the server as strings, but can be built from
+/// integers & will decode from integer responses, as some servers return
+/// numeric IDs.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Id(String);
 
@@ -16,18 +21,83 @@ impl Id {
     }
 }
 
-impl<T: Into<String>> From<T> for Id {
-    fn from(s: T) -> Id {
-        Id(s.into())
+impl From<String> for Id {
+    fn from(s: String) -> Id {
+        Id(s)
+    }
+}
+
+impl From<&String> for Id {
+    fn from(s: &String) -> Id {
+        Id(s.clone())
+    }
+}
+
+impl From<&str> for Id {
+    fn from(s: &str) -> Id {
+        Id(s.to_string())
+    }
+}
+
+macro_rules! impl_from_int_for_id {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Id {
+                fn from(i: $int) -> Id {
+                    Id(i.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int_for_id!(i32, i64, u32, u64, usize);
+
+impl From<Id> for String {
+    fn from(id: Id) -> String {
+        id.0
+    }
+}
+
+impl std::fmt::Display for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
 impl crate::Scalar for Id {
     fn decode(value: &serde_json::Value) -> Result<Self, json_decode::DecodeError> {
-        String::decode(value).map(Into::into)
+        match value {
+            serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => Ok(Id(n.to_string())),
+            _ => String::decode(value).map(Into::into),
+        }
     }
 
     fn encode(&self) -> Result<serde_json::Value, SerializeError> {
         self.0.encode()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scalar;
+    use serde_json::json;
+
+    #[test]
+    fn test_id_conversions() {
+        assert_eq!(Id::from("123"), Id::new("123"));
+        assert_eq!(Id::from(123), Id::new("123"));
+        assert_eq!(Id::from(123u64).to_string(), "123");
+        assert_eq!(String::from(Id::new("abc")), "abc");
+    }
+
+    #[test]
+    fn test_id_decoding() {
+        assert_eq!(Id::decode(&json!("abc")), Ok(Id::new("abc")));
+        assert_eq!(Id::decode(&json!(123)), Ok(Id::new("123")));
+        assert!(Id::decode(&json!(1.5)).is_err());
+
+        assert_eq!(Id::from(123).encode().unwrap(), json!("123"));
+    }
+}
@@ -0,0 +1,436 @@
+//! Handling responses to operations that use `@defer` or `@stream`.
+//!
+//! Servers that support incremental delivery send an initial response
+//! containing everything that wasn't deferred, followed by a series of
+//! payloads that patch deferred fragments & streamed list items into it:
+//!
+//! ```json
+//! { "data": { "film": { "title": "A New Hope" } }, "hasNext": true }
+//! { "incremental": [{ "data": { "director": "George Lucas" }, "path": ["film"] }], "hasNext": false }
+//! ```
+//!
+//! An `IncrementalResponse` applies each of these payloads in turn, and can
+//! decode the response so far after each one.  Over HTTP the payloads are
+//! usually sent as the parts of a `multipart/mixed` response, which
+//! `MultipartMixed` can split a response body into.  Neither does any IO, so
+//! they can be used with any HTTP client - though with the `reqwest`
+//! feature enabled `ReqwestExt::run_graphql_incremental` does this for you.
+//!
+//! Both the `incremental` payloads of the current incremental delivery RFC
+//! and the top level `path` payloads of earlier drafts are supported.
+
+use json_decode::DecodeError;
+use serde_json::Value;
+
+use crate::{result::GraphQLErrorPathSegment, GraphQLError, GraphQLResponse, Operation};
+
+/// The response to an operation that uses incremental delivery, built up
+/// from each payload the server sends.
+pub struct IncrementalResponse<'a, ResponseData> {
+    operation: Operation<'a, ResponseData>,
+    data: Option<Value>,
+    errors: Vec<GraphQLError>,
+    has_next: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct Payload {
+    data: Option<Value>,
+    items: Option<Vec<Value>>,
+    path: Option<Vec<GraphQLErrorPathSegment>>,
+    errors: Option<Vec<GraphQLError>>,
+    #[serde(rename = "hasNext")]
+    has_next: Option<bool>,
+    incremental: Option<Vec<Payload>>,
+}
+
+impl<'a, ResponseData: 'a> IncrementalResponse<'a, ResponseData> {
+    pub fn new(operation: Operation<'a, ResponseData>) -> Self {
+        IncrementalResponse {
+            operation,
+            data: None,
+            errors: vec![],
+            has_next: true,
+        }
+    }
+
+    /// Applies a payload from the server to the response.
+    pub fn apply(&mut self, payload: Value) -> Result<(), DecodeError> {
+        let payload = serde_json::from_value(payload).map_err(|e| {
+            DecodeError::Other(format!("Could not parse incremental payload: {}", e))
+        })?;
+
+        self.apply_payload(payload)?;
+        Ok(())
+    }
+
+    /// Whether the server has more payloads to send.
+    pub fn has_next(&self) -> bool {
+        self.has_next
+    }
+
+    /// Decodes the response, including everything that's been delivered so
+    /// far.
+    pub fn response(&self) -> Result<GraphQLResponse<ResponseData>, DecodeError> {
+        self.operation.decode_response(self.raw_response())
+    }
+
+    /// The operation this is a response to.
+    pub(crate) fn operation(&self) -> &Operation<'a, ResponseData> {
+        &self.operation
+    }
+
+    /// The response as it's been delivered so far, before decoding.
+    pub(crate) fn raw_response(&self) -> GraphQLResponse<Value> {
+        GraphQLResponse {
+            data: self.data.clone(),
+            errors: if self.errors.is_empty() {
+                None
+            } else {
+                Some(self.errors.clone())
+            },
+            extensions: None,
+        }
+    }
+
+    fn apply_payload(&mut self, payload: Payload) -> Result<(), DecodeError> {
+        if let Some(errors) = payload.errors {
+            self.errors.extend(errors);
+        }
+
+        match payload.path {
+            None => {
+                if let Some(data) = payload.data {
+                    match &mut self.data {
+                        Some(existing) => merge(existing, data),
+                        None => self.data = Some(data),
+                    }
+                }
+            }
+            Some(path) => {
+                if let Some(data) = payload.data {
+                    merge(self.lookup(&path)?, data);
+                }
+                if let Some(items) = payload.items {
+                    self.insert_items(&path, items)?;
+                }
+            }
+        }
+
+        for payload in payload.incremental.into_iter().flatten() {
+            self.apply_payload(payload)?;
+        }
+
+        if let Some(has_next) = payload.has_next {
+            self.has_next = has_next;
+        }
+
+        Ok(())
+    }
+
+    /// Finds the value at `path` in the data delivered so far.
+    fn lookup(&mut self, path: &[GraphQLErrorPathSegment]) -> Result<&mut Value, DecodeError> {
+        let mut value = self
+            .data
+            .as_mut()
+            .ok_or_else(|| DecodeError::Other("Received a patch before any data".into()))?;
+
+        for segment in path {
+            let next = match segment {
+                GraphQLErrorPathSegment::Field(name) => value.get_mut(name.as_str()),
+                GraphQLErrorPathSegment::Index(index) => value.get_mut(*index as usize),
+            };
+
+            value = next.ok_or_else(|| {
+                DecodeError::Other(format!("Could not find the path {:?} to patch", path))
+            })?;
+        }
+
+        Ok(value)
+    }
+
+    /// Inserts streamed items into a list.  The last segment of `path` is
+    /// the index of the first item.
+    fn insert_items(
+        &mut self,
+        path: &[GraphQLErrorPathSegment],
+        items: Vec<Value>,
+    ) -> Result<(), DecodeError> {
+        let (start, list_path) = match path.split_last() {
+            Some((GraphQLErrorPathSegment::Index(index), list_path)) => {
+                (*index as usize, list_path)
+            }
+            _ => {
+                return Err(DecodeError::Other(format!(
+                    "The path {:?} for streamed items doesn't end with an index",
+                    path
+                )))
+            }
+        };
+
+        let list = match self.lookup(list_path)? {
+            Value::Array(list) => list,
+            _ => {
+                return Err(DecodeError::Other(format!(
+                    "Streamed items were sent for {:?}, which isn't a list",
+                    list_path
+                )))
+            }
+        };
+
+        for (index, item) in (start..).zip(items) {
+            if index < list.len() {
+                list[index] = item;
+            } else if index == list.len() {
+                list.push(item);
+            } else {
+                return Err(DecodeError::Other(format!(
+                    "Streamed item {} was sent before item {}",
+                    index,
+                    list.len()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Merges the fields of `patch` into `target`.
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// Splits a `multipart/mixed` response body into the payloads it contains.
+///
+/// Chunks of the body should be passed to `push` as they're received, and
+/// then any payloads that have been completed can be taken with
+/// `next_payload`.
+pub struct MultipartMixed {
+    delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl MultipartMixed {
+    /// Creates a `MultipartMixed` for a response with the given
+    /// `Content-Type` header.
+    ///
+    /// Returns `None` if the response isn't `multipart/mixed`.
+    pub fn from_content_type(content_type: &str) -> Option<MultipartMixed> {
+        let mut parameters = content_type.split(';').map(str::trim);
+        if !parameters.next()?.eq_ignore_ascii_case("multipart/mixed") {
+            return None;
+        }
+
+        let boundary = parameters
+            .filter_map(|parameter| {
+                let mut parts = parameter.splitn(2, '=');
+                match (parts.next(), parts.next()) {
+                    (Some(name), Some(value)) if name.eq_ignore_ascii_case("boundary") => {
+                        Some(value.trim_matches('"'))
+                    }
+                    _ => None,
+                }
+            })
+            .next()
+            .unwrap_or("-");
+
+        Some(MultipartMixed::new(boundary))
+    }
+
+    pub fn new(boundary: &str) -> MultipartMixed {
+        MultipartMixed {
+            delimiter: format!("--{}", boundary).into_bytes(),
+            buffer: vec![],
+        }
+    }
+
+    /// Adds a chunk of the response body.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Takes the next complete payload from the body, if there is one.
+    pub fn next_payload(&mut self) -> Option<Result<Value, DecodeError>> {
+        loop {
+            let start = find(&self.buffer, &self.delimiter)? + self.delimiter.len();
+            if self.buffer[start..].starts_with(b"--") {
+                // This is the final delimiter, so there's nothing else to read.
+                self.buffer.clear();
+                return None;
+            }
+
+            let mut next_delimiter = b"\r\n".to_vec();
+            next_delimiter.extend_from_slice(&self.delimiter);
+            let end = start + find(&self.buffer[start..], &next_delimiter)?;
+
+            let part = self.buffer[start..end].to_vec();
+            self.buffer.drain(..end + 2);
+
+            // Each part has some headers, followed by a blank line & the body
+            let body = match find(&part, b"\r\n\r\n") {
+                Some(index) => &part[index + 4..],
+                None => &part[..],
+            };
+
+            // Some servers send empty parts to keep the connection alive
+            if body.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            return Some(serde_json::from_slice(body).map_err(|e| {
+                DecodeError::Other(format!("Could not parse incremental payload: {}", e))
+            }));
+        }
+    }
+}
+
+pub(crate) fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use crate::{
+        selection_set::{self, field, string, vec},
+        testing::fixtures::Root,
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct Film {
+        title: String,
+        director: Option<String>,
+        characters: Vec<String>,
+    }
+
+    fn operation() -> Operation<'static, Film> {
+        Operation::query(field::<_, Root, ()>(
+            "film",
+            vec![],
+            selection_set::map3(
+                |title, director, characters| Film {
+                    title,
+                    director,
+                    characters,
+                },
+                field::<_, (), ()>("title", vec![], string()),
+                selection_set::defer(None, field::<_, (), ()>("director", vec![], string())),
+                selection_set::stream(1, field::<_, (), ()>("characters", vec![], vec(string()))),
+            ),
+        ))
+    }
+
+    #[test]
+    fn test_incremental_responses() {
+        let mut response = IncrementalResponse::new(operation());
+
+        response
+            .apply(json!({
+                "data": { "film": { "title": "A New Hope", "characters": ["Luke"] } },
+                "hasNext": true
+            }))
+            .unwrap();
+        assert_eq!(
+            response.response().unwrap().data,
+            Some(Film {
+                title: "A New Hope".into(),
+                director: None,
+                characters: vec!["Luke".into()],
+            })
+        );
+
+        response
+            .apply(json!({
+                "incremental": [
+                    { "data": { "director": "George Lucas" }, "path": ["film"] },
+                    { "items": ["Leia", "Han"], "path": ["film", "characters", 1] }
+                ],
+                "hasNext": false
+            }))
+            .unwrap();
+        assert!(!response.has_next());
+        assert_eq!(
+            response.response().unwrap().data,
+            Some(Film {
+                title: "A New Hope".into(),
+                director: Some("George Lucas".into()),
+                characters: vec!["Luke".into(), "Leia".into(), "Han".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_older_payloads() {
+        let mut response = IncrementalResponse::new(operation());
+
+        response
+            .apply(json!({
+                "data": { "film": { "title": "A New Hope", "characters": [] } },
+                "hasNext": true
+            }))
+            .unwrap();
+        response
+            .apply(json!({
+                "data": { "director": "George Lucas" },
+                "path": ["film"],
+                "label": null,
+                "hasNext": true
+            }))
+            .unwrap();
+        assert!(response.has_next());
+        assert_eq!(
+            response.response().unwrap().data.unwrap().director,
+            Some("George Lucas".into())
+        );
+
+        assert_matches!(
+            response.apply(json!({ "items": ["Luke"], "path": ["film", "characters", 3] })),
+            Err(DecodeError::Other(_))
+        );
+    }
+
+    #[test]
+    fn test_multipart_mixed() {
+        let mut parser = MultipartMixed::from_content_type(
+            "multipart/mixed; boundary=\"-\"; deferSpec=20220824",
+        )
+        .unwrap();
+
+        parser.push(b"\r\n---\r\nContent-Type: application/json\r\n\r\n{\"data\": {}, ");
+        assert_matches!(parser.next_payload(), None);
+
+        parser.push(b"\"hasNext\": true}\r\n---\r\n\r\n---\r\nContent-Type: application/json");
+        assert_eq!(
+            parser.next_payload().unwrap().unwrap(),
+            json!({"data": {}, "hasNext": true})
+        );
+        assert_matches!(parser.next_payload(), None);
+
+        parser.push(b"\r\n\r\n{\"hasNext\": false}\r\n-----\r\n");
+        assert_eq!(
+            parser.next_payload().unwrap().unwrap(),
+            json!({"hasNext": false})
+        );
+        assert_matches!(parser.next_payload(), None);
+
+        assert!(MultipartMixed::from_content_type("application/json").is_none());
+    }
+}
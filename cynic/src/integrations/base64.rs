@@ -0,0 +1,80 @@
+use json_decode::DecodeError;
+
+use crate::{scalar::Scalar, SerializeError};
+
+/// Base64 encoded binary scalars can be decoded into a `Vec<u8>`.
+///
+/// Note that a `Vec<u8>` can't be used as an argument, as cynic would treat
+/// it as a list.  Enable the `bytes` feature & use `bytes::Bytes` if you
+/// need to send binary data to a server.
+impl Scalar for Vec<u8> {
+    fn decode(value: &serde_json::Value) -> Result<Self, DecodeError> {
+        match value {
+            serde_json::Value::String(s) => Ok(base64::decode(s).map_err(base64_decode_error)?),
+            _ => Err(DecodeError::IncorrectType(
+                "String".to_string(),
+                value.to_string(),
+            )),
+        }
+    }
+
+    fn encode(&self) -> Result<serde_json::Value, SerializeError> {
+        Ok(serde_json::Value::String(base64::encode(self)))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Scalar for bytes::Bytes {
+    fn decode(value: &serde_json::Value) -> Result<Self, DecodeError> {
+        Vec::<u8>::decode(value).map(bytes::Bytes::from)
+    }
+
+    fn encode(&self) -> Result<serde_json::Value, SerializeError> {
+        Ok(serde_json::Value::String(base64::encode(self)))
+    }
+}
+
+#[cfg(feature = "bytes")]
+crate::impl_serializable_argument_for_scalar!(bytes::Bytes);
+#[cfg(feature = "bytes")]
+crate::impl_into_argument_for_options!(bytes::Bytes);
+
+fn base64_decode_error(err: base64::DecodeError) -> DecodeError {
+    DecodeError::Other(format!("Could not decode base64 scalar: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_scalar() {
+        let data = b"hello world".to_vec();
+
+        assert_eq!(
+            data.encode().unwrap(),
+            serde_json::json!("aGVsbG8gd29ybGQ=")
+        );
+        assert_eq!(Vec::<u8>::decode(&data.encode().unwrap()), Ok(data));
+    }
+
+    #[test]
+    fn test_invalid_base64() {
+        assert!(Vec::<u8>::decode(&serde_json::json!("not base64!")).is_err());
+        assert!(Vec::<u8>::decode(&serde_json::json!(1)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn test_bytes_scalar() {
+        use crate::SerializableArgument;
+
+        let data = bytes::Bytes::from_static(b"hello world");
+
+        assert_eq!(
+            data.serialize().unwrap(),
+            serde_json::json!("aGVsbG8gd29ybGQ=")
+        );
+        assert_eq!(bytes::Bytes::decode(&data.encode().unwrap()), Ok(data));
+    }
+}
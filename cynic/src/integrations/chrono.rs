@@ -39,6 +39,11 @@ impl Scalar for DateTime<Utc> {
     }
 }
 
+crate::impl_serializable_argument_for_scalar!(DateTime<FixedOffset>);
+crate::impl_into_argument_for_options!(DateTime<FixedOffset>);
+crate::impl_serializable_argument_for_scalar!(DateTime<Utc>);
+crate::impl_into_argument_for_options!(DateTime<Utc>);
+
 fn chrono_decode_error(err: chrono::ParseError) -> DecodeError {
     DecodeError::Other(err.to_string())
 }
@@ -15,3 +15,16 @@ pub mod uuid;
 #[cfg(feature = "url")]
 /// Cynic support for [url](https://github.com/servo/rust-url) types.
 pub mod url;
+
+#[cfg(feature = "rust_decimal")]
+/// Cynic support for [rust_decimal](https://github.com/paupino/rust-decimal) types.
+pub mod rust_decimal;
+
+#[cfg(feature = "ordered-float")]
+/// Cynic support for [ordered-float](https://github.com/reem/rust-ordered-float) types.
+pub mod ordered_float;
+
+#[cfg(feature = "base64")]
+/// Cynic support for base64 encoded binary scalars, decoding into `Vec<u8>` or
+/// [bytes](https://github.com/tokio-rs/bytes) types.
+pub mod base64;
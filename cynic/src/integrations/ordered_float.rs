@@ -0,0 +1,55 @@
+use json_decode::DecodeError;
+
+use crate::{scalar::Scalar, IntoArgument, OrderedFloat, SerializeError};
+
+impl Scalar for OrderedFloat {
+    fn decode(value: &serde_json::Value) -> Result<Self, DecodeError> {
+        f64::decode(value).map(ordered_float::OrderedFloat)
+    }
+
+    fn encode(&self) -> Result<serde_json::Value, SerializeError> {
+        self.into_inner().encode()
+    }
+}
+
+crate::impl_serializable_argument_for_scalar!(OrderedFloat);
+crate::impl_into_argument_for_options!(OrderedFloat);
+
+// These allow plain f64s to be passed in as arguments when a query_dsl has
+// been generated with ordered_floats.
+
+impl IntoArgument<OrderedFloat> for f64 {
+    type Output = OrderedFloat;
+
+    fn into_argument(self) -> OrderedFloat {
+        ordered_float::OrderedFloat(self)
+    }
+}
+
+impl IntoArgument<Option<OrderedFloat>> for f64 {
+    type Output = Option<OrderedFloat>;
+
+    fn into_argument(self) -> Option<OrderedFloat> {
+        Some(ordered_float::OrderedFloat(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordered_float_scalar() {
+        let float = ordered_float::OrderedFloat(1.5);
+
+        assert_eq!(OrderedFloat::decode(&float.encode().unwrap()), Ok(float));
+    }
+
+    #[test]
+    fn test_f64_into_ordered_float_argument() {
+        use crate::SerializableArgument;
+
+        let argument = IntoArgument::<Option<OrderedFloat>>::into_argument(1.5);
+        assert_eq!(argument.serialize().unwrap(), serde_json::json!(1.5));
+    }
+}
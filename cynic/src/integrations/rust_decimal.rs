@@ -0,0 +1,52 @@
+use json_decode::DecodeError;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+use crate::{scalar::Scalar, SerializeError};
+
+/// Decimals are usually sent as strings to avoid losing precision, but some
+/// servers send them as numbers - so we accept either.
+impl Scalar for Decimal {
+    fn decode(value: &serde_json::Value) -> Result<Self, DecodeError> {
+        let s = match value {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Number(n) => n.to_string(),
+            _ => {
+                return Err(DecodeError::IncorrectType(
+                    "String".to_string(),
+                    value.to_string(),
+                ))
+            }
+        };
+
+        Decimal::from_str(&s)
+            .or_else(|_| Decimal::from_scientific(&s))
+            .map_err(|err| DecodeError::Other(err.to_string()))
+    }
+
+    fn encode(&self) -> Result<serde_json::Value, SerializeError> {
+        Ok(serde_json::Value::String(self.to_string()))
+    }
+}
+
+crate::impl_serializable_argument_for_scalar!(Decimal);
+crate::impl_into_argument_for_options!(Decimal);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decimal_scalar() {
+        let decimal = Decimal::from_str("1234.5678").unwrap();
+
+        assert_eq!(Decimal::decode(&decimal.encode().unwrap()), Ok(decimal));
+        assert_eq!(Decimal::decode(&json!(1234.5678)), Ok(decimal));
+        assert_eq!(
+            Decimal::decode(&json!(12)),
+            Ok(Decimal::from_str("12").unwrap())
+        );
+        assert!(Decimal::decode(&json!(true)).is_err());
+    }
+}
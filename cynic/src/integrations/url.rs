@@ -21,6 +21,9 @@ impl Scalar for Url {
     }
 }
 
+crate::impl_serializable_argument_for_scalar!(Url);
+crate::impl_into_argument_for_options!(Url);
+
 #[cfg(test)]
 mod tests {
     use super::*;
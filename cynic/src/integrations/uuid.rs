@@ -21,6 +21,9 @@ impl Scalar for Uuid {
     }
 }
 
+crate::impl_serializable_argument_for_scalar!(Uuid);
+crate::impl_into_argument_for_options!(Uuid);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,74 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+/// A pool of interned strings.
+///
+/// Responses often contain the same strings many times over - enum values,
+/// typenames, repeated names etc.  Decoding those into `String`s means
+/// allocating a copy for every occurrence.  A `StringPool` can be used to
+/// decode into `Arc<str>` instead, where every occurrence of a particular
+/// string shares a single allocation.
+///
+/// `StringPool` is cheap to clone - clones share the same underlying pool.
+///
+/// ```rust
+/// # use cynic::{selection_set::string, StringPool};
+/// let pool = StringPool::new();
+/// string().interned(&pool);
+/// ```
+#[derive(Clone, Default)]
+pub struct StringPool {
+    strings: Arc<Mutex<HashSet<Arc<str>>>>,
+}
+
+impl StringPool {
+    pub fn new() -> Self {
+        StringPool::default()
+    }
+
+    /// Returns a shared copy of `s`, adding it to the pool if it's not
+    /// already present.
+    pub fn intern(&self, s: impl AsRef<str>) -> Arc<str> {
+        let mut strings = self.strings.lock().expect("StringPool mutex was poisoned");
+
+        if let Some(existing) = strings.get(s.as_ref()) {
+            return Arc::clone(existing);
+        }
+
+        let interned: Arc<str> = Arc::from(s.as_ref());
+        strings.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// The number of distinct strings in the pool.
+    pub fn len(&self) -> usize {
+        self.strings
+            .lock()
+            .expect("StringPool mutex was poisoned")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interning_shares_allocations() {
+        let pool = StringPool::new();
+
+        let one = pool.intern("CHEESECAKE");
+        let two = pool.intern("CHEESECAKE".to_string());
+        let three = pool.intern("ICE_CREAM");
+
+        assert!(Arc::ptr_eq(&one, &two));
+        assert!(!Arc::ptr_eq(&one, &three));
+        assert_eq!(pool.len(), 2);
+    }
+}
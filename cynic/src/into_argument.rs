@@ -41,6 +41,14 @@ macro_rules! impl_into_argument_for_options {
             }
         }
 
+        impl<'a> $crate::IntoArgument<$inner> for &'a $inner {
+            type Output = &'a $inner;
+
+            fn into_argument(self) -> &'a $inner {
+                self
+            }
+        }
+
         impl<'a> $crate::IntoArgument<Option<$inner>> for &'a $inner {
             type Output = Option<&'a $inner>;
 
@@ -48,6 +56,51 @@ macro_rules! impl_into_argument_for_options {
                 Some(self)
             }
         }
+
+        $crate::impl_into_argument_for_lists!($inner);
+    };
+}
+
+/// Defines argument conversions from single values into lists.
+///
+/// The GraphQL spec says that a single value provided for a list argument
+/// should be coerced into a list containing only that value.  These impls
+/// do that coercion on the client side so users don't need to wrap
+/// arguments in `vec![]`.
+#[macro_export]
+macro_rules! impl_into_argument_for_lists {
+    ($inner:ty) => {
+        impl $crate::IntoArgument<Vec<$inner>> for $inner {
+            type Output = Vec<$inner>;
+
+            fn into_argument(self) -> Vec<$inner> {
+                vec![self]
+            }
+        }
+
+        impl $crate::IntoArgument<Vec<Option<$inner>>> for $inner {
+            type Output = Vec<Option<$inner>>;
+
+            fn into_argument(self) -> Vec<Option<$inner>> {
+                vec![Some(self)]
+            }
+        }
+
+        impl $crate::IntoArgument<Option<Vec<$inner>>> for $inner {
+            type Output = Option<Vec<$inner>>;
+
+            fn into_argument(self) -> Option<Vec<$inner>> {
+                Some(vec![self])
+            }
+        }
+
+        impl $crate::IntoArgument<Option<Vec<Option<$inner>>>> for $inner {
+            type Output = Option<Vec<Option<$inner>>>;
+
+            fn into_argument(self) -> Option<Vec<Option<$inner>>> {
+                Some(vec![Some(self)])
+            }
+        }
     };
 }
 
@@ -83,26 +136,173 @@ impl_into_argument_for_option_refs!(String);
 impl_into_argument_for_option_refs!(bool);
 impl_into_argument_for_option_refs!(Id);
 
-impl<'a> IntoArgument<String> for &'a str {
-    type Output = &'a str;
+/// Defines argument conversions from `&str` for types that serialize as a
+/// string, so string literals can be passed without an explicit conversion.
+macro_rules! impl_into_argument_for_str {
+    ($target:ty) => {
+        impl<'a> IntoArgument<$target> for &'a str {
+            type Output = &'a str;
+
+            fn into_argument(self) -> &'a str {
+                self
+            }
+        }
+
+        impl<'a> IntoArgument<Option<$target>> for &'a str {
+            type Output = Option<&'a str>;
+
+            fn into_argument(self) -> Option<&'a str> {
+                Some(self)
+            }
+        }
+
+        impl<'a> IntoArgument<Option<$target>> for Option<&'a str> {
+            type Output = Option<&'a str>;
+
+            fn into_argument(self) -> Option<&'a str> {
+                self
+            }
+        }
+
+        impl<'a> IntoArgument<Vec<$target>> for &'a str {
+            type Output = Vec<&'a str>;
+
+            fn into_argument(self) -> Vec<&'a str> {
+                vec![self]
+            }
+        }
+
+        impl<'a> IntoArgument<Option<Vec<$target>>> for &'a str {
+            type Output = Option<Vec<&'a str>>;
+
+            fn into_argument(self) -> Option<Vec<&'a str>> {
+                Some(vec![self])
+            }
+        }
+    };
+}
+
+impl_into_argument_for_str!(String);
+impl_into_argument_for_str!(Id);
+
+// Ids are strings on the wire, so owned strings can be used as Ids too.
+
+impl IntoArgument<Id> for String {
+    type Output = String;
+
+    fn into_argument(self) -> String {
+        self
+    }
+}
+
+impl IntoArgument<Option<Id>> for String {
+    type Output = Option<String>;
 
-    fn into_argument(self) -> &'a str {
+    fn into_argument(self) -> Option<String> {
+        Some(self)
+    }
+}
+
+impl<'a> IntoArgument<Id> for &'a String {
+    type Output = &'a String;
+
+    fn into_argument(self) -> &'a String {
         self
     }
 }
 
-impl<'a> IntoArgument<Option<String>> for &'a str {
-    type Output = Option<&'a str>;
+impl<'a> IntoArgument<Option<Id>> for &'a String {
+    type Output = Option<&'a String>;
 
-    fn into_argument(self) -> Option<&'a str> {
+    fn into_argument(self) -> Option<&'a String> {
         Some(self)
     }
 }
 
-impl<'a> IntoArgument<Option<String>> for Option<&'a str> {
-    type Output = Option<&'a str>;
+// Slices can be passed to list arguments, to avoid copying them into a Vec.
+
+impl<'a, T: SerializableArgument> IntoArgument<Vec<T>> for &'a [T] {
+    type Output = &'a [T];
 
-    fn into_argument(self) -> Option<&'a str> {
+    fn into_argument(self) -> &'a [T] {
         self
     }
 }
+
+impl<'a, T: SerializableArgument> IntoArgument<Option<Vec<T>>> for &'a [T] {
+    type Output = Option<&'a [T]>;
+
+    fn into_argument(self) -> Option<&'a [T]> {
+        Some(self)
+    }
+}
+
+impl<'a, 'b> IntoArgument<Vec<String>> for &'a [&'b str] {
+    type Output = &'a [&'b str];
+
+    fn into_argument(self) -> &'a [&'b str] {
+        self
+    }
+}
+
+impl<'a, 'b> IntoArgument<Option<Vec<String>>> for &'a [&'b str] {
+    type Output = Option<&'a [&'b str]>;
+
+    fn into_argument(self) -> Option<&'a [&'b str]> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn serialize<T>(arg: impl IntoArgument<T>) -> serde_json::Value {
+        arg.into_argument().serialize().unwrap()
+    }
+
+    #[test]
+    fn test_single_values_coerce_to_lists() {
+        assert_eq!(serialize::<Vec<i32>>(1), json!([1]));
+        assert_eq!(serialize::<Vec<Option<i32>>>(1), json!([1]));
+        assert_eq!(serialize::<Option<Vec<String>>>("hello"), json!(["hello"]));
+        assert_eq!(
+            serialize::<Option<Vec<Option<Id>>>>(Id::new("123")),
+            json!(["123"])
+        );
+    }
+
+    #[test]
+    fn test_slices() {
+        let ids = vec![1, 2];
+        assert_eq!(serialize::<Vec<i32>>(ids.as_slice()), json!([1, 2]));
+        assert_eq!(serialize::<Option<Vec<i32>>>(&ids[1..]), json!([2]));
+
+        let names = ["one", "two"];
+        assert_eq!(serialize::<Vec<String>>(&names[..]), json!(["one", "two"]));
+    }
+
+    #[test]
+    fn test_string_conversions() {
+        let name = "hello".to_string();
+        assert_eq!(serialize::<String>(&name), json!("hello"));
+        assert_eq!(serialize::<Option<String>>(Some("hello")), json!("hello"));
+
+        assert_eq!(serialize::<Id>("123"), json!("123"));
+        assert_eq!(serialize::<Option<Id>>("123"), json!("123"));
+        assert_eq!(serialize::<Option<Id>>(None::<&str>), json!(null));
+        assert_eq!(serialize::<Vec<Id>>("123"), json!(["123"]));
+        assert_eq!(serialize::<Option<Id>>(name.clone()), json!("hello"));
+        assert_eq!(serialize::<Id>(&name), json!("hello"));
+    }
+
+    #[test]
+    fn test_lists_are_not_coerced() {
+        assert_eq!(serialize::<Vec<i32>>(vec![1, 2]), json!([1, 2]));
+        assert_eq!(
+            serialize::<Option<Vec<String>>>(Some(vec!["hello".to_string()])),
+            json!(["hello"])
+        );
+    }
+}
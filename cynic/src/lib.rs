@@ -160,12 +160,55 @@
 //!
 //! Cynic has a few features that are controlled by feature flags.
 //!
+//! - `pretty-print` (enabled by default) outputs queries with newlines &
+//!   indentation.  Disabling default features outputs queries on a single line,
+//!   which results in slightly smaller WASM bundles.
+//! - `persisted-queries` adds the `manifest` module, which can output
+//!   manifests of operations for use with persisted queries or allowlists,
+//!   and the `persisted_queries` module, which supports Automatic Persisted
+//!   Queries.
+//! - `schema-check` adds the `schema_check` module, which can check operations
+//!   against the schema of a running server.
+//! - `replay` adds the `replay` module, which can record responses from a
+//!   server & replay them in tests.
+//! - `fake-server` adds the `fake_server` module, which can respond to
+//!   operations with fake data generated from a schema, for use in tests.
+//! - `profiling` adds the `profiling` module, which can record how long each
+//!   part of a response takes to decode.
+//! - `graphql-ws` adds `SubscriptionStream`, a `Stream` of responses to a
+//!   subscription sent over a websocket, and `hasura::CursorStream`, a
+//!   `Stream` of the rows from a Hasura streaming subscription.
+//! - `appsync` adds the `appsync` module, which runs subscriptions against
+//!   AWS AppSync's realtime endpoint.
+//! - `pagination` adds `PagedQuery::into_stream`, which returns a `Stream` of
+//!   every node in a paginated connection.
 //! - `chrono` adds support for chrono::DateTime scalars.
 //! - `uuid` adds support for Uuid scalars
 //! - `bson` adds support for ObjectId scalars
 //! - `url` adds support for Url scalars
+//! - `rust_decimal` adds support for Decimal scalars, which can be sent as
+//!   strings or numbers.
+//! - `base64` adds support for base64 encoded binary scalars, which can be
+//!   decoded into a `Vec<u8>`.  Enabling `bytes` as well adds support for
+//!   `bytes::Bytes`, which can also be used in arguments.
+//! - `ordered-float` adds `OrderedFloat`, a totally ordered `f64` that
+//!   implements `Eq` & `Hash`.  A `query_dsl` generated with the
+//!   `ordered_floats` option will use it for all GraphQL `Float`s.
+//! - `log` & `tracing` make the HTTP integrations log a summary of each
+//!   operation & response, with variable values elided.  `tracing` also
+//!   wraps each operation in a span.  See the `logging` module for details.
 //! - `surf` adds integration with the [`surf`](https://github.com/http-rs/surf)
 //!   http client.
+//! - `wasm` adds `http::FetchRequest`, which sends operations with the browser
+//!   `fetch` API.  This has no native dependencies, so can be used from
+//!   `wasm32-unknown-unknown`.
+//! - `reqwest-gzip` & `reqwest-brotli` make the reqwest integrations accept
+//!   compressed responses.  `surf-encoding` does the same for surf.
+//! - `compression` adds `Operation::compress_above`, which makes the reqwest &
+//!   surf integrations - or any other `GraphQlClient` - gzip large request
+//!   bodies.
+//! - `simd-json` adds `Operation::decode_response_slice`, which parses response
+//!   bodies with [`simd-json`](https://github.com/simd-lite/simd-json).
 //!
 //! It's worth noting that each of these features pulls in extra
 //! dependencies, which may impact your build size.  Particularly
@@ -177,26 +220,74 @@ mod argument;
 mod field;
 mod id;
 mod integrations;
+mod interning;
 mod into_argument;
+mod maybe_undefined;
 mod operation;
 mod result;
-mod scalar;
 
+pub mod absinthe;
+#[cfg(feature = "appsync")]
+pub mod appsync;
+pub mod batch;
+pub mod cache;
+pub mod client;
+pub mod debug;
+#[cfg(feature = "fake-server")]
+pub mod fake_server;
+pub mod federation;
+pub mod graphql_sse;
+pub mod graphql_transport_ws;
+pub mod hasura;
 pub mod http;
+pub mod incremental;
+pub mod logging;
+#[cfg(feature = "persisted-queries")]
+pub mod manifest;
+pub mod middleware;
+pub mod pagination;
+#[cfg(feature = "persisted-queries")]
+pub mod persisted_queries;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+pub mod rate_limit;
+pub mod registry;
+#[cfg(feature = "replay")]
+pub mod replay;
+pub mod retry;
+pub mod scalar;
+#[cfg(feature = "schema-check")]
+pub mod schema_check;
 pub mod selection_set;
+pub mod subscriptions_transport_ws;
+pub mod testing;
+pub mod upload;
+#[doc(hidden)]
 pub mod utils;
 
 pub use json_decode::DecodeError;
 
 pub use argument::{Argument, SerializableArgument};
 pub use id::Id;
-pub use operation::Operation;
+pub use interning::StringPool;
+pub use maybe_undefined::MaybeUndefined;
+pub use operation::{
+    DocumentError, Operation, OperationDocument, OperationType, StreamingOperation,
+};
 pub use result::{GraphQLError, GraphQLResponse, GraphQLResult, PossiblyParsedData};
 pub use scalar::Scalar;
 pub use selection_set::SelectionSet;
 
 pub use into_argument::IntoArgument;
 
+/// An `f64` that implements `Eq`, `Ord` & `Hash`.
+///
+/// This is used for GraphQL `Float`s when `query_dsl` is called with the
+/// `ordered_floats` option, so that query results containing floats can be
+/// used as map keys or compared.
+#[cfg(feature = "ordered-float")]
+pub type OrderedFloat = ordered_float::OrderedFloat<f64>;
+
 pub trait QueryFragment {
     type SelectionSet;
     type Arguments: FragmentArguments;
@@ -213,6 +304,14 @@ pub trait InlineFragments: Sized {
     fn fragments(
         arguments: &Self::Arguments,
     ) -> Vec<(String, SelectionSet<'static, Self, Self::TypeLock>)>;
+
+    /// Determines the GraphQL type of an object in a response, for servers
+    /// that don't return a usable `__typename`.
+    ///
+    /// Returning `None` falls back to the object's `__typename`.
+    fn discriminant(_value: &serde_json::Value) -> Option<String> {
+        None
+    }
 }
 
 impl<T> QueryFragment for T
@@ -223,7 +322,10 @@ where
     type Arguments = <T as InlineFragments>::Arguments;
 
     fn fragment(arguments: &Self::Arguments) -> Self::SelectionSet {
-        selection_set::inline_fragments(Self::fragments(arguments))
+        selection_set::inline_fragments_with_discriminant(
+            Self::discriminant,
+            Self::fragments(arguments),
+        )
     }
 
     fn graphql_type() -> String {
@@ -242,6 +344,26 @@ pub trait Enum<TypeLock>: Sized {
     fn select() -> SelectionSet<'static, Self, ()>;
 }
 
+/// The error returned by the `FromStr` impl that the `Enum` derive generates
+/// when given `impl_from_str`.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown variant of {enum_name}: {value}")]
+pub struct UnknownVariantError {
+    /// The name of the GraphQL enum we were parsing.
+    pub enum_name: String,
+    /// The string that didn't match any of the enums variants.
+    pub value: String,
+}
+
+impl UnknownVariantError {
+    pub fn new(enum_name: impl Into<String>, value: impl Into<String>) -> Self {
+        UnknownVariantError {
+            enum_name: enum_name.into(),
+            value: value.into(),
+        }
+    }
+}
+
 /// A trait for GraphQL input objects.
 ///
 /// This trait is generic over some TypeLock which is used to tie an InputType
@@ -293,12 +415,29 @@ pub trait QueryRoot {}
 /// mutation hierarchy.
 pub trait MutationRoot {}
 
+/// A marker trait that indicates a particular type is at the root of a GraphQL schemas
+/// subscription hierarchy.
+pub trait SubscriptionRoot {}
+
 pub use cynic_proc_macros::{
-    query_dsl, query_module, Enum, FragmentArguments, InlineFragments, InputObject, QueryFragment,
-    Scalar,
+    query_dsl, query_module, schema_for_derives, Enum, FragmentArguments, InlineFragments,
+    InputObject, QueryFragment, Scalar,
 };
 
-// We re-export serde_json as the output from a lot of our derive macros require it,
-// and this way we can point at our copy rather than forcing users to add it to
-// their Cargo.toml
+// We re-export serde & serde_json as the output from a lot of our derive macros
+// require them, and this way we can point at our copy rather than forcing users
+// to add them to their Cargo.toml
+#[doc(hidden)]
+pub use serde;
+#[doc(hidden)]
 pub use serde_json;
+
+/// Things that the output of our macros relies on, but that users shouldn't
+/// need to use directly.  None of this is part of the public API, and can
+/// change without warning.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::utils::{FlattenFrom, FlattenInto, WrapInto};
+    pub use serde;
+    pub use serde_json;
+}
@@ -0,0 +1,319 @@
+//! Structured summaries of operations & responses, for logging.
+//!
+//! `OperationSummary` & `ResponseSummary` describe GraphQL traffic without
+//! including any variable values, which might contain secrets or personal
+//! data.  They can be passed to any logging library, or if the `log` or
+//! `tracing` features are enabled the HTTP integrations & `client` module
+//! will emit them automatically:
+//!
+//! - With `log`, operations are logged at debug level and responses with
+//!   errors at warn level, both with the target `cynic`.
+//! - With `tracing`, the same events are emitted with the summary as
+//!   structured fields.  Each operation also runs inside a
+//!   `graphql_operation` span, which records the operation's name, kind,
+//!   hash & number of variables along with the HTTP status & number of
+//!   GraphQL errors in its response.  Serializing the operation & decoding
+//!   the response have their own `serialize` & `decode` spans inside that.
+
+use std::fmt;
+
+use crate::{GraphQLResponse, Operation};
+
+/// A summary of an operation, with variable values elided.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationSummary<'a> {
+    /// Whether this is a `query`, `mutation` or `subscription`.
+    pub kind: &'static str,
+    /// The name of the operation.
+    pub name: &'a str,
+    /// A hash of the operation's document, which can be used to correlate
+    /// log lines for the same operation.  This is not cryptographically
+    /// secure.
+    pub hash: String,
+    /// The names of the operation's variables.
+    pub variable_keys: Vec<&'a str>,
+}
+
+impl<'a> OperationSummary<'a> {
+    pub fn new<ResponseData>(operation: &'a Operation<'_, ResponseData>) -> Self {
+        OperationSummary {
+            kind: operation.operation_type().keyword(),
+            name: operation.name(),
+            hash: format!("{:016x}", fnv1a(operation.query.as_bytes())),
+            variable_keys: operation.variables.keys().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl fmt::Display for OperationSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({})", self.kind, self.name, self.hash)?;
+
+        if !self.variable_keys.is_empty() {
+            let variables = self
+                .variable_keys
+                .iter()
+                .map(|key| format!("${}: <elided>", key))
+                .collect::<Vec<_>>();
+            write!(f, " with {}", variables.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A summary of a response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseSummary {
+    /// Whether the response contained any data.
+    pub has_data: bool,
+    /// The number of errors in the response.
+    pub error_count: usize,
+}
+
+impl ResponseSummary {
+    pub fn new<T>(response: &GraphQLResponse<T>) -> Self {
+        ResponseSummary {
+            has_data: response.data.is_some(),
+            error_count: response.errors.as_ref().map(Vec::len).unwrap_or(0),
+        }
+    }
+}
+
+impl fmt::Display for ResponseSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = if self.has_data { "data" } else { "no data" };
+        write!(f, "{} & {} errors", data, self.error_count)
+    }
+}
+
+/// Emits an event for an outgoing operation, if any logging features are
+/// enabled.
+#[allow(unused_variables)]
+pub(crate) fn log_operation<ResponseData>(operation: &Operation<'_, ResponseData>) {
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    let summary = OperationSummary::new(operation);
+
+    #[cfg(feature = "log")]
+    log::debug!(target: "cynic", "Sending {}", summary);
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        target: "cynic",
+        operation.kind = summary.kind,
+        operation.name = summary.name,
+        operation.hash = summary.hash.as_str(),
+        operation.variables = ?summary.variable_keys,
+        "Sending GraphQL operation"
+    );
+}
+
+/// Emits an event for a response, if any logging features are enabled.
+#[allow(unused_variables)]
+pub(crate) fn log_response<ResponseData, T>(
+    operation: &Operation<'_, ResponseData>,
+    response: &GraphQLResponse<T>,
+) {
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    let (operation, response) = (
+        OperationSummary::new(operation),
+        ResponseSummary::new(response),
+    );
+
+    #[cfg(feature = "log")]
+    {
+        let level = if response.error_count > 0 {
+            log::Level::Warn
+        } else {
+            log::Level::Debug
+        };
+        log::log!(target: "cynic", level, "Received {} for {}", response, operation);
+    }
+
+    #[cfg(feature = "tracing")]
+    {
+        if response.error_count > 0 {
+            tracing::warn!(
+                target: "cynic",
+                operation.name = operation.name,
+                operation.hash = operation.hash.as_str(),
+                response.has_data = response.has_data,
+                response.error_count = response.error_count,
+                "Received GraphQL response with errors"
+            );
+        } else {
+            tracing::debug!(
+                target: "cynic",
+                operation.name = operation.name,
+                operation.hash = operation.hash.as_str(),
+                response.has_data = response.has_data,
+                response.error_count = response.error_count,
+                "Received GraphQL response"
+            );
+        }
+    }
+}
+
+/// A span covering the whole of an operation, from serializing it to
+/// decoding its response.
+///
+/// This is `()` if the `tracing` feature isn't enabled, so the helpers below
+/// compile to nothing.
+#[cfg(feature = "tracing")]
+pub(crate) type OperationSpan = tracing::Span;
+#[cfg(not(feature = "tracing"))]
+pub(crate) type OperationSpan = ();
+
+/// Creates the span for `operation`.  The HTTP status & error count are
+/// recorded on it once the response arrives.
+#[allow(unused_variables)]
+pub(crate) fn operation_span<ResponseData>(
+    operation: &Operation<'_, ResponseData>,
+) -> OperationSpan {
+    #[cfg(feature = "tracing")]
+    {
+        let summary = OperationSummary::new(operation);
+        tracing::info_span!(
+            target: "cynic",
+            "graphql_operation",
+            operation.kind = summary.kind,
+            operation.name = summary.name,
+            operation.hash = summary.hash.as_str(),
+            operation.variable_count = summary.variable_keys.len(),
+            http.status = tracing::field::Empty,
+            response.error_count = tracing::field::Empty,
+        )
+    }
+}
+
+/// Runs `future` inside `span`.
+#[allow(unused_variables)]
+pub(crate) fn instrument<F: std::future::Future>(
+    span: OperationSpan,
+    future: F,
+) -> impl std::future::Future<Output = F::Output> {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::Instrument::instrument(future, span)
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        future
+    }
+}
+
+/// Runs `f` inside `span`.
+#[cfg(feature = "reqwest-blocking")]
+#[allow(unused_variables)]
+pub(crate) fn in_span<T>(span: OperationSpan, f: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "tracing")]
+    {
+        span.in_scope(f)
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        f()
+    }
+}
+
+/// Records the HTTP status of a response on the current operation span.
+#[allow(unused_variables)]
+pub(crate) fn record_status(status: u16) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("http.status", &status);
+}
+
+/// Serializes an operation with `f`, inside a span if `tracing` is enabled.
+pub(crate) fn serializing<T>(f: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::debug_span!(target: "cynic", "serialize").in_scope(f)
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        f()
+    }
+}
+
+/// Decodes `response` with `f`, inside a span if `tracing` is enabled.
+///
+/// The number of errors in the response is recorded on the current
+/// operation span.
+pub(crate) fn decoding<R, T>(
+    response: GraphQLResponse<R>,
+    f: impl FnOnce(GraphQLResponse<R>) -> T,
+) -> T {
+    #[cfg(feature = "tracing")]
+    {
+        let error_count = ResponseSummary::new(&response).error_count;
+        tracing::Span::current().record("response.error_count", &error_count);
+
+        tracing::debug_span!(target: "cynic", "decode").in_scope(|| f(response))
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        f(response)
+    }
+}
+
+/// The 64 bit FNV-1a hash, which is stable across platforms & rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::fixtures::query, Argument, GraphQLError};
+
+    #[test]
+    fn test_operation_summary() {
+        let operation = query(
+            "film",
+            vec![Argument::new("id", "ID!", "secret".to_string())],
+        )
+        .named("Film");
+
+        let summary = OperationSummary::new(&operation);
+
+        assert_eq!(summary.kind, "query");
+        assert_eq!(summary.name, "Film");
+        assert_eq!(summary.variable_keys, vec!["_0"]);
+        assert_eq!(
+            summary.hash,
+            format!("{:016x}", fnv1a(operation.query.as_bytes()))
+        );
+
+        let display = summary.to_string();
+        assert!(display.starts_with("query Film ("));
+        assert!(display.ends_with(") with $_0: <elided>"));
+        assert!(!display.contains("secret"));
+    }
+
+    #[test]
+    fn test_response_summary() {
+        let response = GraphQLResponse::<()> {
+            data: None,
+            errors: Some(vec![GraphQLError::new("oops".into(), None, None, None)]),
+            extensions: None,
+        };
+
+        assert_eq!(
+            ResponseSummary::new(&response).to_string(),
+            "no data & 1 errors"
+        );
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        // Reference values from the FNV spec
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,185 @@
+//! Manifests of operations for use with persisted queries or server side
+//! allowlists.
+//!
+//! An `OperationManifest` collects the documents of several operations and
+//! can output them in the formats expected by Apollo & Relay tooling.  It's
+//! intended to be built from some code that runs at build time - either a
+//! test or a small binary - with the output uploaded to the server alongside
+//! deployments.
+//!
+//! ```rust
+//! # use cynic::{manifest::OperationManifest, selection_set::{field, string}, QueryRoot};
+//! # struct Root;
+//! # impl QueryRoot for Root {}
+//! # let operation = cynic::Operation::query(field::<_, Root, ()>("test", vec![], string()));
+//! let mut manifest = OperationManifest::new();
+//! manifest.add("TestQuery", &operation);
+//!
+//! let json = manifest.to_apollo_json();
+//! ```
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::Operation;
+
+/// A collection of operations, keyed by the hash of their documents.
+#[derive(Clone, Debug, Default)]
+pub struct OperationManifest {
+    operations: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct ManifestEntry {
+    name: String,
+    operation_type: String,
+    body: String,
+}
+
+impl OperationManifest {
+    pub fn new() -> Self {
+        OperationManifest::default()
+    }
+
+    /// Adds an operation to the manifest under `name`.
+    ///
+    /// Operations with identical documents are only stored once.
+    pub fn add<ResponseData>(
+        &mut self,
+        name: impl Into<String>,
+        operation: &Operation<'_, ResponseData>,
+    ) -> &mut Self {
+        let operation_type = operation
+            .query
+            .split_whitespace()
+            .next()
+            .unwrap_or("query")
+            .to_string();
+
+        self.operations.insert(
+            document_hash(&operation.query),
+            ManifestEntry {
+                name: name.into(),
+                operation_type,
+                body: operation.query.clone(),
+            },
+        );
+
+        self
+    }
+
+    /// The number of distinct operations in the manifest.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Outputs the manifest in the format used by Apollos persisted query
+    /// tooling.
+    pub fn to_apollo_json(&self) -> serde_json::Value {
+        let operations = self
+            .operations
+            .iter()
+            .map(|(hash, entry)| {
+                serde_json::json!({
+                    "id": hash,
+                    "name": entry.name,
+                    "type": entry.operation_type,
+                    "body": entry.body,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "format": "apollo-persisted-query-manifest",
+            "version": 1,
+            "operations": operations,
+        })
+    }
+
+    /// Outputs the manifest in the format used by Relays persisted queries:
+    /// a map of hashes to documents.
+    pub fn to_relay_json(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.operations
+                .iter()
+                .map(|(hash, entry)| (hash.clone(), entry.body.clone().into()))
+                .collect(),
+        )
+    }
+
+    /// Writes the manifest to `path` in Apollos format.
+    pub fn write_apollo(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        write_json(path.as_ref(), &self.to_apollo_json())
+    }
+
+    /// Writes the manifest to `path` in Relays format.
+    pub fn write_relay(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        write_json(path.as_ref(), &self.to_relay_json())
+    }
+}
+
+/// Hashes a GraphQL document with SHA-256, which is what both Apollo & Relay
+/// use to identify persisted documents.
+pub(crate) fn document_hash(document: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    format!("{:x}", Sha256::digest(document.as_bytes()))
+}
+
+fn write_json(path: &Path, json: &serde_json::Value) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_string_pretty(json)?;
+    std::fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::query;
+    use serde_json::json;
+
+    #[test]
+    fn test_document_hash() {
+        assert_eq!(
+            document_hash("query { test }"),
+            "f2f8ccd721ae2dcf38dc5365e42e3bd5686f5c823b079a2e0ca3de8ec45e8399"
+        );
+        assert_ne!(
+            document_hash("query { test }"),
+            document_hash("query { other }")
+        );
+    }
+
+    #[test]
+    fn test_manifest_formats() {
+        let one = query("one", vec![]);
+        let two = query("two", vec![]);
+
+        let mut manifest = OperationManifest::new();
+        manifest
+            .add("One", &one)
+            .add("Two", &two)
+            .add("OneAgain", &one);
+
+        assert_eq!(manifest.len(), 2);
+
+        let relay = manifest.to_relay_json();
+        assert_eq!(relay[document_hash(&one.query)], json!(one.query));
+        assert_eq!(relay[document_hash(&two.query)], json!(two.query));
+
+        let apollo = manifest.to_apollo_json();
+        assert_eq!(apollo["format"], json!("apollo-persisted-query-manifest"));
+        assert_eq!(apollo["operations"].as_array().unwrap().len(), 2);
+        assert!(apollo["operations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|op| op["type"] == json!("query")));
+    }
+}
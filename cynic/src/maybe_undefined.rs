@@ -0,0 +1,98 @@
+use crate::{SerializableArgument, SerializeError};
+
+/// A value that can be explicitly `null` as well as missing entirely.
+///
+/// GraphQL treats an input field that's been set to `null` differently from
+/// one that hasn't been provided - partial update mutations often use `null`
+/// to clear a value and leave out any fields that shouldn't change.  An
+/// `Option` can only express one of these, so InputObjects can use
+/// `MaybeUndefined` fields instead:
+///
+/// - `Undefined` fields are left out of the serialized InputObject.
+/// - `Null` fields are sent as `null`.
+/// - `Value` fields are sent as their value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MaybeUndefined<T> {
+    Undefined,
+    Null,
+    Value(T),
+}
+
+impl<T> MaybeUndefined<T> {
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, MaybeUndefined::Undefined)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, MaybeUndefined::Null)
+    }
+
+    pub fn is_value(&self) -> bool {
+        matches!(self, MaybeUndefined::Value(_))
+    }
+
+    /// Returns the value, if there is one.
+    ///
+    /// This loses the distinction between `Null` & `Undefined`.
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            MaybeUndefined::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_ref(&self) -> MaybeUndefined<&T> {
+        match self {
+            MaybeUndefined::Undefined => MaybeUndefined::Undefined,
+            MaybeUndefined::Null => MaybeUndefined::Null,
+            MaybeUndefined::Value(value) => MaybeUndefined::Value(value),
+        }
+    }
+}
+
+impl<T> Default for MaybeUndefined<T> {
+    fn default() -> Self {
+        MaybeUndefined::Undefined
+    }
+}
+
+/// Converts `Some` into `Value` & `None` into `Null`.
+impl<T> From<Option<T>> for MaybeUndefined<T> {
+    fn from(option: Option<T>) -> Self {
+        match option {
+            Some(value) => MaybeUndefined::Value(value),
+            None => MaybeUndefined::Null,
+        }
+    }
+}
+
+/// `Undefined` values serialize as `null`, as there's no way to leave
+/// out a value at this level.  InputObjects skip `Undefined` fields before
+/// they get this far.
+impl<T: SerializableArgument> SerializableArgument for MaybeUndefined<T> {
+    fn serialize(&self) -> Result<serde_json::Value, SerializeError> {
+        match self {
+            MaybeUndefined::Value(value) => value.serialize(),
+            _ => Ok(serde_json::Value::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_maybe_undefined() {
+        assert!(MaybeUndefined::<i32>::default().is_undefined());
+        assert_eq!(MaybeUndefined::from(Some(1)), MaybeUndefined::Value(1));
+        assert_eq!(MaybeUndefined::<i32>::from(None), MaybeUndefined::Null);
+
+        assert_eq!(MaybeUndefined::Value(1).serialize().unwrap(), json!(1));
+        assert_eq!(
+            MaybeUndefined::<i32>::Null.serialize().unwrap(),
+            json!(null)
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Hooks for customising the requests the HTTP integrations send.
+//!
+//! A `Middleware` is called before each request is sent & after each
+//! response is received.  This can be used to add authentication or other
+//! headers to every request, or to inspect responses - without having to
+//! wrap the HTTP client:
+//!
+//! ```rust,ignore
+//! use cynic::middleware;
+//!
+//! let auth = middleware::before_request(|request| {
+//!     request.add_header("Authorization", format!("Bearer {}", mint_jwt()?));
+//!     request.add_header("X-Tenant", "acme");
+//!     Ok(())
+//! });
+//!
+//! let response = client
+//!     .post(url)
+//!     .run_graphql_with_middleware(operation, &auth)
+//!     .await?;
+//! ```
+//!
+//! Several middlewares can be combined by putting them in a
+//! `Vec<Box<dyn Middleware>>`, which runs each of them in order.
+
+use std::collections::BTreeMap;
+
+use crate::{Argument, GraphQLResponse};
+
+/// An error returned by a `Middleware`, which stops the request from being
+/// sent.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+pub struct MiddlewareError(pub Box<dyn std::error::Error + Send + Sync>);
+
+impl MiddlewareError {
+    pub fn new(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        MiddlewareError(error.into())
+    }
+}
+
+/// A request that's about to be sent.
+#[derive(Debug)]
+pub struct MiddlewareRequest<'a> {
+    /// The name of the operation being sent.
+    pub operation_name: &'a str,
+    /// The query document being sent.
+    pub query: &'a str,
+    /// The variables being sent.
+    pub variables: &'a BTreeMap<String, Argument>,
+    /// Any headers to add to the request.
+    pub headers: Vec<(String, String)>,
+}
+
+impl MiddlewareRequest<'_> {
+    pub fn add_header(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.headers.push((name.into(), value.into()));
+    }
+}
+
+/// A response that's been received, before it's decoded.
+#[derive(Debug)]
+pub struct MiddlewareResponse<'a> {
+    /// The name of the operation this is a response to.
+    pub operation_name: &'a str,
+    /// The HTTP status of the response.
+    pub status: u16,
+    /// The HTTP headers of the response.
+    pub headers: &'a [(String, String)],
+    /// The body of the response.
+    pub body: &'a GraphQLResponse<serde_json::Value>,
+}
+
+/// Hooks that are called around each request the HTTP integrations send.
+pub trait Middleware: Send + Sync {
+    /// Called before a request is sent.  Returning an error stops the
+    /// request from being sent.
+    fn before_request(&self, _request: &mut MiddlewareRequest<'_>) -> Result<(), MiddlewareError> {
+        Ok(())
+    }
+
+    /// Called after a response has been received, before it's decoded.
+    fn after_response(&self, _response: &MiddlewareResponse<'_>) {}
+}
+
+/// The empty middleware, which does nothing.
+impl Middleware for () {}
+
+/// Runs each of the middlewares in order.
+impl Middleware for Vec<Box<dyn Middleware>> {
+    fn before_request(&self, request: &mut MiddlewareRequest<'_>) -> Result<(), MiddlewareError> {
+        for middleware in self {
+            middleware.before_request(request)?;
+        }
+        Ok(())
+    }
+
+    fn after_response(&self, response: &MiddlewareResponse<'_>) {
+        for middleware in self {
+            middleware.after_response(response);
+        }
+    }
+}
+
+/// Creates a `Middleware` that calls `f` before each request.
+pub fn before_request<F>(f: F) -> impl Middleware
+where
+    F: Fn(&mut MiddlewareRequest<'_>) -> Result<(), MiddlewareError> + Send + Sync,
+{
+    struct BeforeRequest<F>(F);
+
+    impl<F> Middleware for BeforeRequest<F>
+    where
+        F: Fn(&mut MiddlewareRequest<'_>) -> Result<(), MiddlewareError> + Send + Sync,
+    {
+        fn before_request(
+            &self,
+            request: &mut MiddlewareRequest<'_>,
+        ) -> Result<(), MiddlewareError> {
+            (self.0)(request)
+        }
+    }
+
+    BeforeRequest(f)
+}
+
+/// Creates a `Middleware` that calls `f` after each response.
+pub fn after_response<F>(f: F) -> impl Middleware
+where
+    F: Fn(&MiddlewareResponse<'_>) + Send + Sync,
+{
+    struct AfterResponse<F>(F);
+
+    impl<F> Middleware for AfterResponse<F>
+    where
+        F: Fn(&MiddlewareResponse<'_>) + Send + Sync,
+    {
+        fn after_response(&self, response: &MiddlewareResponse<'_>) {
+            (self.0)(response)
+        }
+    }
+
+    AfterResponse(f)
+}
+
+/// Runs the `before_request` hook of `middleware` for `operation`,
+/// returning the headers it wants to add.
+pub(crate) fn request_headers<ResponseData>(
+    middleware: &dyn Middleware,
+    operation: &crate::Operation<'_, ResponseData>,
+) -> Result<Vec<(String, String)>, MiddlewareError> {
+    let mut request = MiddlewareRequest {
+        operation_name: operation.name(),
+        query: &operation.query,
+        variables: &operation.variables,
+        headers: vec![],
+    };
+    middleware.before_request(&mut request)?;
+
+    Ok(request.headers)
+}
+
+/// Runs the `after_response` hook of `middleware` for a response to
+/// `operation`.
+pub(crate) fn inspect_response<ResponseData>(
+    middleware: &dyn Middleware,
+    operation: &crate::Operation<'_, ResponseData>,
+    status: u16,
+    headers: &[(String, String)],
+    body: &GraphQLResponse<serde_json::Value>,
+) {
+    middleware.after_response(&MiddlewareResponse {
+        operation_name: operation.name(),
+        status,
+        headers,
+        body,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn request(variables: &BTreeMap<String, Argument>) -> MiddlewareRequest<'_> {
+        MiddlewareRequest {
+            operation_name: "Query",
+            query: "query Query { test }",
+            variables,
+            headers: vec![],
+        }
+    }
+
+    #[test]
+    fn test_middleware_chains() {
+        let statuses = Arc::new(Mutex::new(vec![]));
+        let recorded_statuses = statuses.clone();
+
+        let middleware: Vec<Box<dyn Middleware>> = vec![
+            Box::new(before_request(|request| {
+                request.add_header("Authorization", "Bearer token");
+                Ok(())
+            })),
+            Box::new(before_request(|request| {
+                request.add_header("X-Tenant", request.operation_name.to_string());
+                Ok(())
+            })),
+            Box::new(after_response(move |response| {
+                recorded_statuses.lock().unwrap().push(response.status)
+            })),
+        ];
+
+        let variables = BTreeMap::new();
+        let mut request = request(&variables);
+        middleware.before_request(&mut request).unwrap();
+        assert_eq!(
+            request.headers,
+            vec![
+                ("Authorization".to_string(), "Bearer token".to_string()),
+                ("X-Tenant".to_string(), "Query".to_string()),
+            ]
+        );
+
+        middleware.after_response(&MiddlewareResponse {
+            operation_name: "Query",
+            status: 200,
+            headers: &[],
+            body: &GraphQLResponse {
+                data: None,
+                errors: None,
+                extensions: None,
+            },
+        });
+        assert_eq!(*statuses.lock().unwrap(), vec![200]);
+    }
+
+    #[test]
+    fn test_middleware_errors() {
+        let middleware = before_request(|_| Err(MiddlewareError::new("Could not mint a token")));
+
+        let variables = BTreeMap::new();
+        let error = middleware
+            .before_request(&mut request(&variables))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Could not mint a token");
+    }
+}
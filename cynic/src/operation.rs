@@ -1,41 +1,112 @@
 use json_decode::BoxDecoder;
-use std::collections::HashMap;
+use std::{collections::BTreeMap, sync::Arc};
 
 use crate::{
-    selection_set::{mutation_root, query_root},
-    Argument, GraphQLResponse, MutationRoot, QueryRoot, SelectionSet,
+    field::FragmentText,
+    selection_set::{mutation_root, query_root, subscription_root},
+    Argument, GraphQLResponse, GraphQLResult, MutationRoot, PossiblyParsedData, QueryRoot,
+    SelectionSet, SubscriptionRoot,
 };
 
+/// An Operation that can be sent to a GraphQL server.
+///
+/// Operations are `Send + Sync` so can be built on one thread and run
+/// from another.  They're also cheap to `Clone`, so a single Operation
+/// can be run many times without having to rebuild it.
 #[derive(serde::Serialize)]
 pub struct Operation<'a, ResponseData> {
     pub query: String,
-    pub variables: HashMap<String, Argument>,
+    /// The variables for this operation.
+    ///
+    /// These are kept in a BTreeMap so they always serialize in the same
+    /// order, which keeps any hashes of operations stable.
+    pub variables: BTreeMap<String, Argument>,
+    /// The name of the operation in `query` that the server should run.
+    ///
+    /// This is only needed when the document contains several operations -
+    /// see `OperationDocument`.
+    #[serde(rename = "operationName", skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+    /// A key that lets the server recognise retries of this operation, so
+    /// it can avoid repeating any side effects.
+    ///
+    /// The HTTP integrations send this in an `Idempotency-Key` header.
     #[serde(skip)]
-    decoder: BoxDecoder<'a, ResponseData>,
+    pub idempotency_key: Option<String>,
+    /// Request bodies larger than this many bytes are sent gzipped.
+    #[serde(skip)]
+    pub(crate) compression_threshold: Option<usize>,
+    #[serde(skip)]
+    operation_type: OperationType,
+    /// The named fragments that are defined at the end of `query`.
+    #[serde(skip)]
+    fragment_definitions: Vec<FragmentText>,
+    #[serde(skip)]
+    decoder: Arc<BoxDecoder<'a, ResponseData>>,
 }
 
-impl<'a, ResponseData: 'a> Operation<'a, ResponseData> {
-    pub fn query<Root: QueryRoot>(selection_set: SelectionSet<'a, ResponseData, Root>) -> Self {
-        let (query, arguments, decoder) = query_root(selection_set).query_arguments_and_decoder();
+/// The type of a GraphQL operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationType {
+    Query,
+    Mutation,
+    Subscription,
+}
 
-        let variables = arguments
-            .into_iter()
-            .enumerate()
-            .map(|(i, a)| (format!("_{}", i), a))
-            .collect();
+impl OperationType {
+    /// The keyword that starts operations of this type in a document.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            OperationType::Query => "query",
+            OperationType::Mutation => "mutation",
+            OperationType::Subscription => "subscription",
+        }
+    }
 
+    fn default_name(self) -> &'static str {
+        match self {
+            OperationType::Query => "Query",
+            OperationType::Mutation => "Mutation",
+            OperationType::Subscription => "Subscription",
+        }
+    }
+}
+
+impl<'a, ResponseData> Clone for Operation<'a, ResponseData> {
+    fn clone(&self) -> Self {
         Operation {
-            query,
-            variables,
-            decoder,
+            query: self.query.clone(),
+            variables: self.variables.clone(),
+            operation_name: self.operation_name.clone(),
+            idempotency_key: self.idempotency_key.clone(),
+            compression_threshold: self.compression_threshold,
+            operation_type: self.operation_type,
+            fragment_definitions: self.fragment_definitions.clone(),
+            decoder: Arc::clone(&self.decoder),
         }
     }
+}
+
+impl<'a, ResponseData: 'a> Operation<'a, ResponseData> {
+    pub fn query<Root: QueryRoot>(selection_set: SelectionSet<'a, ResponseData, Root>) -> Self {
+        Operation::from_root(OperationType::Query, query_root(selection_set))
+    }
 
     pub fn mutation<Root: MutationRoot>(
         selection_set: SelectionSet<'a, ResponseData, Root>,
     ) -> Self {
-        let (query, arguments, decoder) =
-            mutation_root(selection_set).query_arguments_and_decoder();
+        Operation::from_root(OperationType::Mutation, mutation_root(selection_set))
+    }
+
+    fn from_root(
+        operation_type: OperationType,
+        selection_set: SelectionSet<'a, ResponseData, ()>,
+    ) -> Self {
+        let (operation, fragment_definitions, arguments, decoder) =
+            selection_set.operation_arguments_and_decoder();
+        let query = fragment_definitions
+            .iter()
+            .fold(operation, |query, (_, definition)| query + definition);
 
         let variables = arguments
             .into_iter()
@@ -46,10 +117,167 @@ impl<'a, ResponseData: 'a> Operation<'a, ResponseData> {
         Operation {
             query,
             variables,
-            decoder,
+            operation_name: None,
+            idempotency_key: None,
+            compression_threshold: None,
+            operation_type,
+            fragment_definitions,
+            decoder: Arc::new(decoder),
+        }
+    }
+
+    /// Gives this operation a name.
+    ///
+    /// Operations are called `Query` or `Mutation` by default.  Any
+    /// operations that are going to be combined into an `OperationDocument`
+    /// need unique names.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        let (keyword, current_name) = self.keyword_and_name();
+        let current_header = format!("{} {}", keyword, current_name);
+
+        if self.query.starts_with(&current_header) {
+            self.query = format!(
+                "{} {}{}",
+                keyword,
+                name,
+                &self.query[current_header.len()..]
+            );
+        }
+
+        self.operation_name = Some(name);
+        self
+    }
+
+    /// Sets an idempotency key for this operation.
+    ///
+    /// Servers that support idempotency keys can use this to detect retries
+    /// of a mutation, and a `RetryPolicy` will retry mutations that have one.
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Compresses the body of this operation with gzip when it's larger than
+    /// `bytes`.
+    ///
+    /// This is done by the reqwest & surf integrations and any other
+    /// `GraphQlClient`, but only for JSON request bodies.  Not every server accepts compressed requests.
+    #[cfg(feature = "compression")]
+    pub fn compress_above(mut self, bytes: usize) -> Self {
+        self.compression_threshold = Some(bytes);
+        self
+    }
+
+    /// The type of this operation.
+    pub fn operation_type(&self) -> OperationType {
+        self.operation_type
+    }
+
+    /// Whether this operation is a mutation.
+    pub fn is_mutation(&self) -> bool {
+        self.operation_type == OperationType::Mutation
+    }
+
+    /// The name of this operation in its document.
+    pub fn name(&self) -> &str {
+        self.keyword_and_name().1
+    }
+
+    /// The query document that will be sent for this operation.
+    ///
+    /// This is deterministic: building the same operation always produces
+    /// the same string, with fields in the order they were selected and
+    /// variables named `_0`, `_1` etc. in the order they appear.  This
+    /// makes it suitable for snapshot testing, though note that the
+    /// `pretty-print` feature changes the formatting.
+    pub fn query_string(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns a copy of this operation that sends the whole of `document`,
+    /// using `operationName` to tell the server to run this operation.
+    ///
+    /// This operation should have been added to `document`, otherwise the
+    /// server will reject the request.
+    pub fn in_document(&self, document: &OperationDocument) -> Self {
+        Operation {
+            query: document.to_string(),
+            variables: self.variables.clone(),
+            operation_name: Some(self.name().to_string()),
+            idempotency_key: self.idempotency_key.clone(),
+            compression_threshold: self.compression_threshold,
+            operation_type: self.operation_type,
+            fragment_definitions: vec![],
+            decoder: Arc::clone(&self.decoder),
+        }
+    }
+
+    /// The URL query string parameters to send with this operation when
+    /// using an `application/graphql` request body.
+    ///
+    /// Some servers & caches prefer to receive the bare `query` as the body
+    /// of a request, with the variables & operation name moved into the
+    /// query string.  This returns those parameters, ready to be URL
+    /// encoded by an HTTP client.
+    pub fn query_string_parameters(
+        &self,
+    ) -> Result<Vec<(&'static str, String)>, serde_json::Error> {
+        let mut parameters = vec![];
+
+        if !self.variables.is_empty() {
+            parameters.push(("variables", serde_json::to_string(&self.variables)?));
+        }
+        if let Some(name) = &self.operation_name {
+            parameters.push(("operationName", name.clone()));
+        }
+
+        Ok(parameters)
+    }
+
+    /// Returns a copy of this operation that decodes responses into
+    /// `serde_json::Value`s rather than `ResponseData`.
+    ///
+    /// This lets operations with different response types be stored & run
+    /// together, e.g. in an `OperationRegistry`.
+    pub fn untyped(&self) -> Operation<'static, serde_json::Value> {
+        Operation {
+            query: self.query.clone(),
+            variables: self.variables.clone(),
+            operation_name: self.operation_name.clone(),
+            idempotency_key: self.idempotency_key.clone(),
+            compression_threshold: self.compression_threshold,
+            operation_type: self.operation_type,
+            fragment_definitions: self.fragment_definitions.clone(),
+            decoder: Arc::new(json_decode::json()),
+        }
+    }
+
+    /// Splits `query` into the operation definition & the definitions of
+    /// the fragments that follow it.
+    fn document_parts(&self) -> (&str, &[FragmentText]) {
+        let fragments: String = self
+            .fragment_definitions
+            .iter()
+            .map(|(_, definition)| definition.as_str())
+            .collect();
+
+        // `query` is public, so it may have been changed since we built it.
+        match self.query.strip_suffix(fragments.as_str()) {
+            Some(operation) => (operation, &self.fragment_definitions),
+            None => (&self.query, &[]),
         }
     }
 
+    fn keyword_and_name(&self) -> (&'static str, &str) {
+        let name = match &self.operation_name {
+            Some(name) => name.as_str(),
+            None => self.operation_type.default_name(),
+        };
+
+        (self.operation_type.keyword(), name)
+    }
+
     pub fn decode_response(
         &self,
         response: GraphQLResponse<serde_json::Value>,
@@ -58,12 +286,588 @@ impl<'a, ResponseData: 'a> Operation<'a, ResponseData> {
             Ok(GraphQLResponse {
                 data: Some(self.decoder.decode(&data)?),
                 errors: response.errors,
+                extensions: response.extensions,
             })
         } else {
             Ok(GraphQLResponse {
                 data: None,
                 errors: response.errors,
+                extensions: response.extensions,
             })
         }
     }
+
+    /// Decodes a response into a `GraphQLResult`, tolerating partial data.
+    ///
+    /// If the server returned errors, some fields might be unexpectedly null,
+    /// so the data might not decode.  In that case this returns the errors
+    /// along with `PossiblyParsedData::UnparsedData` rather than failing.
+    /// Decoding errors are only returned for responses without any GraphQL
+    /// errors.
+    pub fn decode_result(
+        &self,
+        response: GraphQLResponse<serde_json::Value>,
+    ) -> Result<GraphQLResult<ResponseData>, json_decode::DecodeError> {
+        let errors = response.errors.unwrap_or_default();
+
+        if errors.is_empty() {
+            let data = response.data.ok_or_else(|| {
+                json_decode::DecodeError::Other("Response had no data or errors".into())
+            })?;
+            return Ok(Ok(self.decoder.decode(&data)?));
+        }
+
+        let data = match response.data.map(|data| self.decoder.decode(&data)) {
+            Some(Ok(data)) => PossiblyParsedData::ParsedData(data),
+            _ => PossiblyParsedData::UnparsedData,
+        };
+
+        Ok(Err((data, errors)))
+    }
+
+    /// Renders a response alongside the selections in this operation, along
+    /// with any errors that are encountered when decoding it.
+    ///
+    /// This is intended to help debug responses that don't decode - see the
+    /// `debug` module for more details.
+    pub fn debug_response(&self, response: &GraphQLResponse<serde_json::Value>) -> String {
+        let mut output = String::new();
+
+        if let Some(errors) = &response.errors {
+            for error in errors {
+                output.push_str(&format!("GraphQL error: {}\n", error));
+            }
+        }
+
+        match &response.data {
+            None => output.push_str("The response contained no data\n"),
+            Some(data) => {
+                output.push_str(&crate::debug::render_response(
+                    &self.query,
+                    self.operation_name.as_deref(),
+                    data,
+                ));
+                if let Err(e) = self.decoder.decode(data) {
+                    output.push_str(&format!("Decoding failed: {}\n", e));
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Parses a response body from `reader` & then decodes it.
+    ///
+    /// This saves reading the body into a buffer before it's parsed, but the
+    /// whole response is still parsed into a `serde_json::Value` before it's
+    /// decoded - so the parsed response still needs to fit in memory.  There's
+    /// no async equivalent, as serde_json can only parse from a blocking
+    /// reader.
+    pub fn decode_response_reader(
+        &self,
+        reader: impl std::io::Read,
+    ) -> Result<GraphQLResponse<ResponseData>, json_decode::DecodeError> {
+        let response = serde_json::from_reader::<_, GraphQLResponse<serde_json::Value>>(reader)
+            .map_err(|e| json_decode::DecodeError::Other(e.to_string()))?;
+
+        self.decode_response(response)
+    }
+
+    /// Writes the GraphQL document for this operation to `{name}.graphql` in
+    /// `directory`, creating the directory if it doesn't exist.
+    ///
+    /// This is intended to be called from a test or a build step, so that
+    /// tools like GraphQL IDE plugins, linters or server side allowlists can
+    /// see the exact documents that an application will send.
+    pub fn write_document(
+        &self,
+        directory: impl AsRef<std::path::Path>,
+        name: &str,
+    ) -> std::io::Result<std::path::PathBuf> {
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)?;
+
+        let path = directory.join(format!("{}.graphql", name));
+        std::fs::write(&path, &self.query)?;
+
+        Ok(path)
+    }
+
+    /// Parses a raw response body with simd-json & then decodes it.
+    ///
+    /// Note that simd-json parses in place, so the contents of `body` will be
+    /// clobbered.
+    #[cfg(feature = "simd-json")]
+    pub fn decode_response_slice(
+        &self,
+        body: &mut [u8],
+    ) -> Result<GraphQLResponse<ResponseData>, json_decode::DecodeError> {
+        let response = simd_json::serde::from_slice::<GraphQLResponse<serde_json::Value>>(body)
+            .map_err(|e| json_decode::DecodeError::Other(e.to_string()))?;
+
+        self.decode_response(response)
+    }
+}
+
+/// A subscription that can be sent to a GraphQL server, which responds with a
+/// stream of responses rather than a single one.
+///
+/// Subscriptions can't be sent over plain HTTP, so these are kept separate
+/// from `Operation` - see the `graphql_transport_ws` module for a transport.
+#[derive(serde::Serialize)]
+#[serde(transparent, bound = "")]
+pub struct StreamingOperation<'a, ResponseData> {
+    inner: Operation<'a, ResponseData>,
+}
+
+impl<'a, ResponseData> Clone for StreamingOperation<'a, ResponseData> {
+    fn clone(&self) -> Self {
+        StreamingOperation {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, ResponseData: 'a> StreamingOperation<'a, ResponseData> {
+    pub fn subscription<Root: SubscriptionRoot>(
+        selection_set: SelectionSet<'a, ResponseData, Root>,
+    ) -> Self {
+        StreamingOperation {
+            inner: Operation::from_root(
+                OperationType::Subscription,
+                subscription_root(selection_set),
+            ),
+        }
+    }
+
+    /// Gives this subscription a name.
+    ///
+    /// Subscriptions are called `Subscription` by default.
+    pub fn named(self, name: impl Into<String>) -> Self {
+        StreamingOperation {
+            inner: self.inner.named(name),
+        }
+    }
+
+    /// The name of this subscription in its document.
+    pub fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    /// The query document for this subscription.
+    pub fn query(&self) -> &str {
+        &self.inner.query
+    }
+
+    /// This subscription as an `Operation`, for the helpers that take one.
+    pub(crate) fn as_operation(&self) -> &Operation<'a, ResponseData> {
+        &self.inner
+    }
+
+    /// Decodes one of the responses the server sent for this subscription.
+    pub fn decode_response(
+        &self,
+        response: GraphQLResponse<serde_json::Value>,
+    ) -> Result<GraphQLResponse<ResponseData>, json_decode::DecodeError> {
+        self.inner.decode_response(response)
+    }
+}
+
+/// A GraphQL document containing several named operations.
+///
+/// Some persisted document workflows require a single document containing
+/// every operation, with `operationName` used to select which one to run.
+///
+/// ```rust
+/// # use cynic::{selection_set::{field, string}, Operation, OperationDocument, QueryRoot};
+/// # struct Root;
+/// # impl QueryRoot for Root {}
+/// # let films = Operation::query(field::<_, Root, ()>("films", vec![], string()));
+/// # let people = Operation::query(field::<_, Root, ()>("people", vec![], string()));
+/// let films = films.named("Films");
+/// let people = people.named("People");
+///
+/// let mut document = OperationDocument::new();
+/// document.add(&films).unwrap().add(&people).unwrap();
+///
+/// // This operation sends both queries, but asks the server to run `People`
+/// let operation = people.in_document(&document);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OperationDocument {
+    operations: BTreeMap<String, String>,
+    /// The fragment definitions used by `operations`, which are shared
+    /// between any operations that use the same fragment.
+    fragments: BTreeMap<String, String>,
+}
+
+impl OperationDocument {
+    pub fn new() -> Self {
+        OperationDocument::default()
+    }
+
+    /// Adds an operation to the document.
+    ///
+    /// This will fail if the document already contains a different operation
+    /// with the same name, or a different definition of one of the named
+    /// fragments that the operation uses.
+    pub fn add<ResponseData>(
+        &mut self,
+        operation: &Operation<'_, ResponseData>,
+    ) -> Result<&mut Self, DocumentError> {
+        let name = operation.name();
+        let (definition, fragments) = operation.document_parts();
+
+        match self.operations.get(name) {
+            Some(existing) if existing != definition => {
+                return Err(DocumentError::DuplicateOperationName(name.to_string()))
+            }
+            _ => {}
+        }
+
+        for (fragment_name, fragment) in fragments {
+            match self.fragments.get(fragment_name) {
+                Some(existing) if existing != fragment => {
+                    return Err(DocumentError::DuplicateFragmentName(fragment_name.clone()))
+                }
+                _ => {}
+            }
+        }
+
+        self.operations
+            .insert(name.to_string(), definition.to_string());
+        self.fragments.extend(fragments.iter().cloned());
+
+        Ok(self)
+    }
+}
+
+impl std::fmt::Display for OperationDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, query) in self.operations.values().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", query)?;
+        }
+        for fragment in self.fragments.values() {
+            write!(f, "{}", fragment)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error that can occur when adding an operation to an `OperationDocument`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum DocumentError {
+    #[error("The document already contains a different operation named {0}")]
+    DuplicateOperationName(String),
+    #[error("The document already contains a different fragment named {0}")]
+    DuplicateFragmentName(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::query;
+
+    #[test]
+    fn test_operations_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Operation<'static, String>>();
+    }
+
+    #[test]
+    fn test_variables_serialize_in_a_stable_order() {
+        let operation = query(
+            "test",
+            (0..12)
+                .map(|i| Argument::new(&format!("arg{}", i), "Int!", i))
+                .collect(),
+        );
+
+        let expected = (0..12)
+            .map(|i| format!("_{}", i))
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            operation.variables.keys().cloned().collect::<Vec<_>>(),
+            expected
+        );
+
+        let serialized = serde_json::to_string(&operation).unwrap();
+        assert_eq!(
+            serialized,
+            serde_json::to_string(&operation.clone()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_query_strings_are_deterministic() {
+        use crate::{
+            selection_set::{field, integer, map2, string},
+            testing::fixtures::Root,
+        };
+
+        let build = || {
+            Operation::query(map2(
+                |a, b| (a, b),
+                field::<_, Root, ()>(
+                    "zebra",
+                    vec![Argument::new("b", "Int!", 2), Argument::new("a", "Int!", 1)],
+                    string(),
+                ),
+                field::<_, Root, ()>("apple", vec![Argument::new("c", "Int!", 3)], integer()),
+            ))
+        };
+
+        let query = build().query_string().to_string();
+        assert_eq!(query, build().query_string());
+
+        let zebra = query.find("zebra(b: $_0, a: $_1)").unwrap();
+        let apple = query.find("apple(c: $_2)").unwrap();
+        assert!(zebra < apple);
+    }
+
+    #[test]
+    fn test_query_string_parameters() {
+        let operation = query("test", vec![]);
+        assert_eq!(operation.query_string_parameters().unwrap(), vec![]);
+
+        let operation = query("test", vec![Argument::new("id", "Int!", 1)]).named("Test");
+
+        assert_eq!(
+            operation.query_string_parameters().unwrap(),
+            vec![
+                ("variables", r#"{"_0":1}"#.to_string()),
+                ("operationName", "Test".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cloned_operations_decode() {
+        let operation = query("test", vec![]);
+        let cloned = operation.clone();
+
+        let response = || GraphQLResponse {
+            data: Some(serde_json::json!({"test": "hello"})),
+            errors: None,
+            extensions: None,
+        };
+
+        assert_eq!(cloned.query, operation.query);
+        assert_eq!(
+            cloned.decode_response(response()).unwrap().data,
+            Some("hello".to_string())
+        );
+        assert_eq!(
+            operation.decode_response(response()).unwrap().data,
+            Some("hello".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decode_result() {
+        use crate::GraphQLError;
+        use assert_matches::assert_matches;
+
+        let operation = query("test", vec![]);
+        let errors = || Some(vec![GraphQLError::new("oops".into(), None, None, None)]);
+
+        assert_matches!(
+            operation.decode_result(GraphQLResponse {
+                data: Some(serde_json::json!({"test": "hello"})),
+                errors: None,
+                extensions: None,
+            }),
+            Ok(Ok(data)) => assert_eq!(data, "hello")
+        );
+        assert_matches!(
+            operation.decode_result(GraphQLResponse {
+                data: Some(serde_json::json!({"test": "hello"})),
+                errors: errors(),
+                extensions: None,
+            }),
+            Ok(Err((PossiblyParsedData::ParsedData(data), errors))) => {
+                assert_eq!(data, "hello");
+                assert_eq!(errors.len(), 1);
+            }
+        );
+        assert_matches!(
+            operation.decode_result(GraphQLResponse {
+                data: Some(serde_json::json!({"test": null})),
+                errors: errors(),
+                extensions: None,
+            }),
+            Ok(Err((PossiblyParsedData::UnparsedData, _)))
+        );
+        assert_matches!(
+            operation.decode_result(GraphQLResponse {
+                data: Some(serde_json::json!({"test": null})),
+                errors: Some(vec![]),
+                extensions: None,
+            }),
+            Err(_)
+        );
+    }
+
+    #[test]
+    fn test_decode_response_reader() {
+        let operation = query("test", vec![]);
+
+        let body: &[u8] = br#"{"data": {"test": "hello"}}"#;
+        assert_eq!(
+            operation.decode_response_reader(body).unwrap().data,
+            Some("hello".to_string())
+        );
+
+        let body: &[u8] = br#"{"data": {"test": "hel"#;
+        assert!(operation.decode_response_reader(body).is_err());
+    }
+
+    #[test]
+    fn test_debug_response() {
+        let operation = query("test", vec![]);
+
+        let output = operation.debug_response(&GraphQLResponse {
+            data: Some(serde_json::json!({"test": 1})),
+            errors: None,
+            extensions: None,
+        });
+
+        assert!(output.starts_with("test "));
+        assert!(output.contains("Decoding failed"));
+    }
+
+    #[test]
+    fn test_named_operations() {
+        let operation = query("test", vec![]);
+        assert_eq!(operation.name(), "Query");
+        assert!(serde_json::to_value(&operation)
+            .unwrap()
+            .get("operationName")
+            .is_none());
+
+        let operation = operation.named("TestQuery");
+        assert_eq!(operation.name(), "TestQuery");
+        assert!(operation.query.starts_with("query TestQuery {"));
+        assert_eq!(
+            serde_json::to_value(&operation).unwrap()["operationName"],
+            "TestQuery"
+        );
+    }
+
+    #[test]
+    fn test_operation_documents() {
+        let one = query("one", vec![]).named("One");
+        let two = query("two", vec![]).named("Two");
+
+        let mut document = OperationDocument::new();
+        document
+            .add(&two)
+            .unwrap()
+            .add(&one)
+            .unwrap()
+            .add(&one)
+            .unwrap();
+
+        let operation = two.in_document(&document);
+        assert_eq!(operation.query, format!("{}\n{}", one.query, two.query));
+        assert_eq!(operation.operation_name.as_deref(), Some("Two"));
+        assert_eq!(
+            operation
+                .decode_response(GraphQLResponse {
+                    data: Some(serde_json::json!({"two": "hello"})),
+                    errors: None,
+                    extensions: None,
+                })
+                .unwrap()
+                .data,
+            Some("hello".to_string())
+        );
+
+        let other_one = query("other", vec![]).named("One");
+        assert_eq!(
+            document.add(&other_one).unwrap_err(),
+            DocumentError::DuplicateOperationName("One".into())
+        );
+    }
+
+    #[test]
+    fn test_operation_documents_share_fragments() {
+        use crate::{
+            selection_set::{field, named_fragment, string},
+            testing::fixtures::Root,
+        };
+
+        let film_fields = |root_field: &str, arguments| {
+            Operation::query(field::<_, Root, ()>(
+                root_field,
+                vec![],
+                named_fragment(
+                    "FilmFields",
+                    "Film",
+                    field::<_, (), ()>("title", arguments, string()),
+                ),
+            ))
+        };
+        let one = film_fields("film", vec![]).named("One");
+        let two = film_fields("sequel", vec![]).named("Two");
+
+        let mut document = OperationDocument::new();
+        document.add(&one).unwrap().add(&two).unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            "query One {\n  film {\n    ...FilmFields\n  }\n}\n\nquery Two {\n  sequel {\n    ...FilmFields\n  }\n}\nfragment FilmFields on Film {\n  title\n}\n"
+        );
+
+        let three =
+            film_fields("prequel", vec![Argument::new("lang", "String", "en")]).named("Three");
+        assert_eq!(
+            document.add(&three).unwrap_err(),
+            DocumentError::DuplicateFragmentName("FilmFields".into())
+        );
+    }
+
+    #[test]
+    fn test_operation_types() {
+        use crate::testing::fixtures::{mutation, subscription};
+
+        let query = query("film", vec![]);
+        assert_eq!(query.operation_type(), OperationType::Query);
+        assert!(!query.is_mutation());
+
+        let mutation = mutation("createFilm", vec![]).named("CreateFilm");
+        assert_eq!(mutation.operation_type(), OperationType::Mutation);
+        assert!(mutation.is_mutation());
+
+        let mut document = OperationDocument::new();
+        document.add(&query.clone().named("A")).unwrap();
+        document.add(&mutation).unwrap();
+        assert!(document.to_string().starts_with("query A"));
+        assert!(mutation.in_document(&document).is_mutation());
+
+        assert_eq!(
+            subscription("filmAdded").inner.operation_type(),
+            OperationType::Subscription
+        );
+    }
+
+    #[test]
+    fn test_write_document() {
+        let operation = query("test", vec![]);
+
+        let directory = std::env::temp_dir().join("cynic-test-write-document");
+        let path = operation.write_document(&directory, "TestQuery").unwrap();
+
+        assert_eq!(path, directory.join("TestQuery.graphql"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), operation.query);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
 }
@@ -0,0 +1,394 @@
+//! Helpers for paginating Relay style connections.
+//!
+//! Fetching every node of a connection means running the same query many
+//! times, passing the `endCursor` of each page into the `after` argument of
+//! the next, until `hasNextPage` is false.  A `PagedQuery` keeps track of
+//! this, given a function that builds the query for a cursor:
+//!
+//! ```rust,ignore
+//! impl Page for AllFilmsQuery {
+//!     type Node = Film;
+//!
+//!     fn page_info(&self) -> PageInfo {
+//!         let page_info = &self.all_films.page_info;
+//!         PageInfo::new(page_info.has_next_page, page_info.end_cursor.clone())
+//!     }
+//!
+//!     fn into_nodes(self) -> Vec<Film> {
+//!         self.all_films.edges.into_iter().map(|edge| edge.node).collect()
+//!     }
+//! }
+//!
+//! let films = PagedQuery::new(|after| {
+//!     AllFilmsQuery::build(&AllFilmsArguments { first: 10, after })
+//! })
+//! .all_nodes(|operation| send(operation))?;
+//! ```
+//!
+//! With the `pagination` feature enabled, `PagedQuery::into_stream` returns
+//! a `Stream` of nodes that fetches each page as it's needed.
+
+use std::collections::VecDeque;
+
+use crate::{GraphQLError, GraphQLResponse, Operation};
+
+/// The `pageInfo` of a Relay connection.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageInfo {
+    pub has_next_page: bool,
+    pub end_cursor: Option<String>,
+}
+
+impl PageInfo {
+    pub fn new(has_next_page: bool, end_cursor: Option<String>) -> Self {
+        PageInfo {
+            has_next_page,
+            end_cursor,
+        }
+    }
+}
+
+/// A page of a connection.
+///
+/// This is usually implemented for the `QueryFragment` of a paginated query,
+/// pulling the nodes & page info out of the connection it contains.
+pub trait Page {
+    type Node;
+
+    /// The page info of the connection in this page.
+    fn page_info(&self) -> PageInfo;
+
+    /// The nodes of the connection in this page.
+    fn into_nodes(self) -> Vec<Self::Node>;
+}
+
+/// Runs a paginated query, fetching each page of a connection in turn.
+///
+/// `PagedQuery` doesn't send any operations itself: `next_operation` returns
+/// the operation for the next page, and the response is passed back into
+/// `handle_response`.  `all_nodes` & `into_stream` do this for you.
+pub struct PagedQuery<'a, ResponseData, Build> {
+    build: Build,
+    cursor: Option<String>,
+    current: Option<Operation<'a, ResponseData>>,
+    finished: bool,
+}
+
+impl<'a, ResponseData, Build> PagedQuery<'a, ResponseData, Build>
+where
+    ResponseData: Page + 'a,
+    Build: Fn(Option<String>) -> Operation<'a, ResponseData>,
+{
+    /// Creates a PagedQuery from a function that builds the query for a
+    /// page, given the cursor to pass as its `after` argument.
+    ///
+    /// The first page is fetched with a cursor of `None`.
+    pub fn new(build: Build) -> Self {
+        PagedQuery {
+            build,
+            cursor: None,
+            current: None,
+            finished: false,
+        }
+    }
+
+    /// The operation that fetches the next page, or `None` if every page
+    /// has been fetched.
+    pub fn next_operation(&mut self) -> Option<Operation<'a, ResponseData>> {
+        if self.finished {
+            return None;
+        }
+
+        let operation = (self.build)(self.cursor.clone());
+        self.current = Some(operation.clone());
+        Some(operation)
+    }
+
+    /// Handles the response to the operation returned by `next_operation`,
+    /// returning the nodes it contained.
+    ///
+    /// Any error stops the pagination, as does a page without a new cursor.
+    pub fn handle_response<E>(
+        &mut self,
+        response: Result<GraphQLResponse<serde_json::Value>, E>,
+    ) -> Result<Vec<ResponseData::Node>, PaginationError<E>> {
+        let result = self.decode_page(response);
+        match &result {
+            Ok((page_info, _)) if page_info.has_next_page => {
+                match &page_info.end_cursor {
+                    Some(cursor) if Some(cursor) != self.cursor.as_ref() => {
+                        self.cursor = Some(cursor.clone());
+                    }
+                    // Without a new cursor we'd just fetch the same page again.
+                    _ => self.finished = true,
+                }
+            }
+            _ => self.finished = true,
+        }
+
+        result.map(|(_, nodes)| nodes)
+    }
+
+    /// Fetches every page using `send`, returning all of their nodes.
+    pub fn all_nodes<E>(
+        mut self,
+        mut send: impl FnMut(
+            &Operation<'a, ResponseData>,
+        ) -> Result<GraphQLResponse<serde_json::Value>, E>,
+    ) -> Result<Vec<ResponseData::Node>, PaginationError<E>> {
+        let mut nodes = vec![];
+        while let Some(operation) = self.next_operation() {
+            nodes.extend(self.handle_response(send(&operation))?);
+        }
+        Ok(nodes)
+    }
+
+    fn decode_page<E>(
+        &mut self,
+        response: Result<GraphQLResponse<serde_json::Value>, E>,
+    ) -> Result<(PageInfo, Vec<ResponseData::Node>), PaginationError<E>> {
+        let response = response.map_err(PaginationError::Transport)?;
+        let operation = self
+            .current
+            .take()
+            .expect("handle_response should only be called after next_operation");
+
+        let response = operation.decode_response(response)?;
+        if let Some(errors) = response.errors.filter(|errors| !errors.is_empty()) {
+            return Err(PaginationError::Errors(errors));
+        }
+
+        let page = response.data.ok_or(PaginationError::MissingData)?;
+        Ok((page.page_info(), page.into_nodes()))
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum PaginationError<E> {
+    #[error("Error sending operation: {0}")]
+    Transport(E),
+    #[error("Error decoding GraphQL response: {0}")]
+    Decode(#[from] json_decode::DecodeError),
+    #[error("Page failed with {} errors", .0.len())]
+    Errors(Vec<GraphQLError>),
+    #[error("The response to a page did not contain any data")]
+    MissingData,
+}
+
+#[cfg(feature = "pagination")]
+pub use stream::PageStream;
+
+#[cfg(feature = "pagination")]
+mod stream {
+    use futures_core::Stream;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use super::{Page, PagedQuery, PaginationError, VecDeque};
+    use crate::{GraphQLResponse, Operation};
+
+    impl<'a, ResponseData, Build> PagedQuery<'a, ResponseData, Build>
+    where
+        ResponseData: Page + 'a,
+        Build: Fn(Option<String>) -> Operation<'a, ResponseData>,
+    {
+        /// Converts this into a `Stream` of nodes, that uses `send` to fetch
+        /// each page when the nodes from the previous page run out.
+        pub fn into_stream<Sender, Fut, E>(
+            self,
+            send: Sender,
+        ) -> PageStream<'a, ResponseData, Build, Sender, Fut>
+        where
+            Sender: FnMut(Operation<'a, ResponseData>) -> Fut,
+            Fut: Future<Output = Result<GraphQLResponse<serde_json::Value>, E>>,
+        {
+            PageStream {
+                query: self,
+                send,
+                in_flight: None,
+                nodes: VecDeque::new(),
+            }
+        }
+    }
+
+    /// A `Stream` of every node in a paginated connection.
+    ///
+    /// The stream ends after the first error.
+    pub struct PageStream<'a, ResponseData: Page, Build, Sender, Fut> {
+        query: PagedQuery<'a, ResponseData, Build>,
+        send: Sender,
+        in_flight: Option<Pin<Box<Fut>>>,
+        nodes: VecDeque<ResponseData::Node>,
+    }
+
+    impl<'a, ResponseData, Build, Sender, Fut, E> Stream
+        for PageStream<'a, ResponseData, Build, Sender, Fut>
+    where
+        ResponseData: Page + 'a,
+        Build: Fn(Option<String>) -> Operation<'a, ResponseData> + Unpin,
+        Sender: FnMut(Operation<'a, ResponseData>) -> Fut + Unpin,
+        Fut: Future<Output = Result<GraphQLResponse<serde_json::Value>, E>>,
+    {
+        type Item = Result<ResponseData::Node, PaginationError<E>>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+
+            loop {
+                if let Some(node) = this.nodes.pop_front() {
+                    return Poll::Ready(Some(Ok(node)));
+                }
+
+                if this.in_flight.is_none() {
+                    let operation = match this.query.next_operation() {
+                        Some(operation) => operation,
+                        None => return Poll::Ready(None),
+                    };
+                    this.in_flight = Some(Box::pin((this.send)(operation)));
+                }
+
+                let response = match this.in_flight.as_mut().map(|f| f.as_mut().poll(cx)) {
+                    Some(Poll::Ready(response)) => response,
+                    _ => return Poll::Pending,
+                };
+                this.in_flight = None;
+
+                match this.query.handle_response(response) {
+                    Ok(nodes) => this.nodes.extend(nodes),
+                    Err(e) => return Poll::Ready(Some(Err(e))),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use crate::{
+        selection_set::{field, integer, map2, option, string, vec},
+        testing::fixtures::Root,
+        Argument,
+    };
+
+    #[derive(Debug)]
+    struct FilmsPage {
+        end_cursor: Option<String>,
+        films: Vec<i32>,
+    }
+
+    impl Page for FilmsPage {
+        type Node = i32;
+
+        fn page_info(&self) -> PageInfo {
+            PageInfo::new(self.end_cursor.is_some(), self.end_cursor.clone())
+        }
+
+        fn into_nodes(self) -> Vec<i32> {
+            self.films
+        }
+    }
+
+    fn films_query(after: Option<String>) -> Operation<'static, FilmsPage> {
+        Operation::query(field::<_, Root, ()>(
+            "films",
+            vec![Argument::new("after", "String", after)],
+            map2(
+                |end_cursor, films| FilmsPage { end_cursor, films },
+                field("endCursor", vec![], option(string())),
+                field("ids", vec![], vec(integer())),
+            ),
+        ))
+    }
+
+    fn page(
+        operation: &Operation<'_, FilmsPage>,
+    ) -> Result<GraphQLResponse<serde_json::Value>, ()> {
+        let (end_cursor, ids) = match operation.variables["_0"].serialize_result.as_ref() {
+            Ok(serde_json::Value::Null) => (json!("one"), json!([1, 2])),
+            Ok(cursor) if cursor == "one" => (json!("two"), json!([3])),
+            _ => (json!(null), json!([4])),
+        };
+
+        Ok(GraphQLResponse {
+            data: Some(json!({ "films": { "endCursor": end_cursor, "ids": ids } })),
+            errors: None,
+            extensions: None,
+        })
+    }
+
+    #[test]
+    fn test_all_nodes() {
+        let nodes = PagedQuery::new(films_query).all_nodes(page).unwrap();
+
+        assert_eq!(nodes, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_errors_stop_pagination() {
+        let mut query = PagedQuery::new(films_query);
+
+        query.next_operation().unwrap();
+        assert_matches!(
+            query.handle_response(Err("oops")),
+            Err(PaginationError::Transport("oops"))
+        );
+        assert!(query.next_operation().is_none());
+    }
+
+    #[test]
+    fn test_repeated_cursors_stop_pagination() {
+        let mut query = PagedQuery::new(films_query);
+        let response = || {
+            Ok::<_, ()>(GraphQLResponse {
+                data: Some(json!({ "films": { "endCursor": "one", "ids": [1] } })),
+                errors: None,
+                extensions: None,
+            })
+        };
+
+        query.next_operation().unwrap();
+        assert_eq!(query.handle_response(response()).unwrap(), vec![1]);
+        query.next_operation().unwrap();
+        assert_eq!(query.handle_response(response()).unwrap(), vec![1]);
+        assert!(query.next_operation().is_none());
+    }
+
+    #[cfg(feature = "pagination")]
+    #[test]
+    fn test_page_stream() {
+        use futures_core::Stream;
+        use std::{
+            pin::Pin,
+            task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+        };
+
+        fn noop_waker() -> Waker {
+            fn clone(_: *const ()) -> RawWaker {
+                RawWaker::new(std::ptr::null(), &VTABLE)
+            }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+            unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+        }
+
+        let mut stream = PagedQuery::new(films_query)
+            .into_stream(|operation| std::future::ready(page(&operation)));
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut nodes = vec![];
+        while let Poll::Ready(Some(node)) = Pin::new(&mut stream).poll_next(&mut cx) {
+            nodes.push(node.unwrap());
+        }
+
+        assert_eq!(nodes, vec![1, 2, 3, 4]);
+    }
+}
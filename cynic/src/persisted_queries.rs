@@ -0,0 +1,238 @@
+//! Support for Automatic Persisted Queries (APQs).
+//!
+//! Servers that support APQs let clients send the SHA-256 hash of a query
+//! document rather than the document itself:
+//!
+//! ```json
+//! { "extensions": { "persistedQuery": { "version": 1, "sha256Hash": "..." } } }
+//! ```
+//!
+//! If the server hasn't seen the document before it responds with a
+//! `PersistedQueryNotFound` error, and the client resends the request with
+//! the full document to register it.
+//!
+//! `PersistedQueries` keeps track of which operations have been registered,
+//! and whether the server supports APQs at all.  The reqwest integrations
+//! use it in `run_graphql_persisted`, but it doesn't do any IO itself so can
+//! be used with any HTTP client.
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::{json, Value};
+
+use crate::{manifest::document_hash, GraphQLResponse, Operation};
+
+/// Tracks the operations that have been registered with a server.
+///
+/// `PersistedQueries` is cheap to clone - clones share the same state.
+#[derive(Clone, Debug)]
+pub struct PersistedQueries {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    registered: HashSet<String>,
+    supported: bool,
+}
+
+impl PersistedQueries {
+    pub fn new() -> Self {
+        PersistedQueries {
+            state: Arc::new(Mutex::new(State {
+                registered: HashSet::new(),
+                supported: true,
+            })),
+        }
+    }
+
+    /// The body of the first request to send for `operation`.
+    ///
+    /// This only contains the hash of the operation's document, unless the
+    /// server has told us it doesn't support APQs.
+    pub fn request_body<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+    ) -> Result<Value, serde_json::Error> {
+        let mut body = self.registration_body(operation)?;
+        if self.lock().supported {
+            if let Value::Object(fields) = &mut body {
+                fields.remove("query");
+            }
+        }
+        Ok(body)
+    }
+
+    /// The body to send when `handle_response` says an operation needs to
+    /// be registered.
+    ///
+    /// This contains the full document of the operation, along with its
+    /// hash.
+    pub fn registration_body<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+    ) -> Result<Value, serde_json::Error> {
+        let mut body = serde_json::to_value(operation)?;
+        if self.lock().supported {
+            body["extensions"] = json!({
+                "persistedQuery": {
+                    "version": 1,
+                    "sha256Hash": document_hash(&operation.query),
+                }
+            });
+        }
+        Ok(body)
+    }
+
+    /// Handles the response to a request for `operation`.
+    ///
+    /// Returns true if the server didn't recognise the operation, in which
+    /// case the request should be sent again with `registration_body`.
+    pub fn handle_response<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+        response: &GraphQLResponse<Value>,
+    ) -> bool {
+        let hash = document_hash(&operation.query);
+        let mut state = self.lock();
+
+        if has_error(
+            response,
+            "PersistedQueryNotSupported",
+            "PERSISTED_QUERY_NOT_SUPPORTED",
+        ) {
+            state.supported = false;
+            state.registered.clear();
+            return true;
+        }
+
+        if has_error(
+            response,
+            "PersistedQueryNotFound",
+            "PERSISTED_QUERY_NOT_FOUND",
+        ) {
+            state.registered.remove(&hash);
+            return true;
+        }
+
+        if state.supported {
+            state.registered.insert(hash);
+        }
+        false
+    }
+
+    /// Whether `operation` is known to be registered with the server.
+    pub fn is_registered<ResponseData>(&self, operation: &Operation<'_, ResponseData>) -> bool {
+        self.lock()
+            .registered
+            .contains(&document_hash(&operation.query))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .expect("PersistedQueries mutex was poisoned")
+    }
+}
+
+impl Default for PersistedQueries {
+    fn default() -> Self {
+        PersistedQueries::new()
+    }
+}
+
+/// Whether `response` contains an error with the given message or code.
+fn has_error(response: &GraphQLResponse<Value>, message: &str, code: &str) -> bool {
+    response.errors.iter().flatten().any(|error| {
+        error.message == message
+            || error
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions["code"].as_str())
+                == Some(code)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::fixtures::query, GraphQLError};
+
+    fn error_response(message: &str) -> GraphQLResponse<Value> {
+        GraphQLResponse {
+            data: None,
+            errors: Some(vec![GraphQLError::new(message.into(), None, None, None)]),
+            extensions: None,
+        }
+    }
+
+    fn ok_response() -> GraphQLResponse<Value> {
+        GraphQLResponse {
+            data: Some(json!({ "film": "A New Hope" })),
+            errors: None,
+            extensions: None,
+        }
+    }
+
+    #[test]
+    fn test_request_bodies() {
+        let persisted_queries = PersistedQueries::new();
+        let operation = query("film", vec![]);
+        let hash = json!(document_hash(&operation.query));
+
+        let body = persisted_queries.request_body(&operation).unwrap();
+        assert!(body.get("query").is_none());
+        assert_eq!(body["extensions"]["persistedQuery"]["sha256Hash"], hash);
+
+        let body = persisted_queries.registration_body(&operation).unwrap();
+        assert_eq!(body["query"], json!(operation.query));
+        assert_eq!(body["extensions"]["persistedQuery"]["sha256Hash"], hash);
+    }
+
+    #[test]
+    fn test_registration() {
+        let persisted_queries = PersistedQueries::new();
+        let operation = query("film", vec![]);
+
+        assert!(persisted_queries
+            .handle_response(&operation, &error_response("PersistedQueryNotFound")));
+        assert!(!persisted_queries.is_registered(&operation));
+
+        assert!(!persisted_queries.handle_response(&operation, &ok_response()));
+        assert!(persisted_queries.is_registered(&operation));
+
+        // Servers can forget about registered operations.
+        assert!(persisted_queries.handle_response(
+            &operation,
+            &GraphQLResponse {
+                data: None,
+                errors: Some(vec![GraphQLError::new(
+                    "Not found".into(),
+                    None,
+                    None,
+                    Some(json!({ "code": "PERSISTED_QUERY_NOT_FOUND" }))
+                )]),
+                extensions: None,
+            }
+        ));
+        assert!(!persisted_queries.is_registered(&operation));
+    }
+
+    #[test]
+    fn test_unsupported_servers() {
+        let persisted_queries = PersistedQueries::new();
+        let operation = query("film", vec![]);
+
+        assert!(persisted_queries
+            .handle_response(&operation, &error_response("PersistedQueryNotSupported")));
+        assert!(!persisted_queries.handle_response(&operation, &ok_response()));
+        assert!(!persisted_queries.is_registered(&operation));
+
+        let body = persisted_queries.request_body(&operation).unwrap();
+        assert_eq!(body["query"], json!(operation.query));
+        assert!(body.get("extensions").is_none());
+    }
+}
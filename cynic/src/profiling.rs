@@ -0,0 +1,277 @@
+//! Profiling how long each part of a response takes to decode.
+//!
+//! Decoding a huge response can take a noticeable amount of time, and it's not
+//! always obvious which parts of a query are responsible.  `decode_with_profile`
+//! decodes a response while recording the time spent & the number of bytes of
+//! JSON decoded for each top level field, and for each inline fragment:
+//!
+//! ```text
+//! path                                    count   bytes       time
+//! allFilms                                    1   48213    1.203ms
+//! search.... on Film                         12    9023  412.000µs
+//! ```
+//!
+//! Entries for fragments inside lists are combined, with `count` saying how
+//! many times they were decoded.
+//!
+//! This module requires the `profiling` feature, which adds the
+//! instrumentation it relies on to cynic's decoders.  Decoding has no extra
+//! cost when the feature is disabled.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::{GraphQLResponse, Operation};
+
+/// The time spent decoding each part of a response.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodeProfile {
+    /// The total time spent decoding.
+    pub total: Duration,
+    /// Entries for each top level field & inline fragment, in the order they
+    /// finished decoding.
+    pub entries: Vec<ProfileEntry>,
+}
+
+/// The time spent decoding a field or inline fragment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileEntry {
+    /// The path of the field, e.g. `allFilms` or `search.... on Film`.
+    pub path: String,
+    /// The number of times this was decoded.
+    pub count: usize,
+    /// The size of the JSON that was decoded, in bytes.
+    pub bytes: usize,
+    /// The time spent decoding, including any nested fields.
+    pub duration: Duration,
+}
+
+impl DecodeProfile {
+    /// The entries of this profile, slowest first.
+    pub fn slowest(&self) -> Vec<&ProfileEntry> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.duration.cmp(&a.duration));
+        entries
+    }
+}
+
+impl fmt::Display for DecodeProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<40}{:>6}{:>8}{:>11}",
+            "path", "count", "bytes", "time"
+        )?;
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{:<40}{:>6}{:>8}{:>11}",
+                entry.path,
+                entry.count,
+                entry.bytes,
+                format!("{:.3?}", entry.duration)
+            )?;
+        }
+        write!(f, "total: {:.3?}", self.total)
+    }
+}
+
+/// Decodes `response` for `operation`, recording how long each part of it
+/// took.
+pub fn decode_with_profile<ResponseData>(
+    operation: &Operation<'_, ResponseData>,
+    response: GraphQLResponse<Value>,
+) -> (
+    Result<GraphQLResponse<ResponseData>, json_decode::DecodeError>,
+    DecodeProfile,
+) {
+    let previous = PROFILER.with(|profiler| profiler.replace(Some(Profiler::default())));
+
+    let start = Instant::now();
+    let result = operation.decode_response(response);
+    let total = start.elapsed();
+
+    let profiler = PROFILER.with(|profiler| profiler.replace(previous));
+
+    let profile = DecodeProfile {
+        total,
+        entries: profiler.map(|p| p.entries).unwrap_or_default(),
+    };
+
+    (result, profile)
+}
+
+thread_local! {
+    static PROFILER: RefCell<Option<Profiler>> = RefCell::new(None);
+}
+
+#[derive(Default)]
+struct Profiler {
+    path: Vec<String>,
+    entries: Vec<ProfileEntry>,
+}
+
+/// Records the time between its creation & being dropped against the current
+/// path.
+pub(crate) struct SpanGuard {
+    /// Whether a segment was pushed onto the profiler's path.
+    entered: bool,
+    started: Option<(Instant, usize)>,
+}
+
+/// Marks the start of decoding `field_name` from `value`.
+pub(crate) fn enter_field(field_name: &str, value: &Value) -> SpanGuard {
+    enter(
+        || field_name.to_string(),
+        |depth| depth == 0,
+        || value.get(field_name),
+    )
+}
+
+/// Marks the start of decoding an inline fragment on `typename`.
+pub(crate) fn enter_fragment(typename: &str, value: &Value) -> SpanGuard {
+    enter(|| format!("... on {}", typename), |_| true, || Some(value))
+}
+
+/// Pushes a segment onto the path of the active profiler.  Nothing is done
+/// (and `segment` isn't built) when there isn't one.
+fn enter<'v>(
+    segment: impl FnOnce() -> String,
+    should_record: impl FnOnce(usize) -> bool,
+    value: impl FnOnce() -> Option<&'v Value>,
+) -> SpanGuard {
+    PROFILER.with(|profiler| {
+        let mut profiler = profiler.borrow_mut();
+        let profiler = match profiler.as_mut() {
+            Some(profiler) => profiler,
+            None => {
+                return SpanGuard {
+                    entered: false,
+                    started: None,
+                }
+            }
+        };
+
+        let record = should_record(profiler.path.len());
+        profiler.path.push(segment());
+
+        if !record {
+            return SpanGuard {
+                entered: true,
+                started: None,
+            };
+        }
+
+        let bytes = value()
+            .and_then(|value| serde_json::to_vec(value).ok())
+            .map(|json| json.len())
+            .unwrap_or(0);
+
+        SpanGuard {
+            entered: true,
+            started: Some((Instant::now(), bytes)),
+        }
+    })
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        if !self.entered {
+            return;
+        }
+
+        PROFILER.with(|profiler| {
+            let mut profiler = profiler.borrow_mut();
+            let profiler = match profiler.as_mut() {
+                Some(profiler) => profiler,
+                None => return,
+            };
+
+            if let Some((start, bytes)) = self.started {
+                let duration = start.elapsed();
+                let path = profiler.path.join(".");
+
+                match profiler.entries.iter_mut().find(|entry| entry.path == path) {
+                    Some(entry) => {
+                        entry.count += 1;
+                        entry.bytes += bytes;
+                        entry.duration += duration;
+                    }
+                    None => profiler.entries.push(ProfileEntry {
+                        path,
+                        count: 1,
+                        bytes,
+                        duration,
+                    }),
+                }
+            }
+
+            profiler.path.pop();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::{
+        selection_set::{field, inline_fragments, string, vec},
+        testing::fixtures::Root,
+    };
+
+    #[test]
+    fn test_profiling_fields_and_fragments() {
+        let operation = Operation::query(crate::selection_set::map2(
+            |title, names| (title, names),
+            field::<_, Root, ()>("title", vec![], string()),
+            field::<_, Root, ()>(
+                "search",
+                vec![],
+                vec(inline_fragments(vec![(
+                    "Person".to_string(),
+                    field::<_, (), ()>("name", vec![], string()),
+                )])),
+            ),
+        ));
+
+        let response = GraphQLResponse {
+            data: Some(json!({
+                "title": "A New Hope",
+                "search": [
+                    { "__typename": "Person", "name": "Luke" },
+                    { "__typename": "Person", "name": "Leia" }
+                ]
+            })),
+            errors: None,
+            extensions: None,
+        };
+
+        let (result, profile) = decode_with_profile(&operation, response);
+
+        assert_eq!(
+            result.unwrap().data.unwrap().1,
+            vec!["Luke".to_string(), "Leia".to_string()]
+        );
+
+        let paths = profile
+            .entries
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![("title", 1), ("search.... on Person", 2), ("search", 1)]
+        );
+        assert_eq!(profile.entries[0].bytes, "\"A New Hope\"".len());
+
+        // Nothing should be recorded outside of decode_with_profile
+        assert!(PROFILER.with(|profiler| profiler.borrow().is_none()));
+    }
+}
@@ -0,0 +1,238 @@
+//! Client side rate limiting driven by feedback from the server.
+//!
+//! Many GraphQL APIs limit clients to a budget of query cost that refills
+//! over time, and report the state of that budget in response headers or
+//! extensions.  A `RateLimiter` keeps track of that budget and tells callers
+//! how long to wait before sending an operation so they stay under it.
+//!
+//! Cynic doesn't depend on any particular async runtime so the `RateLimiter`
+//! doesn't sleep itself - callers should wait for the returned `Duration`
+//! using whatever timer their runtime provides.
+//!
+//! ```rust
+//! # use cynic::rate_limit::RateLimiter;
+//! # let response_extensions = serde_json::json!({});
+//! // A budget of 1000 points, restoring at 50 points per second.
+//! let limiter = RateLimiter::new(1000.0, 50.0);
+//!
+//! let delay = limiter.acquire(10.0);
+//! // Sleep for `delay`, send the operation, then update the limiter with
+//! // whatever the server reported:
+//! limiter.update_from_extensions(&response_extensions);
+//! ```
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Tracks a budget of query cost that is restored over time.
+///
+/// Costs are reserved up front by `acquire`, so concurrent callers are
+/// queued behind each other rather than all sending at once.
+///
+/// `RateLimiter` is cheap to clone - clones share the same budget.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    capacity: f64,
+    available: f64,
+    restore_rate: f64,
+    updated_at: Instant,
+}
+
+impl State {
+    fn restore(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.available = (self.available + elapsed * self.restore_rate).min(self.capacity);
+        self.updated_at = now;
+    }
+}
+
+impl RateLimiter {
+    /// Creates a new RateLimiter with a full budget of `capacity` that is
+    /// restored at `restore_rate` points per second.
+    pub fn new(capacity: f64, restore_rate: f64) -> Self {
+        RateLimiter {
+            state: Arc::new(Mutex::new(State {
+                capacity,
+                available: capacity,
+                restore_rate,
+                updated_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Reserves `cost` points from the budget, returning how long the caller
+    /// should wait before sending their operation.
+    pub fn acquire(&self, cost: f64) -> Duration {
+        self.acquire_at(cost, Instant::now())
+    }
+
+    /// The amount of the budget that's currently available.
+    ///
+    /// This can be negative if callers have reserved more than the budget and
+    /// are waiting for it to be restored.
+    pub fn available(&self) -> f64 {
+        let mut state = self.lock();
+        state.restore(Instant::now());
+        state.available
+    }
+
+    /// Updates the budget from a `cost` response extension, as sent by
+    /// Shopify & several other APIs:
+    ///
+    /// ```json
+    /// {
+    ///   "cost": {
+    ///     "throttleStatus": {
+    ///       "maximumAvailable": 1000.0,
+    ///       "currentlyAvailable": 990.0,
+    ///       "restoreRate": 50.0
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// Any values that are missing from the extensions are left unchanged.
+    pub fn update_from_extensions(&self, extensions: &serde_json::Value) {
+        let status = &extensions["cost"]["throttleStatus"];
+
+        self.update(
+            status["maximumAvailable"].as_f64(),
+            status["currentlyAvailable"].as_f64(),
+            status["restoreRate"].as_f64(),
+        );
+    }
+
+    /// Updates the budget from the conventional `X-RateLimit-Limit`,
+    /// `X-RateLimit-Remaining` & `X-RateLimit-Reset` headers, where the reset
+    /// header is the number of seconds until the budget is fully restored.
+    ///
+    /// Header names are matched case insensitively, and any headers that are
+    /// missing or can't be parsed are ignored.
+    pub fn update_from_headers<'a>(&self, headers: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        let mut limit = None;
+        let mut remaining = None;
+        let mut reset = None;
+
+        for (name, value) in headers {
+            let value = match value.trim().parse::<f64>() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+
+            match name.to_ascii_lowercase().as_str() {
+                "x-ratelimit-limit" => limit = Some(value),
+                "x-ratelimit-remaining" => remaining = Some(value),
+                "x-ratelimit-reset" => reset = Some(value),
+                _ => {}
+            }
+        }
+
+        let restore_rate = match (limit, remaining, reset) {
+            (Some(limit), Some(remaining), Some(reset)) if reset > 0.0 => {
+                Some((limit - remaining).max(0.0) / reset)
+            }
+            _ => None,
+        };
+
+        self.update(limit, remaining, restore_rate);
+    }
+
+    fn update(&self, capacity: Option<f64>, available: Option<f64>, restore_rate: Option<f64>) {
+        let mut state = self.lock();
+        state.restore(Instant::now());
+
+        if let Some(capacity) = capacity {
+            state.capacity = capacity;
+        }
+        if let Some(available) = available {
+            state.available = available.min(state.capacity);
+        }
+        if let Some(restore_rate) = restore_rate {
+            state.restore_rate = restore_rate;
+        }
+    }
+
+    fn acquire_at(&self, cost: f64, now: Instant) -> Duration {
+        let mut state = self.lock();
+        state.restore(now);
+        state.available -= cost;
+
+        if state.available >= 0.0 {
+            return Duration::from_secs(0);
+        }
+
+        if state.restore_rate <= 0.0 {
+            // We've no idea when the budget will be restored, so the best we
+            // can do is let the server decide.
+            return Duration::from_secs(0);
+        }
+
+        Duration::from_secs_f64(-state.available / state.restore_rate)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("RateLimiter mutex was poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_acquire_queues_once_budget_is_used() {
+        let limiter = RateLimiter::new(100.0, 10.0);
+        let now = Instant::now();
+
+        assert_eq!(limiter.acquire_at(60.0, now), Duration::from_secs(0));
+        assert_eq!(limiter.acquire_at(60.0, now), Duration::from_secs(2));
+        assert_eq!(limiter.acquire_at(10.0, now), Duration::from_secs(3));
+
+        // After 3 seconds the budget should have been restored enough for the
+        // queued operations
+        let later = now + Duration::from_secs(3);
+        assert_eq!(limiter.acquire_at(0.0, later), Duration::from_secs(0));
+    }
+
+    #[test]
+    fn test_update_from_extensions() {
+        let limiter = RateLimiter::new(100.0, 10.0);
+
+        limiter.update_from_extensions(&json!({
+            "cost": {
+                "requestedQueryCost": 12,
+                "throttleStatus": {
+                    "maximumAvailable": 1000.0,
+                    "currentlyAvailable": 0.0,
+                    "restoreRate": 50.0
+                }
+            }
+        }));
+
+        let delay = limiter.acquire(100.0);
+        assert!(delay > Duration::from_millis(1900) && delay <= Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_update_from_headers() {
+        let limiter = RateLimiter::new(100.0, 10.0);
+
+        limiter.update_from_headers(vec![
+            ("X-RateLimit-Limit", "60"),
+            ("x-ratelimit-remaining", "0"),
+            ("X-RateLimit-Reset", "30"),
+            ("Content-Type", "application/json"),
+        ]);
+
+        let delay = limiter.acquire(8.0);
+        assert!(delay > Duration::from_millis(3900) && delay <= Duration::from_secs(4));
+    }
+}
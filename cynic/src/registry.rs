@@ -0,0 +1,176 @@
+//! A registry of every operation an application uses.
+//!
+//! Servers, gateways & test harnesses sometimes need to enumerate the
+//! operations a binary can send - to run them all against a staging server,
+//! to build an allowlist, or to look up an operation from its name in a log
+//! line.  An `OperationRegistry` maps operation names to functions that build
+//! them:
+//!
+//! ```rust,ignore
+//! let mut registry = OperationRegistry::new();
+//! registry
+//!     .register("AllFilms", || AllFilmsQuery::build())
+//!     .register("Film", || FilmQuery::build(&FilmArguments { id: example_id() }));
+//!
+//! for (name, operation) in registry.build_all() {
+//!     let response = run_against_staging(&operation);
+//!     assert!(response.errors.is_none(), "{} failed", name);
+//! }
+//! ```
+//!
+//! Operations are registered explicitly, rather than collected automatically,
+//! so it's up to the application to provide example arguments for operations
+//! that need them.  Registered operations decode into `serde_json::Value`, so
+//! operations with different response types can live in the same registry.
+
+use std::collections::BTreeMap;
+
+use crate::Operation;
+
+type Builder = Box<dyn Fn() -> Operation<'static, serde_json::Value> + Send + Sync>;
+
+/// A collection of named functions that build operations.
+#[derive(Default)]
+pub struct OperationRegistry {
+    builders: BTreeMap<String, Builder>,
+}
+
+impl OperationRegistry {
+    pub fn new() -> Self {
+        OperationRegistry::default()
+    }
+
+    /// Registers `builder` under `name`, replacing any builder that was
+    /// already registered with that name.
+    ///
+    /// The operations that `builder` returns are given `name` as their
+    /// operation name.
+    pub fn register<ResponseData: 'static>(
+        &mut self,
+        name: impl Into<String>,
+        builder: impl Fn() -> Operation<'static, ResponseData> + Send + Sync + 'static,
+    ) -> &mut Self {
+        let name = name.into();
+        let operation_name = name.clone();
+
+        self.builders.insert(
+            name,
+            Box::new(move || builder().named(operation_name.clone()).untyped()),
+        );
+        self
+    }
+
+    /// The names of all the registered operations, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.builders.keys().map(String::as_str)
+    }
+
+    /// The number of registered operations.
+    pub fn len(&self) -> usize {
+        self.builders.len()
+    }
+
+    /// Whether there are any registered operations.
+    pub fn is_empty(&self) -> bool {
+        self.builders.is_empty()
+    }
+
+    /// Builds the operation registered as `name`.
+    pub fn build(&self, name: &str) -> Option<Operation<'static, serde_json::Value>> {
+        self.builders.get(name).map(|builder| builder())
+    }
+
+    /// Builds every registered operation, in alphabetical order of name.
+    pub fn build_all(&self) -> Vec<(&str, Operation<'static, serde_json::Value>)> {
+        self.builders
+            .iter()
+            .map(|(name, builder)| (name.as_str(), builder()))
+            .collect()
+    }
+
+    /// Builds the operation whose document has the SHA-256 hash `hash`.
+    ///
+    /// These are the same hashes used in the manifests output by the
+    /// `manifest` module.
+    #[cfg(feature = "persisted-queries")]
+    pub fn build_by_hash(&self, hash: &str) -> Option<Operation<'static, serde_json::Value>> {
+        self.builders
+            .values()
+            .map(|builder| builder())
+            .find(|operation| crate::manifest::document_hash(&operation.query) == hash)
+    }
+}
+
+impl std::fmt::Debug for OperationRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OperationRegistry")
+            .field("names", &self.builders.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::{testing::fixtures::query, GraphQLResponse};
+
+    fn registry() -> OperationRegistry {
+        let mut registry = OperationRegistry::new();
+        registry
+            .register("Films", || query("films", vec![]))
+            .register("Director", || query("director", vec![]));
+        registry
+    }
+
+    #[test]
+    fn test_building_registered_operations() {
+        let registry = registry();
+
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["Director", "Films"]
+        );
+
+        let operation = registry.build("Films").unwrap();
+        assert!(operation.query.starts_with("query Films"));
+        assert_eq!(operation.operation_name.as_deref(), Some("Films"));
+        assert!(registry.build("Missing").is_none());
+
+        let response = operation
+            .decode_response(GraphQLResponse {
+                data: Some(json!({ "films": "A New Hope" })),
+                errors: None,
+                extensions: None,
+            })
+            .unwrap();
+        assert_eq!(response.data, Some(json!({ "films": "A New Hope" })));
+    }
+
+    #[test]
+    fn test_build_all() {
+        let names = registry()
+            .build_all()
+            .into_iter()
+            .map(|(name, operation)| (name.to_string(), operation.name().to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec![
+                ("Director".to_string(), "Director".to_string()),
+                ("Films".to_string(), "Films".to_string())
+            ]
+        );
+    }
+
+    #[cfg(feature = "persisted-queries")]
+    #[test]
+    fn test_build_by_hash() {
+        let registry = registry();
+        let hash = crate::manifest::document_hash(&registry.build("Films").unwrap().query);
+
+        assert_eq!(registry.build_by_hash(&hash).unwrap().name(), "Films");
+    }
+}
@@ -0,0 +1,263 @@
+//! Record & replay of GraphQL traffic for deterministic tests.
+//!
+//! A `Recorder` wraps whatever function an application uses to send
+//! operations.  In `Mode::Record` it sends operations for real and saves
+//! each response into a cassette file.  In `Mode::Replay` it never sends
+//! anything, and instead responds with the recording that matches the hash of
+//! the operation's document & its variables.
+//!
+//! ```rust,ignore
+//! use cynic::replay::{Mode, Recorder};
+//!
+//! let recorder = Recorder::open("tests/cassettes/films.json", Mode::from_env())?;
+//!
+//! let response = recorder.run(&operation, |operation| {
+//!     reqwest::blocking::Client::new()
+//!         .post(url)
+//!         .json(operation)
+//!         .send()?
+//!         .json()
+//! })?;
+//! ```
+//!
+//! Running the tests with `CYNIC_RECORD=1` records fresh responses, and
+//! running them without it replays those responses.
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde_json::Value;
+
+use crate::{manifest::document_hash, GraphQLResponse, Operation};
+
+/// Whether a `Recorder` should record or replay responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Send operations, and record their responses.
+    Record,
+    /// Respond to operations with recorded responses.
+    Replay,
+}
+
+impl Mode {
+    /// `Mode::Record` if the `CYNIC_RECORD` environment variable is set to
+    /// anything other than `0`, otherwise `Mode::Replay`.
+    pub fn from_env() -> Mode {
+        match std::env::var("CYNIC_RECORD") {
+            Ok(value) if !value.is_empty() && value != "0" => Mode::Record,
+            _ => Mode::Replay,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReplayError<E> {
+    #[error("Error sending operation: {0}")]
+    Transport(E),
+    #[error("There is no recording of {operation_name} with these variables")]
+    NoRecording { operation_name: String },
+    #[error("Error reading or writing the cassette: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Error parsing the cassette: {0}")]
+    InvalidCassette(#[from] serde_json::Error),
+    #[error("Error decoding GraphQL response: {0}")]
+    DecodeError(#[from] json_decode::DecodeError),
+}
+
+/// Records responses to a cassette file, or replays them from one.
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    mode: Mode,
+    recordings: Mutex<Vec<Recording>>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Recording {
+    operation_name: String,
+    hash: String,
+    variables: Value,
+    response: Value,
+}
+
+impl Recorder {
+    /// Opens the cassette at `path`.
+    ///
+    /// In `Mode::Replay` the cassette must already exist.  In `Mode::Record`
+    /// any existing recordings are kept, but are replaced when an operation is
+    /// recorded again.
+    pub fn open(path: impl AsRef<Path>, mode: Mode) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let recordings = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && mode == Mode::Record => {
+                vec![]
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(Recorder {
+            path,
+            mode,
+            recordings: Mutex::new(recordings),
+        })
+    }
+
+    /// The mode this Recorder is in.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Runs `operation`, using `send` to send it if we're recording.
+    pub fn run<'a, ResponseData: 'a, E>(
+        &self,
+        operation: &Operation<'a, ResponseData>,
+        send: impl FnOnce(&Operation<'a, ResponseData>) -> Result<GraphQLResponse<Value>, E>,
+    ) -> Result<GraphQLResponse<ResponseData>, ReplayError<E>> {
+        let response = match self.mode {
+            Mode::Replay => self.replay(operation)?,
+            Mode::Record => {
+                let response = send(operation).map_err(ReplayError::Transport)?;
+                self.record(operation, &response)?
+            }
+        };
+
+        Ok(operation.decode_response(response)?)
+    }
+
+    /// Runs `operation` as with `run`, but with an async `send` function.
+    pub async fn run_async<'a, ResponseData: 'a, E, Fut>(
+        &self,
+        operation: &Operation<'a, ResponseData>,
+        send: impl FnOnce(&Operation<'a, ResponseData>) -> Fut,
+    ) -> Result<GraphQLResponse<ResponseData>, ReplayError<E>>
+    where
+        Fut: Future<Output = Result<GraphQLResponse<Value>, E>>,
+    {
+        let response = match self.mode {
+            Mode::Replay => self.replay(operation)?,
+            Mode::Record => {
+                let response = send(operation).await.map_err(ReplayError::Transport)?;
+                self.record(operation, &response)?
+            }
+        };
+
+        Ok(operation.decode_response(response)?)
+    }
+
+    fn replay<E, ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+    ) -> Result<GraphQLResponse<Value>, ReplayError<E>> {
+        let (hash, variables) = key(operation)?;
+
+        let recordings = self.lock();
+        let recording = recordings
+            .iter()
+            .find(|r| r.hash == hash && r.variables == variables)
+            .ok_or_else(|| ReplayError::NoRecording {
+                operation_name: operation.name().to_string(),
+            })?;
+
+        Ok(serde_json::from_value(recording.response.clone())?)
+    }
+
+    fn record<E, ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+        response: &GraphQLResponse<Value>,
+    ) -> Result<GraphQLResponse<Value>, ReplayError<E>> {
+        let (hash, variables) = key(operation)?;
+        let response = serde_json::to_value(response)?;
+
+        let mut recordings = self.lock();
+        recordings.retain(|r| r.hash != hash || r.variables != variables);
+        recordings.push(Recording {
+            operation_name: operation.name().to_string(),
+            hash,
+            variables,
+            response: response.clone(),
+        });
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&*recordings)?)?;
+
+        Ok(serde_json::from_value(response)?)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Recording>> {
+        self.recordings.lock().expect("Recorder mutex was poisoned")
+    }
+}
+
+fn key<ResponseData>(
+    operation: &Operation<'_, ResponseData>,
+) -> Result<(String, Value), serde_json::Error> {
+    Ok((
+        document_hash(&operation.query),
+        serde_json::to_value(&operation.variables)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use crate::testing::fixtures::film_query;
+
+    fn cassette_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join("cynic-replay-tests")
+            .join(format!("{}.json", name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let path = cassette_path("record_then_replay");
+
+        let recorder = Recorder::open(&path, Mode::Record).unwrap();
+        let response = recorder
+            .run(&film_query(1), |_| {
+                Ok::<_, std::io::Error>(GraphQLResponse {
+                    data: Some(json!({ "film": "A New Hope" })),
+                    errors: None,
+                    extensions: None,
+                })
+            })
+            .unwrap();
+        assert_eq!(response.data.as_deref(), Some("A New Hope"));
+
+        let recorder = Recorder::open(&path, Mode::Replay).unwrap();
+        let response = recorder
+            .run(&film_query(1), |_| -> Result<_, std::io::Error> {
+                panic!("Replay mode shouldn't send operations")
+            })
+            .unwrap();
+        assert_eq!(response.data.as_deref(), Some("A New Hope"));
+
+        let error = recorder
+            .run(&film_query(2), |_| -> Result<_, std::io::Error> {
+                panic!("Replay mode shouldn't send operations")
+            })
+            .unwrap_err();
+        assert_matches!(error, ReplayError::NoRecording { .. });
+    }
+
+    #[test]
+    fn test_replay_requires_a_cassette() {
+        let path = cassette_path("missing");
+
+        assert!(Recorder::open(&path, Mode::Replay).is_err());
+    }
+}
@@ -5,31 +5,17 @@
 /// failed.  We represent that with a PossiblyParsedData<T>.
 pub type GraphQLResult<T> = Result<T, (PossiblyParsedData<T>, Vec<GraphQLError>)>;
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct GraphQLResponse<T> {
     pub data: Option<T>,
     pub errors: Option<Vec<GraphQLError>>,
+    /// Any extra information the server sent, such as tracing or cache hints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<serde_json::Value>,
 }
 
-/*
-impl<T> GraphQLResponse<T> {
-    fn into_result(self) -> GraphQLResult<T> {
-        // TODO: I need access to the query/selection set in order to decode this.
-        if let Some(errors) = self.errors {
-            if !errors.is_empty() {
-                // TODO: Return an error.
-            }
-        }
-
-        match self {
-            GraphQLResponse::Ok(t) => Ok(t),
-            GraphQLResponse::Err(poss_data, errs) => Err((poss_data, errs)),
-        }
-    }
-}*/
-
 /// A model describing an error which has taken place during execution.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, thiserror::Error)]
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize, thiserror::Error)]
 #[error("{message}")]
 pub struct GraphQLError {
     /// A description of the error which has taken place.
@@ -60,7 +46,7 @@ impl GraphQLError {
 }
 
 /// A line and column offset describing the location of an error within a GraphQL document.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct GraphQLErrorLocation {
     /// The line at which the associated error begins.
     pub line: i32,
@@ -69,7 +55,7 @@ pub struct GraphQLErrorLocation {
 }
 
 /// A segment of a GraphQL error path.
-#[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
 pub enum GraphQLErrorPathSegment {
     /// A path segment representing a field by name.
@@ -82,6 +68,7 @@ pub enum GraphQLErrorPathSegment {
 /// GraphQL allows servers to return partial data in this case, but if there's
 /// missing fields that aren't represented by an Option we won't have been
 /// able to decode that data.
+#[derive(Debug)]
 pub enum PossiblyParsedData<T> {
     ParsedData(T),
     // TODO: Could pass serde_json::Value from here, can't be bothered right
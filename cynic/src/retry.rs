@@ -0,0 +1,296 @@
+//! Retrying operations that fail.
+//!
+//! Queries don't have side effects, so it's always safe to retry them.
+//! Mutations are a different story - if a request times out after the server
+//! has run the mutation, retrying it would run it twice.  So a `RetryPolicy`
+//! only retries a mutation if it has an idempotency key (see
+//! `Operation::with_idempotency_key`), or if the policy has explicitly opted
+//! in with `RetryPolicy::retry_mutations`.
+//!
+//! Only transient failures are retried: timeouts, connection failures &
+//! responses with one of the policy's retryable HTTP statuses (by default
+//! 408, 429, 502, 503 & 504).  Errors describe themselves to a policy by
+//! implementing `RetryableError`.
+//!
+//! Like `rate_limit`, this doesn't depend on any particular async runtime -
+//! `RetryPolicy::delay` says how long to wait before the next attempt, and
+//! callers can wait for that with whatever timer they have.  `RetryPolicy::run`
+//! does the whole loop for blocking clients, and `RetryPolicy::run_async` for
+//! async clients given a sleep function.
+//!
+//! ```rust,ignore
+//! let policy = RetryPolicy::new(3);
+//!
+//! let response = policy.run(&operation, |operation| {
+//!     client.post(url).run_graphql(operation.clone())
+//! });
+//! ```
+//!
+//! The reqwest blocking integration does this for you with
+//! `run_graphql_with_retries`.
+
+use std::{future::Future, time::Duration};
+
+use crate::Operation;
+
+/// An error that a `RetryPolicy` can decide whether to retry.
+pub trait RetryableError {
+    /// The HTTP status of the response that caused this error, if any.
+    fn http_status(&self) -> Option<u16>;
+
+    /// Whether this error was caused by a timeout or a failure to connect.
+    fn is_transport_error(&self) -> bool;
+}
+
+/// Decides whether & when a failed operation should be retried.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    retry_mutations: bool,
+    retryable_statuses: Vec<u16>,
+}
+
+impl RetryPolicy {
+    /// A policy that makes at most `max_attempts` attempts at an operation,
+    /// with an exponential backoff that starts at 100ms.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            retry_mutations: false,
+            retryable_statuses: vec![408, 429, 502, 503, 504],
+        }
+    }
+
+    /// Sets the delay before the first retry, which is doubled for each
+    /// subsequent retry up to `max_delay`.
+    pub fn with_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Retries mutations even if they don't have an idempotency key.
+    ///
+    /// This should only be used if every mutation is safe to run more than
+    /// once.
+    pub fn retry_mutations(mut self) -> Self {
+        self.retry_mutations = true;
+        self
+    }
+
+    /// Sets the HTTP statuses that are retried.
+    pub fn with_retryable_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Whether `error` is a transient failure that this policy retries.
+    pub fn is_retryable(&self, error: &impl RetryableError) -> bool {
+        match error.http_status() {
+            Some(status) => self.retryable_statuses.contains(&status),
+            None => error.is_transport_error(),
+        }
+    }
+
+    /// Whether `operation` can be retried under this policy.
+    pub fn can_retry<ResponseData>(&self, operation: &Operation<'_, ResponseData>) -> bool {
+        !operation.is_mutation() || operation.idempotency_key.is_some() || self.retry_mutations
+    }
+
+    /// How long to wait before retrying `operation`, after `attempts` failed
+    /// attempts.  Returns `None` if it shouldn't be retried.
+    pub fn delay<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+        attempts: u32,
+    ) -> Option<Duration> {
+        if attempts == 0 || attempts >= self.max_attempts || !self.can_retry(operation) {
+            return None;
+        }
+
+        let multiplier = 2u32.saturating_pow(attempts - 1);
+        Some(
+            self.base_delay
+                .checked_mul(multiplier)
+                .unwrap_or(self.max_delay)
+                .min(self.max_delay),
+        )
+    }
+
+    /// Runs `send` until it succeeds or this policy says to stop, sleeping
+    /// the current thread in between attempts.
+    pub fn run<'a, ResponseData, T, E: RetryableError>(
+        &self,
+        operation: &Operation<'a, ResponseData>,
+        mut send: impl FnMut(&Operation<'a, ResponseData>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut attempts = 0;
+
+        loop {
+            let error = match send(operation) {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+            attempts += 1;
+
+            match self.retry_delay(operation, &error, attempts) {
+                Some(delay) => std::thread::sleep(delay),
+                None => return Err(error),
+            }
+        }
+    }
+
+    /// Runs `send` until it succeeds or this policy says to stop, using
+    /// `sleep` to wait in between attempts.
+    pub async fn run_async<'a, ResponseData, T, E, SendFut, SleepFut>(
+        &self,
+        operation: &Operation<'a, ResponseData>,
+        mut send: impl FnMut(&Operation<'a, ResponseData>) -> SendFut,
+        mut sleep: impl FnMut(Duration) -> SleepFut,
+    ) -> Result<T, E>
+    where
+        E: RetryableError,
+        SendFut: Future<Output = Result<T, E>>,
+        SleepFut: Future<Output = ()>,
+    {
+        let mut attempts = 0;
+
+        loop {
+            let error = match send(operation).await {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+            attempts += 1;
+
+            match self.retry_delay(operation, &error, attempts) {
+                Some(delay) => sleep(delay).await,
+                None => return Err(error),
+            }
+        }
+    }
+
+    fn retry_delay<ResponseData>(
+        &self,
+        operation: &Operation<'_, ResponseData>,
+        error: &impl RetryableError,
+        attempts: u32,
+    ) -> Option<Duration> {
+        if !self.is_retryable(error) {
+            return None;
+        }
+        self.delay(operation, attempts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::{mutation, query};
+
+    #[derive(Debug, PartialEq)]
+    enum TestError {
+        Timeout(u32),
+        Status(u16),
+        Decode,
+    }
+
+    impl RetryableError for TestError {
+        fn http_status(&self) -> Option<u16> {
+            match self {
+                TestError::Status(status) => Some(*status),
+                _ => None,
+            }
+        }
+
+        fn is_transport_error(&self) -> bool {
+            matches!(self, TestError::Timeout(_))
+        }
+    }
+
+    #[test]
+    fn test_queries_are_retried_with_backoff() {
+        let policy = RetryPolicy::new(4);
+        let query = query("film", vec![]);
+
+        assert_eq!(policy.delay(&query, 1), Some(Duration::from_millis(100)));
+        assert_eq!(policy.delay(&query, 2), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(&query, 3), Some(Duration::from_millis(400)));
+        assert_eq!(policy.delay(&query, 4), None);
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy =
+            RetryPolicy::new(100).with_backoff(Duration::from_secs(1), Duration::from_secs(5));
+
+        let query = query("film", vec![]);
+
+        assert_eq!(policy.delay(&query, 50), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_mutations_need_an_idempotency_key() {
+        let policy = RetryPolicy::new(3);
+        let mutation = mutation("createFilm", vec![]);
+
+        assert!(mutation.is_mutation());
+        assert_eq!(policy.delay(&mutation, 1), None);
+        assert_eq!(
+            policy.delay(&mutation.clone().with_idempotency_key("abc"), 1),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.retry_mutations().delay(&mutation, 1),
+            Some(Duration::from_millis(100))
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let policy =
+            RetryPolicy::new(3).with_backoff(Duration::from_millis(1), Duration::from_millis(1));
+
+        let mut calls = 0;
+        let result = policy.run(&query("film", vec![]), |_| {
+            calls += 1;
+            if calls < 3 {
+                Err(TestError::Timeout(calls))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result, Ok(3));
+
+        let mut calls = 0;
+        let result = policy.run(&mutation("createFilm", vec![]), |_| -> Result<(), _> {
+            calls += 1;
+            Err(TestError::Timeout(calls))
+        });
+        assert_eq!(result, Err(TestError::Timeout(1)));
+    }
+
+    #[test]
+    fn test_only_transient_errors_are_retried() {
+        let policy = RetryPolicy::new(3);
+
+        assert!(policy.is_retryable(&TestError::Timeout(1)));
+        assert!(policy.is_retryable(&TestError::Status(502)));
+        assert!(!policy.is_retryable(&TestError::Status(400)));
+        assert!(!policy.is_retryable(&TestError::Decode));
+        assert!(!policy
+            .with_retryable_statuses(vec![500])
+            .is_retryable(&TestError::Status(502)));
+
+        let mut calls = 0;
+        let result = RetryPolicy::new(3).run(&query("film", vec![]), |_| -> Result<(), _> {
+            calls += 1;
+            Err(TestError::Decode)
+        });
+        assert_eq!(result, Err(TestError::Decode));
+        assert_eq!(calls, 1);
+    }
+}
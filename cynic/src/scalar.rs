@@ -1,11 +1,59 @@
+//! The `Scalar` trait, and helpers for implementing it.
+
 use json_decode::{BoxDecoder, DecodeError, Decoder};
-use std::marker::PhantomData;
+use std::{cell::RefCell, fmt::Display, marker::PhantomData, str::FromStr, sync::Arc};
 
 use crate::SerializeError;
 
 pub trait Scalar: Sized {
     fn decode(value: &serde_json::Value) -> Result<Self, DecodeError>;
     fn encode(&self) -> Result<serde_json::Value, SerializeError>;
+
+    /// Validates a scalar after it's been decoded from a response.
+    ///
+    /// This can be overridden to do range checks, format checks etc.  Any
+    /// error returned will be reported as a `DecodeError` which includes
+    /// the path to the field that failed validation.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+thread_local! {
+    /// The fields that are being decoded on this thread, outermost first.
+    ///
+    /// Field decoders push themselves onto this while they decode, so that
+    /// scalar validation errors can include the path to the scalar.
+    static FIELD_PATH: RefCell<Vec<Arc<str>>> = RefCell::new(Vec::new());
+}
+
+/// Keeps a field on the path of any scalar validation errors until it's
+/// dropped.
+pub(crate) struct FieldGuard(());
+
+/// Marks the start of decoding `field_name`.
+pub(crate) fn enter_field(field_name: &Arc<str>) -> FieldGuard {
+    FIELD_PATH.with(|path| path.borrow_mut().push(Arc::clone(field_name)));
+
+    FieldGuard(())
+}
+
+impl Drop for FieldGuard {
+    fn drop(&mut self) {
+        FIELD_PATH.with(|path| {
+            path.borrow_mut().pop();
+        });
+    }
+}
+
+fn validation_error(message: String) -> DecodeError {
+    let path = FIELD_PATH.with(|path| path.borrow().join("."));
+
+    if path.is_empty() {
+        DecodeError::Other(format!("Scalar validation failed: {}", message))
+    } else {
+        DecodeError::Other(format!("Scalar validation failed at {}: {}", path, message))
+    }
 }
 
 pub fn decoder<'a, S>() -> BoxDecoder<'a, S>
@@ -27,6 +75,30 @@ impl Scalar for i32 {
     }
 }
 
+impl Scalar for i64 {
+    fn decode(value: &serde_json::Value) -> Result<Self, DecodeError> {
+        value
+            .as_i64()
+            .ok_or_else(|| DecodeError::IncorrectType("i64".into(), value.to_string()))
+    }
+
+    fn encode(&self) -> Result<serde_json::Value, SerializeError> {
+        Ok((*self).into())
+    }
+}
+
+impl Scalar for u64 {
+    fn decode(value: &serde_json::Value) -> Result<Self, DecodeError> {
+        value
+            .as_u64()
+            .ok_or_else(|| DecodeError::IncorrectType("u64".into(), value.to_string()))
+    }
+
+    fn encode(&self) -> Result<serde_json::Value, SerializeError> {
+        Ok((*self).into())
+    }
+}
+
 impl Scalar for f64 {
     fn decode(value: &serde_json::Value) -> Result<Self, DecodeError> {
         json_decode::float().decode(value)
@@ -67,6 +139,44 @@ impl Scalar for serde_json::Value {
     }
 }
 
+/// Decodes a scalar that the server sends as a string, such as the `BigInt`
+/// or `Long` scalars that many APIs use to avoid losing precision in JSON
+/// numbers.
+///
+/// Numbers are also accepted, as some servers only use strings for values
+/// that are too large to send as a number.
+///
+/// This is used by `#[derive(Scalar)]` for scalars marked
+/// `#[cynic(string_encoded)]`.
+pub fn decode_string_encoded<T>(value: &serde_json::Value) -> Result<T, DecodeError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let string = match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => {
+            return Err(DecodeError::IncorrectType(
+                "string encoded scalar".into(),
+                value.to_string(),
+            ))
+        }
+    };
+
+    string
+        .parse()
+        .map_err(|e| DecodeError::Other(format!("Could not parse {}: {}", string, e)))
+}
+
+/// Encodes a scalar as a string.  The opposite of `decode_string_encoded`.
+pub fn encode_string_encoded<T>(value: &T) -> Result<serde_json::Value, SerializeError>
+where
+    T: Display,
+{
+    Ok(serde_json::Value::String(value.to_string()))
+}
+
 struct ScalarDecoder<S: Scalar> {
     phantom: PhantomData<S>,
 }
@@ -76,6 +186,123 @@ where
     S: Scalar + Sized,
 {
     fn decode(&self, value: &serde_json::Value) -> Result<S, DecodeError> {
-        S::decode(value)
+        let scalar = S::decode(value)?;
+        scalar.validate().map_err(validation_error)?;
+
+        Ok(scalar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Percentage(i32);
+
+    impl Scalar for Percentage {
+        fn decode(value: &serde_json::Value) -> Result<Self, DecodeError> {
+            i32::decode(value).map(Percentage)
+        }
+
+        fn encode(&self) -> Result<serde_json::Value, SerializeError> {
+            self.0.encode()
+        }
+
+        fn validate(&self) -> Result<(), String> {
+            if self.0 < 0 || self.0 > 100 {
+                return Err(format!("{} is not a valid percentage", self.0));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_scalar_validation() {
+        let decoder = decoder::<Percentage>();
+
+        assert_eq!(decoder.decode(&serde_json::json!(50)).unwrap().0, 50);
+        assert_eq!(
+            decoder.decode(&serde_json::json!(150)).err(),
+            Some(DecodeError::Other(
+                "Scalar validation failed: 150 is not a valid percentage".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validation_errors_in_fields() {
+        use crate::selection_set::{field, scalar, vec};
+
+        let selection_set = field::<_, (), ()>(
+            "stats",
+            vec![],
+            field::<_, (), ()>("percentages", vec![], vec(scalar::<Percentage>())),
+        );
+
+        assert_eq!(
+            selection_set
+                .decoder
+                .decode(&json!({ "stats": { "percentages": [10, 150] } }))
+                .err(),
+            Some(DecodeError::Other(
+                "Scalar validation failed at stats.percentages: 150 is not a valid percentage"
+                    .into()
+            ))
+        );
+        assert!(matches!(
+            selection_set
+                .decoder
+                .decode(&json!({ "stats": { "percentages": ["10"] } })),
+            Err(DecodeError::IncorrectType(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_validation_error_paths() {
+        let error = {
+            let _stats = enter_field(&"stats".into());
+            let _percentage = enter_field(&"percentage".into());
+            validation_error("oops".into())
+        };
+
+        assert_eq!(
+            error,
+            DecodeError::Other("Scalar validation failed at stats.percentage: oops".into())
+        );
+        assert_eq!(
+            validation_error("oops".into()),
+            DecodeError::Other("Scalar validation failed: oops".into())
+        );
+    }
+
+    #[test]
+    fn test_string_encoded_scalars() {
+        let big = 9_007_199_254_740_993i64;
+
+        assert_eq!(
+            decode_string_encoded::<i64>(&json!("9007199254740993")),
+            Ok(big)
+        );
+        assert_eq!(decode_string_encoded::<i64>(&json!(123)), Ok(123));
+        assert_eq!(
+            decode_string_encoded::<u128>(&json!("340282366920938463463374607431768211455")),
+            Ok(u128::MAX)
+        );
+        assert!(decode_string_encoded::<u64>(&json!("-1")).is_err());
+        assert!(decode_string_encoded::<i64>(&json!(true)).is_err());
+
+        assert_eq!(
+            encode_string_encoded(&big).unwrap(),
+            json!("9007199254740993")
+        );
+    }
+
+    #[test]
+    fn test_64_bit_integers() {
+        assert_eq!(i64::decode(&json!(-9_000_000_000i64)), Ok(-9_000_000_000));
+        assert_eq!(u64::decode(&json!(u64::MAX)), Ok(u64::MAX));
+        assert!(u64::decode(&json!(-1)).is_err());
+        assert!(i64::decode(&json!("1")).is_err());
     }
 }
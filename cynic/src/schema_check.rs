@@ -0,0 +1,649 @@
+//! Runtime checks that operations are still compatible with a server.
+//!
+//! Cynic checks queries against a schema at compile time, but that schema can
+//! drift from what's actually deployed.  This module can introspect a live
+//! server and check that every operation an application uses is still valid
+//! against it, so problems can be reported at startup rather than when the
+//! first request fails.
+//!
+//! This is transport agnostic: send `introspection_request()` to the server
+//! with whatever HTTP client you're using, and pass the response to
+//! `IntrospectedSchema::from_response`.
+//!
+//! ```rust,ignore
+//! let response = reqwest::blocking::Client::new()
+//!     .post(url)
+//!     .json(&cynic::schema_check::introspection_request())
+//!     .send()?
+//!     .json()?;
+//!
+//! let schema = IntrospectedSchema::from_response(response)?;
+//! let report = schema
+//!     .check()
+//!     .operation("AllFilms", &all_films_operation)
+//!     .operation("Film", &film_operation)
+//!     .report();
+//!
+//! if !report.is_compatible() {
+//!     panic!("{}", report);
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use graphql_parser::query::{
+    Definition, Document, FragmentDefinition, OperationDefinition, Selection, SelectionSet, Type,
+    TypeCondition,
+};
+
+use crate::{GraphQLError, GraphQLResponse, Operation};
+
+/// The query that `IntrospectedSchema` expects a response to.
+pub const INTROSPECTION_QUERY: &str = r#"query SchemaCheck {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      name
+      kind
+      fields(includeDeprecated: true) {
+        name
+        args { name }
+        type { ...TypeRef }
+      }
+    }
+  }
+}
+
+fragment TypeRef on __Type {
+  name
+  ofType {
+    name
+    ofType {
+      name
+      ofType {
+        name
+        ofType {
+          name
+          ofType { name }
+        }
+      }
+    }
+  }
+}
+"#;
+
+/// Builds the body of a request for `INTROSPECTION_QUERY`.
+pub fn introspection_request() -> serde_json::Value {
+    serde_json::json!({ "query": INTROSPECTION_QUERY })
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SchemaCheckError {
+    #[error("The introspection response contained errors: {0:?}")]
+    GraphQLErrors(Vec<GraphQLError>),
+    #[error("The introspection response contained no data")]
+    NoData,
+    #[error("Could not decode the introspection response: {0}")]
+    DecodeError(#[from] serde_json::Error),
+}
+
+/// A schema that has been introspected from a server.
+#[derive(Debug, Clone)]
+pub struct IntrospectedSchema {
+    query_type: Option<String>,
+    mutation_type: Option<String>,
+    subscription_type: Option<String>,
+    types: HashMap<String, TypeInfo>,
+}
+
+#[derive(Debug, Clone)]
+struct TypeInfo {
+    kind: TypeKind,
+    fields: HashMap<String, FieldInfo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TypeKind {
+    Leaf,
+    Composite,
+    Input,
+}
+
+#[derive(Debug, Clone)]
+struct FieldInfo {
+    arguments: HashSet<String>,
+    type_name: String,
+}
+
+impl IntrospectedSchema {
+    /// Builds an `IntrospectedSchema` from the response to `INTROSPECTION_QUERY`.
+    pub fn from_response(
+        response: GraphQLResponse<serde_json::Value>,
+    ) -> Result<Self, SchemaCheckError> {
+        if let Some(errors) = response.errors {
+            if !errors.is_empty() {
+                return Err(SchemaCheckError::GraphQLErrors(errors));
+            }
+        }
+
+        let data = response.data.ok_or(SchemaCheckError::NoData)?;
+        let data: raw::IntrospectionData = serde_json::from_value(data)?;
+
+        Ok(data.into())
+    }
+
+    /// Starts checking operations against this schema.
+    pub fn check(&self) -> SchemaCheck<'_> {
+        SchemaCheck {
+            schema: self,
+            incompatibilities: vec![],
+        }
+    }
+
+    fn check_document(&self, operation_name: &str, document: &str) -> Vec<Incompatibility> {
+        let document = match graphql_parser::parse_query::<&str>(document) {
+            Ok(document) => document,
+            Err(e) => {
+                return vec![Incompatibility {
+                    operation: operation_name.to_string(),
+                    path: String::new(),
+                    kind: IncompatibilityKind::InvalidDocument(e.to_string()),
+                }]
+            }
+        };
+
+        let mut checker = DocumentChecker {
+            schema: self,
+            operation_name,
+            fragments: fragments(&document),
+            incompatibilities: vec![],
+        };
+
+        for definition in &document.definitions {
+            if let Definition::Operation(operation) = definition {
+                checker.check_operation(operation);
+            }
+        }
+
+        checker.incompatibilities
+    }
+}
+
+/// Checks operations against an `IntrospectedSchema`.
+pub struct SchemaCheck<'a> {
+    schema: &'a IntrospectedSchema,
+    incompatibilities: Vec<Incompatibility>,
+}
+
+impl<'a> SchemaCheck<'a> {
+    /// Checks an operation, recording any incompatibilities under `name`.
+    pub fn operation<ResponseData>(
+        mut self,
+        name: &str,
+        operation: &Operation<'_, ResponseData>,
+    ) -> Self {
+        self.incompatibilities
+            .extend(self.schema.check_document(name, &operation.query));
+        self
+    }
+
+    pub fn report(self) -> CompatibilityReport {
+        CompatibilityReport {
+            incompatibilities: self.incompatibilities,
+        }
+    }
+}
+
+/// The results of checking some operations against an `IntrospectedSchema`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatibilityReport {
+    pub incompatibilities: Vec<Incompatibility>,
+}
+
+impl CompatibilityReport {
+    pub fn is_compatible(&self) -> bool {
+        self.incompatibilities.is_empty()
+    }
+}
+
+impl std::fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_compatible() {
+            return write!(f, "All operations are compatible with the schema");
+        }
+
+        writeln!(f, "Some operations are incompatible with the schema:")?;
+        for incompatibility in &self.incompatibilities {
+            writeln!(f, "- {}", incompatibility)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single problem with an operation.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{operation} at {path}: {kind}")]
+pub struct Incompatibility {
+    /// The name the operation was checked under.
+    pub operation: String,
+    /// The path to the problem within the operation, e.g. `allFilms.films`
+    pub path: String,
+    pub kind: IncompatibilityKind,
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum IncompatibilityKind {
+    #[error("the type {0} does not exist")]
+    UnknownType(String),
+    #[error("the schema has no {0} type")]
+    MissingRootType(&'static str),
+    #[error("the field {field} does not exist on {type_name}")]
+    MissingField { type_name: String, field: String },
+    #[error("the field {field} has no argument named {argument}")]
+    UnknownArgument { field: String, argument: String },
+    #[error("{type_name} is a leaf type but the operation selects fields on it")]
+    UnexpectedSelection { type_name: String },
+    #[error("{type_name} is a composite type but the operation selects no fields on it")]
+    MissingSelection { type_name: String },
+    #[error("could not parse the document: {0}")]
+    InvalidDocument(String),
+}
+
+fn fragments<'a>(
+    document: &'a Document<'a, &'a str>,
+) -> HashMap<&'a str, &'a FragmentDefinition<'a, &'a str>> {
+    document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Fragment(fragment) => Some((fragment.name, fragment)),
+            _ => None,
+        })
+        .collect()
+}
+
+struct DocumentChecker<'a> {
+    schema: &'a IntrospectedSchema,
+    operation_name: &'a str,
+    fragments: HashMap<&'a str, &'a FragmentDefinition<'a, &'a str>>,
+    incompatibilities: Vec<Incompatibility>,
+}
+
+impl<'a> DocumentChecker<'a> {
+    fn check_operation(&mut self, operation: &OperationDefinition<'a, &'a str>) {
+        let (root_kind, root_type, variables, selection_set) = match operation {
+            OperationDefinition::SelectionSet(selection_set) => {
+                ("query", &self.schema.query_type, None, selection_set)
+            }
+            OperationDefinition::Query(query) => (
+                "query",
+                &self.schema.query_type,
+                Some(&query.variable_definitions),
+                &query.selection_set,
+            ),
+            OperationDefinition::Mutation(mutation) => (
+                "mutation",
+                &self.schema.mutation_type,
+                Some(&mutation.variable_definitions),
+                &mutation.selection_set,
+            ),
+            OperationDefinition::Subscription(subscription) => (
+                "subscription",
+                &self.schema.subscription_type,
+                Some(&subscription.variable_definitions),
+                &subscription.selection_set,
+            ),
+        };
+
+        for variable in variables.into_iter().flatten() {
+            let type_name = inner_name(&variable.var_type);
+            if !self.schema.types.contains_key(type_name) {
+                self.report(
+                    format!("${}", variable.name),
+                    IncompatibilityKind::UnknownType(type_name.to_string()),
+                );
+            }
+        }
+
+        match root_type {
+            Some(root_type) => self.check_selection_set(root_type, selection_set, ""),
+            None => self.report(
+                String::new(),
+                IncompatibilityKind::MissingRootType(root_kind),
+            ),
+        }
+    }
+
+    fn check_selection_set(
+        &mut self,
+        type_name: &str,
+        selection_set: &SelectionSet<'a, &'a str>,
+        path: &str,
+    ) {
+        let type_info = match self.schema.types.get(type_name) {
+            Some(type_info) => type_info,
+            None => {
+                self.report(
+                    path.to_string(),
+                    IncompatibilityKind::UnknownType(type_name.to_string()),
+                );
+                return;
+            }
+        };
+
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    if field.name.starts_with("__") {
+                        continue;
+                    }
+
+                    let field_path = if path.is_empty() {
+                        field.name.to_string()
+                    } else {
+                        format!("{}.{}", path, field.name)
+                    };
+
+                    let field_info = match type_info.fields.get(field.name) {
+                        Some(field_info) => field_info,
+                        None => {
+                            self.report(
+                                field_path,
+                                IncompatibilityKind::MissingField {
+                                    type_name: type_name.to_string(),
+                                    field: field.name.to_string(),
+                                },
+                            );
+                            continue;
+                        }
+                    };
+
+                    for (argument, _) in &field.arguments {
+                        if !field_info.arguments.contains(*argument) {
+                            self.report(
+                                field_path.clone(),
+                                IncompatibilityKind::UnknownArgument {
+                                    field: field.name.to_string(),
+                                    argument: argument.to_string(),
+                                },
+                            );
+                        }
+                    }
+
+                    let field_kind = self.schema.types.get(&field_info.type_name).map(|t| t.kind);
+                    let has_selection = !field.selection_set.items.is_empty();
+
+                    match field_kind {
+                        None => self.report(
+                            field_path,
+                            IncompatibilityKind::UnknownType(field_info.type_name.clone()),
+                        ),
+                        Some(TypeKind::Composite) if has_selection => self.check_selection_set(
+                            &field_info.type_name,
+                            &field.selection_set,
+                            &field_path,
+                        ),
+                        Some(TypeKind::Composite) => self.report(
+                            field_path,
+                            IncompatibilityKind::MissingSelection {
+                                type_name: field_info.type_name.clone(),
+                            },
+                        ),
+                        Some(_) if has_selection => self.report(
+                            field_path,
+                            IncompatibilityKind::UnexpectedSelection {
+                                type_name: field_info.type_name.clone(),
+                            },
+                        ),
+                        Some(_) => {}
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    let fragment_type = match &fragment.type_condition {
+                        Some(TypeCondition::On(name)) => *name,
+                        None => type_name,
+                    };
+                    self.check_selection_set(fragment_type, &fragment.selection_set, path);
+                }
+                Selection::FragmentSpread(spread) => {
+                    if let Some(fragment) = self.fragments.get(spread.fragment_name).copied() {
+                        let TypeCondition::On(fragment_type) = fragment.type_condition;
+                        self.check_selection_set(fragment_type, &fragment.selection_set, path);
+                    }
+                }
+            }
+        }
+    }
+
+    fn report(&mut self, path: String, kind: IncompatibilityKind) {
+        self.incompatibilities.push(Incompatibility {
+            operation: self.operation_name.to_string(),
+            path,
+            kind,
+        });
+    }
+}
+
+fn inner_name<'a>(ty: &Type<'a, &'a str>) -> &'a str {
+    match ty {
+        Type::NamedType(name) => name,
+        Type::ListType(inner) | Type::NonNullType(inner) => inner_name(inner),
+    }
+}
+
+/// The raw structure of an introspection response.
+mod raw {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    pub struct IntrospectionData {
+        #[serde(rename = "__schema")]
+        pub schema: Schema,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Schema {
+        pub query_type: Option<NamedType>,
+        pub mutation_type: Option<NamedType>,
+        pub subscription_type: Option<NamedType>,
+        pub types: Vec<FullType>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct NamedType {
+        pub name: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct FullType {
+        pub name: String,
+        pub kind: String,
+        pub fields: Option<Vec<Field>>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Field {
+        pub name: String,
+        pub args: Vec<NamedType>,
+        #[serde(rename = "type")]
+        pub field_type: TypeRef,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TypeRef {
+        pub name: Option<String>,
+        pub of_type: Option<Box<TypeRef>>,
+    }
+
+    impl TypeRef {
+        pub fn inner_name(&self) -> String {
+            match (&self.name, &self.of_type) {
+                (Some(name), _) => name.clone(),
+                (None, Some(of_type)) => of_type.inner_name(),
+                (None, None) => String::new(),
+            }
+        }
+    }
+}
+
+impl From<raw::IntrospectionData> for IntrospectedSchema {
+    fn from(data: raw::IntrospectionData) -> Self {
+        let schema = data.schema;
+
+        let types = schema
+            .types
+            .into_iter()
+            .map(|ty| {
+                let kind = match ty.kind.as_str() {
+                    "OBJECT" | "INTERFACE" | "UNION" => TypeKind::Composite,
+                    "INPUT_OBJECT" => TypeKind::Input,
+                    _ => TypeKind::Leaf,
+                };
+
+                let fields = ty
+                    .fields
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|field| {
+                        (
+                            field.name,
+                            FieldInfo {
+                                arguments: field.args.into_iter().map(|a| a.name).collect(),
+                                type_name: field.field_type.inner_name(),
+                            },
+                        )
+                    })
+                    .collect();
+
+                (ty.name, TypeInfo { kind, fields })
+            })
+            .collect();
+
+        IntrospectedSchema {
+            query_type: schema.query_type.map(|t| t.name),
+            mutation_type: schema.mutation_type.map(|t| t.name),
+            subscription_type: schema.subscription_type.map(|t| t.name),
+            types,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> IntrospectedSchema {
+        let named = |name: &str| json!({ "name": name, "ofType": null });
+        let non_null = |name: &str| json!({ "name": null, "ofType": named(name) });
+
+        IntrospectedSchema::from_response(GraphQLResponse {
+            data: Some(json!({
+                "__schema": {
+                    "queryType": { "name": "Query" },
+                    "mutationType": null,
+                    "subscriptionType": null,
+                    "types": [
+                        {
+                            "name": "Query",
+                            "kind": "OBJECT",
+                            "fields": [
+                                {
+                                    "name": "film",
+                                    "args": [{ "name": "id" }],
+                                    "type": named("Film")
+                                }
+                            ]
+                        },
+                        {
+                            "name": "Film",
+                            "kind": "OBJECT",
+                            "fields": [
+                                { "name": "title", "args": [], "type": non_null("String") }
+                            ]
+                        },
+                        { "name": "String", "kind": "SCALAR", "fields": null },
+                        { "name": "ID", "kind": "SCALAR", "fields": null }
+                    ]
+                }
+            })),
+            errors: None,
+            extensions: None,
+        })
+        .unwrap()
+    }
+
+    fn check(document: &str) -> Vec<IncompatibilityKind> {
+        schema()
+            .check_document("Test", document)
+            .into_iter()
+            .map(|i| i.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_compatible_document() {
+        assert_eq!(
+            check("query Query($_0: ID) { film(id: $_0) { __typename title } }"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_incompatible_documents() {
+        assert_eq!(
+            check("query Query { film { director } }"),
+            vec![IncompatibilityKind::MissingField {
+                type_name: "Film".into(),
+                field: "director".into()
+            }]
+        );
+        assert_eq!(
+            check("query Query { film(first: 1) { title } }"),
+            vec![IncompatibilityKind::UnknownArgument {
+                field: "film".into(),
+                argument: "first".into()
+            }]
+        );
+        assert_eq!(
+            check("query Query { film }"),
+            vec![IncompatibilityKind::MissingSelection {
+                type_name: "Film".into()
+            }]
+        );
+        assert_eq!(
+            check("query Query { film { title { length } } }"),
+            vec![IncompatibilityKind::UnexpectedSelection {
+                type_name: "String".into()
+            }]
+        );
+        assert_eq!(
+            check("mutation Mutation { film { title } }"),
+            vec![IncompatibilityKind::MissingRootType("mutation")]
+        );
+        assert_eq!(
+            check("query Query($_0: Int) { film { title } }"),
+            vec![IncompatibilityKind::UnknownType("Int".into())]
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let schema = schema();
+        let report = schema.check().report();
+        assert!(report.is_compatible());
+
+        let report = CompatibilityReport {
+            incompatibilities: schema.check_document("Test", "query Query { films { title } }"),
+        };
+        assert!(!report.is_compatible());
+        assert_eq!(
+            report.incompatibilities[0].to_string(),
+            "Test at films: the field films does not exist on Query"
+        );
+    }
+}
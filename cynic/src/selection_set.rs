@@ -21,10 +21,11 @@
 use json_decode::{BoxDecoder, DecodeError};
 use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use crate::{
-    field::{Field, OperationType},
-    scalar, Argument, MutationRoot, QueryRoot,
+    field::{Field, FragmentText, OperationType},
+    scalar, Argument, MutationRoot, QueryRoot, StringPool, SubscriptionRoot,
 };
 
 /// A marker trait used to encode GraphQL subtype relationships into the Rust
@@ -42,12 +43,17 @@ pub trait HasSubtype<Subtype> {}
 /// - `TypeLock` is used to enforce type safety.  It allows the `query_dsl`
 ///   functionality in cynic to annotate each SelectionSet it returns such
 ///   that you can't build incorrect queries.
+///
+/// `SelectionSet`s are always `Send + Sync`, so they can be built on one
+/// thread and used on another.
 pub struct SelectionSet<'a, DecodesTo, TypeLock> {
     fields: Vec<Field>,
 
     pub(crate) decoder: BoxDecoder<'a, DecodesTo>,
 
-    phantom: PhantomData<TypeLock>,
+    // We never actually store a TypeLock, so we use a fn pointer here to
+    // make sure it doesn't affect whether we're Send or Sync.
+    phantom: PhantomData<fn() -> TypeLock>,
 }
 
 impl<'a, DecodesTo, TypeLock> SelectionSet<'a, DecodesTo, TypeLock> {
@@ -126,14 +132,52 @@ impl<'a, DecodesTo, TypeLock> SelectionSet<'a, DecodesTo, TypeLock> {
     pub(crate) fn query_arguments_and_decoder(
         self,
     ) -> (String, Vec<Argument>, BoxDecoder<'a, DecodesTo>) {
-        let mut arguments: Vec<Argument> = vec![];
-        let query = self
-            .fields
+        let (operation, fragments, arguments, decoder) = self.operation_arguments_and_decoder();
+        let query = fragments
             .into_iter()
-            .map(|f| f.query(0, 2, &mut arguments))
-            .collect();
+            .fold(operation, |query, (_, definition)| query + &definition);
+
+        (query, arguments, decoder)
+    }
+
+    /// Like `query_arguments_and_decoder`, but returns the definitions of any
+    /// named fragments separately from the operation itself.
+    pub(crate) fn operation_arguments_and_decoder(
+        self,
+    ) -> (
+        String,
+        Vec<FragmentText>,
+        Vec<Argument>,
+        BoxDecoder<'a, DecodesTo>,
+    ) {
+        let mut arguments: Vec<Argument> = vec![];
+        let mut operation = String::new();
+        let mut fragments = vec![];
+        for field in self.fields {
+            let (query, definitions) = field.operation_query(0, 2, &mut arguments);
+            operation.push_str(&query);
+            fragments.extend(definitions);
+        }
+
+        (operation, fragments, arguments, self.decoder)
+    }
+}
+
+impl<'a, TypeLock> SelectionSet<'a, String, TypeLock> {
+    /// Decodes into an `Arc<str>` that is shared with every other occurrence
+    /// of the same string in `pool`.
+    pub fn interned(self, pool: &StringPool) -> SelectionSet<'a, Arc<str>, TypeLock> {
+        let pool = pool.clone();
+        self.map(move |s| pool.intern(s))
+    }
+}
 
-        (query, arguments, self.decoder)
+impl<'a, TypeLock> SelectionSet<'a, Option<String>, TypeLock> {
+    /// Decodes into an `Option<Arc<str>>` that is shared with every other
+    /// occurrence of the same string in `pool`.
+    pub fn interned(self, pool: &StringPool) -> SelectionSet<'a, Option<Arc<str>>, TypeLock> {
+        let pool = pool.clone();
+        self.map(move |s| s.map(|s| pool.intern(s)))
     }
 }
 
@@ -244,15 +288,266 @@ pub fn field<'a, DecodesTo, TypeLock, InnerTypeLock>(
 where
     DecodesTo: 'a,
 {
-    let field = if selection_set.fields.is_empty() {
+    field_with_alias(None, field_name, arguments, selection_set)
+}
+
+/// Selects a field from a GraphQL object under an alias, decoding it with
+/// another `SelectionSet`.
+///
+/// The field is output as `alias: field_name` and decoded from the `alias`
+/// key of the response, so the same field can be selected more than once
+/// with different arguments.
+pub fn aliased_field<'a, DecodesTo, TypeLock, InnerTypeLock>(
+    alias: &str,
+    field_name: &str,
+    arguments: Vec<Argument>,
+    selection_set: SelectionSet<'a, DecodesTo, InnerTypeLock>,
+) -> SelectionSet<'a, DecodesTo, TypeLock>
+where
+    DecodesTo: 'a,
+{
+    field_with_alias(Some(alias), field_name, arguments, selection_set)
+}
+
+/// Selects a field, with an optional alias.
+///
+/// The query DSL uses this so it doesn't need to know whether a field has
+/// an alias until it's selected.  Generally `field` or `aliased_field`
+/// should be used instead.
+#[doc(hidden)]
+pub fn field_with_alias<'a, DecodesTo, TypeLock, InnerTypeLock>(
+    alias: Option<&str>,
+    field_name: &str,
+    arguments: Vec<Argument>,
+    selection_set: SelectionSet<'a, DecodesTo, InnerTypeLock>,
+) -> SelectionSet<'a, DecodesTo, TypeLock>
+where
+    DecodesTo: 'a,
+{
+    let mut field = if selection_set.fields.is_empty() {
         Field::Leaf(field_name.to_string(), arguments)
     } else {
         Field::Composite(field_name.to_string(), arguments, selection_set.fields)
     };
 
+    if let Some(alias) = alias {
+        field = Field::Aliased(alias.to_string(), Box::new(field));
+    }
+
+    let response_key = alias.unwrap_or(field_name);
+
     SelectionSet {
         fields: vec![field],
-        decoder: json_decode::field(field_name, selection_set.decoder),
+        decoder: Box::new(FieldDecoder {
+            field_name: response_key.into(),
+            inner: json_decode::field(response_key, selection_set.decoder),
+        }),
+        phantom: PhantomData,
+    }
+}
+
+/// Wraps a field decoder, adding the field to the path of any scalar
+/// validation errors that occur inside it.
+struct FieldDecoder<'a, DecodesTo> {
+    field_name: Arc<str>,
+    inner: BoxDecoder<'a, DecodesTo>,
+}
+
+impl<'a, DecodesTo> json_decode::Decoder<'a, DecodesTo> for FieldDecoder<'a, DecodesTo> {
+    fn decode(&self, value: &serde_json::Value) -> Result<DecodesTo, DecodeError> {
+        #[cfg(feature = "profiling")]
+        let _span = crate::profiling::enter_field(&self.field_name, value);
+
+        let _field = scalar::enter_field(&self.field_name);
+
+        self.inner.decode(value)
+    }
+}
+
+/// Selects the `__typename` of an object, which can be selected on any GraphQL
+/// type.
+pub fn typename<TypeLock>() -> SelectionSet<'static, String, TypeLock> {
+    field("__typename", vec![], string())
+}
+
+/// Wraps a `SelectionSet` in a named fragment.
+///
+/// Rather than being inlined wherever it's used, the fields are output once
+/// in a `fragment name on type_condition { ... }` definition, and each use
+/// site contains a `...name` spread.  This can make queries that select the
+/// same thing many times a lot smaller.
+///
+/// If the same name is used for fragments with different fields (e.g. because
+/// they were built with different arguments) then only the first is output as
+/// a named fragment, and the rest are inlined.
+pub fn named_fragment<'a, DecodesTo, TypeLock>(
+    name: &str,
+    type_condition: &str,
+    selection_set: SelectionSet<'a, DecodesTo, TypeLock>,
+) -> SelectionSet<'a, DecodesTo, TypeLock> {
+    SelectionSet {
+        fields: vec![Field::FragmentSpread(
+            name.to_string(),
+            type_condition.to_string(),
+            selection_set.fields,
+        )],
+        decoder: selection_set.decoder,
+        phantom: PhantomData,
+    }
+}
+
+/// Only selects `selection_set` if `condition` is true, using an `@include`
+/// directive.
+///
+/// The fields are always in the query, with `condition` passed in as a
+/// variable - so the query itself is the same whatever `condition` is.  This
+/// decodes to `None` when the fields were not included.
+pub fn include_if<'a, DecodesTo, TypeLock>(
+    condition: bool,
+    selection_set: SelectionSet<'a, DecodesTo, TypeLock>,
+) -> SelectionSet<'a, Option<DecodesTo>, TypeLock>
+where
+    DecodesTo: 'a,
+{
+    conditional("include", condition, condition, selection_set)
+}
+
+/// Skips `selection_set` if `condition` is true, using a `@skip` directive.
+///
+/// This decodes to `None` when the fields were skipped.  See `include_if`
+/// for more details.
+pub fn skip_if<'a, DecodesTo, TypeLock>(
+    condition: bool,
+    selection_set: SelectionSet<'a, DecodesTo, TypeLock>,
+) -> SelectionSet<'a, Option<DecodesTo>, TypeLock>
+where
+    DecodesTo: 'a,
+{
+    conditional("skip", condition, !condition, selection_set)
+}
+
+fn conditional<'a, DecodesTo, TypeLock>(
+    directive: &'static str,
+    condition: bool,
+    included: bool,
+    selection_set: SelectionSet<'a, DecodesTo, TypeLock>,
+) -> SelectionSet<'a, Option<DecodesTo>, TypeLock>
+where
+    DecodesTo: 'a,
+{
+    SelectionSet {
+        fields: vec![Field::ConditionalFragment(
+            directive,
+            Argument::new("if", "Boolean!", condition),
+            selection_set.fields,
+        )],
+        decoder: Box::new(ConditionalDecoder {
+            inner: if included {
+                Some(selection_set.decoder)
+            } else {
+                None
+            },
+        }),
+        phantom: PhantomData,
+    }
+}
+
+/// Decodes fields that may have been left out of a response by an `@include`
+/// or `@skip` directive.
+struct ConditionalDecoder<'a, DecodesTo> {
+    inner: Option<BoxDecoder<'a, DecodesTo>>,
+}
+
+impl<'a, DecodesTo> json_decode::Decoder<'a, Option<DecodesTo>>
+    for ConditionalDecoder<'a, DecodesTo>
+{
+    fn decode(&self, value: &serde_json::Value) -> Result<Option<DecodesTo>, DecodeError> {
+        match &self.inner {
+            Some(decoder) => decoder.decode(value).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Defers `selection_set` using a `@defer` directive.
+///
+/// Servers that support incremental delivery can leave deferred fields out of
+/// their initial response and send them later - see the `incremental` module
+/// for how to handle these responses.  This decodes to `None` until the
+/// deferred fields have been delivered.
+///
+/// The `label` is passed to the server, which uses it to identify the
+/// deferred fields when they're sent.
+pub fn defer<'a, DecodesTo, TypeLock>(
+    label: Option<&str>,
+    selection_set: SelectionSet<'a, DecodesTo, TypeLock>,
+) -> SelectionSet<'a, Option<DecodesTo>, TypeLock>
+where
+    DecodesTo: 'a,
+{
+    let field_names = selection_set
+        .fields
+        .iter()
+        .filter_map(Field::response_key)
+        .map(str::to_string)
+        .collect();
+
+    SelectionSet {
+        fields: vec![Field::DeferredFragment(
+            label.map(str::to_string),
+            selection_set.fields,
+        )],
+        decoder: Box::new(DeferredDecoder {
+            field_names,
+            inner: selection_set.decoder,
+        }),
+        phantom: PhantomData,
+    }
+}
+
+/// Decodes fields that may not have been delivered yet because of a `@defer`
+/// directive.
+struct DeferredDecoder<'a, DecodesTo> {
+    field_names: Vec<String>,
+    inner: BoxDecoder<'a, DecodesTo>,
+}
+
+impl<'a, DecodesTo> json_decode::Decoder<'a, Option<DecodesTo>> for DeferredDecoder<'a, DecodesTo> {
+    fn decode(&self, value: &serde_json::Value) -> Result<Option<DecodesTo>, DecodeError> {
+        if self
+            .field_names
+            .iter()
+            .all(|name| value.get(name).is_some())
+        {
+            self.inner.decode(value).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Streams the items of a list field using a `@stream` directive.
+///
+/// `selection_set` should select a list field.  Servers that support
+/// incremental delivery will send the first `initial_count` items in their
+/// initial response, and the rest later - see the `incremental` module for
+/// how to handle these responses.
+pub fn stream<'a, DecodesTo, TypeLock>(
+    initial_count: i32,
+    selection_set: SelectionSet<'a, DecodesTo, TypeLock>,
+) -> SelectionSet<'a, DecodesTo, TypeLock> {
+    SelectionSet {
+        fields: selection_set
+            .fields
+            .into_iter()
+            .map(|field| {
+                Field::Streamed(
+                    Box::new(field),
+                    Argument::new("initialCount", "Int!", initial_count),
+                )
+            })
+            .collect(),
+        decoder: selection_set.decoder,
         phantom: PhantomData,
     }
 }
@@ -264,6 +559,35 @@ where
 pub fn inline_fragments<'a, DecodesTo, TypeLock>(
     fragments: Vec<(String, SelectionSet<'a, DecodesTo, TypeLock>)>,
 ) -> SelectionSet<'a, DecodesTo, TypeLock>
+where
+    DecodesTo: 'a + Send + Sync,
+{
+    inline_fragments_internal(None, fragments)
+}
+
+/// Like `inline_fragments`, but uses `discriminant` to determine the type of
+/// each object that's decoded.
+///
+/// This is useful for servers & gateways that strip or rename `__typename`.
+/// `discriminant` should return the name of the object's GraphQL type, or
+/// `None` to fall back to its `__typename`.
+pub fn inline_fragments_with_discriminant<'a, DecodesTo, TypeLock, F>(
+    discriminant: F,
+    fragments: Vec<(String, SelectionSet<'a, DecodesTo, TypeLock>)>,
+) -> SelectionSet<'a, DecodesTo, TypeLock>
+where
+    DecodesTo: 'a + Send + Sync,
+    F: Fn(&serde_json::Value) -> Option<String> + 'a + Send + Sync,
+{
+    inline_fragments_internal(Some(Box::new(discriminant)), fragments)
+}
+
+type Discriminant<'a> = Box<dyn Fn(&serde_json::Value) -> Option<String> + 'a + Send + Sync>;
+
+fn inline_fragments_internal<'a, DecodesTo, TypeLock>(
+    discriminant: Option<Discriminant<'a>>,
+    fragments: Vec<(String, SelectionSet<'a, DecodesTo, TypeLock>)>,
+) -> SelectionSet<'a, DecodesTo, TypeLock>
 where
     DecodesTo: 'a + Send + Sync,
 {
@@ -285,6 +609,7 @@ where
         decoder: Box::new(FragmentDecoder {
             decoders,
             backup_decoder: None,
+            discriminant,
         }),
         phantom: PhantomData,
     }
@@ -293,15 +618,27 @@ where
 struct FragmentDecoder<'a, DecodesTo> {
     decoders: HashMap<String, BoxDecoder<'a, DecodesTo>>,
     backup_decoder: Option<BoxDecoder<'a, DecodesTo>>,
+    discriminant: Option<Discriminant<'a>>,
 }
 
 impl<'a, DecodesTo> json_decode::Decoder<'a, DecodesTo> for FragmentDecoder<'a, DecodesTo> {
     fn decode(&self, value: &serde_json::Value) -> Result<DecodesTo, DecodeError> {
-        let typename = value["__typename"].as_str().ok_or_else(|| {
-            json_decode::DecodeError::MissingField("__typename".into(), value.to_string())
-        })?;
+        let discriminated_type = self
+            .discriminant
+            .as_ref()
+            .and_then(|discriminant| discriminant(value));
+
+        let typename = match &discriminated_type {
+            Some(typename) => typename.as_str(),
+            None => value["__typename"].as_str().ok_or_else(|| {
+                json_decode::DecodeError::MissingField("__typename".into(), value.to_string())
+            })?,
+        };
 
         if let Some(decoder) = self.decoders.get(typename) {
+            #[cfg(feature = "profiling")]
+            let _span = crate::profiling::enter_fragment(typename, value);
+
             decoder.decode(value)
         } else if let Some(backup_decoder) = &self.backup_decoder {
             backup_decoder.decode(value)
@@ -340,6 +677,22 @@ where
     }
 }
 
+pub(crate) fn subscription_root<'a, DecodesTo, InnerTypeLock: SubscriptionRoot>(
+    selection_set: SelectionSet<'a, DecodesTo, InnerTypeLock>,
+) -> SelectionSet<'a, DecodesTo, ()>
+where
+    DecodesTo: 'a,
+{
+    SelectionSet {
+        fields: vec![Field::Root(
+            selection_set.fields,
+            OperationType::Subscription,
+        )],
+        decoder: selection_set.decoder,
+        phantom: PhantomData,
+    }
+}
+
 /// Applies a function to the result of a selection.
 pub use map as map1;
 
@@ -583,6 +936,22 @@ where
     }
 }
 
+/// Creates a `SelectionSet` that doesn't select any fields, and always
+/// decodes to `None`.
+///
+/// Recursive fragments use this in place of their recursive fields once
+/// they've reached their maximum depth.
+pub fn none<'a, DecodesTo, TypeLock>() -> SelectionSet<'a, Option<DecodesTo>, TypeLock>
+where
+    DecodesTo: 'a,
+{
+    SelectionSet {
+        fields: vec![],
+        decoder: Box::new(ConditionalDecoder { inner: None }),
+        phantom: PhantomData,
+    }
+}
+
 /// Creates a `SelectionSet` that always fails to decode.
 ///
 /// This is handy when used with `SelectionSet::and_then` where you want to
@@ -741,6 +1110,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "pretty-print")]
     fn test_query_building() {
         let selection_set: SelectionSet<_, query_dsl::RootQuery> = map(
             Query::new,
@@ -812,4 +1182,121 @@ mod tests {
         assert_matches!(selection_set.decode(&serde_json::json!("ok")), Ok("YAS"));
         assert_matches!(selection_set.decode(&serde_json::json!("nope")), Err(_));
     }
+
+    #[test]
+    fn test_selection_sets_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        // A TypeLock that is neither Send nor Sync shouldn't matter.
+        struct NotSendLock(std::rc::Rc<()>);
+
+        assert_send_sync::<SelectionSet<'static, Query, query_dsl::RootQuery>>();
+        assert_send_sync::<SelectionSet<'static, String, NotSendLock>>();
+    }
+
+    #[test]
+    fn test_interned_strings() {
+        let pool = StringPool::new();
+        let selection_set = vec(string().interned(&pool));
+
+        let decoded = selection_set
+            .decode(&serde_json::json!(["JEDI", "SITH", "JEDI"]))
+            .unwrap();
+
+        assert_eq!(decoded.len(), 3);
+        assert!(Arc::ptr_eq(&decoded[0], &decoded[2]));
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn test_include_and_skip() {
+        let included = include_if(true, field::<_, (), ()>("title", vec![], string()));
+        let skipped = skip_if(true, field::<_, (), ()>("title", vec![], string()));
+
+        assert_matches!(
+            included.decode(&serde_json::json!({"title": "Jaws"})),
+            Ok(Some(title)) if title == "Jaws"
+        );
+        assert_matches!(skipped.decode(&serde_json::json!({})), Ok(None));
+    }
+
+    #[test]
+    fn test_defer() {
+        let deferred = defer(
+            Some("director"),
+            field::<_, (), ()>("director", vec![], string()),
+        );
+
+        assert_matches!(deferred.decode(&serde_json::json!({})), Ok(None));
+        assert_matches!(
+            deferred.decode(&serde_json::json!({"director": "Spielberg"})),
+            Ok(Some(director)) if director == "Spielberg"
+        );
+        assert_matches!(deferred.decode(&serde_json::json!({"director": 1})), Err(_));
+    }
+
+    #[test]
+    fn test_inline_fragments_with_discriminant() {
+        let selection_set = inline_fragments_with_discriminant(
+            |value| value["kind"].as_str().map(|kind| kind.replace("_", "")),
+            vec![
+                (
+                    "Film".to_string(),
+                    field::<_, (), ()>("title", vec![], string()),
+                ),
+                (
+                    "Person".to_string(),
+                    field::<_, (), ()>("name", vec![], string()),
+                ),
+            ],
+        );
+
+        assert_matches!(
+            selection_set.decode(&serde_json::json!({"kind": "Fi_lm", "title": "Jaws"})),
+            Ok(title) if title == "Jaws"
+        );
+        assert_matches!(
+            selection_set.decode(&serde_json::json!({"__typename": "Person", "name": "Ripley"})),
+            Ok(name) if name == "Ripley"
+        );
+        assert_matches!(
+            selection_set.decode(&serde_json::json!({"name": "Ripley"})),
+            Err(DecodeError::MissingField(_, _))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "pretty-print")]
+    fn test_aliased_fields() {
+        let avatar = |alias: &str, size: i32| {
+            aliased_field::<_, (), ()>(
+                alias,
+                "avatarUrl",
+                vec![Argument::new("size", "Int", size)],
+                string(),
+            )
+        };
+        let selection_set = map2(
+            |small, large| (small, large),
+            avatar("avatarSmall", 32),
+            avatar("avatarLarge", 128),
+        );
+
+        assert_eq!(
+            selection_set
+                .decode(
+                    &serde_json::json!({"avatarSmall": "small.png", "avatarLarge": "large.png"})
+                )
+                .unwrap(),
+            ("small.png".to_string(), "large.png".to_string())
+        );
+
+        let (query, args, _) = selection_set.query_arguments_and_decoder();
+
+        assert_eq!(
+            query,
+            "avatarSmall: avatarUrl(size: $_0)\navatarLarge: avatarUrl(size: $_1)\n"
+        );
+        assert_eq!(args.len(), 2);
+    }
 }
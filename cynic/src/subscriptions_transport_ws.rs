@@ -0,0 +1,261 @@
+//! Running subscriptions with the legacy `subscriptions-transport-ws`
+//! protocol.
+//!
+//! This is the protocol [subscriptions-transport-ws][library] speaks, which
+//! many servers still use.  It's been replaced by `graphql-transport-ws`,
+//! so prefer the `graphql_transport_ws` module if a server supports both.
+//!
+//! `Connection` implements `graphql_transport_ws::Protocol`, so it can be
+//! used with `SubscriptionStream::with_protocol`:
+//!
+//! ```rust,ignore
+//! let mut stream = SubscriptionStream::with_protocol(
+//!     subscriptions_transport_ws::Connection::new(),
+//!     FilmsAddedSubscription::build(()),
+//!     Some(json!({ "authToken": token })),
+//!     incoming.map(|message| message.into_text()),
+//!     move |text| sink.send(text),
+//! );
+//! ```
+//!
+//! [library]: https://github.com/apollographql/subscriptions-transport-ws
+
+use serde_json::Value;
+
+use crate::{
+    graphql_transport_ws::{Event, Protocol, ProtocolError},
+    GraphQLError, GraphQLResponse, StreamingOperation,
+};
+
+/// The websocket subprotocol that should be requested when connecting.
+///
+/// Confusingly, this is the name of the library that implements the
+/// protocol rather than the protocol itself.
+pub const SUBPROTOCOL: &str = "graphql-ws";
+
+/// A message sent from the client to the server.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    ConnectionInit {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<Value>,
+    },
+    Start {
+        id: String,
+        payload: Value,
+    },
+    Stop {
+        id: String,
+    },
+    ConnectionTerminate,
+}
+
+/// A message sent from the server to the client.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    ConnectionAck,
+    ConnectionError {
+        #[serde(default)]
+        payload: Option<Value>,
+    },
+    #[serde(rename = "ka")]
+    KeepAlive,
+    Data {
+        id: String,
+        payload: GraphQLResponse<Value>,
+    },
+    Error {
+        id: String,
+        payload: Value,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+/// The client side of a `subscriptions-transport-ws` connection.
+///
+/// This only tracks the state of the protocol - it's up to the caller to
+/// send & receive messages.  `init_message` should be sent as soon as the
+/// websocket is open, and subscriptions started once `handle_message` has
+/// returned `Event::Acknowledged`.
+#[derive(Debug, Default)]
+pub struct Connection {
+    acknowledged: bool,
+    next_id: u64,
+}
+
+impl Connection {
+    pub fn new() -> Self {
+        Connection::default()
+    }
+
+    /// Whether the server has acknowledged this connection.
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledged
+    }
+
+    /// The message that closes the connection.
+    pub fn terminate_message(&self) -> String {
+        encode(&ClientMessage::ConnectionTerminate)
+    }
+}
+
+impl Protocol for Connection {
+    fn subprotocol(&self) -> &'static str {
+        SUBPROTOCOL
+    }
+
+    fn init_message(&self, payload: Option<Value>) -> String {
+        encode(&ClientMessage::ConnectionInit { payload })
+    }
+
+    /// This protocol has no pings - servers send keep alives instead.
+    fn ping_message(&self) -> Option<String> {
+        None
+    }
+
+    fn subscribe<ResponseData>(
+        &mut self,
+        operation: &StreamingOperation<'_, ResponseData>,
+    ) -> Result<(String, String), serde_json::Error> {
+        let payload = serde_json::to_value(operation)?;
+
+        self.next_id += 1;
+        let id = self.next_id.to_string();
+
+        let message = encode(&ClientMessage::Start {
+            id: id.clone(),
+            payload,
+        });
+        Ok((id, message))
+    }
+
+    fn complete_message(&self, id: &str) -> String {
+        encode(&ClientMessage::Stop { id: id.to_string() })
+    }
+
+    fn handle_message(&mut self, text: &str) -> Result<Event, ProtocolError> {
+        let message = serde_json::from_str::<ServerMessage>(text)?;
+
+        if !self.acknowledged {
+            match message {
+                ServerMessage::ConnectionAck
+                | ServerMessage::ConnectionError { .. }
+                | ServerMessage::KeepAlive => {}
+                ServerMessage::Data { .. } => return Err(ProtocolError::NotAcknowledged("data")),
+                ServerMessage::Error { .. } => return Err(ProtocolError::NotAcknowledged("error")),
+                ServerMessage::Complete { .. } => {
+                    return Err(ProtocolError::NotAcknowledged("complete"))
+                }
+            }
+        }
+
+        Ok(match message {
+            ServerMessage::ConnectionAck => {
+                self.acknowledged = true;
+                Event::Acknowledged
+            }
+            ServerMessage::ConnectionError { payload } => {
+                return Err(ProtocolError::ConnectionRejected(payload))
+            }
+            ServerMessage::KeepAlive => Event::KeepAlive,
+            ServerMessage::Data { id, payload } => Event::Next { id, payload },
+            ServerMessage::Error { id, payload } => Event::Error {
+                id,
+                errors: parse_errors(payload)?,
+            },
+            ServerMessage::Complete { id } => Event::Complete { id },
+        })
+    }
+}
+
+/// Servers send either a single error or a list of them.
+fn parse_errors(payload: Value) -> Result<Vec<GraphQLError>, serde_json::Error> {
+    match payload {
+        Value::Array(_) => serde_json::from_value(payload),
+        _ => Ok(vec![serde_json::from_value(payload)?]),
+    }
+}
+
+fn encode(message: &ClientMessage) -> String {
+    // ClientMessages only contain strings & JSON values, so can't fail to
+    // serialize.
+    serde_json::to_string(message).expect("ClientMessage should always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use crate::testing::fixtures::subscription;
+
+    #[test]
+    fn test_connection_messages() {
+        let mut connection = Connection::new();
+
+        assert_eq!(
+            connection.init_message(Some(json!({ "authToken": "abcd" }))),
+            r#"{"type":"connection_init","payload":{"authToken":"abcd"}}"#
+        );
+
+        let (id, message) = connection.subscribe(&subscription("filmAdded")).unwrap();
+        let message = serde_json::from_str::<Value>(&message).unwrap();
+        assert_eq!(message["type"], "start");
+        assert_eq!(message["id"], "1");
+
+        assert_eq!(
+            connection.complete_message(&id),
+            r#"{"type":"stop","id":"1"}"#
+        );
+        assert_eq!(connection.ping_message(), None);
+    }
+
+    #[test]
+    fn test_handling_messages() {
+        let mut connection = Connection::new();
+
+        assert_matches!(
+            connection.handle_message(r#"{"type":"data","id":"1","payload":{"data":null}}"#),
+            Err(ProtocolError::NotAcknowledged("data"))
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"ka"}"#),
+            Ok(Event::KeepAlive)
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"connection_ack"}"#),
+            Ok(Event::Acknowledged)
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"data","id":"1","payload":{"data":{"filmAdded":"Jaws"}}}"#),
+            Ok(Event::Next { payload, .. }) => {
+                assert_eq!(payload.data, Some(json!({ "filmAdded": "Jaws" })));
+            }
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"error","id":"1","payload":{"message":"oops"}}"#),
+            Ok(Event::Error { errors, .. }) => assert_eq!(errors[0].message, "oops")
+        );
+        assert_matches!(
+            connection.handle_message(r#"{"type":"error","id":"1","payload":[{"message":"a"},{"message":"b"}]}"#),
+            Ok(Event::Error { errors, .. }) => assert_eq!(errors.len(), 2)
+        );
+    }
+
+    #[test]
+    fn test_rejected_connections() {
+        let mut connection = Connection::new();
+
+        assert_matches!(
+            connection.handle_message(r#"{"type":"connection_error","payload":{"message":"bad token"}}"#),
+            Err(ProtocolError::ConnectionRejected(Some(payload))) => {
+                assert_eq!(payload, json!({ "message": "bad token" }))
+            }
+        );
+    }
+}
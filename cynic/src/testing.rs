@@ -0,0 +1,182 @@
+//! Tools for testing code that runs cynic operations.
+//!
+//! A `MockClient` stands in for a real HTTP client.  It responds to
+//! operations with canned JSON, and decodes those responses exactly the
+//! same way as a response from a real server would be - so it can be used to
+//! unit test fragment decoding without any network stubbing:
+//!
+//! ```rust,ignore
+//! use cynic::testing::MockClient;
+//!
+//! let client = MockClient::with_responses(vec![json!({
+//!     "data": { "allFilms": { "films": [{ "title": "A New Hope" }] } }
+//! })]);
+//!
+//! let response = client.run(&AllFilmsQuery::build(()))?;
+//! assert_eq!(client.requests()[0].variables, json!({}));
+//! ```
+//!
+//! `MockClient::new` can be used instead to build responses from each
+//! request as it comes in.
+
+use std::{collections::VecDeque, sync::Mutex};
+
+use serde_json::Value;
+
+use crate::{GraphQLResponse, Operation};
+
+/// A request that was sent to a `MockClient`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockRequest {
+    /// The query document that was sent.
+    pub query: String,
+    /// The variables that were sent, as a JSON object.
+    pub variables: Value,
+    /// The name of the operation to run, if one was sent.
+    pub operation_name: Option<String>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MockError {
+    #[error("The MockClient has no more responses")]
+    NoResponse,
+    #[error("The mock response was not a valid GraphQL response: {0}")]
+    InvalidResponse(#[from] serde_json::Error),
+    #[error("Error decoding GraphQL response: {0}")]
+    DecodeError(#[from] json_decode::DecodeError),
+}
+
+type Handler = Box<dyn Fn(&MockRequest) -> Option<Value> + Send + Sync>;
+
+/// A fake client that responds to operations with canned JSON.
+pub struct MockClient {
+    handler: Handler,
+    requests: Mutex<Vec<MockRequest>>,
+}
+
+impl MockClient {
+    /// Creates a `MockClient` that calls `handler` with each request, and
+    /// responds with the JSON it returns.
+    pub fn new(handler: impl Fn(&MockRequest) -> Value + Send + Sync + 'static) -> Self {
+        MockClient {
+            handler: Box::new(move |request| Some(handler(request))),
+            requests: Mutex::new(vec![]),
+        }
+    }
+
+    /// Creates a `MockClient` that responds with each of `responses` in
+    /// order.  Once they've all been used any further operations fail with
+    /// `MockError::NoResponse`.
+    pub fn with_responses(responses: impl IntoIterator<Item = Value>) -> Self {
+        let responses = Mutex::new(responses.into_iter().collect::<VecDeque<_>>());
+
+        MockClient {
+            handler: Box::new(move |_| responses.lock().unwrap().pop_front()),
+            requests: Mutex::new(vec![]),
+        }
+    }
+
+    /// Runs `operation` against this client, decoding the response.
+    pub fn run<'a, ResponseData: 'a>(
+        &self,
+        operation: &Operation<'a, ResponseData>,
+    ) -> Result<GraphQLResponse<ResponseData>, MockError> {
+        // Going via JSON means the request is exactly what an HTTP client
+        // would have sent.
+        let body = serde_json::to_value(operation)?;
+        let request = MockRequest {
+            query: operation.query.clone(),
+            variables: body.get("variables").cloned().unwrap_or_default(),
+            operation_name: operation.operation_name.clone(),
+        };
+
+        let response = (self.handler)(&request);
+        self.requests.lock().unwrap().push(request);
+
+        let response: GraphQLResponse<Value> =
+            serde_json::from_value(response.ok_or(MockError::NoResponse)?)?;
+
+        Ok(operation.decode_response(response)?)
+    }
+
+    /// The requests this client has received, in the order they were sent.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Operations for the unit tests in this crate, built without a schema.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::{
+        selection_set::{field, string},
+        Argument, MutationRoot, Operation, QueryRoot, StreamingOperation, SubscriptionRoot,
+    };
+
+    /// A root type for every kind of operation.
+    pub struct Root;
+    impl QueryRoot for Root {}
+    impl MutationRoot for Root {}
+    impl SubscriptionRoot for Root {}
+
+    /// A query that selects the string field `name`.
+    pub fn query(name: &str, arguments: Vec<Argument>) -> Operation<'static, String> {
+        Operation::query(field::<_, Root, ()>(name, arguments, string()))
+    }
+
+    /// A mutation that selects the string field `name`.
+    pub fn mutation(name: &str, arguments: Vec<Argument>) -> Operation<'static, String> {
+        Operation::mutation(field::<_, Root, ()>(name, arguments, string()))
+    }
+
+    /// A subscription that selects the string field `name`.
+    pub fn subscription(name: &str) -> StreamingOperation<'static, String> {
+        StreamingOperation::subscription(field::<_, Root, ()>(name, vec![], string()))
+    }
+
+    /// A query that selects the string field `film` with an `Int!` id.
+    pub fn film_query(id: i32) -> Operation<'static, String> {
+        query("film", vec![Argument::new("id", "Int!", id)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use serde_json::json;
+
+    use super::fixtures::film_query;
+
+    #[test]
+    fn test_canned_responses() {
+        let client = MockClient::with_responses(vec![
+            json!({ "data": { "film": "A New Hope" } }),
+            json!({ "data": { "film": 1 } }),
+        ]);
+
+        assert_eq!(
+            client.run(&film_query(1)).unwrap().data.as_deref(),
+            Some("A New Hope")
+        );
+        assert_matches!(client.run(&film_query(2)), Err(MockError::DecodeError(_)));
+        assert_matches!(client.run(&film_query(3)), Err(MockError::NoResponse));
+
+        let requests = client.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].variables, json!({ "_0": 2 }));
+        assert_eq!(requests[1].query, film_query(2).query);
+    }
+
+    #[test]
+    fn test_response_handlers() {
+        let client = MockClient::new(
+            |request| json!({ "data": { "film": format!("Film {}", request.variables["_0"]) } }),
+        );
+
+        assert_eq!(
+            client.run(&film_query(4)).unwrap().data.as_deref(),
+            Some("Film 4")
+        );
+    }
+}
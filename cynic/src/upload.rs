@@ -0,0 +1,364 @@
+//! File uploads using the [GraphQL multipart request spec][1].
+//!
+//! Servers that implement the spec usually define an `Upload` scalar.  To
+//! use it with cynic, re-export `cynic::upload::Upload` from the module
+//! containing your query DSL:
+//!
+//! ```rust,ignore
+//! mod query_dsl {
+//!     cynic::query_dsl!("schema.graphql");
+//!
+//!     pub use cynic::upload::Upload;
+//! }
+//! ```
+//!
+//! Operations that contain uploads need to be sent as a multipart request
+//! rather than JSON.  The reqwest & surf integrations, and any other
+//! `client::GraphQlClient`, do this automatically.  `MultipartRequest` can be
+//! used to build the request for any other HTTP client.
+//!
+//! The files themselves are kept alongside the arguments of an operation
+//! rather than in its variables, which contain `null` wherever an upload was
+//! used.  Sending an operation as JSON will therefore not send its files.
+//!
+//! [1]: https://github.com/jaydenseric/graphql-multipart-request-spec
+
+use std::{cell::RefCell, collections::BTreeMap};
+
+use json_decode::DecodeError;
+use serde_json::{json, Value};
+
+use crate::{incremental::find, scalar::Scalar, Operation, SerializableArgument, SerializeError};
+
+const UPLOAD_MARKER: &str = "__cynic_upload";
+
+thread_local! {
+    /// The uploads encoded by the argument being serialized on this thread,
+    /// if there is one.
+    static UPLOADS: RefCell<Option<Vec<Upload>>> = RefCell::new(None);
+}
+
+/// A file to upload to a server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Upload {
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+impl Upload {
+    pub fn new(filename: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Upload {
+            filename: filename.into(),
+            content_type: None,
+            data: data.into(),
+        }
+    }
+
+    /// Sets the MIME type to send with this file.
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+}
+
+/// Uploads are sent as part of a multipart request, so can't be encoded as
+/// JSON.
+///
+/// Instead they encode as a placeholder, which `Argument::new` replaces with
+/// the `null` the spec requires, keeping the file alongside the argument.
+/// This means uploads can only be encoded as part of an argument.
+impl Scalar for Upload {
+    fn decode(_: &Value) -> Result<Self, DecodeError> {
+        Err(DecodeError::Other(
+            "Uploads can't be decoded from a response".into(),
+        ))
+    }
+
+    fn encode(&self) -> Result<Value, SerializeError> {
+        UPLOADS.with(|uploads| match uploads.borrow_mut().as_mut() {
+            Some(uploads) => {
+                uploads.push(self.clone());
+                Ok(json!({ UPLOAD_MARKER: uploads.len() - 1 }))
+            }
+            None => Err("Uploads can only be encoded as part of an Argument".into()),
+        })
+    }
+}
+
+crate::impl_serializable_argument_for_scalar!(Upload);
+crate::impl_into_argument_for_options!(Upload);
+
+/// Serializes the value of an argument, taking out any uploads it contains.
+///
+/// Each upload is replaced with `null`, and returned along with its path
+/// within the value (e.g. `.0.file`).
+pub(crate) fn serialize_argument<T: SerializableArgument + ?Sized>(
+    value: &T,
+) -> (Result<Value, SerializeError>, Vec<(String, Upload)>) {
+    let outer = UPLOADS.with(|uploads| uploads.replace(Some(vec![])));
+    let result = value.serialize();
+    let uploads = UPLOADS
+        .with(|uploads| uploads.replace(outer))
+        .unwrap_or_default();
+
+    if uploads.is_empty() {
+        return (result, vec![]);
+    }
+
+    let mut uploads = uploads.into_iter().map(Some).collect::<Vec<_>>();
+    let mut found = vec![];
+    let result = result.map(|mut value| {
+        take_uploads(&mut value, String::new(), &mut uploads, &mut found);
+        value
+    });
+
+    (result, found)
+}
+
+fn take_uploads(
+    value: &mut Value,
+    path: String,
+    uploads: &mut [Option<Upload>],
+    found: &mut Vec<(String, Upload)>,
+) {
+    let index = value
+        .as_object()
+        .and_then(|object| object.get(UPLOAD_MARKER))
+        .and_then(Value::as_u64);
+    if let Some(upload) = index.and_then(|i| uploads.get_mut(i as usize)?.take()) {
+        *value = Value::Null;
+        found.push((path, upload));
+        return;
+    }
+
+    match value {
+        Value::Array(values) => {
+            for (i, value) in values.iter_mut().enumerate() {
+                take_uploads(value, format!("{}.{}", path, i), uploads, found);
+            }
+        }
+        Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                take_uploads(value, format!("{}.{}", path, name), uploads, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The parts of a multipart request for an operation containing uploads.
+#[derive(Debug)]
+pub struct MultipartRequest {
+    /// The operation, to be sent in the `operations` part.
+    ///
+    /// This contains `null` wherever an upload was used in the variables.
+    pub operations: Value,
+    /// The map of file part names to the paths of the variables they
+    /// belong in, to be sent in the `map` part.
+    pub map: BTreeMap<String, Vec<String>>,
+    /// The files to send.
+    ///
+    /// Each file should be sent in a part named after its index in this
+    /// list, which is also its key in `map`.
+    pub files: Vec<Upload>,
+}
+
+impl MultipartRequest {
+    /// Builds a multipart request for `operation`.
+    ///
+    /// Returns `None` if the operation doesn't contain any uploads, in which
+    /// case it can be sent as JSON as usual.
+    pub fn for_operation<ResponseData>(
+        operation: &Operation<'_, ResponseData>,
+    ) -> Result<Option<MultipartRequest>, serde_json::Error> {
+        let files = operation
+            .variables
+            .iter()
+            .flat_map(|(name, argument)| {
+                argument.uploads.iter().map(move |(path, upload)| {
+                    (format!("variables.{}{}", name, path), upload.clone())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        let (map, files) = files
+            .into_iter()
+            .enumerate()
+            .map(|(i, (path, upload))| ((i.to_string(), vec![path]), upload))
+            .unzip();
+
+        Ok(Some(MultipartRequest {
+            operations: serde_json::to_value(operation)?,
+            map,
+            files,
+        }))
+    }
+
+    /// Encodes the request as a `multipart/form-data` body.
+    ///
+    /// Returns the `Content-Type` to send, which contains the boundary
+    /// between each part, along with the body.
+    pub fn to_form_data(&self) -> Result<(String, Vec<u8>), serde_json::Error> {
+        let operations = serde_json::to_vec(&self.operations)?;
+        let map = serde_json::to_vec(&self.map)?;
+
+        let mut parts: Vec<(String, Option<&str>, &[u8])> = vec![
+            (
+                "name=\"operations\"".to_string(),
+                None,
+                operations.as_slice(),
+            ),
+            ("name=\"map\"".to_string(), None, map.as_slice()),
+        ];
+        for (i, upload) in self.files.iter().enumerate() {
+            let content_type = upload
+                .content_type
+                .as_deref()
+                .unwrap_or("application/octet-stream");
+
+            parts.push((
+                format!(
+                    "name=\"{}\"; filename=\"{}\"",
+                    i,
+                    escape_filename(&upload.filename)
+                ),
+                Some(content_type),
+                upload.data.as_slice(),
+            ));
+        }
+
+        // The boundary can't appear in any of the parts.
+        let boundary = (0..)
+            .map(|i| format!("cynic-boundary-{}", i))
+            .find(|boundary| {
+                parts
+                    .iter()
+                    .all(|(_, _, data)| find(data, boundary.as_bytes()).is_none())
+            })
+            .expect("an unused boundary");
+
+        let mut body = vec![];
+        for (disposition, content_type, data) in parts {
+            body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+            body.extend_from_slice(
+                format!("Content-Disposition: form-data; {}\r\n", disposition).as_bytes(),
+            );
+            if let Some(content_type) = content_type {
+                body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+            }
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+        Ok((format!("multipart/form-data; boundary={}", boundary), body))
+    }
+}
+
+/// Escapes the characters that can't be used in a quoted filename, the same
+/// way browsers do.
+fn escape_filename(filename: &str) -> String {
+    filename
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::fixtures::mutation, Argument};
+
+    fn upload_operation(files: Vec<Upload>) -> Operation<'static, String> {
+        mutation(
+            "uploadFiles",
+            vec![Argument::new("files", "[Upload!]!", files)],
+        )
+    }
+
+    #[test]
+    fn test_multipart_request() {
+        let avatar = Upload::new("avatar.png", vec![0, 159, 255]).with_content_type("image/png");
+        let notes = Upload::new("notes.txt", "hello");
+
+        let request =
+            MultipartRequest::for_operation(&upload_operation(vec![avatar.clone(), notes.clone()]))
+                .unwrap()
+                .unwrap();
+
+        assert_eq!(request.operations["variables"]["_0"], json!([null, null]));
+        assert_eq!(
+            request.map,
+            vec![
+                ("0".to_string(), vec!["variables._0.0".to_string()]),
+                ("1".to_string(), vec!["variables._0.1".to_string()]),
+            ]
+            .into_iter()
+            .collect()
+        );
+        assert_eq!(request.files, vec![avatar, notes]);
+    }
+
+    #[test]
+    fn test_uploads_are_kept_out_of_variables() {
+        let operation = upload_operation(vec![Upload::new("notes.txt", "hello")]);
+
+        assert_eq!(
+            serde_json::to_value(&operation).unwrap()["variables"],
+            json!({ "_0": [null] })
+        );
+        assert!(Upload::new("notes.txt", "hello").encode().is_err());
+    }
+
+    #[test]
+    fn test_file_part_names() {
+        let files = (0..11)
+            .map(|i| Upload::new(format!("{}.txt", i), "hello"))
+            .collect::<Vec<_>>();
+
+        let request = MultipartRequest::for_operation(&upload_operation(files))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(request.map["10"], vec!["variables._0.10".to_string()]);
+        assert_eq!(request.files[10].filename, "10.txt");
+    }
+
+    #[test]
+    fn test_form_data() {
+        let upload = Upload::new("a\"b.txt", "cynic-boundary-0");
+        let request = MultipartRequest::for_operation(&upload_operation(vec![upload]))
+            .unwrap()
+            .unwrap();
+
+        let (content_type, body) = request.to_form_data().unwrap();
+        assert_eq!(
+            content_type,
+            "multipart/form-data; boundary=cynic-boundary-1"
+        );
+
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.starts_with(
+            "--cynic-boundary-1\r\nContent-Disposition: form-data; name=\"operations\"\r\n\r\n{"
+        ));
+        assert!(body.contains(
+            "\r\n--cynic-boundary-1\r\nContent-Disposition: form-data; name=\"map\"\r\n\r\n{\"0\":[\"variables._0.0\"]}\r\n"
+        ));
+        assert!(body.ends_with(
+            "\r\n--cynic-boundary-1\r\nContent-Disposition: form-data; name=\"0\"; filename=\"a%22b.txt\"\r\nContent-Type: application/octet-stream\r\n\r\ncynic-boundary-0\r\n--cynic-boundary-1--\r\n"
+        ));
+    }
+
+    #[test]
+    fn test_operations_without_uploads() {
+        assert!(MultipartRequest::for_operation(&upload_operation(vec![]))
+            .unwrap()
+            .is_none());
+    }
+}
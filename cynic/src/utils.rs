@@ -1,3 +1,5 @@
+use std::{rc::Rc, sync::Arc};
+
 pub trait FlattenFrom<T> {
     fn flatten_from(args: T) -> Self;
 }
@@ -40,6 +42,48 @@ where
     }
 }
 
+/// Wraps a decoded value in the `Box`, `Arc` or `Rc` pointers that a
+/// QueryFragment field expects, including pointers inside `Option` or `Vec`.
+pub trait WrapInto<T> {
+    fn wrap_into(self) -> T;
+}
+
+impl<T> WrapInto<Box<T>> for T {
+    fn wrap_into(self) -> Box<T> {
+        Box::new(self)
+    }
+}
+
+impl<T> WrapInto<Arc<T>> for T {
+    fn wrap_into(self) -> Arc<T> {
+        Arc::new(self)
+    }
+}
+
+impl<T> WrapInto<Rc<T>> for T {
+    fn wrap_into(self) -> Rc<T> {
+        Rc::new(self)
+    }
+}
+
+impl<T, U> WrapInto<Option<U>> for Option<T>
+where
+    T: WrapInto<U>,
+{
+    fn wrap_into(self) -> Option<U> {
+        self.map(WrapInto::wrap_into)
+    }
+}
+
+impl<T, U> WrapInto<Vec<U>> for Vec<T>
+where
+    T: WrapInto<U>,
+{
+    fn wrap_into(self) -> Vec<U> {
+        self.into_iter().map(WrapInto::wrap_into).collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,4 +121,16 @@ mod tests {
         let output: Vec<i32> = opt_vec.flatten_into();
         assert_eq!(output, expected);
     }
+
+    #[test]
+    fn test_wrap_into() {
+        let output: Option<Vec<Arc<i32>>> = Some(vec![1, 2]).wrap_into();
+        assert_eq!(output, Some(vec![Arc::new(1), Arc::new(2)]));
+
+        let output: Rc<Option<i32>> = None::<i32>.wrap_into();
+        assert_eq!(output, Rc::new(None));
+
+        let output: Vec<Box<i32>> = vec![1].wrap_into();
+        assert_eq!(output, vec![Box::new(1)]);
+    }
 }
@@ -0,0 +1,35 @@
+//! Tests of the optional trait impls generated by the Enum derive
+
+mod query_dsl {
+    cynic::query_dsl!("tests/test-schema.graphql");
+}
+
+#[derive(cynic::Enum, Clone, Copy, Debug, PartialEq)]
+#[cynic(
+    graphql_type = "PostState",
+    schema_path = "tests/test-schema.graphql",
+    query_module = "query_dsl",
+    rename_all = "SCREAMING_SNAKE_CASE",
+    impl_display,
+    impl_from_str
+)]
+enum PostState {
+    Draft,
+    Published,
+}
+
+#[test]
+fn test_enum_display() {
+    assert_eq!(PostState::Draft.to_string(), "DRAFT");
+    assert_eq!(PostState::Published.to_string(), "PUBLISHED");
+}
+
+#[test]
+fn test_enum_from_str() {
+    assert_eq!("DRAFT".parse::<PostState>(), Ok(PostState::Draft));
+    assert_eq!("PUBLISHED".parse::<PostState>(), Ok(PostState::Published));
+    assert_eq!(
+        "Draft".parse::<PostState>(),
+        Err(cynic::UnknownVariantError::new("PostState", "Draft"))
+    );
+}
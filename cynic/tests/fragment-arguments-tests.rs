@@ -0,0 +1,38 @@
+//! Tests of the code generated by the FragmentArguments derive
+
+#[derive(cynic::FragmentArguments, Debug, PartialEq)]
+#[cynic(builder)]
+pub struct FilmArguments {
+    id: String,
+    first: Option<i32>,
+    after: Option<String>,
+    limit: i32,
+}
+
+#[test]
+fn test_fragment_arguments_builder() {
+    let args = FilmArguments::builder()
+        .limit(10)
+        .first(5)
+        .id("1".to_string())
+        .build();
+
+    assert_eq!(
+        args,
+        FilmArguments {
+            id: "1".into(),
+            first: Some(5),
+            after: None,
+            limit: 10
+        }
+    );
+}
+
+#[derive(cynic::FragmentArguments, Debug, PartialEq)]
+#[cynic(builder)]
+struct EmptyArguments {}
+
+#[test]
+fn test_empty_fragment_arguments_builder() {
+    assert_eq!(EmptyArguments::builder().build(), EmptyArguments {});
+}
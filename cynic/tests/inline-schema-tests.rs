@@ -0,0 +1,57 @@
+//! Tests of schemas that are written inline rather than loaded from a file
+
+use cynic::QueryFragment;
+
+mod query_dsl {
+    cynic::query_dsl!(
+        sdl = r#"
+        type Query {
+            film(id: ID!): Film
+        }
+
+        type Film {
+            id: ID!
+            title: String
+        }
+        "#
+    );
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    graphql_type = "Film",
+    sdl = r#"
+        type Query { film(id: ID!): Film }
+        type Film { id: ID!, title: String }
+    "#,
+    query_module = "query_dsl"
+)]
+struct Film {
+    id: cynic::Id,
+    title: Option<String>,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    graphql_type = "Query",
+    sdl = r#"
+        type Query { film(id: ID!): Film }
+        type Film { id: ID!, title: String }
+    "#,
+    query_module = "query_dsl"
+)]
+struct FilmQuery {
+    #[arguments(id = cynic::Id::new("1"))]
+    film: Option<Film>,
+}
+
+#[test]
+fn test_inline_schema_query() {
+    let operation = cynic::Operation::query(FilmQuery::fragment(&()));
+
+    assert!(operation.query.contains("film(id: $_0)"));
+    assert_eq!(
+        serde_json::to_value(&operation).unwrap()["variables"],
+        serde_json::json!({ "_0": "1" })
+    );
+}
@@ -67,3 +67,205 @@ fn test_input_object_skip_serializing() {
     .unwrap();
     assert_eq!(with_author, json!({ "content": "hi", "author": "Me" }));
 }
+
+#[test]
+fn test_input_object_impl_serialize() {
+    #[derive(cynic::Enum)]
+    #[cynic(
+        graphql_type = "PostState",
+        schema_path = "tests/test-schema.graphql",
+        query_module = "query_dsl",
+        rename_all = "SCREAMING_SNAKE_CASE",
+        impl_serialize
+    )]
+    enum PostState {
+        Draft,
+        Published,
+    }
+
+    #[derive(cynic::InputObject)]
+    #[cynic(
+        graphql_type = "BlogPost",
+        schema_path = "tests/test-schema.graphql",
+        query_module = "query_dsl",
+        impl_serialize
+    )]
+    struct BlogPost {
+        content: String,
+        state: Option<PostState>,
+    }
+
+    let post = BlogPost {
+        content: "hi".into(),
+        state: Some(PostState::Published),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&post).unwrap(),
+        json!({ "content": "hi", "state": "PUBLISHED" })
+    );
+    assert_eq!(
+        serde_json::to_value(&PostState::Draft).unwrap(),
+        json!("DRAFT")
+    );
+}
+
+#[test]
+fn test_all_optional_input_object_default() {
+    use cynic::SerializableArgument;
+
+    #[derive(cynic::InputObject)]
+    #[cynic(
+        graphql_type = "PostFilter",
+        schema_path = "tests/test-schema.graphql",
+        query_module = "query_dsl"
+    )]
+    struct PostFilter {
+        author: Option<String>,
+        #[cynic(skip_serializing_if = "Option::is_none")]
+        state: Option<PostState>,
+    }
+
+    #[derive(cynic::Enum)]
+    #[cynic(
+        graphql_type = "PostState",
+        schema_path = "tests/test-schema.graphql",
+        query_module = "query_dsl",
+        rename_all = "SCREAMING_SNAKE_CASE"
+    )]
+    enum PostState {
+        Draft,
+        Published,
+    }
+
+    assert!(PostFilter::default().is_empty());
+
+    let filter = PostFilter {
+        author: Some("Me".into()),
+        ..PostFilter::default()
+    };
+
+    assert!(!filter.is_empty());
+    assert_eq!(filter.serialize().unwrap(), json!({ "author": "Me" }));
+}
+
+#[test]
+fn test_borrowed_input_object() {
+    use cynic::SerializableArgument;
+
+    #[derive(cynic::InputObject)]
+    #[cynic(
+        graphql_type = "BlogPost",
+        schema_path = "tests/test-schema.graphql",
+        query_module = "query_dsl"
+    )]
+    struct BlogPost<'a> {
+        content: &'a str,
+        #[cynic(skip_serializing_if = "Option::is_none")]
+        author: Option<&'a str>,
+        tags: Option<&'a [&'a str]>,
+    }
+
+    let content = String::from("hi");
+    let tags = vec!["news", "rust"];
+
+    let post = BlogPost {
+        content: &content,
+        author: None,
+        tags: Some(&tags),
+    };
+
+    assert_eq!(
+        post.serialize().unwrap(),
+        json!({ "content": "hi", "tags": ["news", "rust"] })
+    );
+}
+
+#[test]
+fn test_maybe_undefined_fields() {
+    use cynic::{MaybeUndefined, SerializableArgument};
+
+    #[derive(cynic::InputObject)]
+    #[cynic(
+        graphql_type = "PostFilter",
+        schema_path = "tests/test-schema.graphql",
+        query_module = "query_dsl"
+    )]
+    struct PostFilter {
+        author: MaybeUndefined<String>,
+    }
+
+    assert!(PostFilter::default().is_empty());
+    assert_eq!(PostFilter::default().serialize().unwrap(), json!({}));
+
+    let filter = PostFilter {
+        author: MaybeUndefined::Null,
+    };
+    assert!(!filter.is_empty());
+    assert_eq!(filter.serialize().unwrap(), json!({ "author": null }));
+
+    let filter = PostFilter {
+        author: MaybeUndefined::Value("Me".into()),
+    };
+    assert_eq!(filter.serialize().unwrap(), json!({ "author": "Me" }));
+}
+
+#[test]
+fn test_one_of_enums() {
+    use cynic::SerializableArgument;
+
+    #[derive(cynic::InputObject)]
+    #[cynic(
+        graphql_type = "PostSelector",
+        schema_path = "tests/test-schema.graphql",
+        query_module = "query_dsl",
+        rename_all = "camelCase"
+    )]
+    enum PostSelector {
+        Title(String),
+        #[cynic(rename = "author")]
+        Writer(String),
+    }
+
+    assert_eq!(
+        PostSelector::Title("Hello".into()).serialize().unwrap(),
+        json!({ "title": "Hello" })
+    );
+    assert_eq!(
+        PostSelector::Writer("Me".into()).serialize().unwrap(),
+        json!({ "author": "Me" })
+    );
+}
+
+#[test]
+fn test_one_of_structs() {
+    use cynic::SerializableArgument;
+
+    #[derive(cynic::InputObject)]
+    #[cynic(
+        graphql_type = "PostSelector",
+        schema_path = "tests/test-schema.graphql",
+        query_module = "query_dsl"
+    )]
+    struct PostSelector {
+        title: Option<String>,
+        author: Option<String>,
+    }
+
+    let selector = PostSelector {
+        title: Some("Hello".into()),
+        author: None,
+    };
+    assert_eq!(
+        selector.serialize().unwrap(),
+        json!({ "title": "Hello", "author": null })
+    );
+
+    assert!(PostSelector::default().serialize().is_err());
+
+    let selector = PostSelector {
+        title: Some("Hello".into()),
+        author: Some("Me".into()),
+    };
+    assert!(selector.serialize().is_err());
+}
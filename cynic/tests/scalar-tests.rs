@@ -0,0 +1,62 @@
+//! Tests of the Scalar derive
+
+use cynic::{
+    selection_set::{field, scalar},
+    Argument, GraphQLResponse, Operation, QueryRoot, Scalar,
+};
+use serde_json::json;
+
+#[derive(cynic::Scalar, Debug, PartialEq)]
+#[cynic(string_encoded)]
+struct BigInt(i128);
+
+struct Root;
+impl QueryRoot for Root {}
+
+const BIG: i128 = i128::MAX;
+
+#[test]
+fn test_string_encoded_decoding() {
+    assert_eq!(
+        BigInt::decode(&json!("170141183460469231731687303715884105727")),
+        Ok(BigInt(BIG))
+    );
+    assert_eq!(BigInt::decode(&json!(12)), Ok(BigInt(12)));
+    assert!(BigInt::decode(&json!("twelve")).is_err());
+
+    let operation = Operation::query(field::<_, Root, ()>("total", vec![], scalar::<BigInt>()));
+    let response = operation
+        .decode_response(GraphQLResponse {
+            data: Some(json!({ "total": "-5" })),
+            errors: None,
+            extensions: None,
+        })
+        .unwrap();
+
+    assert_eq!(response.data, Some(BigInt(-5)));
+}
+
+#[test]
+fn test_string_encoded_arguments() {
+    assert_eq!(
+        BigInt(BIG).encode().unwrap(),
+        json!("170141183460469231731687303715884105727")
+    );
+
+    let operation = Operation::query(field::<_, Root, ()>(
+        "total",
+        vec![
+            Argument::new("above", "BigInt!", BigInt(BIG)),
+            Argument::new("below", "[BigInt]", vec![Some(BigInt(1)), None]),
+        ],
+        scalar::<BigInt>(),
+    ));
+
+    assert_eq!(
+        serde_json::to_value(&operation).unwrap()["variables"],
+        json!({
+            "_0": "170141183460469231731687303715884105727",
+            "_1": ["1", null]
+        })
+    );
+}
@@ -67,6 +67,7 @@ fn run_test(input: serde_json::Value, expected_result: TestQuery) {
     let test_data = cynic::GraphQLResponse {
         errors: None,
         data: Some(input),
+        extensions: None,
     };
     let data = query.decode_response(test_data).unwrap().data.unwrap();
     assert_eq!(data, expected_result);
@@ -111,3 +112,669 @@ fn test_decoding_options() {
     let json = serde_json::json!({ "testStruct": null });
     run_test(json, TestQuery { test_struct: None });
 }
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "TestStruct"
+)]
+struct SharedTestStruct {
+    field_one: std::sync::Arc<String>,
+    nested: Box<Nested>,
+    opt_nested: Option<std::sync::Arc<Nested>>,
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Query"
+)]
+struct SharedTestQuery {
+    test_struct: Option<std::sync::Arc<SharedTestStruct>>,
+}
+
+#[test]
+fn test_decoding_pointers() {
+    use cynic::QueryFragment;
+    use std::sync::Arc;
+
+    let query = cynic::Operation::query(SharedTestQuery::fragment(&()));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "testStruct": {
+                "fieldOne": "test",
+                "nested": { "aString": "test2", "optString": null },
+                "optNested": null
+            }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(SharedTestQuery {
+            test_struct: Some(Arc::new(SharedTestStruct {
+                field_one: Arc::new("test".to_string()),
+                nested: Box::new(Nested {
+                    a_string: "test2".to_string(),
+                    opt_string: None,
+                }),
+                opt_nested: None,
+            }))
+        })
+    );
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Nested"
+)]
+struct NestedWithTypename {
+    __typename: String,
+    #[cynic(typename)]
+    kind: String,
+    a_string: String,
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "TestStruct"
+)]
+struct TypenameTestStruct {
+    nested: NestedWithTypename,
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Query"
+)]
+struct TypenameTestQuery {
+    test_struct: Option<TypenameTestStruct>,
+}
+
+#[test]
+fn test_decoding_typename() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(TypenameTestQuery::fragment(&()));
+    assert!(query.query.contains("__typename"));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "testStruct": {
+                "nested": { "__typename": "Nested", "aString": "test" }
+            }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(TypenameTestQuery {
+            test_struct: Some(TypenameTestStruct {
+                nested: NestedWithTypename {
+                    __typename: "Nested".to_string(),
+                    kind: "Nested".to_string(),
+                    a_string: "test".to_string(),
+                }
+            })
+        })
+    );
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Subscription"
+)]
+struct NestedAddedSubscription {
+    nested_added: Nested,
+}
+
+#[test]
+fn test_subscription() {
+    use cynic::QueryFragment;
+
+    let subscription =
+        cynic::StreamingOperation::subscription(NestedAddedSubscription::fragment(&()));
+    assert!(subscription
+        .query()
+        .starts_with("subscription Subscription"));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "nestedAdded": { "aString": "test", "optString": null }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        subscription.decode_response(test_data).unwrap().data,
+        Some(NestedAddedSubscription {
+            nested_added: Nested {
+                a_string: "test".to_string(),
+                opt_string: None,
+            }
+        })
+    );
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Nested"
+)]
+struct RenamedNested {
+    #[cynic(rename = "aString")]
+    text: String,
+}
+
+#[test]
+fn test_renamed_fields() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "nested",
+        vec![],
+        RenamedNested::fragment(&()),
+    ));
+    assert!(query.query.contains("aString"));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({ "nested": { "aString": "test" } })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(RenamedNested {
+            text: "test".to_string()
+        })
+    );
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "TestStruct"
+)]
+struct AliasedTestStruct {
+    #[cynic(rename = "fieldOne", alias)]
+    #[arguments(x = 1)]
+    first: String,
+    #[cynic(rename = "fieldOne", alias)]
+    #[arguments(x = 2)]
+    second: String,
+}
+
+#[test]
+fn test_aliased_fields() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "testStruct",
+        vec![],
+        AliasedTestStruct::fragment(&()),
+    ));
+    assert!(query.query.contains("first: fieldOne(x: $_0)"));
+    assert!(query.query.contains("second: fieldOne(x: $_1)"));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "testStruct": { "first": "one", "second": "two" }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(AliasedTestStruct {
+            first: "one".to_string(),
+            second: "two".to_string(),
+        })
+    );
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Nested"
+)]
+struct NestedLists {
+    tags: Vec<String>,
+    matrix: Option<Vec<Option<Vec<Option<i32>>>>>,
+}
+
+#[test]
+fn test_nested_lists() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "nested",
+        vec![],
+        NestedLists::fragment(&()),
+    ));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "nested": {
+                "tags": ["one", "two"],
+                "matrix": [[1, null], null, []]
+            }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(NestedLists {
+            tags: vec!["one".to_string(), "two".to_string()],
+            matrix: Some(vec![Some(vec![Some(1), None]), None, Some(vec![])]),
+        })
+    );
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "nested": { "tags": ["one", null], "matrix": null }
+        })),
+        extensions: None,
+    };
+    assert!(query.decode_response(test_data).is_err());
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Nested",
+    named_fragment
+)]
+struct NamedNested {
+    a_string: String,
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "TestStruct"
+)]
+struct TestStructWithNamedFragments {
+    nested: NamedNested,
+    opt_nested: Option<NamedNested>,
+}
+
+#[test]
+fn test_named_fragments() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "testStruct",
+        vec![],
+        TestStructWithNamedFragments::fragment(&()),
+    ));
+    assert_eq!(query.query.matches("...NamedNested").count(), 2);
+    assert_eq!(
+        query
+            .query
+            .matches("fragment NamedNested on Nested")
+            .count(),
+        1
+    );
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "testStruct": {
+                "nested": { "aString": "one" },
+                "optNested": null
+            }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(TestStructWithNamedFragments {
+            nested: NamedNested {
+                a_string: "one".to_string()
+            },
+            opt_nested: None,
+        })
+    );
+}
+
+#[derive(cynic::FragmentArguments)]
+struct ConditionalArgs {
+    with_nested: bool,
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "TestStruct",
+    argument_struct = "ConditionalArgs"
+)]
+struct ConditionalTestStruct {
+    field_one: String,
+    #[cynic(include_if = "args.with_nested")]
+    nested: Option<Nested>,
+    #[cynic(skip_if = "args.with_nested")]
+    opt_nested: Option<Option<Nested>>,
+}
+
+#[test]
+fn test_conditional_fields() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "testStruct",
+        vec![],
+        ConditionalTestStruct::fragment(&ConditionalArgs { with_nested: true }),
+    ));
+    assert!(query.query.contains("@include(if: $_0)"));
+    assert!(query.query.contains("@skip(if: $_1)"));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "testStruct": {
+                "fieldOne": "test",
+                "nested": { "aString": "nested", "optString": null }
+            }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(ConditionalTestStruct {
+            field_one: "test".to_string(),
+            nested: Some(Nested {
+                a_string: "nested".to_string(),
+                opt_string: None
+            }),
+            opt_nested: None,
+        })
+    );
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Comment"
+)]
+struct Comment {
+    body: String,
+    #[cynic(recurse = "2")]
+    replies: Option<Vec<Comment>>,
+    #[cynic(recurse = "1")]
+    parent: Option<Box<Comment>>,
+}
+
+#[test]
+fn test_recursive_fragments() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "comment",
+        vec![],
+        Comment::fragment(&()),
+    ));
+    assert_eq!(query.query.matches("replies").count(), 3);
+    assert_eq!(query.query.matches("parent").count(), 1);
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "comment": {
+                "body": "one",
+                "replies": [{
+                    "body": "two",
+                    "replies": [{ "body": "three" }]
+                }],
+                "parent": {
+                    "body": "zero",
+                    "replies": []
+                }
+            }
+        })),
+        extensions: None,
+    };
+
+    let comment = |body: &str, replies, parent| Comment {
+        body: body.to_string(),
+        replies,
+        parent,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(comment(
+            "one",
+            Some(vec![comment(
+                "two",
+                Some(vec![comment("three", None, None)]),
+                None
+            )]),
+            Some(Box::new(comment("zero", Some(vec![]), None)))
+        ))
+    );
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Nested"
+)]
+struct FlattenedNested {
+    #[cynic(flatten)]
+    opt_tags: Vec<String>,
+}
+
+#[test]
+fn test_flattened_fields() {
+    use cynic::QueryFragment;
+
+    let decode = |opt_tags| {
+        let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+            "nested",
+            vec![],
+            FlattenedNested::fragment(&()),
+        ));
+
+        query
+            .decode_response(cynic::GraphQLResponse {
+                errors: None,
+                data: Some(serde_json::json!({ "nested": { "optTags": opt_tags } })),
+                extensions: None,
+            })
+            .unwrap()
+            .data
+            .unwrap()
+    };
+
+    assert_eq!(
+        decode(serde_json::json!(["one", null, "two"])),
+        FlattenedNested {
+            opt_tags: vec!["one".to_string(), "two".to_string()]
+        }
+    );
+    assert_eq!(
+        decode(serde_json::Value::Null),
+        FlattenedNested { opt_tags: vec![] }
+    );
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "TestStruct"
+)]
+struct TestStructNesting {
+    nested: Nested,
+    opt_nested: Option<Nested>,
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "TestStruct"
+)]
+struct SpreadTestStruct {
+    field_one: String,
+    #[cynic(spread)]
+    nesting: Box<TestStructNesting>,
+}
+
+#[test]
+fn test_spread_fields() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "testStruct",
+        vec![],
+        SpreadTestStruct::fragment(&()),
+    ));
+    assert!(!query.query.contains("nesting"));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "testStruct": {
+                "fieldOne": "test",
+                "nested": { "aString": "nested", "optString": null },
+                "optNested": null
+            }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(SpreadTestStruct {
+            field_one: "test".to_string(),
+            nesting: Box::new(TestStructNesting {
+                nested: Nested {
+                    a_string: "nested".to_string(),
+                    opt_string: None
+                },
+                opt_nested: None,
+            }),
+        })
+    );
+}
+
+#[derive(serde::Deserialize, PartialEq, Debug)]
+struct JsonPayload {
+    count: i32,
+}
+
+#[derive(cynic::QueryFragment, PartialEq, Debug)]
+#[cynic(
+    schema_path = "src/bin/simple.graphql",
+    query_module = "query_dsl",
+    graphql_type = "TestStruct"
+)]
+struct JsonTestStruct {
+    #[cynic(scalar_as_json)]
+    json: Option<JsonPayload>,
+}
+
+#[test]
+fn test_scalar_as_json() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "testStruct",
+        vec![],
+        JsonTestStruct::fragment(&()),
+    ));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({
+            "testStruct": { "json": { "count": 3, "other": true } }
+        })),
+        extensions: None,
+    };
+
+    assert_eq!(
+        query.decode_response(test_data).unwrap().data,
+        Some(JsonTestStruct {
+            json: Some(JsonPayload { count: 3 })
+        })
+    );
+}
+
+macro_rules! wide_fragment {
+    ($($field:ident),*) => {
+        #[derive(cynic::QueryFragment, PartialEq, Debug)]
+        #[cynic(
+            schema_path = "src/bin/simple.graphql",
+            query_module = "query_dsl",
+            graphql_type = "Nested"
+        )]
+        struct WideNested {
+            $(
+                #[cynic(rename = "aString")]
+                $field: String,
+            )*
+        }
+    };
+}
+
+// More fields than the largest `map` function in `selection_set`.
+wide_fragment!(
+    f1, f2, f3, f4, f5, f6, f7, f8, f9, f10, f11, f12, f13, f14, f15, f16, f17, f18, f19, f20, f21,
+    f22, f23, f24, f25, f26, f27, f28, f29, f30, f31, f32, f33, f34, f35, f36, f37, f38, f39, f40,
+    f41, f42, f43, f44, f45, f46, f47, f48, f49, f50, f51, f52, f53, f54, f55
+);
+
+#[test]
+fn test_fragments_with_many_fields() {
+    use cynic::QueryFragment;
+
+    let query = cynic::Operation::query(cynic::selection_set::field::<_, query_dsl::Query, _>(
+        "nested",
+        vec![],
+        WideNested::fragment(&()),
+    ));
+
+    let test_data = cynic::GraphQLResponse {
+        errors: None,
+        data: Some(serde_json::json!({ "nested": { "aString": "wide" } })),
+        extensions: None,
+    };
+
+    let nested = query.decode_response(test_data).unwrap().data.unwrap();
+    assert_eq!(nested.f1, "wide");
+    assert_eq!(nested.f50, "wide");
+    assert_eq!(nested.f51, "wide");
+    assert_eq!(nested.f55, "wide");
+}
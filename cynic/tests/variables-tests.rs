@@ -0,0 +1,185 @@
+//! Tests of how arguments are serialized into the variables of operations
+
+use cynic::QueryFragment;
+use serde_json::json;
+
+mod query_dsl {
+    cynic::query_dsl!("tests/test-schema.graphql");
+}
+
+#[derive(cynic::Enum, Clone, Copy, Debug, PartialEq)]
+#[cynic(
+    graphql_type = "PostState",
+    schema_path = "tests/test-schema.graphql",
+    query_module = "query_dsl",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+enum PostState {
+    Draft,
+    Published,
+}
+
+#[derive(cynic::InputObject, Clone)]
+#[cynic(
+    graphql_type = "PostFilter",
+    schema_path = "tests/test-schema.graphql",
+    query_module = "query_dsl"
+)]
+struct PostFilter {
+    #[cynic(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    state: Option<PostState>,
+}
+
+#[derive(cynic::InputObject, Clone)]
+#[cynic(
+    graphql_type = "BlogPost",
+    schema_path = "tests/test-schema.graphql",
+    query_module = "query_dsl"
+)]
+struct BlogPost {
+    content: String,
+    state: Option<PostState>,
+}
+
+#[derive(cynic::InputObject, Clone)]
+#[cynic(
+    graphql_type = "CreatePostsInput",
+    schema_path = "tests/test-schema.graphql",
+    query_module = "query_dsl",
+    rename_all = "camelCase"
+)]
+struct CreatePostsInput {
+    author_id: cynic::Id,
+    posts: Vec<BlogPost>,
+}
+
+#[derive(cynic::QueryFragment, Debug, PartialEq)]
+#[cynic(
+    graphql_type = "Post",
+    schema_path = "tests/test-schema.graphql",
+    query_module = "query_dsl"
+)]
+struct Post {
+    id: cynic::Id,
+    state: Option<PostState>,
+}
+
+#[derive(cynic::FragmentArguments)]
+struct PostsArguments {
+    id: cynic::Id,
+    filter: PostFilter,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(
+    graphql_type = "Query",
+    schema_path = "tests/test-schema.graphql",
+    query_module = "query_dsl",
+    argument_struct = "PostsArguments"
+)]
+struct PostsQuery {
+    #[arguments(id = args.id.clone())]
+    post: Option<Post>,
+    #[arguments(filter = args.filter.clone())]
+    posts: Vec<Post>,
+}
+
+#[derive(cynic::FragmentArguments)]
+struct CreatePostsArguments {
+    input: CreatePostsInput,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(
+    graphql_type = "Mutation",
+    schema_path = "tests/test-schema.graphql",
+    query_module = "query_dsl",
+    argument_struct = "CreatePostsArguments"
+)]
+struct CreatePostsMutation {
+    #[arguments(input = args.input.clone())]
+    create_posts: Vec<Post>,
+}
+
+fn posts_query() -> cynic::Operation<'static, PostsQuery> {
+    cynic::Operation::query(PostsQuery::fragment(&PostsArguments {
+        id: cynic::Id::new("post-1"),
+        filter: PostFilter {
+            author: None,
+            state: Some(PostState::Published),
+        },
+    }))
+}
+
+fn create_posts_mutation() -> cynic::Operation<'static, CreatePostsMutation> {
+    cynic::Operation::mutation(CreatePostsMutation::fragment(&CreatePostsArguments {
+        input: CreatePostsInput {
+            author_id: cynic::Id::new("1"),
+            posts: vec![
+                BlogPost {
+                    content: "Hello".into(),
+                    state: Some(PostState::Draft),
+                },
+                BlogPost {
+                    content: "World".into(),
+                    state: None,
+                },
+            ],
+        },
+    }))
+}
+
+#[test]
+fn test_enum_and_id_variables() {
+    let operation = serde_json::to_value(&posts_query()).unwrap();
+
+    assert_eq!(
+        operation["variables"],
+        json!({
+            "_0": "post-1",
+            "_1": { "state": "PUBLISHED" }
+        })
+    );
+}
+
+#[test]
+fn test_nested_input_object_variables() {
+    let operation = serde_json::to_value(&create_posts_mutation()).unwrap();
+
+    assert_eq!(
+        operation["variables"],
+        json!({
+            "_0": {
+                "authorId": "1",
+                "posts": [
+                    { "content": "Hello", "state": "DRAFT" },
+                    { "content": "World", "state": null }
+                ]
+            }
+        })
+    );
+}
+
+#[cfg(feature = "fake-server")]
+#[test]
+fn test_variables_are_accepted_by_a_server() {
+    use cynic::fake_server::FakeServer;
+
+    let server = FakeServer::new(include_str!("test-schema.graphql"))
+        .unwrap()
+        .with_generator("PostState", |_| json!("PUBLISHED"));
+
+    let response = server.run(&posts_query()).unwrap();
+    assert!(response.errors.is_none(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.unwrap().posts[0],
+        Post {
+            id: cynic::Id::new("id"),
+            state: Some(PostState::Published)
+        }
+    );
+
+    let response = server.run(&create_posts_mutation()).unwrap();
+    assert!(response.errors.is_none(), "{:?}", response.errors);
+}